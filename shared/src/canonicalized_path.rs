@@ -167,7 +167,7 @@ impl CanonicalizedPath {
         Url::from_file_path(self.0.clone()).ok()
     }
 
-    pub(crate) fn file_name(&self) -> Option<String> {
+    pub fn file_name(&self) -> Option<String> {
         Some(self.0.file_name()?.to_string_lossy().to_string())
     }
 }
@@ -38,10 +38,18 @@ impl Command {
 pub struct Language {
     pub(crate) extensions: &'static [&'static str],
     pub(crate) file_names: &'static [&'static str],
+    /// Interpreters that can appear in the shebang line of a script, e.g. `python` in `#!/usr/bin/env python3`.
+    pub(crate) interpreters: &'static [&'static str],
+    /// The token that starts a line comment, e.g. `//` in Rust.
+    pub(crate) line_comment_prefix: Option<&'static str>,
     pub(crate) lsp_language_id: Option<LanguageId>,
     pub(crate) lsp_command: Option<LspCommand>,
+    /// LSP servers that run alongside the main one, e.g. a linter.
+    pub(crate) additional_lsp_commands: &'static [LspCommand],
     pub(crate) tree_sitter_grammar_config: Option<GrammarConfig>,
     pub(crate) highlight_query: Option<&'static str>,
+    /// The Tree-sitter query that captures the languages embedded in this language
+    pub(crate) injection_query: Option<&'static str>,
     pub(crate) formatter_command: Option<Command>,
 }
 
@@ -64,9 +72,13 @@ impl Language {
         Self {
             extensions: &[""],
             file_names: &[""],
+            interpreters: &[],
+            line_comment_prefix: None,
             lsp_language_id: None,
             highlight_query: None,
+            injection_query: None,
             lsp_command: None,
+            additional_lsp_commands: &[],
             tree_sitter_grammar_config: None,
             formatter_command: None,
         }
@@ -143,7 +155,7 @@ impl Language {
     }
 
     pub fn injection_query(&self) -> Option<&'static str> {
-        None
+        self.injection_query
     }
 
     pub fn lsp_process_command(&self) -> Option<ProcessCommand> {
@@ -152,10 +164,21 @@ impl Language {
             .map(|command| ProcessCommand::new(command.command.0, command.command.1))
     }
 
+    pub fn additional_lsp_process_commands(&self) -> Vec<ProcessCommand> {
+        self.additional_lsp_commands
+            .iter()
+            .map(|command| ProcessCommand::new(command.command.0, command.command.1))
+            .collect()
+    }
+
     pub fn tree_sitter_grammar_id(&self) -> Option<String> {
         Some(self.tree_sitter_grammar_config()?.grammar_id)
     }
 
+    pub fn line_comment_prefix(&self) -> Option<&'static str> {
+        self.line_comment_prefix
+    }
+
     pub fn id(&self) -> Option<LanguageId> {
         self.lsp_language_id
     }
@@ -184,6 +207,23 @@ pub fn from_extension(extension: &str) -> Option<Language> {
         .map(|language| (*language).clone())
 }
 
+/// Get the language of an injection by its name, e.g. the info string of a Markdown code block,
+/// which is either the LSP language ID or an extension of the language.
+pub fn from_injection_name(name: &str) -> Option<Language> {
+    from_lsp_language_id(name).or_else(|| from_extension(name))
+}
+
+pub fn from_lsp_language_id(id: &str) -> Option<Language> {
+    LANGUAGES
+        .iter()
+        .find(|language| {
+            language
+                .lsp_language_id
+                .is_some_and(|language_id| language_id.0 == id)
+        })
+        .map(|language| (*language).clone())
+}
+
 pub(crate) fn from_filename(path: &CanonicalizedPath) -> Option<Language> {
     let file_name = path.file_name()?;
     LANGUAGES
@@ -192,6 +232,42 @@ pub(crate) fn from_filename(path: &CanonicalizedPath) -> Option<Language> {
         .map(|language| (*language).clone())
 }
 
+/// Detect the language by looking at the first line of the content.
+///
+/// This is used as a fallback when the language cannot be inferred from the path,
+/// for example, scripts without extension.
+pub fn from_content(content: &str) -> Option<Language> {
+    let first_line = content.lines().next()?.trim();
+    if let Some(shebang) = first_line.strip_prefix("#!") {
+        return from_shebang(shebang);
+    }
+    let extension = if first_line.starts_with("<?xml") {
+        "xml"
+    } else if first_line.to_lowercase().starts_with("<!doctype html") {
+        "html"
+    } else {
+        return None;
+    };
+    from_extension(extension)
+}
+
+fn from_shebang(shebang: &str) -> Option<Language> {
+    let mut words = shebang.split_whitespace();
+    let program = words.next()?.rsplit('/').next()?;
+    let interpreter = if program == "env" {
+        // Skip the flags and environment variables passed to `env`, e.g. `env -S VAR=1 python`
+        words.find(|word| !word.starts_with('-') && !word.contains('='))?
+    } else {
+        program
+    };
+    // Remove the version suffix, e.g. `python3.11` becomes `python`
+    let interpreter = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    LANGUAGES
+        .iter()
+        .find(|language| language.interpreters.contains(&interpreter))
+        .map(|language| (*language).clone())
+}
+
 #[cfg(test)]
 mod test_language {
     use super::*;
@@ -213,4 +289,21 @@ mod test_language {
         run_test_case("justfile", "just")?;
         Ok(())
     }
+
+    #[test]
+    fn test_from_content() {
+        fn run_test_case(content: &str, expected_language_id: Option<&'static str>) {
+            assert_eq!(
+                from_content(content).and_then(|language| language.tree_sitter_grammar_id()),
+                expected_language_id.map(|id| id.to_string())
+            );
+        }
+        run_test_case("#!/usr/bin/env python\nprint(1)", Some("python"));
+        run_test_case("#!/usr/bin/python3.11", Some("python"));
+        run_test_case("#!/usr/bin/env -S VAR=1 bash -e", Some("bash"));
+        run_test_case("#!/bin/sh", Some("bash"));
+        run_test_case("<?xml version=\"1.0\"?>", Some("xml"));
+        run_test_case("#!/usr/bin/env unknown", None);
+        run_test_case("hello world", None);
+    }
 }
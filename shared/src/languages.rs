@@ -2,6 +2,7 @@ use super::language::{Command, GrammarConfig, Language, LanguageId, LspCommand};
 
 pub const LANGUAGES: &[&Language] = &[
     &bash(),
+    &c(),
     &common_lisp(),
     &css(),
     &csv(),
@@ -33,6 +34,8 @@ const fn bash() -> Language {
         lsp_language_id: Some(LanguageId::new("bash")),
         file_names: &[".bashrc", ".bash_profile", "bashrc", "bash_profile"],
         extensions: &["sh", "bash"],
+        line_comment_prefix: Some("#"),
+        interpreters: &["sh", "bash", "dash", "zsh"],
         lsp_command: Some(LspCommand {
             command: Command("bash-language-server", &["start"]),
             ..LspCommand::default()
@@ -48,12 +51,35 @@ const fn bash() -> Language {
     }
 }
 
+const fn c() -> Language {
+    Language {
+        lsp_language_id: Some(LanguageId::new("c")),
+        extensions: &["c", "h"],
+        line_comment_prefix: Some("//"),
+        lsp_command: Some(LspCommand {
+            command: Command("clangd", &[]),
+            ..LspCommand::default()
+        }),
+        tree_sitter_grammar_config: Some(GrammarConfig {
+            id: "c",
+            url: "https://github.com/tree-sitter/tree-sitter-c",
+            commit: "master",
+            subpath: None,
+        }),
+        formatter_command: Some(Command("clang-format", &[])),
+        ..Language::new()
+    }
+}
+
 const fn common_lisp() -> Language {
     Language {
         file_names: &[],
         lsp_language_id: None,
         lsp_command: None,
+        additional_lsp_commands: &[],
         extensions: &["lisp", "lsp", "l", "cl", "fasl", "sbcl", "el"],
+        line_comment_prefix: Some(";"),
+        interpreters: &[],
         tree_sitter_grammar_config: Some(GrammarConfig {
             id: "commonlisp",
             url: "https://github.com/theHamsta/tree-sitter-commonlisp",
@@ -61,6 +87,7 @@ const fn common_lisp() -> Language {
             subpath: None,
         }),
         highlight_query: None,
+        injection_query: None,
         formatter_command: None,
     }
 }
@@ -69,9 +96,13 @@ const fn csv() -> Language {
     Language {
         file_names: &[],
         extensions: &["csv"],
+        line_comment_prefix: None,
+        interpreters: &[],
         lsp_language_id: None,
         lsp_command: None,
+        additional_lsp_commands: &[],
         highlight_query: None,
+        injection_query: None,
         formatter_command: None,
         tree_sitter_grammar_config: Some(GrammarConfig {
             id: "csv",
@@ -86,9 +117,13 @@ const fn css() -> Language {
     Language {
         file_names: &[],
         extensions: &["css"],
+        line_comment_prefix: None,
+        interpreters: &[],
         lsp_language_id: None,
         lsp_command: None,
+        additional_lsp_commands: &[],
         highlight_query: None,
+        injection_query: None,
         formatter_command: Some(Command("prettierd", &[".css"])),
         tree_sitter_grammar_config: Some(GrammarConfig {
             id: "css",
@@ -103,9 +138,13 @@ const fn dockerfile() -> Language {
     Language {
         file_names: &["Dockerfile"],
         extensions: &[],
+        line_comment_prefix: Some("#"),
+        interpreters: &[],
         lsp_language_id: None,
         lsp_command: None,
+        additional_lsp_commands: &[],
         highlight_query: None,
+        injection_query: None,
         formatter_command: None,
         tree_sitter_grammar_config: Some(GrammarConfig {
             id: "dockerfile",
@@ -120,6 +159,7 @@ const fn graphql() -> Language {
     Language {
         lsp_language_id: Some(LanguageId::new("graphql")),
         extensions: &["graphql", "gql"],
+        line_comment_prefix: Some("#"),
         tree_sitter_grammar_config: Some(GrammarConfig {
             id: "graphql",
             url: "https://github.com/bkegley/tree-sitter-graphql",
@@ -149,11 +189,22 @@ const fn html() -> Language {
             commit: "master",
             subpath: None,
         }),
+        injection_query: Some(
+            "((script_element (raw_text) @injection.content)
+                (#set! injection.language \"javascript\"))
+            ((style_element (raw_text) @injection.content)
+                (#set! injection.language \"css\"))",
+        ),
         formatter_command: Some(Command("prettierd", &[".html"])),
         ..Language::new()
     }
 }
 
+const ESLINT: &[LspCommand] = &[LspCommand {
+    command: Command("vscode-eslint-language-server", &["--stdio"]),
+    initialization_options: None,
+}];
+
 const fn javascript(jsx: bool) -> Language {
     Language {
         lsp_language_id: Some(LanguageId::new(if jsx {
@@ -162,10 +213,13 @@ const fn javascript(jsx: bool) -> Language {
             "javascript"
         })),
         extensions: if jsx { &["jsx"] } else { &["js"] },
+        line_comment_prefix: Some("//"),
+        interpreters: if jsx { &[] } else { &["node"] },
         lsp_command: Some(LspCommand {
             command: Command("typescript-language-server", &["--stdio"]),
             ..LspCommand::default()
         }),
+        additional_lsp_commands: ESLINT,
         tree_sitter_grammar_config: Some(GrammarConfig {
             id: if jsx { "jsx" } else { "javascript" },
             url: "https://github.com/tree-sitter/tree-sitter-javascript",
@@ -181,8 +235,11 @@ const fn json() -> Language {
     Language {
         file_names: &[],
         extensions: &["json"],
+        line_comment_prefix: None,
+        interpreters: &[],
         lsp_language_id: None,
         lsp_command: None,
+        additional_lsp_commands: &[],
         tree_sitter_grammar_config: Some(GrammarConfig {
             id: "json",
             url: "https://github.com/tree-sitter/tree-sitter-json",
@@ -190,6 +247,7 @@ const fn json() -> Language {
             subpath: None,
         }),
         highlight_query: None,
+        injection_query: None,
         formatter_command: Some(Command("prettierd", &[".json"])),
     }
 }
@@ -198,8 +256,11 @@ const fn just() -> Language {
     Language {
         file_names: &["justfile"],
         extensions: &[],
+        line_comment_prefix: Some("#"),
+        interpreters: &[],
         lsp_language_id: None,
         lsp_command: None,
+        additional_lsp_commands: &[],
         tree_sitter_grammar_config: Some(GrammarConfig {
             id: "just",
             url: "https://github.com/IndianBoy42/tree-sitter-just",
@@ -207,6 +268,7 @@ const fn just() -> Language {
             subpath: None,
         }),
         highlight_query: None,
+        injection_query: None,
         formatter_command: None,
     }
 }
@@ -219,7 +281,10 @@ const fn nix() -> Language {
             command: Command("nil", &[]),
             ..LspCommand::default()
         }),
+        additional_lsp_commands: &[],
         extensions: &["nix"],
+        line_comment_prefix: Some("#"),
+        interpreters: &[],
         tree_sitter_grammar_config: Some(GrammarConfig {
             id: "nix",
             url: "https://github.com/nix-community/tree-sitter-nix",
@@ -227,6 +292,7 @@ const fn nix() -> Language {
             subpath: None,
         }),
         highlight_query: None,
+        injection_query: None,
         formatter_command: Some(Command("nixfmt", &[])),
     }
 }
@@ -245,6 +311,11 @@ const fn markdown() -> Language {
             commit: "split_parser",
             subpath: Some("tree-sitter-markdown"),
         }),
+        injection_query: Some(
+            "(fenced_code_block
+                (info_string (language) @injection.language)
+                (code_fence_content) @injection.content)",
+        ),
         formatter_command: Some(Command("prettierd", &[".md"])),
         ..Language::new()
     }
@@ -254,6 +325,8 @@ const fn python() -> Language {
     Language {
         lsp_language_id: Some(LanguageId::new("python")),
         extensions: &["py"],
+        line_comment_prefix: Some("#"),
+        interpreters: &["python"],
         lsp_command: Some(LspCommand {
             command: Command("pyright-langserver", &["--stdio"]),
             ..LspCommand::default()
@@ -277,7 +350,10 @@ const fn rescript() -> Language {
             command: Command("./node_modules/.bin/rescript-language-server", &["--stdio"]),
             ..LspCommand::default()
         }),
+        additional_lsp_commands: &[],
         extensions: &["res"],
+        line_comment_prefix: Some("//"),
+        interpreters: &[],
         tree_sitter_grammar_config: Some(GrammarConfig {
             id: "rescript",
             url: "https://github.com/rescript-lang/tree-sitter-rescript",
@@ -285,6 +361,7 @@ const fn rescript() -> Language {
             subpath: None,
         }),
         highlight_query: None,
+        injection_query: None,
         formatter_command: Some(Command(
             "./node_modules/.bin/rescript",
             &["format", "-stdin", ".res"],
@@ -297,11 +374,15 @@ const fn rust() -> Language {
         file_names: &[],
         lsp_language_id: Some(LanguageId::new("rust")),
         extensions: &["rs"],
+        line_comment_prefix: Some("//"),
+        interpreters: &[],
         lsp_command: Some(LspCommand {
             command: Command("rust-analyzer", &[]),
             ..LspCommand::default()
         }),
+        additional_lsp_commands: &[],
         highlight_query: None,
+        injection_query: None,
         tree_sitter_grammar_config: Some(GrammarConfig {
             id: "rust",
             url: "https://github.com/tree-sitter/tree-sitter-rust",
@@ -316,6 +397,7 @@ const fn sql() -> Language {
     Language {
         lsp_language_id: Some(LanguageId::new("sql")),
         extensions: &["sql"],
+        line_comment_prefix: Some("--"),
         lsp_command: None,
         tree_sitter_grammar_config: Some(GrammarConfig {
             id: "sql",
@@ -332,6 +414,7 @@ const fn swift() -> Language {
     Language {
         lsp_language_id: Some(LanguageId::new("swift")),
         extensions: &["swift"],
+        line_comment_prefix: Some("//"),
         lsp_command: Some(LspCommand {
             command: Command("sourcekit-lsp", &[]),
             ..LspCommand::default()
@@ -351,8 +434,11 @@ const fn toml() -> Language {
     Language {
         file_names: &[],
         extensions: &["toml"],
+        line_comment_prefix: Some("#"),
+        interpreters: &[],
         lsp_language_id: None,
         lsp_command: None,
+        additional_lsp_commands: &[],
         tree_sitter_grammar_config: Some(GrammarConfig {
             id: "toml",
             url: "https://github.com/ikatyang/tree-sitter-toml",
@@ -360,6 +446,7 @@ const fn toml() -> Language {
             subpath: None,
         }),
         highlight_query: None,
+        injection_query: None,
         formatter_command: None,
     }
 }
@@ -368,8 +455,11 @@ const fn tree_sitter_query() -> Language {
     Language {
         file_names: &[],
         extensions: &["scm"],
+        line_comment_prefix: Some(";"),
+        interpreters: &[],
         lsp_language_id: None,
         lsp_command: None,
+        additional_lsp_commands: &[],
         tree_sitter_grammar_config: Some(GrammarConfig {
             id: "tsq",
             url: "https://github.com/tree-sitter/tree-sitter-tsq",
@@ -377,6 +467,7 @@ const fn tree_sitter_query() -> Language {
             subpath: None,
         }),
         highlight_query: None,
+        injection_query: None,
         formatter_command: None,
     }
 }
@@ -397,10 +488,12 @@ const fn typescript(tsx: bool) -> Language {
             "typescript",
         ))),
         extensions: choice(tsx, &["tsx"], &["ts"]),
+        line_comment_prefix: Some("//"),
         lsp_command: Some(LspCommand {
             command: Command("typescript-language-server", &["--stdio"]),
             ..LspCommand::default()
         }),
+        additional_lsp_commands: ESLINT,
         tree_sitter_grammar_config: Some(GrammarConfig {
             id: choice(tsx, "tsx", "typescript"),
             url: "https://github.com/tree-sitter/tree-sitter-typescript",
@@ -417,7 +510,10 @@ const fn xml() -> Language {
         file_names: &[],
         lsp_language_id: Some(LanguageId::new("xml")),
         extensions: &["xml"],
+        line_comment_prefix: None,
+        interpreters: &[],
         lsp_command: None,
+        additional_lsp_commands: &[],
         tree_sitter_grammar_config: Some(GrammarConfig {
             id: "xml",
             url: "https://github.com/tree-sitter-grammars/tree-sitter-xml",
@@ -426,6 +522,7 @@ const fn xml() -> Language {
         }),
         formatter_command: None,
         highlight_query: None,
+        injection_query: None,
     }
 }
 
@@ -434,7 +531,10 @@ const fn yaml() -> Language {
         file_names: &[],
         lsp_language_id: Some(LanguageId::new("yaml")),
         extensions: &["yaml", "yml"],
+        line_comment_prefix: Some("#"),
+        interpreters: &[],
         lsp_command: None,
+        additional_lsp_commands: &[],
         tree_sitter_grammar_config: Some(GrammarConfig {
             id: "yaml",
             url: "https://github.com/ikatyang/tree-sitter-yaml",
@@ -443,6 +543,7 @@ const fn yaml() -> Language {
         }),
         formatter_command: None,
         highlight_query: None,
+        injection_query: None,
     }
 }
 
@@ -451,10 +552,13 @@ const fn zig() -> Language {
         file_names: &[],
         lsp_language_id: Some(LanguageId::new("zig")),
         extensions: &["zig"],
+        line_comment_prefix: Some("//"),
+        interpreters: &[],
         lsp_command: Some(LspCommand {
             command: Command("zls", &[]),
             ..LspCommand::default()
         }),
+        additional_lsp_commands: &[],
         tree_sitter_grammar_config: Some(GrammarConfig {
             id: "zig",
             url: "https://github.com/tree-sitter-grammars/tree-sitter-zig",
@@ -463,5 +567,6 @@ const fn zig() -> Language {
         }),
         formatter_command: Some(Command("zig", &["fmt", "--stdin"])),
         highlight_query: None,
+        injection_query: None,
     }
 }
@@ -1,7 +1,7 @@
 use anyhow::Context;
 use std::io::{Read, Write};
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub struct ProcessCommand {
     command: String,
     args: Vec<String>,
@@ -31,6 +31,14 @@ impl ProcessCommand {
         }
     }
 
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
     pub fn spawn(&self) -> SpawnCommandResult {
         log::info!("ProcessCommand::spawn {:?} {:?}", self.command, self.args);
         if which::which(&self.command).is_err() {
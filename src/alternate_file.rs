@@ -0,0 +1,135 @@
+use itertools::Itertools;
+use shared::canonicalized_path::CanonicalizedPath;
+
+/// The path, relative to the working directory, of the file that declares extra alternate file rules.
+///
+/// Each line of this file is a rule such as `*.rs -> *_test.rs`,
+/// where `*` stands for the common part of both file names.
+pub(crate) const ALTERNATE_FILE_RULES_PATH: &str = ".ki/alternate_files.txt";
+
+const DEFAULT_RULES: &[(&str, &str)] = &[
+    ("*.h", "*.c"),
+    ("*.h", "*.cpp"),
+    ("*.hpp", "*.cpp"),
+    ("*.ts", "*.test.ts"),
+    ("*.tsx", "*.test.tsx"),
+    ("*.js", "*.test.js"),
+    ("*.py", "test_*.py"),
+];
+
+/// A rule relating two files in the same directory, it applies in both directions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AlternateFileRule {
+    left: Pattern,
+    right: Pattern,
+}
+
+impl AlternateFileRule {
+    fn parse(line: &str) -> Option<Self> {
+        let (left, right) = line.split_once("->")?;
+        Some(Self {
+            left: Pattern::parse(left.trim())?,
+            right: Pattern::parse(right.trim())?,
+        })
+    }
+
+    fn alternate_file_names(&self, file_name: &str) -> Vec<String> {
+        [(&self.left, &self.right), (&self.right, &self.left)]
+            .into_iter()
+            .filter_map(|(from, to)| Some(to.fill(from.stem(file_name)?)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pattern {
+    prefix: String,
+    suffix: String,
+}
+
+impl Pattern {
+    fn parse(pattern: &str) -> Option<Self> {
+        let (prefix, suffix) = pattern.split_once('*')?;
+        Some(Self {
+            prefix: prefix.to_string(),
+            suffix: suffix.to_string(),
+        })
+    }
+
+    /// Returns the part of `file_name` that matches `*`.
+    fn stem<'a>(&self, file_name: &'a str) -> Option<&'a str> {
+        file_name
+            .strip_prefix(&self.prefix)?
+            .strip_suffix(&self.suffix)
+            .filter(|stem| !stem.is_empty())
+    }
+
+    fn fill(&self, stem: &str) -> String {
+        format!("{}{stem}{}", self.prefix, self.suffix)
+    }
+}
+
+/// Returns the rules declared in the working directory, followed by the default rules.
+pub(crate) fn rules(working_directory: &CanonicalizedPath) -> Vec<AlternateFileRule> {
+    std::fs::read_to_string(
+        working_directory
+            .to_path_buf()
+            .join(ALTERNATE_FILE_RULES_PATH),
+    )
+    .unwrap_or_default()
+    .lines()
+    .filter_map(AlternateFileRule::parse)
+    .chain(DEFAULT_RULES.iter().filter_map(|(left, right)| {
+        Some(AlternateFileRule {
+            left: Pattern::parse(left)?,
+            right: Pattern::parse(right)?,
+        })
+    }))
+    .collect()
+}
+
+/// Returns the existing files related to `path` according to `rules`.
+pub(crate) fn alternate_files(
+    path: &CanonicalizedPath,
+    rules: &[AlternateFileRule],
+) -> anyhow::Result<Vec<CanonicalizedPath>> {
+    let file_name = path
+        .to_path_buf()
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string());
+    let (Some(file_name), Some(directory)) = (file_name, path.parent()?) else {
+        return Ok(Vec::new());
+    };
+    Ok(rules
+        .iter()
+        .flat_map(|rule| rule.alternate_file_names(&file_name))
+        .filter(|alternate| alternate != &file_name)
+        .filter_map(|alternate| directory.join(&alternate).ok())
+        .filter(|alternate| alternate.is_file())
+        .unique()
+        .collect())
+}
+
+#[cfg(test)]
+mod test_alternate_file {
+    use super::*;
+
+    #[test]
+    fn rule_applies_in_both_directions() {
+        let rule = AlternateFileRule::parse("*.rs -> *_test.rs").unwrap();
+        assert_eq!(rule.alternate_file_names("foo.rs"), ["foo_test.rs"]);
+        assert_eq!(
+            rule.alternate_file_names("foo_test.rs"),
+            ["foo_test_test.rs", "foo.rs"]
+        );
+    }
+
+    #[test]
+    fn prefix_pattern() {
+        let rule = AlternateFileRule::parse("*.py -> test_*.py").unwrap();
+        assert_eq!(
+            rule.alternate_file_names("test_foo.py"),
+            ["test_test_foo.py", "foo.py"]
+        );
+    }
+}
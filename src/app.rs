@@ -1,10 +1,11 @@
 use crate::{
     buffer::Buffer,
+    buffer_stats::{self, BufferStats},
     clipboard::CopiedTexts,
     components::{
         component::{Component, ComponentId, GetGridResult},
         dropdown::{DropdownItem, DropdownRender},
-        editor::{DispatchEditor, Editor, IfCurrentNotFound, Movement},
+        editor::{Direction, DispatchEditor, Editor, IfCurrentNotFound, Movement},
         keymap_legend::{
             Keymap, KeymapLegendBody, KeymapLegendConfig, KeymapLegendSection, Keymaps,
         },
@@ -21,33 +22,41 @@ use crate::{
     layout::Layout,
     list::{self, grep::RegexConfig, WalkBuilderConfig},
     lsp::{
-        completion::CompletionItem,
+        completion::{CompletionItem, PositionalEdit},
+        diagnostic::PulledDiagnostics,
+        documentation::Documentation,
         goto_definition_response::GotoDefinitionResponse,
-        manager::LspManager,
+        manager::{LanguageConfig, LspManager},
         process::{FromEditor, LspNotification, ResponseContext},
         symbols::Symbols,
         workspace_edit::WorkspaceEdit,
     },
     position::Position,
     quickfix_list::{Location, QuickfixList, QuickfixListItem, QuickfixListType},
+    rectangle::LayoutKind,
     screen::{Screen, Window},
     selection::SelectionMode,
+    session::{Session, SessionFile},
     syntax_highlight::{HighlighedSpans, SyntaxHighlightRequest},
     ui_tree::{ComponentKind, KindedComponent},
 };
 use event::event::Event;
 use itertools::{Either, Itertools};
 use name_variant::NamedVariant;
-use shared::{canonicalized_path::CanonicalizedPath, language::Language};
+use shared::{
+    canonicalized_path::CanonicalizedPath, language::Language, process_command::ProcessCommand,
+};
 use std::{
     any::TypeId,
     cell::RefCell,
     path::{Path, PathBuf},
     rc::Rc,
     sync::{
+        atomic::{AtomicBool, Ordering},
         mpsc::{Receiver, Sender},
-        Mutex,
+        Arc, Mutex,
     },
+    time::{Duration, Instant},
 };
 use DispatchEditor::*;
 
@@ -65,6 +74,9 @@ pub(crate) struct App<T: Frontend> {
     enable_lsp: bool,
 
     working_directory: CanonicalizedPath,
+    /// Additional project roots besides the working directory,
+    /// which are also covered by the file picker, global search and LSP servers.
+    workspace_folders: Vec<CanonicalizedPath>,
     global_title: Option<String>,
 
     layout: Layout,
@@ -75,19 +87,185 @@ pub(crate) struct App<T: Frontend> {
 
     /// Used for navigating between opened files
     file_path_history: History<CanonicalizedPath>,
+    /// The file shown again when the file explorer is closed, refer `App::close_file_explorer`
+    file_explorer_return_path: Option<CanonicalizedPath>,
+    /// The locations before and after each jump, e.g. go to definition,
+    /// used by `Dispatch::JumpBack` and `Dispatch::JumpForward`
+    jump_list: History<Location>,
     status_line_components: Vec<StatusLineComponent>,
     last_action_description: Option<String>,
+
+    /// References of the symbol under the cursor, sorted by location.
+    /// Used by `Dispatch::GotoReference`, and is refreshed when the cursor leaves them,
+    /// or cleared when any document is changed.
+    references_cache: Vec<Location>,
+
+    /// Git status of the repository of the focused file,
+    /// cached because it is expensive to compute on every render.
+    git_status: Option<git::GitStatus>,
+    /// The git status of each repository keyed by its working directory,
+    /// so that a repository is not scanned again whenever one of its files is focused.
+    /// Cleared whenever a file is saved or changed, refer `App::refresh_git_status`
+    git_statuses: std::collections::HashMap<CanonicalizedPath, Option<git::GitStatus>>,
+
+    /// The maximum number of undo entries of each buffer, unlimited if `None`
+    undo_limit: Option<usize>,
+
+    /// Used for estimating the reading time of a buffer
+    words_per_minute: usize,
+    /// If defined, the stats of the current buffer are shown at the end of the status line,
+    /// and Markdown syntax is not counted as words if it is true, refer `Dispatch::BufferStats`
+    buffer_stats: Option<bool>,
+    /// The buffer stats last shown, keyed by the component, the revision of its buffer,
+    /// and whether Markdown syntax is excluded, so that they are not counted on every render
+    buffer_stats_cache: RefCell<Option<((ComponentId, usize, bool), BufferStats)>>,
+
+    /// If true, moving through the quickfix list only previews the location of the
+    /// current item, instead of jumping to it.
+    quickfix_preview: bool,
+
+    /// Callbacks that are notified of every change applied to the content of any buffer
+    buffer_change_observers: Vec<Box<dyn FnMut(&crate::buffer::BufferChange)>>,
+
+    /// The selection mode last used in each closed file, which is restored when it is opened again.
+    /// It is not stored in the buffer, so that each pane of a buffer keeps its own selection mode.
+    selection_modes: std::collections::HashMap<CanonicalizedPath, SelectionMode>,
+
+    /// Runs the shell command of `Dispatch::RunWithSelection` in the background
+    shell_command_runner: ShellCommandRunner,
+
+    /// The code actions received since the last code action request, refer `App::receive_code_actions`
+    code_actions: CodeActions,
+    /// Reads the content of the files that are loaded in the background, refer `App::open_file`
+    file_reader: FileReader,
+    /// Files of at least this many bytes are loaded in the background
+    background_loading_min_file_size: u64,
+    /// Called before the global search searches each file, replaceable so that tests can
+    /// pace the search
+    global_search_throttle: GlobalSearchThrottle,
+
+    /// Refer `Dispatch::EnableAutosave`
+    autosave: Option<Autosave>,
+    /// If false, the modified buffers are not autosaved until insert mode is exited
+    autosave_in_insert_mode: bool,
+    /// Returns the current time, replaceable so that tests can advance the time
+    clock: Clock,
+
+    /// The global search streaming its results to the quickfix list, refer `App::global_search`
+    running_global_search: Option<GlobalSearch>,
+    next_global_search_id: usize,
+
+    /// The location of each named mark, whose range is tracked by the buffer of the file
+    /// while it is opened, refer `Buffer::set_named_mark` and `App::named_mark_location`
+    named_marks: std::collections::HashMap<char, Location>,
+
+    /// The macro being recorded, and the inputs recorded so far, refer `App::record_input`
+    recording_macro: Option<(char, Vec<Dispatch>)>,
+    /// The recorded macros, replayed with `Dispatch::ReplayMacro`
+    macros: std::collections::HashMap<char, Vec<Dispatch>>,
+    /// The number of inputs being handled, so that only the outermost input is recorded,
+    /// not the dispatches resulting from it.
+    input_depth: usize,
+    /// The registers of the macros being replayed, so that a macro cannot replay itself
+    replaying_macros: Vec<char>,
 }
 
 const GLOBAL_TITLE_BAR_HEIGHT: u16 = 1;
 
-#[derive(Clone)]
+/// Returns the output of a shell command, the first argument is the script,
+/// and the second argument is available as `$1` to it
+type ShellCommandRunner = Arc<dyn Fn(&str, &str) -> anyhow::Result<String> + Send + Sync>;
+
+type FileReader = Arc<dyn Fn(&CanonicalizedPath) -> anyhow::Result<String> + Send + Sync>;
+
+type GlobalSearchThrottle = Arc<dyn Fn(&Path) + Send + Sync>;
+
+#[derive(Default)]
+struct CodeActions {
+    /// The code actions received from every LSP server
+    actions: Vec<crate::lsp::code_action::CodeAction>,
+    /// The prompt showing `actions`, `None` if it is not opened yet
+    prompt_id: Option<ComponentId>,
+}
+
+type Clock = Rc<dyn Fn() -> Instant>;
+
+/// Writes the modified buffers once no edit is made for `interval`, refer `App::autosave`
+struct Autosave {
+    interval: Duration,
+    /// When the last edit is made, `None` if it is already saved
+    last_edit: Option<Instant>,
+    /// Set to true to stop sending `AppMessage::AutosaveTick`
+    cancelled: Arc<AtomicBool>,
+}
+
+struct GlobalSearch {
+    /// Tells apart the messages of this search from the ones of the cancelled searches
+    id: usize,
+    /// Set to true to stop walking the files
+    cancelled: Arc<AtomicBool>,
+    /// The location when the search started, the first result is only jumped to
+    /// if the location is unchanged when the search completes
+    origin: Option<Location>,
+}
+
+/// Reading files larger than this blocks the editor noticeably
+const BACKGROUND_LOADING_MIN_FILE_SIZE: u64 = 1024 * 1024;
+
+/// The file name suggested by the prompts of `Dispatch::SaveSession` and `Dispatch::LoadSession`
+const DEFAULT_SESSION_FILE_NAME: &str = ".ki-session.json";
+
+/// How often the opened files are checked for modifications by other programs
+const EXTERNAL_MODIFICATIONS_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) enum StatusLineComponent {
     CurrentWorkingDirectory,
     GitBranch,
     Mode,
     SelectionMode,
     LastDispatch,
+    CurrentFilePath,
+    SelectionCount,
+    CursorPosition,
+    /// Number of errors and warnings of the current file
+    Diagnostics,
+}
+
+impl StatusLineComponent {
+    const ALL: &'static [StatusLineComponent] = &[
+        StatusLineComponent::CurrentWorkingDirectory,
+        StatusLineComponent::GitBranch,
+        StatusLineComponent::Mode,
+        StatusLineComponent::SelectionMode,
+        StatusLineComponent::LastDispatch,
+        StatusLineComponent::CurrentFilePath,
+        StatusLineComponent::SelectionCount,
+        StatusLineComponent::CursorPosition,
+        StatusLineComponent::Diagnostics,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            StatusLineComponent::CurrentWorkingDirectory => "cwd",
+            StatusLineComponent::GitBranch => "git-branch",
+            StatusLineComponent::Mode => "mode",
+            StatusLineComponent::SelectionMode => "selection-mode",
+            StatusLineComponent::LastDispatch => "last-dispatch",
+            StatusLineComponent::CurrentFilePath => "file-path",
+            StatusLineComponent::SelectionCount => "selection-count",
+            StatusLineComponent::CursorPosition => "cursor-position",
+            StatusLineComponent::Diagnostics => "diagnostics",
+        }
+    }
+
+    fn from_name(name: &str) -> anyhow::Result<StatusLineComponent> {
+        StatusLineComponent::ALL
+            .iter()
+            .find(|component| component.name() == name)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Unknown status line segment: {:?}", name))
+    }
 }
 
 impl<T: Frontend> App<T> {
@@ -112,6 +290,61 @@ impl<T: Frontend> App<T> {
         self.enable_lsp = false
     }
 
+    #[cfg(test)]
+    pub(crate) fn use_fake_system_clipboard(&mut self) {
+        self.context.use_fake_system_clipboard()
+    }
+
+    /// Register a callback which will be invoked with every change
+    /// applied to the content of any buffer, in the order they are applied.
+    pub(crate) fn register_buffer_change_observer(
+        &mut self,
+        observer: Box<dyn FnMut(&crate::buffer::BufferChange)>,
+    ) {
+        // The changes are not recorded until there is any observer
+        for buffer in self.layout.buffers() {
+            buffer.borrow_mut().record_changes()
+        }
+        self.buffer_change_observers.push(observer)
+    }
+
+    #[cfg(test)]
+    /// Replace the runner of shell commands, so that tests do not spawn real processes
+    pub(crate) fn set_shell_command_runner(&mut self, runner: ShellCommandRunner) {
+        self.shell_command_runner = runner
+    }
+
+    #[cfg(test)]
+    /// Wait for the output of the shell command run in the background, then show it.
+    /// The other messages received meanwhile are queued again.
+    pub(crate) fn wait_for_shell_command_output(&mut self) -> anyhow::Result<()> {
+        let mut other_messages = Vec::new();
+        let result = loop {
+            match self
+                .receiver
+                .recv_timeout(std::time::Duration::from_secs(5))
+            {
+                Ok(AppMessage::ShellCommandOutput { command, output }) => {
+                    break self.handle_shell_command_output(command, output)
+                }
+                Ok(message) => other_messages.push(message),
+                Err(error) => break Err(error.into()),
+            }
+        };
+        for message in other_messages {
+            self.sender.send(message)?
+        }
+        result
+    }
+
+    /// Persist the clipboard history to `path`, restoring the history persisted previously
+    pub(crate) fn enable_clipboard_persistence(
+        &mut self,
+        path: std::path::PathBuf,
+    ) -> anyhow::Result<()> {
+        self.context.enable_clipboard_persistence(path)
+    }
+
     pub(crate) fn from_channel(
         frontend: Rc<Mutex<T>>,
         working_directory: CanonicalizedPath,
@@ -130,12 +363,42 @@ impl<T: Frontend> App<T> {
                 dimension.decrement_height(GLOBAL_TITLE_BAR_HEIGHT),
                 &working_directory,
             )?,
+            git_status: git::GitStatus::of(&working_directory),
+            git_statuses: Default::default(),
             working_directory,
+            workspace_folders: Vec::new(),
+            references_cache: Vec::new(),
+            undo_limit: None,
+            quickfix_preview: false,
+            words_per_minute: buffer_stats::DEFAULT_WORDS_PER_MINUTE,
+            buffer_stats: None,
+            buffer_stats_cache: Default::default(),
+            buffer_change_observers: Vec::new(),
+            selection_modes: Default::default(),
+            shell_command_runner: Arc::new(|script, argument| {
+                ProcessCommand::new("bash", &["-c", script, "_", argument]).run_with_input("")
+            }),
+            code_actions: Default::default(),
+            named_marks: Default::default(),
+            recording_macro: None,
+            macros: Default::default(),
+            input_depth: 0,
+            replaying_macros: Vec::new(),
+            file_reader: Arc::new(|path: &CanonicalizedPath| path.read()),
+            background_loading_min_file_size: BACKGROUND_LOADING_MIN_FILE_SIZE,
+            global_search_throttle: Arc::new(|_: &Path| {}),
+            autosave: None,
+            autosave_in_insert_mode: false,
+            clock: Rc::new(Instant::now),
+            running_global_search: None,
+            next_global_search_id: 0,
             frontend,
             syntax_highlight_request_sender: None,
             global_title: None,
 
             file_path_history: History::new(),
+            file_explorer_return_path: None,
+            jump_list: History::new(),
 
             status_line_components,
             last_action_description: None,
@@ -166,6 +429,8 @@ impl<T: Frontend> App<T> {
             self.open_file(&entry_path, OpenFileOption::Focus)?;
         }
 
+        self.watch_external_modifications();
+
         self.render()?;
 
         while let Ok(message) = self.receiver.recv() {
@@ -184,6 +449,25 @@ impl<T: Frontend> App<T> {
                 } => self
                     .update_highlighted_spans(component_id, highlighted_spans)
                     .map(|_| false),
+                AppMessage::ShellCommandOutput { command, output } => self
+                    .handle_shell_command_output(command, output)
+                    .map(|_| false),
+                AppMessage::FileLoaded { path, content } => {
+                    self.handle_file_loaded(path, content).map(|_| false)
+                }
+                AppMessage::GlobalSearchResults {
+                    search_id,
+                    locations,
+                } => self
+                    .handle_global_search_results(search_id, locations)
+                    .map(|_| false),
+                AppMessage::GlobalSearchFinished { search_id, result } => self
+                    .handle_global_search_finished(search_id, result)
+                    .map(|_| false),
+                AppMessage::CheckExternalModifications => {
+                    self.handle_external_modifications().map(|_| false)
+                }
+                AppMessage::AutosaveTick => self.autosave().map(|_| false),
             }
             .unwrap_or_else(|e| {
                 self.show_global_info(Info::new("ERROR".to_string(), e.to_string()));
@@ -214,8 +498,92 @@ impl<T: Frontend> App<T> {
         self.layout.components()
     }
 
+    /// Handle the key event `input` with `handle`, recording `input` into the macro being recorded
+    /// if it is not the result of another input.
+    ///
+    /// Only key events are recorded, so that the dispatches of the message loop,
+    /// such as LSP notifications or search results, are not replayed.
+    fn record_input<R>(&mut self, input: Dispatch, handle: impl FnOnce(&mut Self) -> R) -> R {
+        if self.input_depth == 0 {
+            if let Some((_, inputs)) = &mut self.recording_macro {
+                inputs.push(input)
+            }
+        }
+        self.input_depth += 1;
+        let result = handle(self);
+        self.input_depth -= 1;
+        result
+    }
+
+    /// Handle `dispatches` that are not caused by the inputs of the user,
+    /// so that they are not recorded into the macro being recorded
+    fn handle_internal_dispatches(&mut self, dispatches: Dispatches) -> anyhow::Result<()> {
+        self.input_depth += 1;
+        let result = self.handle_dispatches(dispatches);
+        self.input_depth -= 1;
+        result
+    }
+
+    fn start_recording_macro(&mut self, register: char) {
+        self.recording_macro = Some((register, Vec::new()))
+    }
+
+    fn stop_recording_macro(&mut self) {
+        if let Some((register, inputs)) = self.recording_macro.take() {
+            self.macros.insert(register, inputs);
+        }
+    }
+
+    /// Replay the inputs recorded in `register`, which are undone as a single entry
+    fn replay_macro(&mut self, register: char) -> anyhow::Result<()> {
+        let Some(inputs) = self.macros.get(&register).cloned() else {
+            return Err(anyhow::anyhow!(
+                "No macro is recorded in register {register:?}"
+            ));
+        };
+        if self.replaying_macros.contains(&register) {
+            return Err(anyhow::anyhow!(
+                "The macro in register {register:?} cannot replay itself"
+            ));
+        }
+        let component = self.current_component();
+        let undo_group_depth = {
+            let mut component = component.borrow_mut();
+            let editor = component.editor_mut();
+            let selection_set = editor.selection_set.clone();
+            let depth = editor.buffer().undo_group_depth();
+            editor.buffer_mut().begin_undo_group(selection_set);
+            depth
+        };
+        self.replaying_macros.push(register);
+        let result = inputs
+            .into_iter()
+            .try_for_each(|input| self.handle_dispatch(input));
+        self.replaying_macros.pop();
+        let mut component = component.borrow_mut();
+        let editor = component.editor_mut();
+        let selection_set = editor.selection_set.clone();
+        if result.is_ok() {
+            editor.buffer_mut().end_undo_group(selection_set);
+        } else {
+            // The failed input might have begun undo groups that it never ended
+            editor
+                .buffer_mut()
+                .end_undo_groups_until(undo_group_depth, selection_set);
+        }
+        result
+    }
+
     /// Returns true if the app should quit.
     fn handle_event(&mut self, event: Event) -> anyhow::Result<bool> {
+        let Event::Key(key_event) = &event else {
+            return self.handle_event_(event);
+        };
+        let input = Dispatch::HandleKeyEvent(key_event.clone());
+        self.record_input(input, |app| app.handle_event_(event))
+    }
+
+    fn handle_event_(&mut self, event: Event) -> anyhow::Result<bool> {
         // Pass event to focused window
         let component = self.current_component();
         self.context
@@ -298,32 +666,10 @@ impl<T: Frontend> App<T> {
 
         // Set the global title
         let global_title_window = {
-            let title = self.global_title.clone().unwrap_or_else(|| {
-                self.status_line_components
-                    .iter()
-                    .filter_map(|component| match component {
-                        StatusLineComponent::CurrentWorkingDirectory => {
-                            Some(self.working_directory.display_absolute())
-                        }
-                        StatusLineComponent::GitBranch => self.current_branch(),
-                        StatusLineComponent::Mode => Some(
-                            self.context
-                                .mode()
-                                .map(|mode| mode.display())
-                                .unwrap_or_else(|| {
-                                    self.current_component().borrow().editor().display_mode()
-                                }),
-                        ),
-                        StatusLineComponent::SelectionMode => Some(
-                            self.current_component()
-                                .borrow()
-                                .editor()
-                                .display_selection_mode(),
-                        ),
-                        StatusLineComponent::LastDispatch => self.last_action_description.clone(),
-                    })
-                    .join(" │ ")
-            });
+            let title = self
+                .global_title
+                .clone()
+                .unwrap_or_else(|| self.status_line_segments().join(" │ "));
             let title = format!(" {}", title);
             let grid = Grid::new(Dimension {
                 height: 1,
@@ -357,14 +703,94 @@ impl<T: Frontend> App<T> {
         Ok(screen)
     }
 
+    /// Returns the rendered content of each configured status line component, in order.
+    /// Components without content (e.g. the git branch outside of a git repository) are omitted.
+    pub(crate) fn status_line_segments(&self) -> Vec<String> {
+        let component = self.current_component();
+        let component = component.borrow();
+        let editor = component.editor();
+        self.status_line_components
+            .iter()
+            .filter_map(|status_line_component| match status_line_component {
+                StatusLineComponent::CurrentWorkingDirectory => {
+                    Some(self.working_directory.display_absolute())
+                }
+                StatusLineComponent::GitBranch => self.current_branch(),
+                StatusLineComponent::Mode => Some(
+                    self.context
+                        .mode()
+                        .map(|mode| mode.display())
+                        .unwrap_or_else(|| editor.display_mode()),
+                ),
+                StatusLineComponent::SelectionMode => Some(editor.display_selection_mode()),
+                StatusLineComponent::LastDispatch => self.last_action_description.clone(),
+                StatusLineComponent::CurrentFilePath => editor.path().map(|path| {
+                    path.display_relative_to(&self.working_directory)
+                        .unwrap_or_else(|_| path.display_absolute())
+                }),
+                StatusLineComponent::SelectionCount => {
+                    Some(format!("{} sel", editor.selection_set.len()))
+                }
+                StatusLineComponent::CursorPosition => {
+                    let position = component.get_cursor_position().ok()?;
+                    Some(format!("{}:{}", position.line + 1, position.column + 1))
+                }
+                StatusLineComponent::Diagnostics => {
+                    let diagnostics = editor.buffer().diagnostics();
+                    let count = |severity: lsp_types::DiagnosticSeverity| {
+                        diagnostics
+                            .iter()
+                            .filter(|diagnostic| diagnostic.severity == Some(severity))
+                            .count()
+                    };
+                    Some(format!(
+                        "E:{} W:{}",
+                        count(lsp_types::DiagnosticSeverity::ERROR),
+                        count(lsp_types::DiagnosticSeverity::WARNING)
+                    ))
+                }
+            })
+            .chain(self.buffer_stats.and_then(|exclude_markdown_syntax| {
+                let key = (
+                    component.id(),
+                    editor.buffer().revision(),
+                    exclude_markdown_syntax,
+                );
+                let mut cache = self.buffer_stats_cache.borrow_mut();
+                if cache.as_ref().map(|(cached_key, _)| cached_key) != Some(&key) {
+                    let stats =
+                        BufferStats::new(&editor.buffer().content(), exclude_markdown_syntax);
+                    *cache = Some((key, stats));
+                }
+                cache
+                    .as_ref()
+                    .map(|(_, stats)| stats.display(self.words_per_minute))
+            }))
+            .collect()
+    }
+
     fn current_branch(&self) -> Option<String> {
-        // Open the repository
-        let repo = git2::Repository::open(self.working_directory.display_absolute()).ok()?;
+        self.git_status
+            .as_ref()
+            .map(|git_status| git_status.display())
+    }
+
+    /// Scan the repositories again, since their files might have been changed
+    fn refresh_git_status(&mut self) {
+        self.git_statuses.clear();
+        let path = self
+            .get_current_file_path()
+            .unwrap_or_else(|| self.working_directory.clone());
+        self.update_git_status(&path)
+    }
 
-        // Get the current branch
-        let head = repo.head().ok()?;
-        let branch = head.shorthand()?;
-        Some(branch.to_string())
+    fn update_git_status(&mut self, path: &CanonicalizedPath) {
+        self.git_status = git::repository_root(path).and_then(|root| {
+            self.git_statuses
+                .entry(root.clone())
+                .or_insert_with(|| git::GitStatus::of(&root))
+                .clone()
+        })
     }
 
     fn render_screen(&mut self, screen: Screen) -> Result<(), anyhow::Error> {
@@ -397,13 +823,22 @@ impl<T: Frontend> App<T> {
     }
 
     fn get_request_params(&self) -> Option<RequestParams> {
+        self.get_current_editor_request_params(Editor::get_request_params)
+    }
+
+    /// Used by requests that target the symbol under the primary selection, such as rename and hover.
+    fn get_selection_request_params(&self) -> Option<RequestParams> {
+        self.get_current_editor_request_params(Editor::get_selection_request_params)
+    }
+
+    fn get_current_editor_request_params(
+        &self,
+        get: impl Fn(&Editor) -> Option<RequestParams>,
+    ) -> Option<RequestParams> {
         if self.current_component().borrow().type_id() != TypeId::of::<SuggestiveEditor>() {
             None
         } else {
-            self.current_component()
-                .borrow()
-                .editor()
-                .get_request_params()
+            get(self.current_component().borrow().editor())
         }
     }
 
@@ -432,6 +867,32 @@ impl<T: Frontend> App<T> {
             Dispatch::OpenFilePicker(kind) => {
                 self.open_file_picker(kind)?;
             }
+            Dispatch::GotoAlternateFile => self.goto_alternate_file()?,
+            Dispatch::AddWorkspaceFolder(path) => self.add_workspace_folder(path)?,
+            Dispatch::RemoveWorkspaceFolder(path) => self.remove_workspace_folder(path)?,
+            Dispatch::SetExtraIgnores(extra_ignores) => {
+                self.context.set_extra_ignores(extra_ignores)
+            }
+            Dispatch::OpenSetExtraIgnoresPrompt => self.open_set_extra_ignores_prompt()?,
+            Dispatch::ToggleIncludeIgnoredFiles => self.context.toggle_include_ignored_files(),
+            Dispatch::SetStatusLineSegments(components) => self.status_line_components = components,
+            Dispatch::SetUndoLimit(limit) => self.set_undo_limit(limit)?,
+            Dispatch::SetDiagnosticMinSeverity(severity) => self
+                .current_component()
+                .borrow_mut()
+                .editor_mut()
+                .buffer_mut()
+                .set_diagnostic_min_severity(severity),
+            Dispatch::OpenSetUndoLimitPrompt => self.open_set_undo_limit_prompt()?,
+            Dispatch::SetClipboardHistoryDepth(depth) => {
+                self.context.set_clipboard_history_depth(depth)?
+            }
+            Dispatch::OpenSetClipboardHistoryDepthPrompt => {
+                self.open_set_clipboard_history_depth_prompt()?
+            }
+            Dispatch::OpenSetStatusLineSegmentsPrompt => {
+                self.open_set_status_line_segments_prompt()?
+            }
             Dispatch::RequestCompletion => {
                 if let Some(params) = self.get_request_params() {
                     self.lsp_manager.send_message(
@@ -442,10 +903,11 @@ impl<T: Frontend> App<T> {
             }
             Dispatch::ResolveCompletionItem(completion_item) => {
                 if let Some(params) = self.get_request_params() {
-                    self.lsp_manager.send_message(
+                    self.lsp_manager.send_message_to_server(
                         params.path.clone(),
+                        completion_item.server_name.as_deref(),
                         FromEditor::CompletionItemResolve {
-                            completion_item,
+                            completion_item: completion_item.completion_item(),
                             params,
                         },
                     )?
@@ -473,8 +935,9 @@ impl<T: Frontend> App<T> {
                     )?;
                 }
             }
+            Dispatch::GotoReference(direction) => self.goto_reference(direction)?,
             Dispatch::RequestHover => {
-                if let Some(params) = self.get_request_params() {
+                if let Some(params) = self.get_selection_request_params() {
                     let params = params.set_description("Hover");
                     self.lsp_manager
                         .send_message(params.path.clone(), FromEditor::TextDocumentHover(params))?;
@@ -489,6 +952,18 @@ impl<T: Frontend> App<T> {
                     )?;
                 }
             }
+            Dispatch::PeekDefinition => {
+                self.request_definitions_with_context(|context| ResponseContext {
+                    peek: true,
+                    ..context
+                })?
+            }
+            Dispatch::GotoDefinitionInSplit => {
+                self.request_definitions_with_context(|context| ResponseContext {
+                    open_in_split: true,
+                    ..context
+                })?
+            }
             Dispatch::RequestDeclarations(scope) => {
                 if let Some(params) = self.get_request_params() {
                     let params = params.set_kind(Some(scope)).set_description("Declarations");
@@ -520,9 +995,16 @@ impl<T: Frontend> App<T> {
                     )?;
                 }
             }
-            Dispatch::RequestDocumentSymbols => {
+            Dispatch::RequestDocumentSymbols { kinds } => {
                 if let Some(params) = self.get_request_params() {
-                    let params = params.set_description("Document Symbols");
+                    let params = RequestParams {
+                        context: ResponseContext {
+                            symbol_kinds: kinds,
+                            ..params.context.clone()
+                        },
+                        ..params
+                    }
+                    .set_description("Document Symbols");
                     self.lsp_manager.send_message(
                         params.path.clone(),
                         FromEditor::TextDocumentDocumentSymbol(params),
@@ -530,7 +1012,7 @@ impl<T: Frontend> App<T> {
                 }
             }
             Dispatch::PrepareRename => {
-                if let Some(params) = self.get_request_params() {
+                if let Some(params) = self.get_selection_request_params() {
                     self.lsp_manager.send_message(
                         params.path.clone(),
                         FromEditor::TextDocumentPrepareRename(params),
@@ -538,7 +1020,7 @@ impl<T: Frontend> App<T> {
                 }
             }
             Dispatch::RenameSymbol { new_name } => {
-                if let Some(params) = self.get_request_params() {
+                if let Some(params) = self.get_selection_request_params() {
                     self.lsp_manager.send_message(
                         params.path.clone(),
                         FromEditor::TextDocumentRename { params, new_name },
@@ -546,16 +1028,60 @@ impl<T: Frontend> App<T> {
                 }
             }
             Dispatch::RequestCodeAction { diagnostics } => {
+                self.code_actions = Default::default();
                 if let Some(params) = self.get_request_params() {
+                    let range = self
+                        .current_component()
+                        .borrow()
+                        .editor()
+                        .primary_selection_position_range()?;
                     self.lsp_manager.send_message(
                         params.path.clone(),
                         FromEditor::TextDocumentCodeAction {
                             params,
+                            range,
                             diagnostics,
                         },
                     )?;
                 }
             }
+            Dispatch::RequestSelectionRanges => {
+                if let Some(params) = self.get_request_params() {
+                    if self.lsp_manager.is_running(&params.path) {
+                        self.lsp_manager.send_message(
+                            params.path.clone(),
+                            FromEditor::TextDocumentSelectionRange(params),
+                        )?;
+                    } else {
+                        self.handle_dispatch_editor(SetSemanticRanges(Vec::new()))?;
+                    }
+                }
+            }
+            Dispatch::Format => {
+                if let Some(params) = self.get_request_params() {
+                    let tab_size = self
+                        .current_component()
+                        .borrow()
+                        .editor()
+                        .buffer()
+                        .options()
+                        .tab_width;
+                    self.lsp_manager.send_message(
+                        params.path.clone(),
+                        FromEditor::TextDocumentFormatting { params, tab_size },
+                    )?;
+                }
+            }
+            Dispatch::RefreshDiagnostics => {
+                if let Some(params) = self.get_request_params() {
+                    self.lsp_manager.send_message(
+                        params.path.clone(),
+                        FromEditor::TextDocumentDiagnostic {
+                            file_path: params.path,
+                        },
+                    )?;
+                }
+            }
             Dispatch::RequestSignatureHelp => {
                 if let Some(params) = self.get_request_params() {
                     self.lsp_manager.send_message(
@@ -570,11 +1096,16 @@ impl<T: Frontend> App<T> {
                 language,
                 component_id,
             } => {
+                // The cached references are stale once any document changes
+                self.references_cache.clear();
                 if let Some(language) = language {
                     self.request_syntax_highlight(component_id, language, content.clone())?;
                     // let highlight_spans = self.context.highlight(language, &content)?;
                     // self.update_highlighted_spans(component_id, highlight_spans)?
                 }
+                if let Some(autosave) = &mut self.autosave {
+                    autosave.last_edit = Some((self.clock)())
+                }
                 if let Some(path) = path {
                     self.lsp_manager.send_message(
                         path.clone(),
@@ -586,7 +1117,15 @@ impl<T: Frontend> App<T> {
                     )?;
                 }
             }
+            Dispatch::BufferChanged(changes) => {
+                for change in &changes {
+                    for observer in self.buffer_change_observers.iter_mut() {
+                        observer(change)
+                    }
+                }
+            }
             Dispatch::DocumentDidSave { path } => {
+                self.refresh_git_status();
                 self.lsp_manager.send_message(
                     path.clone(),
                     FromEditor::TextDocumentDidSave { file_path: path },
@@ -597,6 +1136,13 @@ impl<T: Frontend> App<T> {
                 self.set_quickfix_list_type(Default::default(), r#type)?;
             }
             Dispatch::GotoQuickfixListItem(movement) => self.goto_quickfix_list_item(movement)?,
+            Dispatch::SelectionsToQuickfix => self.selections_to_quickfix()?,
+            Dispatch::ToggleQuickfixPreview => self.toggle_quickfix_preview()?,
+            Dispatch::CancelGlobalSearch => self.cancel_global_search(),
+            Dispatch::ReplaceAllInResults(replacement) => {
+                self.replace_all_in_results(replacement)?
+            }
+            Dispatch::OpenReplaceAllInResultsPrompt => self.open_replace_all_in_results_prompt()?,
             Dispatch::ApplyWorkspaceEdit(workspace_edit) => {
                 self.apply_workspace_edit(workspace_edit)?;
             }
@@ -612,6 +1158,13 @@ impl<T: Frontend> App<T> {
             Dispatch::OpenMoveToIndexPrompt => self.open_move_to_index_prompt()?,
             Dispatch::RunCommand(command) => self.run_command(command)?,
             Dispatch::QuitAll => self.quit_all()?,
+            Dispatch::Quit { force } => self.handle_quit(force)?,
+            Dispatch::ToggleConfirmQuit => self.context.toggle_confirm_quit(),
+            Dispatch::ToggleShowAbsolutePaths => self.context.toggle_show_absolute_paths(),
+            Dispatch::ToggleHighlightCursorLine => self.context.toggle_highlight_cursor_line(),
+            Dispatch::ToggleHighlightSelectionLines => {
+                self.context.toggle_highlight_selection_lines()
+            }
             Dispatch::OpenCommandPrompt => self.open_command_prompt()?,
             Dispatch::SaveQuitAll => self.save_quit_all()?,
             Dispatch::RevealInExplorer(path) => self.reveal_path_in_explorer(&path)?,
@@ -627,28 +1180,83 @@ impl<T: Frontend> App<T> {
             Dispatch::RefreshFileExplorer => {
                 self.layout.refresh_file_explorer(&self.working_directory)?
             }
+            Dispatch::OpenFileExplorer => {
+                let path = self
+                    .current_component()
+                    .borrow()
+                    .path()
+                    .unwrap_or_else(|| self.working_directory.clone());
+                self.reveal_path_in_explorer(&path)?
+            }
+            Dispatch::CloseFileExplorer => self.close_file_explorer()?,
+            Dispatch::SaveSession(path) => self.save_session(&path)?,
+            Dispatch::LoadSession(path) => self.load_session(&path)?,
+            Dispatch::OpenAlignSelectionsPrompt => self.open_align_selections_prompt()?,
+            Dispatch::OpenSaveSessionPrompt => self.open_session_prompt(true)?,
+            Dispatch::OpenLoadSessionPrompt => self.open_session_prompt(false)?,
+            Dispatch::ToggleFileExplorerHiddenFiles => {
+                self.layout.toggle_file_explorer_hidden_files()?
+            }
+            Dispatch::ToggleFileExplorerIgnoredFiles => {
+                self.layout.toggle_file_explorer_ignored_files()?
+            }
             Dispatch::SetClipboardContent {
                 copied_texts: contents,
                 use_system_clipboard,
             } => self
                 .context
                 .set_clipboard_content(contents, use_system_clipboard)?,
+            Dispatch::SetRegisterContent {
+                register,
+                copied_texts,
+            } => self.context.set_register_content(register, copied_texts),
+            Dispatch::RecordDeletion {
+                copied_texts,
+                small,
+            } => self.context.record_deletion(copied_texts, small),
+            Dispatch::OpenRegisterPrompt(operation) => self.open_register_prompt(operation)?,
             Dispatch::SetGlobalMode(mode) => self.set_global_mode(mode),
 
-            #[cfg(test)]
             Dispatch::HandleKeyEvent(key_event) => {
                 self.handle_event(Event::Key(key_event))?;
             }
+            Dispatch::SetMark(name) => self.set_mark(name)?,
+            Dispatch::GoToMark(name) => self.go_to_mark(name)?,
+            Dispatch::StartRecordingMacro(register) => self.start_recording_macro(register),
+            Dispatch::StopRecordingMacro => self.stop_recording_macro(),
+            Dispatch::ReplayMacro(register) => self.replay_macro(register)?,
             Dispatch::GetRepoGitHunks(diff_mode) => self.get_repo_git_hunks(diff_mode)?,
             Dispatch::SaveAll => self.save_all()?,
+            Dispatch::EnableAutosave(interval) => self.enable_autosave(interval)?,
+            Dispatch::DisableAutosave => self.disable_autosave(),
+            Dispatch::OpenEnableAutosavePrompt => self.open_enable_autosave_prompt()?,
+            Dispatch::ToggleAutosaveInInsertMode => {
+                self.autosave_in_insert_mode = !self.autosave_in_insert_mode
+            }
+            Dispatch::ReloadFile => {
+                let path = self.current_component().borrow().path();
+                let dispatches = match path {
+                    Some(path) => self.layout.reload_buffer(&path)?,
+                    None => self
+                        .current_component()
+                        .borrow_mut()
+                        .editor_mut()
+                        .reload()?,
+                };
+                self.handle_dispatches(dispatches)?
+            }
             #[cfg(test)]
             Dispatch::TerminalDimensionChanged(dimension) => self.resize(dimension),
             #[cfg(test)]
             Dispatch::SetGlobalTitle(title) => self.set_global_title(title),
-            Dispatch::LspExecuteCommand { command } => {
+            Dispatch::LspExecuteCommand {
+                command,
+                server_name,
+            } => {
                 if let Some(params) = self.get_request_params() {
-                    self.lsp_manager.send_message(
+                    self.lsp_manager.send_message_to_server(
                         params.path.clone(),
+                        server_name.as_deref(),
                         FromEditor::WorkspaceExecuteCommand { params, command },
                     )?
                 };
@@ -719,11 +1327,15 @@ impl<T: Frontend> App<T> {
             } => self.open_prompt(config, key, current_line)?,
             Dispatch::ShowEditorInfo(info) => self.show_editor_info(info)?,
             Dispatch::ReceiveCodeActions(code_actions) => {
-                self.open_code_actions_prompt(code_actions)?;
+                self.receive_code_actions(code_actions)?;
             }
             Dispatch::OtherWindow => self.layout.cycle_window(),
+            Dispatch::SplitVertical => self.split(LayoutKind::Tall)?,
+            Dispatch::SplitHorizontal => self.split(LayoutKind::Wide)?,
             Dispatch::GoToPreviousFile => self.go_to_previous_file()?,
             Dispatch::GoToNextFile => self.go_to_next_file()?,
+            Dispatch::JumpBack => self.jump_back()?,
+            Dispatch::JumpForward => self.jump_forward()?,
             Dispatch::PushPromptHistory { key, line } => self.push_history_prompt(key, line),
             Dispatch::OpenThemePrompt => self.open_theme_prompt()?,
             Dispatch::SetLastNonContiguousSelectionMode(selection_mode) => self
@@ -738,6 +1350,83 @@ impl<T: Frontend> App<T> {
             Dispatch::OpenFilterSelectionsPrompt { keep } => {
                 self.open_filter_selections_prompt(keep)?
             }
+            Dispatch::OpenSplitSelectionsPrompt => self.open_split_selections_prompt()?,
+            Dispatch::ApplyEditsJson(json) => self.apply_edits_json(&json)?,
+            Dispatch::DiffBuffers { left, right } => self.diff_buffers(&left, &right)?,
+            Dispatch::ApplyPatch(patch) => self.apply_patch(&patch)?,
+            Dispatch::BufferStats {
+                exclude_markdown_syntax,
+            } => self.toggle_buffer_stats(exclude_markdown_syntax),
+            Dispatch::SetWordsPerMinute(words_per_minute) => {
+                self.words_per_minute = words_per_minute
+            }
+            Dispatch::OpenSetWordsPerMinutePrompt => self.open_set_words_per_minute_prompt()?,
+            Dispatch::ToggleConfirmDelete => self.context.toggle_confirm_delete(),
+            Dispatch::ToggleWrapAroundMovements => self.context.toggle_wrap_around_movements(),
+            Dispatch::SetMaxLineLength(max_line_length) => {
+                self.context.set_max_line_length(max_line_length)
+            }
+            Dispatch::OpenSetMaxLineLengthPrompt => self.open_set_max_line_length_prompt()?,
+            Dispatch::SetFoldMarkers { start, end } => self.context.set_fold_markers(start, end),
+            Dispatch::SetSearch(search) => self
+                .context
+                .update_local_search_config(LocalSearchConfigUpdate::Search(search), Scope::Local),
+            Dispatch::OpenSetSearchPrompt => self.open_set_search_prompt()?,
+            Dispatch::RunWithSelection { command_template } => {
+                self.run_with_selection(&command_template)
+            }
+            Dispatch::OpenRunWithSelectionPrompt => self.open_run_with_selection_prompt()?,
+            Dispatch::ClearSearchHighlight => self.context.clear_search_highlight(),
+            Dispatch::SetSearchPreview(search_preview) => {
+                self.context.set_search_preview(search_preview)
+            }
+            Dispatch::OpenSetBufferVarPrompt => self.open_set_buffer_var_prompt()?,
+            Dispatch::OpenSetFoldMarkersPrompt => self.open_set_fold_markers_prompt()?,
+            Dispatch::SetSelectionModeCycle(selection_modes) => {
+                self.context.set_selection_mode_cycle(selection_modes)
+            }
+            Dispatch::OpenSurroundWithPrompt => self.open_surround_with_prompt()?,
+            Dispatch::OpenSelectNextNodeOfKindPrompt => {
+                self.open_select_next_node_of_kind_prompt()?
+            }
+            Dispatch::OpenSetSelectionModeCyclePrompt => {
+                self.open_set_selection_mode_cycle_prompt()?
+            }
+            Dispatch::GotoNextMatch => self.goto_match(Movement::Next)?,
+            Dispatch::GotoPreviousMatch => self.goto_match(Movement::Previous)?,
+            Dispatch::UpdateLanguageConfig(configs) => {
+                self.lsp_manager.update_language_configs(configs)?
+            }
+            Dispatch::OpenUpdateLanguageConfigPrompt => {
+                self.open_update_language_config_prompt(false)?
+            }
+            Dispatch::SetLanguageServer {
+                language_id,
+                command,
+                args,
+                additional,
+            } => self
+                .lsp_manager
+                .set_language_server(language_id, command, args, additional)?,
+            Dispatch::OpenAddLanguageServerPrompt => {
+                self.open_update_language_config_prompt(true)?
+            }
+            Dispatch::OpenInsertAtEachSelectionPrompt => {
+                self.open_insert_at_each_selection_prompt()?
+            }
+            Dispatch::ApplyPatchFromClipboard => {
+                if let Some(copied_texts) = self.context.get_clipboard_content(false, 0)? {
+                    self.handle_dispatch(Dispatch::ApplyPatch(copied_texts.get(0)))?
+                }
+            }
+            Dispatch::OpenDiffBuffersPrompt => self.open_diff_buffers_prompt()?,
+            Dispatch::OpenApplyEditsJsonPrompt => self.open_apply_edits_json_prompt()?,
+            Dispatch::OpenReplaceAllMatchingPrompt { regex } => {
+                self.open_replace_all_matching_prompt(regex)?
+            }
+            Dispatch::OpenReplaceAllMatchingReplacementPrompt { pattern, regex } => {
+                self.open_replace_all_matching_replacement_prompt(pattern, regex)?
+            }
         }
         Ok(())
     }
@@ -747,6 +1436,23 @@ impl<T: Frontend> App<T> {
     }
 
     fn close_current_window(&mut self) {
+        // The marks of a closed file are no longer tracked by its buffer
+        self.named_marks = self
+            .named_marks
+            .keys()
+            .filter_map(|&name| Some((name, self.named_mark_location(name)?)))
+            .collect();
+        let (path, selection_mode) = {
+            let component = self.current_component();
+            let component = component.borrow();
+            (
+                component.path(),
+                component.editor().selection_set.mode.clone(),
+            )
+        };
+        if let Some(path) = path {
+            self.selection_modes.insert(path, selection_mode);
+        }
         self.layout.close_current_window()
     }
 
@@ -771,127 +1477,321 @@ impl<T: Frontend> App<T> {
         Ok(())
     }
 
-    fn resize(&mut self, dimension: Dimension) {
-        self.layout
-            .set_terminal_dimension(dimension.decrement_height(GLOBAL_TITLE_BAR_HEIGHT));
+    /// Go to the next or previous match of the last local search in the focused editor,
+    /// which is shared by all buffers.
+    fn goto_match(&mut self, movement: Movement) -> anyhow::Result<()> {
+        let Some(search) = self.context.local_search_config().last_search() else {
+            return Ok(());
+        };
+        let selection_mode = SelectionMode::Find { search };
+        let is_searching = self
+            .current_component()
+            .borrow()
+            .editor()
+            .selection_set
+            .mode
+            == selection_mode;
+        self.handle_dispatch_editor(if is_searching {
+            MoveSelection(movement)
+        } else if matches!(movement, Movement::Previous) {
+            SetSelectionMode(IfCurrentNotFound::LookBackward, selection_mode)
+        } else {
+            SetSelectionMode(IfCurrentNotFound::LookForward, selection_mode)
+        })
     }
 
-    fn open_move_to_index_prompt(&mut self) -> anyhow::Result<()> {
+    fn open_register_prompt(&mut self, operation: RegisterOperation) -> anyhow::Result<()> {
         self.open_prompt(
             PromptConfig {
-                title: "Move to index".to_string(),
-                on_enter: DispatchPrompt::MoveSelectionByIndex,
-                items: vec![],
+                title: format!("{} (register name)", operation.display()),
+                items: Default::default(),
+                on_enter: DispatchPrompt::Register(operation),
                 enter_selects_first_matching_item: false,
-                leaves_current_line_empty: true,
+                leaves_current_line_empty: false,
                 fire_dispatches_on_change: None,
             },
-            PromptHistoryKey::MoveToIndex,
+            PromptHistoryKey::Register,
             None,
         )
     }
 
-    fn open_rename_prompt(&mut self, current_name: Option<String>) -> anyhow::Result<()> {
+    fn open_surround_with_prompt(&mut self) -> anyhow::Result<()> {
         self.open_prompt(
             PromptConfig {
-                title: "Rename Symbol".to_string(),
-                on_enter: DispatchPrompt::RenameSymbol,
-                items: vec![],
+                title: "Surround with (character)".to_string(),
+                items: Default::default(),
+                on_enter: DispatchPrompt::SurroundWith,
                 enter_selects_first_matching_item: false,
                 leaves_current_line_empty: false,
                 fire_dispatches_on_change: None,
             },
-            PromptHistoryKey::Rename,
-            current_name,
+            PromptHistoryKey::SurroundWith,
+            None,
         )
     }
 
-    fn open_search_prompt(
-        &mut self,
-        scope: Scope,
-        if_current_not_found: IfCurrentNotFound,
-    ) -> anyhow::Result<()> {
-        let config = self.context.get_local_search_config(scope);
-        let mode = config.mode;
+    fn open_select_next_node_of_kind_prompt(&mut self) -> anyhow::Result<()> {
         self.open_prompt(
             PromptConfig {
-                title: format!("{:?} search ({})", scope, mode.display()),
-                items: self.words(),
-                on_enter: DispatchPrompt::UpdateLocalSearchConfigSearch {
-                    scope,
-                    show_config_after_enter: false,
-                    if_current_not_found,
-                },
+                title: "Select next syntax node of kind".to_string(),
+                items: Default::default(),
+                on_enter: DispatchPrompt::SelectNextNodeOfKind,
                 enter_selects_first_matching_item: false,
-                leaves_current_line_empty: true,
+                leaves_current_line_empty: false,
                 fire_dispatches_on_change: None,
             },
-            PromptHistoryKey::Search(scope),
+            PromptHistoryKey::SelectNextNodeOfKind,
             None,
         )
     }
 
-    fn open_add_path_prompt(&mut self, path: CanonicalizedPath) -> anyhow::Result<()> {
+    fn open_set_buffer_var_prompt(&mut self) -> anyhow::Result<()> {
         self.open_prompt(
             PromptConfig {
-                title: "Add path".to_string(),
-                on_enter: DispatchPrompt::AddPath,
-                items: Vec::new(),
+                title: "Set buffer variable (<key> <value>)".to_string(),
+                items: Default::default(),
+                on_enter: DispatchPrompt::SetBufferVar,
                 enter_selects_first_matching_item: false,
                 leaves_current_line_empty: false,
                 fire_dispatches_on_change: None,
             },
-            PromptHistoryKey::AddPath,
-            Some(path.display_absolute()),
+            PromptHistoryKey::BufferVar,
+            None,
         )
     }
 
-    fn open_move_file_prompt(&mut self, path: CanonicalizedPath) -> anyhow::Result<()> {
+    fn open_set_fold_markers_prompt(&mut self) -> anyhow::Result<()> {
+        let (start, end) = self.context.fold_markers();
+        let current = format!("{start} {end}");
         self.open_prompt(
             PromptConfig {
-                title: "Move path".to_string(),
-                on_enter: DispatchPrompt::MovePath { from: path.clone() },
-                items: Vec::new(),
+                title: "Set fold markers (<start> <end>)".to_string(),
+                items: Default::default(),
+                on_enter: DispatchPrompt::SetFoldMarkers,
                 enter_selects_first_matching_item: false,
                 leaves_current_line_empty: false,
                 fire_dispatches_on_change: None,
             },
-            PromptHistoryKey::MovePath,
-            Some(path.display_absolute()),
+            PromptHistoryKey::FoldMarkers,
+            Some(current),
         )
     }
 
-    fn open_symbol_picker(&mut self, symbols: Symbols) -> anyhow::Result<()> {
+    fn open_set_selection_mode_cycle_prompt(&mut self) -> anyhow::Result<()> {
         self.open_prompt(
             PromptConfig {
-                title: "Symbols".to_string(),
-                items: symbols
-                    .symbols
-                    .clone()
-                    .into_iter()
-                    .map(|symbol| symbol.into())
-                    .collect_vec(),
-                on_enter: DispatchPrompt::SelectSymbol { symbols },
-                enter_selects_first_matching_item: true,
-                leaves_current_line_empty: true,
+                title: "Set selection modes to cycle through (e.g. column word line syntax-node)"
+                    .to_string(),
+                items: Default::default(),
+                on_enter: DispatchPrompt::SetSelectionModeCycle,
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: false,
                 fire_dispatches_on_change: None,
             },
-            PromptHistoryKey::Symbol,
+            PromptHistoryKey::SelectionModeCycle,
             None,
         )
     }
 
-    fn open_command_prompt(&mut self) -> anyhow::Result<()> {
+    fn open_set_search_prompt(&mut self) -> anyhow::Result<()> {
         self.open_prompt(
             PromptConfig {
-                title: "Command".to_string(),
-                on_enter: DispatchPrompt::RunCommand,
-                items: crate::command::COMMANDS
-                    .iter()
-                    .flat_map(|command| command.to_dropdown_items())
-                    .collect(),
-                enter_selects_first_matching_item: true,
-                leaves_current_line_empty: true,
+                title: "Set search (without moving)".to_string(),
+                items: Default::default(),
+                on_enter: DispatchPrompt::SetSearch,
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: false,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::Search(Scope::Local),
+            None,
+        )
+    }
+
+    fn open_run_with_selection_prompt(&mut self) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: "Run with selection (`{selection}` is substituted)".to_string(),
+                items: Default::default(),
+                on_enter: DispatchPrompt::RunWithSelection,
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: false,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::RunWithSelection,
+            None,
+        )
+    }
+
+    /// Run `command_template` with `{selection}` substituted by the primary selection
+    /// without blocking the editor, the output is received as `AppMessage::ShellCommandOutput`.
+    fn run_with_selection(&mut self, command_template: &str) {
+        let selection = self
+            .current_component()
+            .borrow()
+            .editor()
+            .get_selected_texts()
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        // The selection is passed as an argument instead of being substituted into the script,
+        // so that the shell does not interpret it
+        let script = command_template.replace("{selection}", "\"$1\"");
+        let command = command_template.replace("{selection}", &selection);
+        let sender = self.sender.clone();
+        let shell_command_runner = self.shell_command_runner.clone();
+        std::thread::spawn(move || {
+            let output =
+                shell_command_runner(&script, &selection).map_err(|error| error.to_string());
+            sender
+                .send(AppMessage::ShellCommandOutput { command, output })
+                .unwrap_or_else(|error| {
+                    log::info!("App::run_with_selection: {:?}", error);
+                })
+        });
+    }
+
+    /// Show the output of `command` in a scratch editor next to the current one.
+    fn handle_shell_command_output(
+        &mut self,
+        command: String,
+        output: Result<String, String>,
+    ) -> anyhow::Result<()> {
+        let output =
+            output.map_err(|error| anyhow::anyhow!("Unable to run `{command}`: {error}"))?;
+        let buffer = Rc::new(RefCell::new(Buffer::new(None, &output)));
+        let editor = Rc::new(RefCell::new(SuggestiveEditor::from_buffer(
+            buffer,
+            SuggestiveEditorFilter::CurrentWord,
+        )));
+        editor
+            .borrow_mut()
+            .set_title(format!("Output of `{command}`"));
+        self.layout.add_and_focus_split(editor);
+        Ok(())
+    }
+
+    fn resize(&mut self, dimension: Dimension) {
+        self.layout
+            .set_terminal_dimension(dimension.decrement_height(GLOBAL_TITLE_BAR_HEIGHT));
+    }
+
+    fn open_move_to_index_prompt(&mut self) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: "Move to index".to_string(),
+                on_enter: DispatchPrompt::MoveSelectionByIndex,
+                items: vec![],
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: true,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::MoveToIndex,
+            None,
+        )
+    }
+
+    fn open_rename_prompt(&mut self, current_name: Option<String>) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: "Rename Symbol".to_string(),
+                on_enter: DispatchPrompt::RenameSymbol,
+                items: vec![],
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: false,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::Rename,
+            current_name,
+        )
+    }
+
+    fn open_search_prompt(
+        &mut self,
+        scope: Scope,
+        if_current_not_found: IfCurrentNotFound,
+    ) -> anyhow::Result<()> {
+        let config = self.context.get_local_search_config(scope);
+        let mode = config.mode;
+        self.open_prompt(
+            PromptConfig {
+                title: format!("{:?} search ({})", scope, mode.display()),
+                items: self.words(),
+                on_enter: DispatchPrompt::UpdateLocalSearchConfigSearch {
+                    scope,
+                    show_config_after_enter: false,
+                    if_current_not_found,
+                },
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: true,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::Search(scope),
+            None,
+        )
+    }
+
+    fn open_add_path_prompt(&mut self, path: CanonicalizedPath) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: "Add path".to_string(),
+                on_enter: DispatchPrompt::AddPath,
+                items: Vec::new(),
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: false,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::AddPath,
+            Some(path.display_absolute()),
+        )
+    }
+
+    fn open_move_file_prompt(&mut self, path: CanonicalizedPath) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: "Move path".to_string(),
+                on_enter: DispatchPrompt::MovePath { from: path.clone() },
+                items: Vec::new(),
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: false,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::MovePath,
+            Some(path.display_absolute()),
+        )
+    }
+
+    fn open_symbol_picker(&mut self, symbols: Symbols) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: "Symbols".to_string(),
+                items: symbols
+                    .symbols
+                    .clone()
+                    .into_iter()
+                    .map(|symbol| symbol.into())
+                    .collect_vec(),
+                on_enter: DispatchPrompt::SelectSymbol { symbols },
+                enter_selects_first_matching_item: true,
+                leaves_current_line_empty: true,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::Symbol,
+            None,
+        )
+    }
+
+    fn open_command_prompt(&mut self) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: "Command".to_string(),
+                on_enter: DispatchPrompt::RunCommand,
+                items: crate::command::COMMANDS
+                    .iter()
+                    .flat_map(|command| command.to_dropdown_items())
+                    .collect(),
+                enter_selects_first_matching_item: true,
+                leaves_current_line_empty: true,
                 fire_dispatches_on_change: None,
             },
             PromptHistoryKey::Command,
@@ -909,9 +1809,16 @@ impl<T: Frontend> App<T> {
                     match kind {
                         FilePickerKind::NonGitIgnored => {
                             // Note: we should not use CanonicalizedPath here, as it is resource-intensive
-                            list::WalkBuilderConfig::non_git_ignored_files(
-                                self.working_directory.clone(),
-                            )?
+                            self.workspace_roots()
+                                .into_iter()
+                                .map(|root| {
+                                    list::WalkBuilderConfig::non_git_ignored_files(
+                                        root,
+                                        self.context.ignore_config(),
+                                    )
+                                })
+                                .flatten_ok()
+                                .try_collect::<_, Vec<_>, _>()?
                         }
                         FilePickerKind::GitStatus(diff_mode) => {
                             git::GitRepo::try_from(&self.working_directory)?
@@ -926,28 +1833,18 @@ impl<T: Frontend> App<T> {
                             .into_iter()
                             .map(|path| path.into_path_buf())
                             .collect_vec(),
+                        FilePickerKind::Alternate(paths) => paths
+                            .into_iter()
+                            .map(|path| path.into_path_buf())
+                            .collect_vec(),
                     }
                     .into_iter()
                     .map(|path| {
-                        DropdownItem::new({
-                            let name = path
-                                .file_name()
-                                .unwrap_or_default()
-                                .to_string_lossy()
-                                .to_string();
-                            let icon = shared::canonicalized_path::get_path_icon(&path);
-                            format!("{icon} {name}")
-                        })
-                        .set_group(path.parent().map(|parent| {
-                            let relative = parent
-                                .strip_prefix(&self.working_directory)
-                                .map(|path| path.display().to_string())
-                                .unwrap_or_else(|_| parent.display().to_string());
-                            format!("{} {}", shared::icons::get_icon_config().folder, relative,)
-                        }))
-                        .set_dispatches(Dispatches::one(
-                            crate::app::Dispatch::OpenFileFromPathBuf(path),
-                        ))
+                        DropdownItem::from_path(
+                            path,
+                            &self.working_directory,
+                            self.context.show_absolute_paths(),
+                        )
                     })
                     .collect_vec()
                 },
@@ -960,6 +1857,26 @@ impl<T: Frontend> App<T> {
         )
     }
 
+    /// Open the alternate file of the current file, refer `crate::alternate_file::rules`.
+    /// A file picker is shown if there are multiple alternate files.
+    fn goto_alternate_file(&mut self) -> anyhow::Result<()> {
+        let Some(path) = self.get_current_file_path() else {
+            return Ok(());
+        };
+        let rules = crate::alternate_file::rules(&self.working_directory);
+        let alternate_files = crate::alternate_file::alternate_files(&path, &rules)?;
+        match alternate_files.len() {
+            0 => Err(anyhow::anyhow!(
+                "No alternate file found for {}",
+                path.try_display_relative()
+            )),
+            1 => self
+                .open_file(&alternate_files[0], OpenFileOption::Focus)
+                .map(|_| ()),
+            _ => self.open_file_picker(FilePickerKind::Alternate(alternate_files)),
+        }
+    }
+
     /// This only opens the file in the background but does not focus it.
     /// If you need to focus it, use `Self::go_to_file` instead.
     fn open_file(
@@ -970,17 +1887,45 @@ impl<T: Frontend> App<T> {
         if option.store_history() {
             self.file_path_history.push(path.clone())
         }
+        if option.is_focus() {
+            self.update_git_status(path)
+        }
         // Check if the file is opened before
         // so that we won't notify the LSP twice
         if let Some(matching_editor) = self.layout.open_file(path, option.is_focus()) {
             return Ok(matching_editor);
         }
 
-        let buffer = Buffer::from_path(path, true)?;
+        let is_large = std::fs::metadata(path.to_path_buf())
+            .map(|metadata| metadata.len() >= self.background_loading_min_file_size)
+            .unwrap_or(false);
+        let mut buffer = if is_large {
+            self.load_file_in_background(path.clone());
+            Buffer::loading(path)
+        } else {
+            Buffer::from_path(path, true)?
+        };
+        if let Some(limit) = self.undo_limit {
+            buffer.set_undo_limit(limit)?
+        }
+        if !self.buffer_change_observers.is_empty() {
+            buffer.record_changes()
+        }
+        // Otherwise they are restored after loading, refer `App::handle_file_loaded`
+        if !buffer.is_loading() {
+            self.restore_named_marks(&mut buffer);
+        }
         let language = buffer.language();
         let content = buffer.content();
+        let is_loading = buffer.is_loading();
         let buffer = Rc::new(RefCell::new(buffer));
-        let editor = SuggestiveEditor::from_buffer(buffer, SuggestiveEditorFilter::CurrentWord);
+        let mut editor = SuggestiveEditor::from_buffer(buffer, SuggestiveEditorFilter::CurrentWord);
+        if let Some(selection_mode) = self.selection_modes.get(path).cloned() {
+            let dispatches = editor
+                .editor_mut()
+                .set_selection_mode(IfCurrentNotFound::LookForward, selection_mode)?;
+            self.handle_dispatches(dispatches)?;
+        }
         let component_id = editor.id();
         let component = Rc::new(RefCell::new(editor));
 
@@ -991,13 +1936,257 @@ impl<T: Frontend> App<T> {
                 .replace_and_focus_current_suggestive_editor(component.clone())
         }
 
+        // Otherwise they are done after loading, refer `App::handle_file_loaded`
+        if !is_loading {
+            if let Some(language) = language {
+                self.request_syntax_highlight(component_id, language, content)?;
+            }
+            if self.enable_lsp {
+                self.lsp_manager.open_file(path.clone())?;
+            }
+        }
+        Ok(component)
+    }
+
+    /// Read the content of `path` without blocking the editor,
+    /// the content is received as `AppMessage::FileLoaded`.
+    fn load_file_in_background(&self, path: CanonicalizedPath) {
+        let sender = self.sender.clone();
+        let file_reader = self.file_reader.clone();
+        std::thread::spawn(move || {
+            let content = file_reader(&path).map_err(|error| error.to_string());
+            sender
+                .send(AppMessage::FileLoaded { path, content })
+                .unwrap_or_else(|error| {
+                    log::info!("App::load_file_in_background: {:?}", error);
+                })
+        });
+    }
+
+    fn enable_autosave(&mut self, interval: Duration) -> anyhow::Result<()> {
+        if interval.is_zero() {
+            return Err(anyhow::anyhow!("The autosave interval must be positive"));
+        }
+        self.disable_autosave();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let sender = self.sender.clone();
+        std::thread::spawn({
+            let cancelled = cancelled.clone();
+            // Ticking at half of the interval writes the buffers
+            // at most one and a half intervals after the last edit
+            move || loop {
+                std::thread::sleep(interval / 2);
+                if cancelled.load(Ordering::Relaxed)
+                    || sender.send(AppMessage::AutosaveTick).is_err()
+                {
+                    break;
+                }
+            }
+        });
+        self.autosave = Some(Autosave {
+            interval,
+            last_edit: None,
+            cancelled,
+        });
+        Ok(())
+    }
+
+    fn disable_autosave(&mut self) {
+        if let Some(autosave) = self.autosave.take() {
+            autosave.cancelled.store(true, Ordering::Relaxed)
+        }
+    }
+
+    /// Write the modified buffers if no edit is made for the autosave interval,
+    /// so that rapid edits are coalesced into a single write.
+    ///
+    /// The buffers are written without formatting, so that the content being edited is not changed.
+    /// If any buffer fails to be written, it is retried after another interval.
+    pub(crate) fn autosave(&mut self) -> anyhow::Result<()> {
+        let now = (self.clock)();
+        let Some(autosave) = &self.autosave else {
+            return Ok(());
+        };
+        if !autosave
+            .last_edit
+            .is_some_and(|last_edit| now.duration_since(last_edit) >= autosave.interval)
+        {
+            return Ok(());
+        }
+        if !self.autosave_in_insert_mode
+            && matches!(
+                self.current_component().borrow().editor().mode,
+                crate::components::editor::Mode::Insert | crate::components::editor::Mode::Overtype
+            )
+        {
+            return Ok(());
+        }
+        let mut failed = false;
+        for buffer in self.layout.buffers() {
+            let path = {
+                let mut buffer = buffer.borrow_mut();
+                if !buffer.is_dirty() {
+                    continue;
+                }
+                // A buffer failing to be saved should not keep the other buffers unsaved
+                match buffer.save_without_formatting() {
+                    Ok(path) => path,
+                    Err(error) => {
+                        log::error!("Failed to autosave {:?}: {error}", buffer.path());
+                        failed = true;
+                        continue;
+                    }
+                }
+            };
+            if let Some(path) = path {
+                self.handle_internal_dispatches(Dispatches::one(Dispatch::DocumentDidSave {
+                    path,
+                }))?
+            }
+        }
+        if let Some(autosave) = &mut self.autosave {
+            // The failed buffers are saved again after another interval
+            autosave.last_edit = failed.then_some(now)
+        }
+        Ok(())
+    }
+
+    fn open_enable_autosave_prompt(&mut self) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: "Enable autosave after idle milliseconds".to_string(),
+                items: Default::default(),
+                on_enter: DispatchPrompt::EnableAutosave,
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: false,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::AutosaveInterval,
+            self.autosave
+                .as_ref()
+                .map(|autosave| autosave.interval.as_millis().to_string()),
+        )
+    }
+
+    #[cfg(test)]
+    pub(crate) fn set_clock(&mut self, clock: Clock) {
+        self.clock = clock
+    }
+
+    /// Send `AppMessage::CheckExternalModifications` periodically until the app is dropped
+    fn watch_external_modifications(&self) {
+        let sender = self.sender.clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(EXTERNAL_MODIFICATIONS_CHECK_INTERVAL);
+            if sender.send(AppMessage::CheckExternalModifications).is_err() {
+                break;
+            }
+        });
+    }
+
+    /// Reload the buffers of the files modified by other programs,
+    /// unless they have unsaved changes, in which case a prompt is shown instead.
+    pub(crate) fn handle_external_modifications(&mut self) -> anyhow::Result<()> {
+        let mut is_modified = false;
+        for buffer in self.layout.buffers() {
+            let (path, is_dirty) = {
+                let mut buffer = buffer.borrow_mut();
+                if !buffer.poll_external_modification() {
+                    continue;
+                }
+                is_modified = true;
+                let Some(path) = buffer.path() else {
+                    continue;
+                };
+                (path, buffer.is_dirty())
+            };
+            if is_dirty {
+                self.open_reload_file_prompt(path)?
+            } else {
+                let dispatches = self.layout.reload_buffer(&path)?;
+                self.handle_internal_dispatches(dispatches)?
+            }
+        }
+        if is_modified {
+            self.refresh_git_status()
+        }
+        Ok(())
+    }
+
+    fn open_reload_file_prompt(&mut self, path: CanonicalizedPath) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: format!("{} is modified by another program", path.display_absolute()),
+                on_enter: DispatchPrompt::Null,
+                // Keeping the changes is the first item, because this prompt is opened
+                // without the user asking, so an Enter meant for the editor should not discard them
+                items: [
+                    ("Keep changes", Dispatches::default()),
+                    (
+                        "Reload and discard changes",
+                        Dispatches::new(vec![Dispatch::OpenFile(path), Dispatch::ReloadFile]),
+                    ),
+                ]
+                .into_iter()
+                .map(|(label, dispatches)| {
+                    DropdownItem::new(label.to_string()).set_dispatches(dispatches)
+                })
+                .collect_vec(),
+                enter_selects_first_matching_item: true,
+                leaves_current_line_empty: true,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::ReloadFile,
+            None,
+        )
+    }
+
+    /// Replace the placeholder of the buffer of `path` with its loaded content
+    fn handle_file_loaded(
+        &mut self,
+        path: CanonicalizedPath,
+        content: Result<String, String>,
+    ) -> anyhow::Result<()> {
+        // The editor might be closed while loading
+        let Some(component) = self.layout.get_existing_editor(&path) else {
+            return Ok(());
+        };
+        let content = match content {
+            Ok(content) => content,
+            Err(error) => {
+                // Close the placeholder, which would otherwise be loading forever
+                if component.borrow().id() == self.current_component().borrow().id() {
+                    self.close_current_window()
+                } else {
+                    self.layout.remove_suggestive_editor(&path)
+                }
+                return Err(anyhow::anyhow!(
+                    "Unable to load {}: {}",
+                    path.display_absolute(),
+                    error
+                ));
+            }
+        };
+        let mut buffer = Buffer::from_content(&path, &content, true);
+        if let Some(limit) = self.undo_limit {
+            buffer.set_undo_limit(limit)?
+        }
+        if !self.buffer_change_observers.is_empty() {
+            buffer.record_changes()
+        }
+        self.restore_named_marks(&mut buffer);
+        let language = buffer.language();
+        let component_id = component.borrow().id();
+        *component.borrow().editor().buffer_rc().borrow_mut() = buffer;
+        component.borrow_mut().editor_mut().handle_buffer_loaded()?;
+
         if let Some(language) = language {
             self.request_syntax_highlight(component_id, language, content)?;
         }
         if self.enable_lsp {
-            self.lsp_manager.open_file(path.clone())?;
+            self.lsp_manager.open_file(path)?;
         }
-        Ok(component)
+        Ok(())
     }
 
     pub(crate) fn handle_lsp_notification(
@@ -1005,12 +2194,23 @@ impl<T: Frontend> App<T> {
         notification: LspNotification,
     ) -> anyhow::Result<()> {
         match notification {
-            LspNotification::Hover(hover) => self.show_editor_info(Info::new(
-                "Hover Info".to_string(),
-                hover.contents.join("\n\n"),
-            )),
+            LspNotification::Hover(hover) => {
+                let documentation = Documentation::from(hover);
+                self.show_editor_info(Info::new("Hover Info".to_string(), documentation.content))
+            }
             LspNotification::Definition(context, response) => {
                 match response {
+                    GotoDefinitionResponse::Single(location) if context.peek => {
+                        self.peek_location(&location)?
+                    }
+                    GotoDefinitionResponse::Multiple(locations)
+                        if context.peek && !locations.is_empty() =>
+                    {
+                        self.peek_location(&locations[0])?
+                    }
+                    GotoDefinitionResponse::Single(location) if context.open_in_split => {
+                        self.go_to_location_in_split(&location)?
+                    }
                     GotoDefinitionResponse::Single(location) => self.go_to_location(&location)?,
                     GotoDefinitionResponse::Multiple(locations) => {
                         if locations.is_empty() {
@@ -1031,6 +2231,19 @@ impl<T: Frontend> App<T> {
 
                 Ok(())
             }
+            LspNotification::References(
+                ResponseContext {
+                    goto_reference: Some(direction),
+                    ..
+                },
+                locations,
+            ) => {
+                self.references_cache = locations
+                    .into_iter()
+                    .sorted_by_key(|location| (location.path.clone(), location.range.start))
+                    .collect();
+                self.goto_cached_reference(direction)
+            }
             LspNotification::References(context, locations) => self.set_quickfix_list_type(
                 context,
                 QuickfixListType::Items(
@@ -1044,10 +2257,14 @@ impl<T: Frontend> App<T> {
 
                 Ok(())
             }
-            LspNotification::Initialized(language) => {
+            LspNotification::Initialized {
+                language,
+                server_name,
+            } => {
                 // Need to notify LSP that the file is opened
                 self.lsp_manager.initialized(
                     language,
+                    &server_name,
                     self.layout
                         .buffers()
                         .into_iter()
@@ -1056,8 +2273,13 @@ impl<T: Frontend> App<T> {
                 );
                 Ok(())
             }
-            LspNotification::PublishDiagnostics(params) => {
+            LspNotification::PublishDiagnostics {
+                server_name,
+                params,
+            } => {
                 self.update_diagnostics(
+                    &server_name,
+                    false,
                     params
                         .uri
                         .to_file_path()
@@ -1069,6 +2291,21 @@ impl<T: Frontend> App<T> {
                 )?;
                 Ok(())
             }
+            LspNotification::PulledDiagnostics {
+                server_name,
+                pulled,
+            } => {
+                for PulledDiagnostics {
+                    path, diagnostics, ..
+                } in pulled
+                {
+                    // Unchanged diagnostics are kept as they are
+                    if let Some(diagnostics) = diagnostics {
+                        self.update_diagnostics(&server_name, true, path, diagnostics)?;
+                    }
+                }
+                Ok(())
+            }
             LspNotification::PrepareRenameResponse(response) => {
                 let editor = self.current_component();
 
@@ -1105,18 +2342,31 @@ impl<T: Frontend> App<T> {
                 self.handle_signature_help(signature_help)?;
                 Ok(())
             }
-            LspNotification::Symbols(symbols) => {
-                self.open_symbol_picker(symbols)?;
+            LspNotification::SelectionRanges(ranges) => {
+                self.handle_dispatch_editor(SetSemanticRanges(ranges))
+            }
+            LspNotification::Symbols(context, symbols) => {
+                self.open_symbol_picker(symbols.filter_kinds(&context.symbol_kinds))?;
                 Ok(())
             }
             LspNotification::CompletionItemResolve(completion_item) => {
-                self.update_current_completion_item(completion_item.into())
+                self.update_current_completion_item(completion_item)
+            }
+            LspNotification::Formatting { path, edits } => {
+                // The formatted file is not necessarily the current file anymore
+                let Some(editor) = self.layout.get_existing_editor(&path) else {
+                    return Ok(());
+                };
+                let dispatches = editor.borrow_mut().editor_mut().apply_edits(edits)?;
+                self.handle_dispatches(dispatches)
             }
         }
     }
 
     fn update_diagnostics(
         &mut self,
+        server_name: &str,
+        pulled: bool,
         path: CanonicalizedPath,
         diagnostics: Vec<lsp_types::Diagnostic>,
     ) -> anyhow::Result<()> {
@@ -1126,7 +2376,7 @@ impl<T: Frontend> App<T> {
             .borrow_mut()
             .editor_mut()
             .buffer_mut()
-            .set_diagnostics(diagnostics);
+            .set_diagnostics(server_name, pulled, diagnostics);
         Ok(())
     }
 
@@ -1135,17 +2385,22 @@ impl<T: Frontend> App<T> {
             QuickfixList::new(
                 self.layout.get_quickfix_list_items(&state.source),
                 self.layout.buffers(),
+                self.context.show_absolute_paths(),
             )
             .set_current_item_index(state.current_item_index)
         })
     }
 
+    /// In preview mode, only `Movement::Current` jumps to the location of the current item.
     fn goto_quickfix_list_item(&mut self, movement: Movement) -> anyhow::Result<()> {
+        let jump = !self.quickfix_preview || matches!(movement, Movement::Current(_));
         if let Some(mut quickfix_list) = self.get_quickfix_list() {
             if let Some((current_item_index, dispatches)) = quickfix_list.get_item(movement) {
                 self.context
                     .set_quickfix_list_current_item_index(current_item_index);
-                self.handle_dispatches(dispatches)?;
+                if jump {
+                    self.handle_dispatches(dispatches)?;
+                }
                 self.render_quickfix_list(
                     quickfix_list.set_current_item_index(current_item_index),
                 )?;
@@ -1155,15 +2410,270 @@ impl<T: Frontend> App<T> {
         Ok(())
     }
 
+    fn toggle_quickfix_preview(&mut self) -> anyhow::Result<()> {
+        self.quickfix_preview = !self.quickfix_preview;
+        if let Some(quickfix_list) = self.get_quickfix_list() {
+            self.render_quickfix_list(quickfix_list)?;
+        }
+        Ok(())
+    }
+
+    /// Refer `UndoTree::set_limit`
+    fn set_undo_limit(&mut self, limit: usize) -> anyhow::Result<()> {
+        // Rejected before it is stored, otherwise no file could be opened afterwards
+        if limit == 0 {
+            return Err(anyhow::anyhow!("The undo limit must be at least 1"));
+        }
+        self.undo_limit = Some(limit);
+        for buffer in self.layout.buffers() {
+            buffer.borrow_mut().set_undo_limit(limit)?
+        }
+        Ok(())
+    }
+
+    fn selections_to_quickfix(&mut self) -> anyhow::Result<()> {
+        let component = self.current_component();
+        let borrow = component.borrow();
+        let editor = borrow.editor();
+        let Some(path) = editor.buffer().path() else {
+            return Ok(());
+        };
+        let items = editor
+            .selection_position_ranges()?
+            .into_iter()
+            .map(|range| {
+                QuickfixListItem::new(
+                    Location {
+                        path: path.clone(),
+                        range,
+                    },
+                    None,
+                )
+            })
+            .collect_vec();
+        drop(borrow);
+        self.set_quickfix_list_type(
+            ResponseContext::default().set_description("Selections"),
+            QuickfixListType::Items(items),
+        )
+    }
+
     fn show_global_info(&mut self, info: Info) {
         self.layout.show_global_info(info).unwrap_or_else(|err| {
             log::error!("Error showing info: {:?}", err);
         });
     }
 
-    fn go_to_location(&mut self, Location { path, range }: &Location) -> Result<(), anyhow::Error> {
-        let component = self.open_file(path, OpenFileOption::Focus)?;
-        let dispatches = component
+    /// Jump to the next or previous reference of the symbol under the cursor.
+    /// The references are requested from the LSP server only if the cursor is
+    /// not on any of the cached references, i.e. the symbol changed.
+    fn goto_reference(&mut self, direction: Direction) -> anyhow::Result<()> {
+        let Some(params) = self.get_request_params() else {
+            return Ok(());
+        };
+        if self.references_cache.iter().any(|location| {
+            location.path == params.path && location.range.contains(&params.position)
+        }) {
+            return self.goto_cached_reference(direction);
+        }
+        let params = RequestParams {
+            context: ResponseContext {
+                goto_reference: Some(direction),
+                ..params.context.clone()
+            },
+            ..params
+        };
+        self.lsp_manager.send_message(
+            params.path.clone(),
+            FromEditor::TextDocumentReferences {
+                params,
+                include_declaration: true,
+            },
+        )
+    }
+
+    /// Wraps around when there is no more reference in the given direction.
+    fn goto_cached_reference(&mut self, direction: Direction) -> anyhow::Result<()> {
+        let Some(params) = self.get_request_params() else {
+            return Ok(());
+        };
+        let len = self.references_cache.len();
+        if len == 0 {
+            return Ok(());
+        }
+        let current = (params.path, params.position);
+        let index = match direction {
+            Direction::End => self
+                .references_cache
+                .iter()
+                .position(|location| (location.path.clone(), location.range.start) > current)
+                .unwrap_or(0),
+            Direction::Start => self
+                .references_cache
+                .iter()
+                .rposition(|location| (location.path.clone(), location.range.end) <= current)
+                .unwrap_or(len - 1),
+        };
+        let location = self.references_cache[index].clone();
+        self.go_to_location(&location)
+    }
+
+    /// The locations before and after this jump are recorded in the jump list.
+    fn go_to_location(&mut self, location: &Location) -> Result<(), anyhow::Error> {
+        if let Some(current_location) = self.current_location() {
+            self.jump_list.push(current_location)
+        }
+        self.open_location(location)?;
+        self.jump_list.push(location.clone());
+        Ok(())
+    }
+
+    /// Name the primary selection of the current file as `name`, replacing the previous mark of
+    /// the same name, which might be in another file
+    fn set_mark(&mut self, name: char) -> anyhow::Result<()> {
+        let component = self.current_component();
+        let mut component = component.borrow_mut();
+        let editor = component.editor_mut();
+        let Some(path) = editor.path() else {
+            return Err(anyhow::anyhow!("Marks can only be set in a file"));
+        };
+        let range = editor.selection_set.primary_selection().extended_range();
+        let position_range = editor.buffer().char_index_range_to_position_range(range)?;
+        editor.buffer_mut().set_named_mark(name, range);
+        self.named_marks.insert(
+            name,
+            Location {
+                path,
+                range: position_range,
+            },
+        );
+        Ok(())
+    }
+
+    /// Let `buffer` track the marks of its file, which were recorded when the file was closed
+    fn restore_named_marks(&self, buffer: &mut Buffer) {
+        let Some(path) = buffer.path() else { return };
+        for (name, location) in &self.named_marks {
+            if location.path != path {
+                continue;
+            }
+            if let Ok(range) = buffer.position_range_to_char_index_range(&location.range) {
+                buffer.set_named_mark(*name, range)
+            }
+        }
+    }
+
+    /// The location of the mark `name`, as tracked by the buffer of its file if it is opened,
+    /// otherwise where it was when the file was closed.
+    ///
+    /// Returns `None` if the mark is not set, or its text has been deleted.
+    fn named_mark_location(&self, name: char) -> Option<Location> {
+        let location = self.named_marks.get(&name)?;
+        let range = match self
+            .layout
+            .buffers()
+            .into_iter()
+            .find(|buffer| buffer.borrow().path().as_ref() == Some(&location.path))
+        {
+            Some(buffer) => {
+                let buffer = buffer.borrow();
+                buffer
+                    .char_index_range_to_position_range(buffer.named_mark(name)?)
+                    .ok()?
+            }
+            None => location.range.clone(),
+        };
+        Some(Location {
+            path: location.path.clone(),
+            range,
+        })
+    }
+
+    fn go_to_mark(&mut self, name: char) -> anyhow::Result<()> {
+        let Some(location) = self.named_mark_location(name) else {
+            return Err(match self.named_marks.remove(&name) {
+                Some(_) => anyhow::anyhow!("The text of mark {name:?} has been deleted"),
+                None => anyhow::anyhow!("Mark {name:?} is not set"),
+            });
+        };
+        self.go_to_location(&location)
+    }
+
+    fn open_location(&mut self, Location { path, range }: &Location) -> Result<(), anyhow::Error> {
+        let component = self.open_file(path, OpenFileOption::Focus)?;
+        let dispatches = component
+            .borrow_mut()
+            .editor_mut()
+            .set_position_range(range.clone())?;
+        self.handle_dispatches(dispatches)
+    }
+
+    /// Show the lines surrounding `location` in the editor info popup.
+    fn peek_location(&mut self, location: &Location) -> anyhow::Result<()> {
+        let preview = location.preview("Definition", &self.layout.buffers())?;
+        self.show_editor_info(preview)
+    }
+
+    /// Request the definitions of the symbol under the cursor,
+    /// where `context` decides how they are shown, e.g. in a split or in a popup.
+    fn request_definitions_with_context(
+        &mut self,
+        context: impl FnOnce(ResponseContext) -> ResponseContext,
+    ) -> anyhow::Result<()> {
+        if let Some(params) = self.get_request_params() {
+            let params = RequestParams {
+                context: context(params.context.clone()),
+                ..params
+            }
+            .set_description("Definitions");
+            self.lsp_manager.send_message(
+                params.path.clone(),
+                FromEditor::TextDocumentDefinition(params),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Open the buffer of the current editor in a new editor with the same selections,
+    /// edits made in either editor are visible in both.
+    fn split(&mut self, layout_kind: LayoutKind) -> anyhow::Result<()> {
+        let (buffer, selection_set) = {
+            let component = self.current_component();
+            let component = component.borrow();
+            let editor = component.editor();
+            (editor.buffer_rc(), editor.selection_set.clone())
+        };
+        let editor = Rc::new(RefCell::new(SuggestiveEditor::from_buffer(
+            buffer,
+            SuggestiveEditorFilter::CurrentWord,
+        )));
+        self.layout
+            .add_and_focus_split_with_layout(editor.clone(), layout_kind);
+        let dispatches = editor
+            .borrow_mut()
+            .editor_mut()
+            .update_selection_set(selection_set, false);
+        self.handle_dispatches(dispatches)
+    }
+
+    /// Open `path` in a new editor pane next to the current one.
+    /// The new editor shares the buffer of the existing editor of `path` (if any),
+    /// but keeps its own selections.
+    fn go_to_location_in_split(
+        &mut self,
+        Location { path, range }: &Location,
+    ) -> Result<(), anyhow::Error> {
+        let buffer = self
+            .open_file(path, OpenFileOption::Background)?
+            .borrow()
+            .editor()
+            .buffer_rc();
+        let editor = Rc::new(RefCell::new(SuggestiveEditor::from_buffer(
+            buffer,
+            SuggestiveEditorFilter::CurrentWord,
+        )));
+        self.layout.add_and_focus_split(editor.clone());
+        let dispatches = editor
             .borrow_mut()
             .editor_mut()
             .set_position_range(range.clone())?;
@@ -1186,6 +2696,9 @@ impl<T: Frontend> App<T> {
                 self.layout.clear_quickfix_list_items();
                 items
                     .into_iter()
+                    // Sorted so that the items of the same path are grouped together,
+                    // otherwise the later groups would replace the earlier groups of the same path
+                    .sorted_by_key(|item| item.location().path.clone())
                     .group_by(|item| item.location().path.clone())
                     .into_iter()
                     .map(|(path, items)| -> anyhow::Result<()> {
@@ -1226,7 +2739,7 @@ impl<T: Frontend> App<T> {
             let dispatches = component
                 .borrow_mut()
                 .editor_mut()
-                .apply_positional_edits(edit.edits)?;
+                .apply_edits(edit.edits)?;
 
             self.handle_dispatches(dispatches)?;
 
@@ -1249,52 +2762,294 @@ impl<T: Frontend> App<T> {
         self.layout.show_keymap_legend(keymap_legend_config)
     }
 
-    fn global_replace(&mut self) -> anyhow::Result<()> {
-        let working_directory = self.working_directory.clone();
+    /// The working directory followed by the additional workspace folders.
+    fn workspace_roots(&self) -> Vec<CanonicalizedPath> {
+        std::iter::once(self.working_directory.clone())
+            .chain(self.workspace_folders.clone())
+            .collect()
+    }
+
+    fn add_workspace_folder(&mut self, path: CanonicalizedPath) -> anyhow::Result<()> {
+        if self.workspace_roots().contains(&path) {
+            return Ok(());
+        }
+        self.workspace_folders.push(path.clone());
+        self.lsp_manager.add_workspace_folder(path)
+    }
+
+    fn remove_workspace_folder(&mut self, path: CanonicalizedPath) -> anyhow::Result<()> {
+        if !self.workspace_folders.contains(&path) {
+            return Ok(());
+        }
+        self.workspace_folders.retain(|folder| folder != &path);
+        self.lsp_manager.remove_workspace_folder(path)
+    }
+
+    /// One walk builder config for each workspace root.
+    fn global_walk_builder_configs(&self) -> Vec<WalkBuilderConfig> {
         let global_search_config = self.context.global_search_config();
-        let walk_builder_config = WalkBuilderConfig {
-            root: working_directory.clone().into(),
-            include: global_search_config.include_glob(),
-            exclude: global_search_config.exclude_glob(),
-        };
+        self.workspace_roots()
+            .into_iter()
+            .map(|root| WalkBuilderConfig {
+                root: root.into(),
+                include: global_search_config.include_glob(),
+                exclude: global_search_config.exclude_glob(),
+                ignore_config: self.context.ignore_config().clone(),
+            })
+            .collect()
+    }
+
+    fn global_replace(&mut self) -> anyhow::Result<()> {
         let config = self.context.global_search_config().local_config();
-        let affected_paths = list::grep::replace(walk_builder_config, config.clone())?;
-        self.layout.reload_buffers(affected_paths)
+        let affected_paths: Vec<_> = self
+            .global_walk_builder_configs()
+            .into_iter()
+            .map(|walk_builder_config| list::grep::replace(walk_builder_config, config.clone()))
+            .flatten_ok()
+            .try_collect()?;
+        let dispatches = self.layout.reload_buffers(affected_paths)?;
+        self.handle_dispatches(dispatches)
     }
 
+    /// Search the workspace in the background, the results are added to the quickfix list
+    /// as soon as they are found, and the first result is jumped to once the search completes.
     fn global_search(&mut self) -> anyhow::Result<()> {
-        let working_directory = self.working_directory.clone();
-
-        let global_search_config = self.context.global_search_config();
-        let walk_builder_config = WalkBuilderConfig {
-            root: working_directory.clone().into(),
-            include: global_search_config.include_glob(),
-            exclude: global_search_config.exclude_glob(),
-        };
-        let config = global_search_config.local_config();
+        let config = self.context.global_search_config().local_config().clone();
         if config.search().is_empty() {
             return Ok(());
         }
-        let locations = match config.mode {
-            LocalSearchConfigMode::Regex(regex) => {
-                list::grep::run(&config.search(), walk_builder_config, regex)
-            }
-            LocalSearchConfigMode::AstGrep => {
-                list::ast_grep::run(config.search().clone(), walk_builder_config)
-            }
-            LocalSearchConfigMode::CaseAgnostic => {
-                list::case_agnostic::run(config.search().clone(), walk_builder_config)
-            }
-        }?;
+        self.cancel_global_search();
         self.set_quickfix_list_type(
             ResponseContext::default().set_description("Global search"),
-            QuickfixListType::Items(
-                locations
-                    .into_iter()
-                    .map(|location| QuickfixListItem::new(location, None))
-                    .collect_vec(),
-            ),
+            QuickfixListType::Items(Vec::new()),
         )?;
+
+        let search_id = self.next_global_search_id;
+        self.next_global_search_id += 1;
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.running_global_search = Some(GlobalSearch {
+            id: search_id,
+            cancelled: cancelled.clone(),
+            origin: self.current_location(),
+        });
+        let throttle = self.global_search_throttle.clone();
+        let should_stop = move |path: &Path| {
+            throttle(path);
+            cancelled.load(Ordering::Relaxed)
+        };
+        let walk_builder_configs = self.global_walk_builder_configs();
+        let app_message_sender = self.sender.clone();
+        std::thread::spawn(move || {
+            let (sender, receiver) = crossbeam::channel::unbounded();
+            let search = std::thread::spawn(move || -> anyhow::Result<()> {
+                for walk_builder_config in walk_builder_configs {
+                    let sender = sender.clone();
+                    match config.mode {
+                        LocalSearchConfigMode::Regex(regex) => list::grep::run(
+                            &config.search(),
+                            walk_builder_config,
+                            regex,
+                            sender,
+                            &should_stop,
+                        ),
+                        LocalSearchConfigMode::AstGrep => list::ast_grep::run(
+                            config.search().clone(),
+                            walk_builder_config,
+                            sender,
+                            &should_stop,
+                        ),
+                        LocalSearchConfigMode::CaseAgnostic => list::case_agnostic::run(
+                            config.search().clone(),
+                            walk_builder_config,
+                            sender,
+                            &should_stop,
+                        ),
+                    }?;
+                }
+                Ok(())
+            });
+            // The locations found meanwhile are sent together,
+            // so that the app is not re-rendered for every location
+            while let Ok(location) = receiver.recv() {
+                let locations = std::iter::once(location)
+                    .chain(receiver.try_iter())
+                    .collect_vec();
+                let message = AppMessage::GlobalSearchResults {
+                    search_id,
+                    locations,
+                };
+                if app_message_sender.send(message).is_err() {
+                    return;
+                }
+            }
+            let result = match search.join() {
+                Ok(result) => result.map_err(|error| error.to_string()),
+                Err(_) => Err("The global search panicked".to_string()),
+            };
+            let _ = app_message_sender.send(AppMessage::GlobalSearchFinished { search_id, result });
+        });
+        Ok(())
+    }
+
+    /// Replace the text of every quickfix list item with `replacement`.
+    ///
+    /// Buffers with unsaved changes are only edited in place,
+    /// while the other buffers are saved, so that their files on disk are rewritten.
+    ///
+    /// The locations of a file that was modified outside of the editor since they were found
+    /// might no longer contain the matched text, so they are skipped and reported instead.
+    fn replace_all_in_results(&mut self, replacement: String) -> anyhow::Result<()> {
+        let Some(items) = self
+            .context
+            .quickfix_list_state()
+            .as_ref()
+            .map(|state| self.layout.get_quickfix_list_items(&state.source))
+        else {
+            return Err(anyhow::anyhow!("There is no quickfix list to replace"));
+        };
+        // Every file is checked before anything is replaced,
+        // so that the replacement is either applied to all files or to none of them
+        let mut editors = Vec::new();
+        let mut stale_locations = Vec::new();
+        for (path, items) in items
+            .into_iter()
+            .into_group_map_by(|item| item.location().path.clone())
+        {
+            let ranges = items
+                .into_iter()
+                .map(|item| item.location().range.clone())
+                .collect_vec();
+            // Every quickfix list item belongs to the buffer of an opened file
+            let Some(editor) = self.layout.get_existing_editor(&path) else {
+                continue;
+            };
+            let buffer = editor.borrow().editor().buffer_rc();
+            let buffer = buffer.borrow();
+            if buffer.is_loading() {
+                return Err(anyhow::anyhow!(
+                    "Nothing is replaced because {} is still loading",
+                    path.display_absolute()
+                ));
+            }
+            if !buffer.is_dirty() && path.read()? != buffer.content() {
+                stale_locations.extend(ranges.into_iter().map(|range| Location {
+                    path: path.clone(),
+                    range,
+                }));
+                continue;
+            }
+            drop(buffer);
+            editors.push((editor, ranges))
+        }
+        for (editor, ranges) in editors {
+            let is_dirty = editor.borrow().editor().buffer().is_dirty();
+            let edits = ranges
+                .into_iter()
+                .map(|range| PositionalEdit {
+                    range,
+                    new_text: replacement.clone(),
+                })
+                .collect_vec();
+            let dispatches = editor.borrow_mut().editor_mut().apply_edits(edits)?;
+            self.handle_dispatches(dispatches)?;
+            if !is_dirty {
+                let dispatches = editor.borrow_mut().editor_mut().save()?;
+                self.handle_dispatches(dispatches)?;
+            }
+        }
+        if !stale_locations.is_empty() {
+            let locations = stale_locations
+                .into_iter()
+                .sorted()
+                .map(|location| {
+                    format!(
+                        "{}:{}",
+                        location.path.display_absolute(),
+                        location.range.start.line + 1
+                    )
+                })
+                .join("\n");
+            self.show_global_info(Info::new(
+                "Replace all".to_string(),
+                format!(
+                    "These locations are skipped, because their files were modified outside of the editor:\n{locations}"
+                ),
+            ))
+        }
+        Ok(())
+    }
+
+    fn open_replace_all_in_results_prompt(&mut self) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: "Replace all quickfix list items with".to_string(),
+                items: Default::default(),
+                on_enter: DispatchPrompt::ReplaceAllInResults,
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: true,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::Replacement(Scope::Global),
+            None,
+        )
+    }
+
+    /// Stop the running global search, the results found so far are kept
+    fn cancel_global_search(&mut self) {
+        if let Some(global_search) = self.running_global_search.take() {
+            global_search.cancelled.store(true, Ordering::Relaxed)
+        }
+    }
+
+    fn is_running_global_search(&self, search_id: usize) -> bool {
+        self.running_global_search
+            .as_ref()
+            .is_some_and(|global_search| global_search.id == search_id)
+    }
+
+    fn handle_global_search_results(
+        &mut self,
+        search_id: usize,
+        locations: Vec<Location>,
+    ) -> anyhow::Result<()> {
+        if !self.is_running_global_search(search_id) {
+            return Ok(());
+        }
+        for (path, items) in locations
+            .into_iter()
+            .map(|location| QuickfixListItem::new(location, None))
+            .into_group_map_by(|item| item.location().path.clone())
+        {
+            let editor = self.open_file(&path, OpenFileOption::Background)?;
+            editor
+                .borrow_mut()
+                .editor_mut()
+                .buffer_mut()
+                .add_quickfix_list_items(items);
+        }
+        if let Some(quickfix_list) = self.get_quickfix_list() {
+            self.render_quickfix_list(quickfix_list)?;
+        }
+        Ok(())
+    }
+
+    fn handle_global_search_finished(
+        &mut self,
+        search_id: usize,
+        result: Result<(), String>,
+    ) -> anyhow::Result<()> {
+        if !self.is_running_global_search(search_id) {
+            return Ok(());
+        }
+        let origin = self
+            .running_global_search
+            .take()
+            .and_then(|global_search| global_search.origin);
+        result.map_err(|error| anyhow::anyhow!(error))?;
+        // Do not take the user away from where they moved to during the search
+        if self.current_location() == origin {
+            self.goto_quickfix_list_item(Movement::Current(IfCurrentNotFound::LookForward))?
+        }
         Ok(())
     }
 
@@ -1302,6 +3057,127 @@ impl<T: Frontend> App<T> {
         Ok(self.sender.send(AppMessage::QuitAll)?)
     }
 
+    /// Quit, unless there are modified buffers and `force` is false,
+    /// in which case a prompt to save, discard or cancel is shown instead.
+    fn handle_quit(&mut self, force: bool) -> anyhow::Result<()> {
+        let modified_buffers_count = self
+            .layout
+            .buffers()
+            .into_iter()
+            .filter(|buffer| buffer.borrow().is_dirty())
+            .count();
+        if force || !self.context.confirm_quit() || modified_buffers_count == 0 {
+            return self.quit_all();
+        }
+        self.open_prompt(
+            PromptConfig {
+                title: format!("Quit with {modified_buffers_count} modified buffer(s)"),
+                on_enter: DispatchPrompt::Null,
+                items: [
+                    ("Save all and quit", Dispatches::one(Dispatch::SaveQuitAll)),
+                    (
+                        "Discard changes and quit",
+                        Dispatches::one(Dispatch::QuitAll),
+                    ),
+                    ("Cancel", Dispatches::default()),
+                ]
+                .into_iter()
+                .map(|(label, dispatches)| {
+                    DropdownItem::new(label.to_string()).set_dispatches(dispatches)
+                })
+                .collect_vec(),
+                enter_selects_first_matching_item: true,
+                leaves_current_line_empty: true,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::Quit,
+            None,
+        )
+    }
+
+    #[cfg(test)]
+    pub(crate) fn set_global_search_throttle(&mut self, throttle: GlobalSearchThrottle) {
+        self.global_search_throttle = throttle
+    }
+
+    #[cfg(test)]
+    /// Load every file in the background with `file_reader`, to simulate slow file systems
+    pub(crate) fn set_file_reader(&mut self, file_reader: FileReader) {
+        self.file_reader = file_reader;
+        self.background_loading_min_file_size = 0;
+    }
+
+    #[cfg(test)]
+    /// Wait for the content of a file loaded in the background, then populate its buffer.
+    /// The other messages received meanwhile are queued again.
+    pub(crate) fn wait_for_file_loaded(&mut self) -> anyhow::Result<()> {
+        let mut other_messages = Vec::new();
+        let result = loop {
+            match self
+                .receiver
+                .recv_timeout(std::time::Duration::from_secs(5))
+            {
+                Ok(AppMessage::FileLoaded { path, content }) => {
+                    break self.handle_file_loaded(path, content)
+                }
+                Ok(message) => other_messages.push(message),
+                Err(error) => break Err(error.into()),
+            }
+        };
+        for message in other_messages {
+            self.sender.send(message)?
+        }
+        result
+    }
+
+    #[cfg(test)]
+    pub(crate) fn is_global_search_running(&self) -> bool {
+        self.running_global_search.is_some()
+    }
+
+    #[cfg(test)]
+    /// Handle the next message of any global search, returns the number of locations it carries,
+    /// or `None` if it tells that a search finished
+    pub(crate) fn handle_next_global_search_message(&mut self) -> anyhow::Result<Option<usize>> {
+        loop {
+            match self
+                .receiver
+                .recv_timeout(std::time::Duration::from_secs(5))?
+            {
+                AppMessage::GlobalSearchResults {
+                    search_id,
+                    locations,
+                } => {
+                    let count = locations.len();
+                    self.handle_global_search_results(search_id, locations)?;
+                    return Ok(Some(count));
+                }
+                AppMessage::GlobalSearchFinished { search_id, result } => {
+                    self.handle_global_search_finished(search_id, result)?;
+                    return Ok(None);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    #[cfg(test)]
+    /// Wait until the running global search completes, with its results in the quickfix list
+    pub(crate) fn wait_for_global_search(&mut self) -> anyhow::Result<()> {
+        while self.running_global_search.is_some() {
+            self.handle_next_global_search_message()?;
+        }
+        Ok(())
+    }
+
+    #[cfg(test)]
+    /// Returns true if quitting is requested since the last call of this method
+    pub(crate) fn take_quit_requested(&mut self) -> bool {
+        self.receiver
+            .try_iter()
+            .any(|message| matches!(message, AppMessage::QuitAll))
+    }
+
     pub(crate) fn sender(&self) -> Sender<AppMessage> {
         self.sender.clone()
     }
@@ -1347,6 +3223,7 @@ impl<T: Frontend> App<T> {
         }
         self.layout.remove_suggestive_editor(path);
         self.layout.refresh_file_explorer(&self.working_directory)?;
+        self.refresh_git_status();
         Ok(())
     }
 
@@ -1355,6 +3232,7 @@ impl<T: Frontend> App<T> {
         self.add_path_parent(&to)?;
         fs::rename(from.clone(), to.clone())?;
         self.layout.refresh_file_explorer(&self.working_directory)?;
+        self.refresh_git_status();
         let to = to.try_into()?;
         self.reveal_path_in_explorer(&to)?;
         self.lsp_manager.send_message(
@@ -1386,6 +3264,7 @@ impl<T: Frontend> App<T> {
             std::fs::File::create(&path)?;
         }
         self.layout.refresh_file_explorer(&self.working_directory)?;
+        self.refresh_git_status();
         self.reveal_path_in_explorer(&path.try_into()?)?;
 
         Ok(())
@@ -1445,6 +3324,88 @@ impl<T: Frontend> App<T> {
         Ok(())
     }
 
+    fn diff_buffers(
+        &mut self,
+        left: &CanonicalizedPath,
+        right: &CanonicalizedPath,
+    ) -> anyhow::Result<()> {
+        let left_content = self
+            .open_file(left, OpenFileOption::Background)?
+            .borrow()
+            .content();
+        let right_content = self
+            .open_file(right, OpenFileOption::Background)?
+            .borrow()
+            .content();
+        let hunks = git::hunk::Hunk::get(&left_content, &right_content);
+        let title = format!(
+            "Diff of {} and {}",
+            left.try_display_relative(),
+            right.try_display_relative()
+        );
+        if hunks.is_empty() {
+            self.show_global_info(Info::new(
+                "Diff buffers info".to_string(),
+                "The buffers are identical".to_string(),
+            ));
+            return Ok(());
+        }
+        self.set_quickfix_list_type(
+            ResponseContext::default().set_description("Buffer Diff"),
+            QuickfixListType::Items(
+                hunks
+                    .into_iter()
+                    .map(|hunk| {
+                        let line_range = hunk.line_range();
+                        let location = Location {
+                            path: right.clone(),
+                            range: Position::new(line_range.start, 0)
+                                ..Position::new(line_range.end, 0),
+                        };
+                        QuickfixListItem::new(location, hunk.to_info_with_title(title.clone()))
+                    })
+                    .collect_vec(),
+            ),
+        )
+    }
+
+    /// Diff the current buffer against one of the other opened buffers
+    fn open_diff_buffers_prompt(&mut self) -> anyhow::Result<()> {
+        let Some(left) = self.get_current_file_path() else {
+            return Ok(());
+        };
+        self.open_prompt(
+            PromptConfig {
+                title: "Diff with opened file".to_string(),
+                on_enter: DispatchPrompt::Null,
+                items: self
+                    .layout
+                    .get_opened_files()
+                    .into_iter()
+                    .filter(|path| path != &left)
+                    .map(|right| {
+                        DropdownItem::new(
+                            right
+                                .display_relative()
+                                .unwrap_or_else(|_| right.display_absolute()),
+                        )
+                        .set_dispatches(Dispatches::one(
+                            Dispatch::DiffBuffers {
+                                left: left.clone(),
+                                right,
+                            },
+                        ))
+                    })
+                    .collect_vec(),
+                enter_selects_first_matching_item: true,
+                leaves_current_line_empty: true,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::DiffBuffers,
+            None,
+        )
+    }
+
     fn get_repo_git_hunks(&mut self, diff_mode: git::DiffMode) -> anyhow::Result<()> {
         let working_directory = self.working_directory.clone();
         let repo = git::GitRepo::try_from(&working_directory)?;
@@ -1808,6 +3769,36 @@ impl<T: Frontend> App<T> {
         Ok(())
     }
 
+    /// The location of the primary selection of the current editor, if it has a path.
+    fn current_location(&self) -> Option<Location> {
+        let component = self.current_component();
+        let component = component.borrow();
+        let editor = component.editor();
+        Some(Location {
+            path: editor.path()?,
+            range: editor.primary_selection_position_range().ok()?,
+        })
+    }
+
+    fn jump_back(&mut self) -> anyhow::Result<()> {
+        // The cursor might have moved away from the latest location of the jump list,
+        // so the current location replaces it, for `Dispatch::JumpForward` to return to
+        if let Some(current_location) = self.current_location() {
+            self.jump_list.replace_last(current_location)
+        }
+        if let Some(location) = self.jump_list.undo() {
+            self.open_location(&location)?;
+        }
+        Ok(())
+    }
+
+    fn jump_forward(&mut self) -> anyhow::Result<()> {
+        if let Some(location) = self.jump_list.redo() {
+            self.open_location(&location)?;
+        }
+        Ok(())
+    }
+
     fn go_to_next_file(&mut self) -> anyhow::Result<()> {
         if let Some(path) = self.file_path_history.redo() {
             self.open_file(&path, OpenFileOption::FocusNoHistory)?;
@@ -1903,7 +3894,9 @@ impl<T: Frontend> App<T> {
         &mut self,
         quickfix_list: QuickfixList,
     ) -> anyhow::Result<()> {
-        let dispatches = self.layout.show_quickfix_list(quickfix_list)?;
+        let dispatches = self
+            .layout
+            .show_quickfix_list(quickfix_list, self.quickfix_preview)?;
         self.handle_dispatches(dispatches)
     }
 
@@ -1922,15 +3915,161 @@ impl<T: Frontend> App<T> {
     }
 
     fn reveal_path_in_explorer(&mut self, path: &CanonicalizedPath) -> anyhow::Result<()> {
+        if let Some(path) = self.current_component().borrow().path() {
+            self.file_explorer_return_path = Some(path)
+        }
         let dispatches = self.layout.reveal_path_in_explorer(path)?;
         self.handle_dispatches(dispatches)
     }
 
+    /// Write the opened files, their selections and the focused file to `path`
+    pub(crate) fn save_session(&self, path: &Path) -> anyhow::Result<()> {
+        let files = self
+            .layout
+            .get_opened_files()
+            .into_iter()
+            .filter_map(|file_path| {
+                let component = self.layout.get_existing_editor(&file_path)?;
+                let component = component.borrow();
+                let selection_set = &component.editor().selection_set;
+                Some(SessionFile {
+                    path: file_path.into_path_buf(),
+                    selection_mode: selection_set.mode.name().map(ToString::to_string),
+                    selections: selection_set
+                        .selections()
+                        .iter()
+                        .map(|selection| {
+                            let range = selection.extended_range();
+                            range.start.0..range.end.0
+                        })
+                        .collect(),
+                    cursor_index: selection_set.cursor_index(),
+                })
+            })
+            .collect();
+        let current_file = self
+            .current_component()
+            .borrow()
+            .path()
+            .map(CanonicalizedPath::into_path_buf);
+        Session::new(files, current_file).write(path)
+    }
+
+    /// Open the files of the session written by `App::save_session` with their selections,
+    /// and focus the file focused when the session is saved.
+    /// Files that no longer exist are skipped.
+    pub(crate) fn load_session(&mut self, path: &Path) -> anyhow::Result<()> {
+        let session = Session::read(path)?;
+        for file in session.files {
+            let Ok(file_path) = CanonicalizedPath::try_from(file.path) else {
+                continue;
+            };
+            let component = self.open_file(&file_path, OpenFileOption::Background)?;
+            // Selection modes that require parameters are not saved,
+            // so their selections are restored as custom selections
+            let selection_mode = file
+                .selection_mode
+                .and_then(|name| SelectionMode::from_name(&name).ok())
+                .unwrap_or(SelectionMode::Custom);
+            component.borrow_mut().editor_mut().restore_selections(
+                selection_mode,
+                file.selections,
+                file.cursor_index,
+            )?;
+        }
+        if let Some(Ok(current_file)) = session.current_file.map(CanonicalizedPath::try_from) {
+            self.open_file(&current_file, OpenFileOption::Focus)?;
+        }
+        Ok(())
+    }
+
+    fn open_align_selections_prompt(&mut self) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: "Align selections on delimiter".to_string(),
+                items: Default::default(),
+                on_enter: DispatchPrompt::AlignSelections,
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: true,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::AlignSelections,
+            None,
+        )
+    }
+
+    fn open_session_prompt(&mut self, save: bool) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: if save {
+                    "Save session to"
+                } else {
+                    "Load session from"
+                }
+                .to_string(),
+                items: Default::default(),
+                on_enter: if save {
+                    DispatchPrompt::SaveSession
+                } else {
+                    DispatchPrompt::LoadSession
+                },
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: false,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::Session,
+            Some(
+                self.working_directory
+                    .to_path_buf()
+                    .join(DEFAULT_SESSION_FILE_NAME)
+                    .display()
+                    .to_string(),
+            ),
+        )
+    }
+
+    /// Close the file explorer, and show the file shown before it is opened, if any.
+    fn close_file_explorer(&mut self) -> anyhow::Result<()> {
+        self.close_current_window();
+        if let Some(path) = self.file_explorer_return_path.take() {
+            self.open_file(&path, OpenFileOption::FocusNoHistory)?;
+        }
+        Ok(())
+    }
+
     #[cfg(test)]
     pub(crate) fn file_explorer_content(&self) -> String {
         self.layout.file_explorer_content()
     }
 
+    /// The code actions of every LSP server of the current file are shown in the same prompt,
+    /// which is reopened whenever another server responds.
+    /// The prompt is not opened if there are no code actions.
+    ///
+    /// Once the prompt is closed or no longer focused, the responses of the remaining servers
+    /// are ignored, so that they do not bring the prompt back.
+    fn receive_code_actions(
+        &mut self,
+        code_actions: Vec<crate::lsp::code_action::CodeAction>,
+    ) -> anyhow::Result<()> {
+        if let Some(prompt_id) = self.code_actions.prompt_id {
+            if prompt_id != self.current_component().borrow().id() {
+                return Ok(());
+            }
+        }
+        self.code_actions.actions.extend(code_actions);
+        if self.code_actions.actions.is_empty() {
+            return Ok(());
+        }
+        if self.code_actions.prompt_id.is_some() {
+            self.close_current_window_and_focus_parent()
+        }
+        let code_actions = self.code_actions.actions.clone();
+        self.open_code_actions_prompt(code_actions)?;
+        self.code_actions.prompt_id = Some(self.current_component().borrow().id());
+        Ok(())
+    }
+
     fn open_code_actions_prompt(
         &mut self,
         code_actions: Vec<crate::lsp::code_action::CodeAction>,
@@ -1967,6 +4106,11 @@ impl<T: Frontend> App<T> {
         self.layout.quickfix_list_info()
     }
 
+    #[cfg(test)]
+    pub(crate) fn quickfix_list_preview(&self) -> Option<String> {
+        self.layout.quickfix_list_preview()
+    }
+
     #[cfg(test)]
     pub(crate) fn get_component_by_kind(
         &self,
@@ -2040,11 +4184,98 @@ impl<T: Frontend> App<T> {
         )
     }
 
+    #[cfg(test)]
+    pub(crate) fn mock_lsp_server(&mut self, language: Language, server_name: &str) {
+        self.lsp_manager.mock_lsp_server(language, server_name)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn has_initialized_lsp_server(
+        &self,
+        language_id: shared::language::LanguageId,
+    ) -> bool {
+        self.lsp_manager.has_initialized_lsp_server(language_id)
+    }
+
     #[cfg(test)]
     pub(crate) fn lsp_request_sent(&self, from_editor: &FromEditor) -> bool {
         self.lsp_manager.lsp_request_sent(from_editor)
     }
 
+    fn open_set_extra_ignores_prompt(&mut self) -> anyhow::Result<()> {
+        let current_line = self.context.ignore_config().extra_ignores.join(" ");
+        self.open_prompt(
+            PromptConfig {
+                title: "Set extra ignores (space-separated, in .gitignore format)".to_string(),
+                items: Default::default(),
+                on_enter: DispatchPrompt::SetExtraIgnores,
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: false,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::ExtraIgnores,
+            Some(current_line),
+        )
+    }
+
+    fn open_set_undo_limit_prompt(&mut self) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: "Set undo limit".to_string(),
+                items: Default::default(),
+                on_enter: DispatchPrompt::SetUndoLimit,
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: false,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::UndoLimit,
+            self.undo_limit.map(|limit| limit.to_string()),
+        )
+    }
+
+    fn open_set_clipboard_history_depth_prompt(&mut self) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: "Set clipboard history depth".to_string(),
+                items: Default::default(),
+                on_enter: DispatchPrompt::SetClipboardHistoryDepth,
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: false,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::ClipboardHistoryDepth,
+            self.context
+                .clipboard_history_depth()
+                .map(|depth| depth.to_string()),
+        )
+    }
+
+    fn open_set_status_line_segments_prompt(&mut self) -> anyhow::Result<()> {
+        let current_line = self
+            .status_line_components
+            .iter()
+            .map(|component| component.name())
+            .join(" ");
+        self.open_prompt(
+            PromptConfig {
+                title: format!(
+                    "Set status line segments (available: {})",
+                    StatusLineComponent::ALL
+                        .iter()
+                        .map(|component| component.name())
+                        .join(" ")
+                ),
+                items: Default::default(),
+                on_enter: DispatchPrompt::SetStatusLineSegments,
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: false,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::StatusLineSegments,
+            Some(current_line),
+        )
+    }
+
     fn open_pipe_to_shell_prompt(&mut self) -> anyhow::Result<()> {
         self.open_prompt(
             PromptConfig {
@@ -2104,8 +4335,174 @@ impl<T: Frontend> App<T> {
             None,
         )
     }
+
+    fn apply_edits_json(&mut self, json: &str) -> anyhow::Result<()> {
+        let dispatches = self
+            .current_component()
+            .borrow_mut()
+            .editor_mut()
+            .apply_edits_json(json)?;
+        self.handle_dispatches(dispatches)
+    }
+
+    /// Hide the buffer stats if they are shown with the same `exclude_markdown_syntax`,
+    /// otherwise show them
+    fn toggle_buffer_stats(&mut self, exclude_markdown_syntax: bool) {
+        self.buffer_stats = if self.buffer_stats == Some(exclude_markdown_syntax) {
+            None
+        } else {
+            Some(exclude_markdown_syntax)
+        }
+    }
+
+    fn open_set_words_per_minute_prompt(&mut self) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: "Set reading speed (words per minute)".to_string(),
+                items: Default::default(),
+                on_enter: DispatchPrompt::SetWordsPerMinute,
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: false,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::WordsPerMinute,
+            Some(self.words_per_minute.to_string()),
+        )
+    }
+
+    /// Each server is entered separately, so that the arguments can contain any character
+    /// other than whitespace, refer `Dispatch::SetLanguageServer`.
+    fn open_update_language_config_prompt(&mut self, additional: bool) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: format!(
+                    "{} language server (<language> <command> [args...])",
+                    if additional { "Add" } else { "Set" }
+                ),
+                items: Default::default(),
+                on_enter: DispatchPrompt::SetLanguageServer { additional },
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: false,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::LanguageConfig,
+            None,
+        )
+    }
+
+    fn open_set_max_line_length_prompt(&mut self) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: "Set max line length (empty to disable)".to_string(),
+                items: Default::default(),
+                on_enter: DispatchPrompt::SetMaxLineLength,
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: false,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::MaxLineLength,
+            self.context
+                .max_line_length()
+                .map(|max_line_length| max_line_length.to_string()),
+        )
+    }
+
+    fn open_insert_at_each_selection_prompt(&mut self) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: "Insert at each selection ({i} = index, {i:start:step})".to_string(),
+                items: Default::default(),
+                on_enter: DispatchPrompt::InsertAtEachSelection,
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: true,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::InsertAtEachSelection,
+            None,
+        )
+    }
+
+    fn apply_patch(&mut self, patch: &str) -> anyhow::Result<()> {
+        let dispatches = self
+            .current_component()
+            .borrow_mut()
+            .editor_mut()
+            .apply_patch(patch)?;
+        self.handle_dispatches(dispatches)
+    }
+
+    /// The replacement is asked after the pattern, refer `DispatchEditor::ReplaceAllMatching`
+    fn open_replace_all_matching_prompt(&mut self, regex: bool) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: if regex {
+                    "Replace all matches of regex"
+                } else {
+                    "Replace all matches of literal"
+                }
+                .to_string(),
+                items: Default::default(),
+                on_enter: DispatchPrompt::OpenReplaceAllMatchingReplacementPrompt { regex },
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: true,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::Search(Scope::Local),
+            None,
+        )
+    }
+
+    fn open_replace_all_matching_replacement_prompt(
+        &mut self,
+        pattern: String,
+        regex: bool,
+    ) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: format!("Replace all matches of {pattern:?} with"),
+                items: Default::default(),
+                on_enter: DispatchPrompt::ReplaceAllMatching { pattern, regex },
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: true,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::Replacement(Scope::Local),
+            None,
+        )
+    }
+
+    fn open_apply_edits_json_prompt(&mut self) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: "Apply edits (JSON)".to_string(),
+                on_enter: DispatchPrompt::ApplyEditsJson,
+                items: Vec::new(),
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: true,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::ApplyEditsJson,
+            None,
+        )
+    }
+
+    fn open_split_selections_prompt(&mut self) -> anyhow::Result<()> {
+        self.open_prompt(
+            PromptConfig {
+                title: "Split selections on regex".to_string(),
+                on_enter: DispatchPrompt::SplitSelectionOnRegex,
+                items: Vec::new(),
+                enter_selects_first_matching_item: false,
+                leaves_current_line_empty: true,
+                fire_dispatches_on_change: None,
+            },
+            PromptHistoryKey::SplitSelectionsOnRegex,
+            None,
+        )
+    }
 }
 
+/// Returns `content` with every range of `ranges` replaced by `replacement`
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub(crate) struct Dimension {
     pub(crate) height: u16,
@@ -2182,6 +4579,28 @@ pub(crate) enum Dispatch {
     SetTheme(crate::themes::Theme),
     CloseCurrentWindow,
     OpenFilePicker(FilePickerKind),
+    GotoAlternateFile,
+    AddWorkspaceFolder(CanonicalizedPath),
+    RemoveWorkspaceFolder(CanonicalizedPath),
+    /// Set the patterns, in the `.gitignore` format, to be skipped by the file picker and global search
+    SetExtraIgnores(Vec<String>),
+    OpenSetExtraIgnoresPrompt,
+    ToggleIncludeIgnoredFiles,
+    SetStatusLineSegments(Vec<StatusLineComponent>),
+    OpenSetStatusLineSegmentsPrompt,
+    /// Limit the number of undo entries of every buffer, refer `UndoTree::set_limit`.
+    ///
+    /// There is no limit on the total size of the entries,
+    /// because the undo history can only be limited by the number of entries,
+    /// so limiting the size would require rebuilding the history on every edit.
+    SetUndoLimit(usize),
+    /// Hide the diagnostics of the current buffer that are less severe than the given severity
+    SetDiagnosticMinSeverity(lsp_types::DiagnosticSeverity),
+    OpenSetUndoLimitPrompt,
+    /// Retain only the latest copied texts, which can be cycled through
+    /// with `DispatchEditor::PasteCycleOlder` and `DispatchEditor::PasteCycleNewer`
+    SetClipboardHistoryDepth(usize),
+    OpenSetClipboardHistoryDepthPrompt,
     OpenSearchPrompt {
         scope: Scope,
         if_current_not_found: IfCurrentNotFound,
@@ -2191,8 +4610,18 @@ pub(crate) enum Dispatch {
     ShowGlobalInfo(Info),
     RequestCompletion,
     RequestSignatureHelp,
+    RequestSelectionRanges,
+    /// Format the current file with the LSP server
+    Format,
+    /// Pull the diagnostics of the current file from the LSP servers
+    RefreshDiagnostics,
     RequestHover,
     RequestDefinitions(Scope),
+    /// Same as `RequestDefinitions(Scope::Local)`, but the definition is opened
+    /// in a new split pane, so that the current editor remains visible.
+    GotoDefinitionInSplit,
+    /// Show the surroundings of the definition in a popup, without leaving the current editor
+    PeekDefinition,
     RequestDeclarations(Scope),
     RequestImplementations(Scope),
     RequestTypeDefinitions(Scope),
@@ -2200,6 +4629,7 @@ pub(crate) enum Dispatch {
         scope: Scope,
         include_declaration: bool,
     },
+    GotoReference(Direction),
     PrepareRename,
     RequestCodeAction {
         diagnostics: Vec<lsp_types::Diagnostic>,
@@ -2216,8 +4646,22 @@ pub(crate) enum Dispatch {
     DocumentDidSave {
         path: CanonicalizedPath,
     },
+    /// The changes applied to the content of a buffer,
+    /// refer `App::register_buffer_change_observer`
+    BufferChanged(Vec<crate::buffer::BufferChange>),
     SetQuickfixList(QuickfixListType),
     GotoQuickfixListItem(Movement),
+    /// Snapshot the selections of the current editor into a quickfix list,
+    /// so that they can be navigated after the cursors are dismissed
+    SelectionsToQuickfix,
+    /// Toggle between jumping to and only previewing the quickfix list items
+    /// while moving through the quickfix list.
+    ToggleQuickfixPreview,
+    /// Stop the running global search, keeping the results found so far
+    CancelGlobalSearch,
+    /// Replace the text of every quickfix list item, refer `App::replace_all_in_results`
+    ReplaceAllInResults(String),
+    OpenReplaceAllInResultsPrompt,
     ApplyWorkspaceEdit(WorkspaceEdit),
     ShowKeymapLegend(KeymapLegendConfig),
     RemainOnlyCurrentComponent,
@@ -2226,11 +4670,26 @@ pub(crate) enum Dispatch {
     /// Used for testing
     Custom(String),
     ToEditor(DispatchEditor),
-    RequestDocumentSymbols,
+    /// If `kinds` is not empty, only the symbols of the given kinds are shown
+    RequestDocumentSymbols {
+        kinds: Vec<lsp_types::SymbolKind>,
+    },
     GotoLocation(Location),
     OpenMoveToIndexPrompt,
     RunCommand(String),
     QuitAll,
+    /// Quit the editor, `force` skips the confirmation of discarding modified buffers
+    Quit {
+        force: bool,
+    },
+    /// Toggle asking whether to save modified buffers before quitting
+    ToggleConfirmQuit,
+    /// Toggle between relative and absolute paths in file pickers and quickfix lists
+    ToggleShowAbsolutePaths,
+    /// Toggle highlighting the line of the cursor
+    ToggleHighlightCursorLine,
+    /// Toggle highlighting the lines touched by any selection
+    ToggleHighlightSelectionLines,
     OpenCommandPrompt,
     SaveQuitAll,
     RevealInExplorer(CanonicalizedPath),
@@ -2245,23 +4704,75 @@ pub(crate) enum Dispatch {
     },
     AddPath(String),
     RefreshFileExplorer,
+    /// Reveal the current file in the file explorer,
+    /// or open the file explorer at the working directory if there is no current file
+    OpenFileExplorer,
+    CloseFileExplorer,
+    ToggleFileExplorerHiddenFiles,
+    ToggleFileExplorerIgnoredFiles,
+    /// Refer `App::save_session`
+    SaveSession(PathBuf),
+    /// Refer `App::load_session`
+    LoadSession(PathBuf),
+    OpenSaveSessionPrompt,
+    OpenLoadSessionPrompt,
+    OpenAlignSelectionsPrompt,
+    /// Refer `App::open_replace_all_matching_prompt`
+    OpenReplaceAllMatchingPrompt {
+        regex: bool,
+    },
+    OpenReplaceAllMatchingReplacementPrompt {
+        pattern: String,
+        regex: bool,
+    },
     SetClipboardContent {
         copied_texts: CopiedTexts,
         use_system_clipboard: bool,
     },
+    /// Store `copied_texts` in the named `register`, refer `DispatchEditor::CopyToRegister`
+    SetRegisterContent {
+        register: char,
+        copied_texts: CopiedTexts,
+    },
+    /// Store the deleted `copied_texts` in the delete registers, refer `Clipboard::record_deletion`
+    RecordDeletion {
+        copied_texts: CopiedTexts,
+        small: bool,
+    },
+    OpenRegisterPrompt(RegisterOperation),
     SetGlobalMode(Option<GlobalMode>),
-    #[cfg(test)]
     HandleKeyEvent(event::KeyEvent),
+    /// Remember the primary selection of the current file under the given name,
+    /// which follows the edits before it
+    SetMark(char),
+    /// Go to the selection remembered by `SetMark`, opening its file if necessary
+    GoToMark(char),
+    /// Record the following inputs into the named register, until `StopRecordingMacro`
+    StartRecordingMacro(char),
+    StopRecordingMacro,
+    /// Replay the inputs recorded in the named register, refer `App::replay_macro`
+    ReplayMacro(char),
     #[cfg(test)]
     HandleKeyEvents(Vec<event::KeyEvent>),
     GetRepoGitHunks(git::DiffMode),
     SaveAll,
+    /// Replace the content of the current editor with the content of its file,
+    /// discarding the unsaved changes
+    ReloadFile,
+    /// Write the modified buffers once no edit is made for the given interval
+    EnableAutosave(Duration),
+    DisableAutosave,
+    OpenEnableAutosavePrompt,
+    /// Toggle whether autosave writes the buffers while insert mode is not exited yet
+    ToggleAutosaveInInsertMode,
     #[cfg(test)]
     TerminalDimensionChanged(Dimension),
     #[cfg(test)]
     SetGlobalTitle(String),
     LspExecuteCommand {
         command: crate::lsp::code_action::Command,
+        /// The LSP server that provides this command, the main server if `None`
+        server_name: Option<String>,
     },
     UpdateLocalSearchConfig {
         update: LocalSearchConfigUpdate,
@@ -2307,16 +4818,25 @@ pub(crate) enum Dispatch {
     ShowEditorInfo(Info),
     ReceiveCodeActions(Vec<crate::lsp::code_action::CodeAction>),
     OtherWindow,
+    /// Open the buffer of the current editor in a new pane beside the current pane,
+    /// the panes can be switched with `OtherWindow`
+    SplitVertical,
+    /// Same as `SplitVertical`, but the new pane is below the current pane
+    SplitHorizontal,
     CloseCurrentWindowAndFocusParent,
     CloseEditorInfo,
     GoToPreviousFile,
     GoToNextFile,
+    /// Return to the location where the last jump was triggered, e.g. go to definition
+    JumpBack,
+    /// Redo the jump undone by `Dispatch::JumpBack`
+    JumpForward,
     PushPromptHistory {
         key: PromptHistoryKey,
         line: String,
     },
     OpenThemePrompt,
-    ResolveCompletionItem(lsp_types::CompletionItem),
+    ResolveCompletionItem(crate::lsp::completion::CompletionItem),
     OpenPipeToShellPrompt,
     SetLastNonContiguousSelectionMode(Either<SelectionMode, GlobalMode>),
     UseLastNonContiguousSelectionMode(IfCurrentNotFound),
@@ -2324,6 +4844,82 @@ pub(crate) enum Dispatch {
     OpenFilterSelectionsPrompt {
         keep: bool,
     },
+    OpenSplitSelectionsPrompt,
+    /// Apply a JSON array of `{start, end, replacement}` to the current editor,
+    /// where `start` and `end` are char indices
+    ApplyEditsJson(String),
+    OpenApplyEditsJsonPrompt,
+    /// Show the line diff between the contents of two buffers as a quickfix list,
+    /// where each item is a hunk located in the `right` buffer
+    DiffBuffers {
+        left: CanonicalizedPath,
+        right: CanonicalizedPath,
+    },
+    OpenDiffBuffersPrompt,
+    /// Apply a unified diff to the current buffer
+    ApplyPatch(String),
+    ApplyPatchFromClipboard,
+    /// Toggle showing the word, character and line counts,
+    /// and the estimated reading time of the current buffer in the status line
+    BufferStats {
+        exclude_markdown_syntax: bool,
+    },
+    SetWordsPerMinute(usize),
+    OpenSetWordsPerMinutePrompt,
+    OpenInsertAtEachSelectionPrompt,
+    /// Toggle previewing deletions, which are then applied only when issued again
+    ToggleConfirmDelete,
+    /// Toggle whether moving past the last or first selection wraps around,
+    /// refer `Context::wrap_around_movements`
+    ToggleWrapAroundMovements,
+    /// Characters beyond this column are highlighted, `None` disables the highlight
+    SetMaxLineLength(Option<usize>),
+    OpenSetMaxLineLengthPrompt,
+    /// Set the markers of the regions folded by `DispatchEditor::ToggleMarkerFold`,
+    /// e.g. `region` and `endregion` for `// region` and `// endregion`
+    SetFoldMarkers {
+        start: String,
+        end: String,
+    },
+    OpenSetFoldMarkersPrompt,
+    /// Override the LSP server commands, the running servers of the changed languages are restarted
+    UpdateLanguageConfig(Vec<LanguageConfig>),
+    OpenUpdateLanguageConfigPrompt,
+    /// Set the main LSP server command of a language, or add an additional one if `additional`
+    /// is true, the other servers of the language are kept, refer `LspManager::set_language_server`
+    SetLanguageServer {
+        language_id: shared::language::LanguageId,
+        command: String,
+        args: Vec<String>,
+        additional: bool,
+    },
+    OpenAddLanguageServerPrompt,
+    /// Set the local search without moving, refer `Dispatch::GotoNextMatch`
+    SetSearch(String),
+    OpenSetSearchPrompt,
+    /// Run the shell command with `{selection}` substituted by the primary selection,
+    /// which is quoted, its output is shown in a scratch editor
+    RunWithSelection {
+        command_template: String,
+    },
+    OpenRunWithSelectionPrompt,
+    /// Go to the next match of the last local search in the focused editor
+    GotoNextMatch,
+    /// Go to the previous match of the last local search in the focused editor
+    GotoPreviousMatch,
+    /// Stop highlighting the matches of the last local search, until the next search
+    ClearSearchHighlight,
+    /// Highlight the matches of the given search instead of the last local search,
+    /// without changing the last local search, refer `Context::highlighted_search`
+    SetSearchPreview(Option<String>),
+    /// Set the selection modes cycled through by `DispatchEditor::CycleSelectionMode`
+    SetSelectionModeCycle(Vec<SelectionMode>),
+    OpenSetSelectionModeCyclePrompt,
+    OpenSetBufferVarPrompt,
+    /// Prompt for the character to surround the selections with, refer `DispatchEditor::SurroundWith`
+    OpenSurroundWithPrompt,
+    /// Prompt for the syntax node kind, refer `DispatchEditor::SelectNextNodeOfKind`
+    OpenSelectNextNodeOfKindPrompt,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -2355,6 +4951,8 @@ pub(crate) enum FilePickerKind {
     NonGitIgnored,
     GitStatus(git::DiffMode),
     Opened,
+    /// Files related to the current file, see [`crate::alternate_file`]
+    Alternate(Vec<CanonicalizedPath>),
 }
 impl FilePickerKind {
     pub(crate) fn display(&self) -> String {
@@ -2362,6 +4960,7 @@ impl FilePickerKind {
             FilePickerKind::NonGitIgnored => "Not Git Ignored".to_string(),
             FilePickerKind::GitStatus(diff_mode) => format!("Git Status ({})", diff_mode.display()),
             FilePickerKind::Opened => "Opened".to_string(),
+            FilePickerKind::Alternate(_) => "Alternate".to_string(),
         }
     }
 }
@@ -2400,6 +4999,45 @@ pub(crate) enum Scope {
     Global,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum RegisterOperation {
+    Copy,
+    Cut,
+    Paste,
+    RecordMacro,
+    ReplayMacro,
+    SetMark,
+    GoToMark,
+}
+
+impl RegisterOperation {
+    fn display(&self) -> &'static str {
+        match self {
+            RegisterOperation::Copy => "Copy to register",
+            RegisterOperation::Cut => "Cut to register",
+            RegisterOperation::Paste => "Paste from register",
+            RegisterOperation::RecordMacro => "Record macro",
+            RegisterOperation::ReplayMacro => "Replay macro",
+            RegisterOperation::SetMark => "Set mark",
+            RegisterOperation::GoToMark => "Go to mark",
+        }
+    }
+
+    fn to_dispatch(&self, register: char) -> Dispatch {
+        match self {
+            RegisterOperation::Copy => Dispatch::ToEditor(DispatchEditor::CopyToRegister(register)),
+            RegisterOperation::Cut => Dispatch::ToEditor(DispatchEditor::CutToRegister(register)),
+            RegisterOperation::Paste => {
+                Dispatch::ToEditor(DispatchEditor::PasteFromRegister(register))
+            }
+            RegisterOperation::RecordMacro => Dispatch::StartRecordingMacro(register),
+            RegisterOperation::ReplayMacro => Dispatch::ReplayMacro(register),
+            RegisterOperation::SetMark => Dispatch::SetMark(register),
+            RegisterOperation::GoToMark => Dispatch::GoToMark(register),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub(crate) enum AppMessage {
     LspNotification(LspNotification),
@@ -2409,6 +5047,29 @@ pub(crate) enum AppMessage {
         component_id: ComponentId,
         highlighted_spans: HighlighedSpans,
     },
+    /// The output of the shell command of `Dispatch::RunWithSelection`, or the error of running it
+    ShellCommandOutput {
+        command: String,
+        output: Result<String, String>,
+    },
+    /// The content of a file loaded in the background, or the error of reading it
+    FileLoaded {
+        path: CanonicalizedPath,
+        content: Result<String, String>,
+    },
+    /// Locations found by the global search of `search_id`, refer `App::global_search`
+    GlobalSearchResults {
+        search_id: usize,
+        locations: Vec<Location>,
+    },
+    GlobalSearchFinished {
+        search_id: usize,
+        result: Result<(), String>,
+    },
+    /// Refer `App::handle_external_modifications`
+    CheckExternalModifications,
+    /// Refer `App::autosave`
+    AutosaveTick,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -2448,8 +5109,64 @@ pub(crate) enum DispatchPrompt {
     FilterSelectionMatchingSearch {
         keep: bool,
     },
+    SetStatusLineSegments,
+    SetExtraIgnores,
+    SetUndoLimit,
+    SetClipboardHistoryDepth,
+    SplitSelectionOnRegex,
+    ApplyEditsJson,
+    SetWordsPerMinute,
+    InsertAtEachSelection,
+    SetMaxLineLength,
+    EnableAutosave,
+    SaveSession,
+    LoadSession,
+    AlignSelections,
+    ReplaceAllInResults,
+    SetLanguageServer {
+        additional: bool,
+    },
+    SetSearch,
+    RunWithSelection,
+    Register(RegisterOperation),
+    SurroundWith,
+    SelectNextNodeOfKind,
+    SetSelectionModeCycle,
+    SetBufferVar,
+    SetFoldMarkers,
+    OpenReplaceAllMatchingReplacementPrompt {
+        regex: bool,
+    },
+    ReplaceAllMatching {
+        pattern: String,
+        regex: bool,
+    },
 }
 impl DispatchPrompt {
+    /// The dispatches fired whenever the text of the prompt is changed,
+    /// e.g. the matches of a local search are highlighted while it is being typed.
+    pub(crate) fn to_dispatches_on_change(&self, text: &str) -> Dispatches {
+        match self {
+            DispatchPrompt::UpdateLocalSearchConfigSearch {
+                scope: Scope::Local,
+                ..
+            } => Dispatches::one(Dispatch::SetSearchPreview(Some(text.to_string()))),
+            _ => Default::default(),
+        }
+    }
+
+    /// The dispatches fired when the prompt is closed, whether it is entered or cancelled,
+    /// which revert the effects of `Self::to_dispatches_on_change`.
+    pub(crate) fn to_dispatches_on_close(&self) -> Dispatches {
+        match self {
+            DispatchPrompt::UpdateLocalSearchConfigSearch {
+                scope: Scope::Local,
+                ..
+            } => Dispatches::one(Dispatch::SetSearchPreview(None)),
+            _ => Default::default(),
+        }
+    }
+
     pub(crate) fn to_dispatches(&self, text: &str) -> anyhow::Result<Dispatches> {
         match self.clone() {
             DispatchPrompt::GlobalSearchConfigSetGlob {
@@ -2548,6 +5265,158 @@ impl DispatchPrompt {
                     search: text.to_string(),
                 }),
             )),
+            DispatchPrompt::SetStatusLineSegments => {
+                Ok(Dispatches::one(Dispatch::SetStatusLineSegments(
+                    text.split_whitespace()
+                        .map(StatusLineComponent::from_name)
+                        .try_collect()?,
+                )))
+            }
+            DispatchPrompt::SetExtraIgnores => Ok(Dispatches::one(Dispatch::SetExtraIgnores(
+                text.split_whitespace().map(|s| s.to_string()).collect(),
+            ))),
+            DispatchPrompt::SetUndoLimit => Ok(Dispatches::one(Dispatch::SetUndoLimit(
+                text.trim().parse::<usize>()?,
+            ))),
+            DispatchPrompt::SetClipboardHistoryDepth => Ok(Dispatches::one(
+                Dispatch::SetClipboardHistoryDepth(text.trim().parse::<usize>()?),
+            )),
+            DispatchPrompt::SplitSelectionOnRegex => Ok(Dispatches::one(Dispatch::ToEditor(
+                DispatchEditor::SplitSelectionOnRegex(text.to_string()),
+            ))),
+            DispatchPrompt::SetWordsPerMinute => Ok(Dispatches::one(Dispatch::SetWordsPerMinute(
+                text.trim().parse::<usize>()?,
+            ))),
+            DispatchPrompt::Register(operation) => {
+                let mut chars = text.trim().chars();
+                let (Some(register), None) = (chars.next(), chars.next()) else {
+                    return Err(anyhow::anyhow!(
+                        "Expected a single character as the register name, but got {:?}",
+                        text
+                    ));
+                };
+                Ok(Dispatches::one(operation.to_dispatch(register)))
+            }
+            DispatchPrompt::SurroundWith => {
+                let mut chars = text.trim().chars();
+                let (Some(c), None) = (chars.next(), chars.next()) else {
+                    return Err(anyhow::anyhow!(
+                        "Expected a single character to surround with, but got {:?}",
+                        text
+                    ));
+                };
+                Ok(Dispatches::one(Dispatch::ToEditor(
+                    DispatchEditor::SurroundWith(c),
+                )))
+            }
+            DispatchPrompt::SelectNextNodeOfKind => Ok(Dispatches::one(Dispatch::ToEditor(
+                DispatchEditor::SelectNextNodeOfKind(text.trim().to_string()),
+            ))),
+            DispatchPrompt::SetBufferVar => {
+                let Some((key, value)) = text.trim().split_once(char::is_whitespace) else {
+                    return Err(anyhow::anyhow!(
+                        "Expected `<key> <value>`, but got {:?}",
+                        text
+                    ));
+                };
+                Ok(Dispatches::one(Dispatch::ToEditor(
+                    DispatchEditor::SetBufferVar(key.to_string(), value.trim().to_string()),
+                )))
+            }
+            DispatchPrompt::OpenReplaceAllMatchingReplacementPrompt { regex } => Ok(
+                Dispatches::one(Dispatch::OpenReplaceAllMatchingReplacementPrompt {
+                    pattern: text.to_string(),
+                    regex,
+                }),
+            ),
+            DispatchPrompt::ReplaceAllMatching { pattern, regex } => Ok(Dispatches::one(
+                Dispatch::ToEditor(DispatchEditor::ReplaceAllMatching {
+                    pattern,
+                    replacement: text.to_string(),
+                    regex,
+                }),
+            )),
+            DispatchPrompt::SetFoldMarkers => {
+                let mut markers = text.split_whitespace();
+                let (Some(start), Some(end), None) =
+                    (markers.next(), markers.next(), markers.next())
+                else {
+                    return Err(anyhow::anyhow!(
+                        "Expected `<start> <end>`, but got {:?}",
+                        text
+                    ));
+                };
+                Ok(Dispatches::one(Dispatch::SetFoldMarkers {
+                    start: start.to_string(),
+                    end: end.to_string(),
+                }))
+            }
+            DispatchPrompt::SetSelectionModeCycle => {
+                Ok(Dispatches::one(Dispatch::SetSelectionModeCycle(
+                    text.split_whitespace()
+                        .map(SelectionMode::from_name)
+                        .try_collect()?,
+                )))
+            }
+            DispatchPrompt::SetSearch => Ok(Dispatches::one(Dispatch::SetSearch(text.to_string()))),
+            DispatchPrompt::RunWithSelection => Ok(Dispatches::one(Dispatch::RunWithSelection {
+                command_template: text.to_string(),
+            })),
+            DispatchPrompt::SetLanguageServer { additional } => {
+                let mut words = text.split_whitespace();
+                let (Some(language), Some(command)) = (words.next(), words.next()) else {
+                    return Err(anyhow::anyhow!(
+                        "Expected `<language> <command> [args...]`, but got {:?}",
+                        text
+                    ));
+                };
+                let language_id = shared::language::from_lsp_language_id(language)
+                    .and_then(|language| language.id())
+                    .ok_or_else(|| anyhow::anyhow!("Unknown language: {:?}", language))?;
+                Ok(Dispatches::one(Dispatch::SetLanguageServer {
+                    language_id,
+                    command: command.to_string(),
+                    args: words.map(|arg| arg.to_string()).collect(),
+                    additional,
+                }))
+            }
+            DispatchPrompt::SetMaxLineLength => {
+                let text = text.trim();
+                Ok(Dispatches::one(Dispatch::SetMaxLineLength(
+                    if text.is_empty() {
+                        None
+                    } else {
+                        Some(text.parse::<usize>()?)
+                    },
+                )))
+            }
+            DispatchPrompt::AlignSelections => {
+                let delimiter = text
+                    .chars()
+                    .exactly_one()
+                    .map_err(|_| anyhow::anyhow!("Expected a single delimiter character"))?;
+                Ok(Dispatches::one(Dispatch::ToEditor(
+                    DispatchEditor::AlignSelections(delimiter),
+                )))
+            }
+            DispatchPrompt::SaveSession => {
+                Ok(Dispatches::one(Dispatch::SaveSession(text.trim().into())))
+            }
+            DispatchPrompt::LoadSession => {
+                Ok(Dispatches::one(Dispatch::LoadSession(text.trim().into())))
+            }
+            DispatchPrompt::EnableAutosave => Ok(Dispatches::one(Dispatch::EnableAutosave(
+                Duration::from_millis(text.trim().parse::<u64>()?),
+            ))),
+            DispatchPrompt::ReplaceAllInResults => Ok(Dispatches::one(
+                Dispatch::ReplaceAllInResults(text.to_string()),
+            )),
+            DispatchPrompt::InsertAtEachSelection => Ok(Dispatches::one(Dispatch::ToEditor(
+                DispatchEditor::InsertAtEachSelection(text.to_string()),
+            ))),
+            DispatchPrompt::ApplyEditsJson => {
+                Ok(Dispatches::one(Dispatch::ApplyEditsJson(text.to_string())))
+            }
         }
     }
 }
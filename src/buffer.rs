@@ -1,5 +1,7 @@
 use crate::history::History;
+use crate::injection::{injection_query, shift_byte, update_injections, Injection};
 use crate::lsp::diagnostic::Diagnostic;
+use crate::modeline::BufferOptions;
 use crate::quickfix_list::QuickfixListItem;
 use crate::selection_mode::case_agnostic::CaseAgnostic;
 use crate::{
@@ -9,7 +11,8 @@ use crate::{
     edit::{Action, ActionGroup, Edit, EditTransaction},
     position::Position,
     selection::{CharIndex, Selection, SelectionSet},
-    selection_mode::{AstGrep, ByteRange},
+    selection_mode::{AstGrep, ByteRange, Misspelling},
+    spell_check::Dictionary,
     syntax_highlight::{HighlighedSpan, HighlighedSpans},
     undo_tree::{Applicable, OldNew, UndoTree},
     utils::find_previous,
@@ -21,24 +24,111 @@ use shared::{
     canonicalized_path::CanonicalizedPath,
     language::{self, Language},
 };
-use std::{collections::HashSet, ops::Range};
-use tree_sitter::{Node, Parser, Tree};
+use std::{
+    cell::OnceCell,
+    collections::{hash_map::DefaultHasher, BTreeMap, HashSet},
+    hash::{Hash, Hasher},
+    ops::Range,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+use tree_sitter::{InputEdit, Node, Parser, Point, Tree};
 use tree_sitter_traversal::{traverse, Order};
 
 #[derive(Clone)]
 pub(crate) struct Buffer {
     rope: Rope,
     tree: Option<Tree>,
+    /// The trees of the languages embedded in `tree`, refer `crate::injection`
+    injections: Vec<Injection>,
+    /// Compiled from the injection query of `language`, refer `Buffer::set_injection_query`
+    injection_query: Option<Arc<tree_sitter::Query>>,
+    /// The byte ranges edited since `injections` was last updated,
+    /// refer `Buffer::update_injections`
+    edited_byte_ranges: Vec<std::ops::Range<usize>>,
     treesitter_language: Option<tree_sitter::Language>,
     undo_tree: UndoTree<Patch>,
     language: Option<Language>,
     path: Option<CanonicalizedPath>,
     highlighted_spans: HighlighedSpans,
     marks: Vec<CharIndexRange>,
+    /// Marks named by a character, refer `Dispatch::SetMark`
+    named_marks: BTreeMap<char, CharIndexRange>,
+    /// Folded ranges are hidden when rendered, except for their first line
+    folds: Vec<CharIndexRange>,
     diagnostics: Vec<Diagnostic>,
+    /// Diagnostics less severe than this are hidden, refer `Buffer::diagnostics`
+    diagnostic_min_severity: lsp_types::DiagnosticSeverity,
     quickfix_list_items: Vec<QuickfixListItem>,
     decorations: Vec<Decoration>,
     selection_set_history: History<SelectionSet>,
+    /// Arbitrary key/value metadata for scripts and integrations,
+    /// e.g. marking this buffer as a test file. It lives as long as this buffer.
+    vars: BTreeMap<String, String>,
+    options: BufferOptions,
+    /// Loaded when the misspellings are first looked up, refer `Buffer::misspellings`.
+    /// It is loaded again after the file is saved or reloaded,
+    /// so that the changes of the custom word list are picked up.
+    dictionary: OnceCell<Dictionary>,
+    /// Cleared whenever the content or the syntax tree changes, refer `Buffer::misspellings`
+    misspellings: OnceCell<Vec<ByteRange>>,
+    /// Incremented whenever the content changes,
+    /// so that the values derived from the content can be cached, refer `Buffer::revision`
+    revision: usize,
+    /// Changes applied since the last call of `Buffer::take_changes`,
+    /// which are only recorded after `Buffer::record_changes` is called
+    changes: Option<Vec<BufferChange>>,
+    /// The number of bytes read by `Buffer::reparse_tree` since this buffer is created
+    #[cfg(test)]
+    reparsed_bytes: usize,
+    /// True if the content is modified since it was last loaded or saved
+    dirty: bool,
+    /// The state and content when the pending undo group began, refer `Buffer::begin_undo_group`
+    undo_group: Option<(BufferState, String)>,
+    /// The number of undo groups that have begun but not ended,
+    /// an undo group nested in another one is undone together with the outer one.
+    undo_group_depth: usize,
+    /// True while the content is a placeholder, refer `Buffer::loading`
+    loading: bool,
+    /// The file as it was last read or written, refer `Buffer::poll_external_modification`
+    disk_state: Option<DiskState>,
+}
+
+#[derive(Clone, Debug)]
+struct DiskState {
+    /// `None` if the metadata of the file cannot be read
+    metadata: Option<FileMetadata>,
+    content_hash: u64,
+}
+
+/// Compared instead of the content of the file,
+/// so that unmodified files do not have to be read
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct FileMetadata {
+    modified: SystemTime,
+    len: u64,
+}
+
+impl FileMetadata {
+    fn of(path: &CanonicalizedPath) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        Some(Self {
+            modified: metadata.modified().ok()?,
+            len: metadata.len(),
+        })
+    }
+}
+
+/// The content of a buffer whose file is still being read
+pub(crate) const LOADING_PLACEHOLDER: &str = "Loading...";
+
+/// A change applied to the content of a buffer.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct BufferChange {
+    pub(crate) path: Option<CanonicalizedPath>,
+    /// The replaced range, relative to the content before this change is applied
+    pub(crate) range: Range<Position>,
+    pub(crate) new_text: String,
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
@@ -51,7 +141,7 @@ pub(crate) struct Line {
 
 impl Buffer {
     pub(crate) fn new(language: Option<tree_sitter::Language>, text: &str) -> Self {
-        Self {
+        let mut buffer = Self {
             rope: Rope::from_str(text),
             treesitter_language: language.clone(),
             language: None,
@@ -67,11 +157,48 @@ impl Buffer {
             path: None,
             highlighted_spans: HighlighedSpans::default(),
             marks: Vec::new(),
+            named_marks: BTreeMap::new(),
+            folds: Vec::new(),
             decorations: Vec::new(),
             undo_tree: UndoTree::new(),
             diagnostics: Vec::new(),
+            diagnostic_min_severity: lsp_types::DiagnosticSeverity::HINT,
             quickfix_list_items: Vec::new(),
             selection_set_history: History::new(),
+            vars: BTreeMap::new(),
+            options: BufferOptions::default(),
+            dictionary: OnceCell::new(),
+            misspellings: OnceCell::new(),
+            revision: 0,
+            changes: None,
+            #[cfg(test)]
+            reparsed_bytes: 0,
+            dirty: false,
+            injections: Vec::new(),
+            injection_query: None,
+            edited_byte_ranges: Vec::new(),
+            undo_group: None,
+            undo_group_depth: 0,
+            loading: false,
+            disk_state: None,
+        };
+        buffer.edited_byte_ranges = vec![0..buffer.rope.len_bytes()];
+        buffer.update_injections();
+        buffer
+    }
+
+    /// Only the injections touching the ranges edited since the last update are parsed again.
+    fn update_injections(&mut self) {
+        let edited_byte_ranges = std::mem::take(&mut self.edited_byte_ranges);
+        match (self.tree.as_ref(), self.injection_query.as_ref()) {
+            (Some(tree), Some(query)) => update_injections(
+                &mut self.injections,
+                tree,
+                &self.rope,
+                &edited_byte_ranges,
+                query,
+            ),
+            _ => self.injections.clear(),
         }
     }
     pub(crate) fn clear_quickfix_list_items(&mut self) {
@@ -83,14 +210,75 @@ impl Buffer {
     ) {
         self.quickfix_list_items = quickfix_list_items
     }
-    pub(crate) fn reload(&mut self) -> anyhow::Result<()> {
-        if let Some(path) = self.path() {
-            let updated_content = path.read()?;
 
-            self.update_content(&updated_content, SelectionSet::default())?;
+    pub(crate) fn add_quickfix_list_items(&mut self, quickfix_list_items: Vec<QuickfixListItem>) {
+        self.quickfix_list_items.extend(quickfix_list_items)
+    }
+    /// The returned `SelectionSet` is `current_selection_set` mapped to the content read from the file.
+    pub(crate) fn reload(
+        &mut self,
+        current_selection_set: SelectionSet,
+    ) -> anyhow::Result<SelectionSet> {
+        let Some(path) = self.path() else {
+            return Ok(current_selection_set);
+        };
+        let updated_content = path.read()?;
+        let selection_set = self.update_content(&updated_content, current_selection_set)?;
+        self.dirty = false;
+        self.update_disk_state(&updated_content);
+        self.reset_dictionary();
+        Ok(selection_set)
+    }
+
+    /// Record that `content` is the content of the file
+    fn update_disk_state(&mut self, content: &str) {
+        self.disk_state = Some(DiskState {
+            metadata: self.path.as_ref().and_then(FileMetadata::of),
+            content_hash: content_hash(content),
+        })
+    }
+
+    /// Returns true if the file was modified by another program
+    /// since it was last read, written or polled.
+    ///
+    /// A file that can no longer be read is not considered modified.
+    ///
+    /// The file is only read if its modification time or length changed,
+    /// and it is considered modified only if its content changed.
+    pub(crate) fn poll_external_modification(&mut self) -> bool {
+        if self.loading {
+            return false;
         }
-        Ok(())
+        let Some(path) = self.path() else {
+            return false;
+        };
+        let metadata = FileMetadata::of(&path);
+        if metadata.is_none()
+            || self
+                .disk_state
+                .as_ref()
+                .is_some_and(|state| state.metadata == metadata)
+        {
+            return false;
+        }
+        let Ok(content) = path.read() else {
+            return false;
+        };
+        let content_hash = content_hash(&content);
+        let modified = self
+            .disk_state
+            .as_ref()
+            .is_some_and(|state| state.content_hash != content_hash);
+        self.disk_state = Some(DiskState {
+            metadata,
+            content_hash,
+        });
+        modified
+    }
+    pub(crate) fn is_dirty(&self) -> bool {
+        self.dirty
     }
+
     pub(crate) fn content(&self) -> String {
         self.rope.to_string()
     }
@@ -102,6 +290,15 @@ impl Buffer {
         decorations.clone_into(&mut self.decorations)
     }
 
+    pub(crate) fn set_named_mark(&mut self, name: char, range: CharIndexRange) {
+        self.named_marks.insert(name, range);
+    }
+
+    /// Returns `None` if the mark is not set, or its text has been deleted
+    pub(crate) fn named_mark(&self, name: char) -> Option<CharIndexRange> {
+        self.named_marks.get(&name).copied()
+    }
+
     pub(crate) fn save_marks(&mut self, new_ranges: Vec<CharIndexRange>) {
         let old_ranges = std::mem::take(&mut self.marks)
             .into_iter()
@@ -119,20 +316,59 @@ impl Buffer {
         self.path.clone()
     }
 
+    /// Start recording the changes applied to the content, refer `App::register_buffer_change_observer`
+    pub(crate) fn record_changes(&mut self) {
+        self.changes.get_or_insert_with(Vec::new);
+    }
+
+    pub(crate) fn take_changes(&mut self) -> Vec<BufferChange> {
+        self.changes
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
     #[cfg(test)]
     pub(crate) fn set_path(&mut self, path: CanonicalizedPath) {
         self.path = Some(path);
     }
 
-    pub(crate) fn set_diagnostics(&mut self, diagnostics: Vec<lsp_types::Diagnostic>) {
-        self.diagnostics = diagnostics
+    /// Replaces the diagnostics of `server_name` that are pulled if `pulled` is true,
+    /// otherwise the ones that are pushed, the other diagnostics are kept.
+    pub(crate) fn set_diagnostics(
+        &mut self,
+        server_name: &str,
+        pulled: bool,
+        diagnostics: Vec<lsp_types::Diagnostic>,
+    ) {
+        let diagnostics = diagnostics
             .into_iter()
-            .filter_map(|diagnostic| Diagnostic::try_from(self, diagnostic).ok())
-            .collect()
+            .filter_map(|diagnostic| {
+                Diagnostic::try_from(self, server_name, pulled, diagnostic).ok()
+            })
+            .collect_vec();
+        self.diagnostics.retain(|diagnostic| {
+            diagnostic.server_name != server_name || diagnostic.pulled != pulled
+        });
+        self.diagnostics.extend(diagnostics)
     }
 
+    /// Returns the diagnostics that are at least as severe as the min severity,
+    /// so that the hidden ones are neither rendered, navigated nor counted.
     pub(crate) fn diagnostics(&self) -> Vec<Diagnostic> {
-        self.diagnostics.clone()
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.is_at_least(self.diagnostic_min_severity))
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn set_diagnostic_min_severity(&mut self, severity: lsp_types::DiagnosticSeverity) {
+        self.diagnostic_min_severity = severity
+    }
+
+    pub(crate) fn options(&self) -> &BufferOptions {
+        &self.options
     }
 
     pub(crate) fn words(&self) -> Vec<String> {
@@ -199,37 +435,6 @@ impl Buffer {
         let tree = language
             .map(|language| parser.set_language(&language))
             .and_then(|_| parser.parse(text, None));
-        // let start_char_index = edit.start;
-        // let old_end_char_index = edit.end();
-        // let new_end_char_index = edit.start + edit.new.len_chars();
-
-        // let start_byte = self.char_to_byte(start_char_index);
-        // let old_end_byte = self.char_to_byte(old_end_char_index);
-        // let start_position = self.char_to_point(start_char_index);
-        // let old_end_position = self.char_to_point(old_end_char_index);
-
-        // self.rope.remove(edit.start.0..edit.end().0);
-        // self.rope
-        //     .insert(edit.start.0, edit.new.to_string().as_str());
-
-        // let new_end_byte = self.char_to_byte(new_end_char_index);
-        // let new_end_position = self.char_to_point(new_end_char_index);
-
-        // let mut parser = tree_sitter::Parser::new();
-        // parser.set_language(self.tree.language()).unwrap();
-        // self.tree.edit(&InputEdit {
-        //     start_byte,
-        //     old_end_byte,
-        //     new_end_byte,
-        //     start_position,
-        //     old_end_position,
-        //     new_end_position,
-        // });
-
-        // self.tree = parser
-        //     .parse(&self.rope.to_string(), Some(&self.tree))
-        //     .unwrap();
-
         (Rope::from_str(text), tree)
     }
 
@@ -258,6 +463,31 @@ impl Buffer {
 
     pub(crate) fn update(&mut self, text: &str) {
         (self.rope, self.tree) = Self::get_rope_and_tree(self.treesitter_language.clone(), text);
+        self.injections.clear();
+        self.edited_byte_ranges = vec![0..self.rope.len_bytes()];
+        self.update_injections();
+        self.misspellings = OnceCell::new();
+        self.revision += 1;
+    }
+
+    pub(crate) fn revision(&self) -> usize {
+        self.revision
+    }
+
+    fn reset_dictionary(&mut self) {
+        self.dictionary = OnceCell::new();
+        self.misspellings = OnceCell::new();
+    }
+
+    /// The misspelled words within the comments and strings, refer `Misspelling::find`.
+    /// They are only looked up again after the content is changed.
+    pub(crate) fn misspellings(&self) -> &[ByteRange] {
+        self.misspellings.get_or_init(|| {
+            let dictionary = self
+                .dictionary
+                .get_or_init(|| Dictionary::new(self.path.as_ref()));
+            Misspelling::find(self, dictionary)
+        })
     }
 
     pub(crate) fn get_line_by_char_index(&self, char_index: CharIndex) -> anyhow::Result<Rope> {
@@ -318,6 +548,13 @@ impl Buffer {
         Ok(self.rope.try_char_to_byte(char_index.0)?)
     }
 
+    /// The column of a Tree-sitter `Point` is counted in bytes.
+    fn char_to_point(&self, char_index: CharIndex) -> anyhow::Result<Point> {
+        let byte = self.char_to_byte(char_index)?;
+        let row = self.rope.try_byte_to_line(byte)?;
+        Ok(Point::new(row, byte - self.rope.try_line_to_byte(row)?))
+    }
+
     /// Note: this method is expensive, be sure not pass in an out-of-view `char_index`
     pub(crate) fn char_to_position(&self, char_index: CharIndex) -> anyhow::Result<Position> {
         let line = self.char_to_line(char_index)?;
@@ -379,9 +616,6 @@ impl Buffer {
         selection: &Selection,
         get_largest_end: bool,
     ) -> anyhow::Result<Option<Node<'a>>> {
-        let Some(tree) = self.tree.as_ref() else {
-            return Ok(None);
-        };
         let range = selection.range();
         let start = self.char_to_byte(range.start)?;
         let (start, end) = if get_largest_end {
@@ -389,6 +623,18 @@ impl Buffer {
         } else {
             (start, self.char_to_byte(range.end)?)
         };
+        // Descend into the injected language if the range is within an injection
+        let Some(tree) = self
+            .injections
+            .iter()
+            .find(|injection| {
+                injection.byte_range.start <= start && end <= injection.byte_range.end
+            })
+            .map(|injection| &injection.tree)
+            .or(self.tree.as_ref())
+        else {
+            return Ok(None);
+        };
         let node = tree
             .root_node()
             .descendant_for_byte_range(start, end)
@@ -417,6 +663,42 @@ impl Buffer {
         Ok(Some(node))
     }
 
+    /// Returns the open and close indices of the innermost syntax node that encloses `range`,
+    /// and whose first and last children are the given `open` and `close` symbols,
+    /// e.g. the arguments of a function call.
+    ///
+    /// Returns `None` if that node contains syntax errors, because its children might be
+    /// misparsed.
+    pub(crate) fn get_enclosing_pair(
+        &self,
+        range: CharIndexRange,
+        (open, close): (char, char),
+    ) -> Option<(CharIndex, CharIndex)> {
+        let byte_range = self.char_index_range_to_byte_range(range).ok()?;
+        let mut node = self
+            .tree
+            .as_ref()?
+            .root_node()
+            .descendant_for_byte_range(byte_range.start, byte_range.end)?;
+        loop {
+            let first = node.child(0);
+            let last = node.child(node.child_count().saturating_sub(1));
+            if node.child_count() >= 2
+                && first.is_some_and(|first| first.kind() == open.to_string())
+                && last.is_some_and(|last| last.kind() == close.to_string())
+            {
+                if node.has_error() {
+                    return None;
+                }
+                let range = self
+                    .byte_range_to_char_index_range(&node.byte_range())
+                    .ok()?;
+                return Some((range.start, range.end - 1));
+            }
+            node = node.parent()?;
+        }
+    }
+
     #[cfg(test)]
     pub(crate) fn get_next_token(&self, char_index: CharIndex, is_named: bool) -> Option<Node> {
         let byte = self.char_to_byte(char_index).ok()?;
@@ -439,7 +721,24 @@ impl Buffer {
         current_selection_set: SelectionSet,
         reparse_tree: bool,
     ) -> Result<SelectionSet, anyhow::Error> {
-        let before = self.rope.to_string();
+        if self.loading {
+            return Err(anyhow::anyhow!(
+                "Edits are not applied because the file is still loading."
+            ));
+        }
+        if let Some((first, second)) = edit_transaction.overlapping_edits() {
+            return Err(anyhow::anyhow!(
+                "Edits are not applied because they overlap each other at {}..{} and {}..{}, \
+                 consider merging the overlapping selections first.",
+                first.start.0,
+                first.end.0,
+                second.start.0,
+                second.end.0
+            ));
+        }
+        // The content before the edits is only needed for creating an undo patch,
+        // so it is not materialized on every keystroke of an undo group
+        let before = self.undo_group.is_none().then(|| self.rope.to_string());
         let new_selection_set = edit_transaction
             .non_empty_selections()
             .map(|selections| current_selection_set.clone().set_selections(selections))
@@ -459,7 +758,9 @@ impl Buffer {
             marks: self.marks.clone(),
         };
 
-        self.add_undo_patch(current_buffer_state, new_buffer_state.clone(), &before);
+        if let Some(before) = before {
+            self.add_undo_patch(current_buffer_state, new_buffer_state.clone(), &before);
+        }
         if reparse_tree {
             self.reparse_tree()?;
         }
@@ -480,12 +781,49 @@ impl Buffer {
                     ))
                 })
                 .collect_vec();
+        let change = if self.changes.is_some() {
+            Some(BufferChange {
+                path: self.path(),
+                range: self.char_index_range_to_position_range(edit.range)?,
+                new_text: edit.new.to_string(),
+            })
+        } else {
+            None
+        };
+        let start_byte = self.char_to_byte(edit.range.start)?;
+        let old_end_byte = self.char_to_byte(edit.end())?;
+        let start_position = self.char_to_point(edit.range.start)?;
+        let old_end_position = self.char_to_point(edit.end())?;
 
         // Update the content
+        self.misspellings = OnceCell::new();
+        self.revision += 1;
         self.rope.try_remove(edit.range.start.0..edit.end().0)?;
         self.rope
             .try_insert(edit.range.start.0, edit.new.to_string().as_str())?;
 
+        // Update the syntax trees, so that they can be reparsed incrementally
+        let new_end_position = self.char_to_point(edit.range.start + edit.new.len_chars())?;
+        let input_edit = InputEdit {
+            start_byte,
+            old_end_byte,
+            new_end_byte: start_byte + edit.new.len_bytes(),
+            start_position,
+            old_end_position,
+            new_end_position,
+        };
+        if let Some(tree) = self.tree.as_mut() {
+            tree.edit(&input_edit);
+        }
+        for injection in &mut self.injections {
+            injection.edit(&input_edit)
+        }
+        for range in &mut self.edited_byte_ranges {
+            *range = shift_byte(range.start, &input_edit)..shift_byte(range.end, &input_edit)
+        }
+        self.edited_byte_ranges
+            .push(input_edit.start_byte..input_edit.new_end_byte);
+
         // Update all the positional spans (by using the char index ranges computed before the content is updated
         self.quickfix_list_items = quickfix_list_items_with_char_index_range
             .into_iter()
@@ -502,6 +840,14 @@ impl Buffer {
             .into_iter()
             .filter_map(|mark| mark.apply_edit(edit))
             .collect();
+        self.named_marks = std::mem::take(&mut self.named_marks)
+            .into_iter()
+            .filter_map(|(name, mark)| Some((name, mark.apply_edit(edit)?)))
+            .collect();
+        self.folds = std::mem::take(&mut self.folds)
+            .into_iter()
+            .filter_map(|fold| fold.apply_edit(edit))
+            .collect();
         self.diagnostics = std::mem::take(&mut self.diagnostics)
             .into_iter()
             .filter_map(|diagnostic| {
@@ -520,6 +866,10 @@ impl Buffer {
                 edit.new.len_bytes() as isize - byte_range.len() as isize,
             )
         }
+        if let (Some(changes), Some(change)) = (self.changes.as_mut(), change) {
+            changes.push(change)
+        }
+        self.dirty = true;
         Ok(())
     }
 
@@ -549,6 +899,49 @@ impl Buffer {
             .unwrap();
     }
 
+    /// Edits applied until `Buffer::end_undo_group` is called are undone as a single entry,
+    /// e.g. everything typed in one insert mode session.
+    pub(crate) fn begin_undo_group(&mut self, selection_set: SelectionSet) {
+        self.undo_group_depth += 1;
+        if self.undo_group.is_none() {
+            let state = BufferState {
+                selection_set,
+                marks: self.marks.clone(),
+            };
+            self.undo_group = Some((state, self.rope.to_string()))
+        }
+    }
+
+    pub(crate) fn undo_group_depth(&self) -> usize {
+        self.undo_group_depth
+    }
+
+    /// End the undo groups begun since the undo group depth was `depth`,
+    /// such as those left open by an operation that failed midway
+    pub(crate) fn end_undo_groups_until(&mut self, depth: usize, selection_set: SelectionSet) {
+        while self.undo_group_depth > depth {
+            self.end_undo_group(selection_set.clone())
+        }
+    }
+
+    pub(crate) fn end_undo_group(&mut self, selection_set: SelectionSet) {
+        self.undo_group_depth = self.undo_group_depth.saturating_sub(1);
+        if self.undo_group_depth > 0 {
+            return;
+        }
+        if let Some((old_state, before)) = self.undo_group.take() {
+            let new_state = BufferState {
+                selection_set,
+                marks: self.marks.clone(),
+            };
+            self.add_undo_patch(old_state, new_state, &before)
+        }
+    }
+
+    pub(crate) fn set_undo_limit(&mut self, limit: usize) -> anyhow::Result<()> {
+        self.undo_tree.set_limit(&self.rope.to_string(), limit)
+    }
+
     pub(crate) fn display_history(&self) -> String {
         self.undo_tree.display()
     }
@@ -592,9 +985,20 @@ impl Buffer {
         path: &CanonicalizedPath,
         enable_tree_sitter: bool,
     ) -> anyhow::Result<Buffer> {
-        let content = path.read()?;
+        Ok(Buffer::from_content(
+            path,
+            &path.read()?,
+            enable_tree_sitter,
+        ))
+    }
+
+    pub(crate) fn from_content(
+        path: &CanonicalizedPath,
+        content: &str,
+        enable_tree_sitter: bool,
+    ) -> Buffer {
         let language = if enable_tree_sitter {
-            language::from_path(path)
+            language::from_path(path).or_else(|| language::from_content(content))
         } else {
             None
         };
@@ -603,21 +1007,58 @@ impl Buffer {
             language
                 .as_ref()
                 .and_then(|language| language.tree_sitter_language()),
-            &content,
+            content,
         );
 
         buffer.path = Some(path.clone());
         buffer.language = language;
+        buffer.set_injection_query();
+        buffer.options = BufferOptions::from_modeline(content);
+        buffer.update_disk_state(content);
+
+        buffer
+    }
+
+    /// A buffer showing a placeholder until the content of `path` is read in the background.
+    /// It cannot be edited meanwhile, and saving it does nothing.
+    pub(crate) fn loading(path: &CanonicalizedPath) -> Buffer {
+        let mut buffer = Buffer::new(None, LOADING_PLACEHOLDER);
+        buffer.path = Some(path.clone());
+        buffer.loading = true;
+        buffer
+    }
 
-        Ok(buffer)
+    pub(crate) fn is_loading(&self) -> bool {
+        self.loading
     }
 
     pub(crate) fn reparse_tree(&mut self) -> anyhow::Result<()> {
         let mut parser = tree_sitter::Parser::new();
         if let Some(tree) = self.tree.as_ref() {
             parser.set_language(&tree.language())?;
-            self.tree = parser.parse(self.rope.to_string(), None);
+            let rope = &self.rope;
+            #[cfg(test)]
+            let reparsed_bytes = &mut self.reparsed_bytes;
+            // Reuse the unchanged parts of the edited tree, and read the rope chunk by chunk
+            // instead of collecting it into a `String`
+            self.tree = parser.parse_with(
+                &mut |byte: usize, _: Point| -> &[u8] {
+                    if byte >= rope.len_bytes() {
+                        return &[];
+                    }
+                    let (chunk, chunk_byte_index, _, _) = rope.chunk_at_byte(byte);
+                    let bytes = &chunk.as_bytes()[byte - chunk_byte_index..];
+                    #[cfg(test)]
+                    {
+                        *reparsed_bytes += bytes.len();
+                    }
+                    bytes
+                },
+                Some(tree),
+            );
         }
+        self.misspellings = OnceCell::new();
+        self.update_injections();
         Ok(())
     }
 
@@ -641,8 +1082,15 @@ impl Buffer {
     }
 
     pub(crate) fn save_without_formatting(&mut self) -> anyhow::Result<Option<CanonicalizedPath>> {
-        if let Some(path) = &self.path.clone() {
-            path.write(&self.content())?;
+        if self.loading {
+            // Otherwise the file would be overwritten by the placeholder
+            Ok(None)
+        } else if let Some(path) = &self.path.clone() {
+            let content = self.content();
+            path.write(&content)?;
+            self.dirty = false;
+            self.update_disk_state(&content);
+            self.reset_dictionary();
 
             Ok(Some(path.clone()))
         } else {
@@ -651,24 +1099,35 @@ impl Buffer {
         }
     }
 
+    /// The returned `SelectionSet` is `current_selection_set` mapped to the formatted content.
     pub(crate) fn save(
         &mut self,
         current_selection_set: SelectionSet,
-    ) -> anyhow::Result<Option<CanonicalizedPath>> {
-        if let Some(formatted_content) = self.get_formatted_content() {
-            self.update_content(&formatted_content, current_selection_set)?;
+    ) -> anyhow::Result<(Option<CanonicalizedPath>, SelectionSet)> {
+        if self.loading {
+            return Ok((None, current_selection_set));
         }
+        let selection_set = if let Some(formatted_content) = self.get_formatted_content() {
+            self.update_content(&formatted_content, current_selection_set)?
+        } else {
+            current_selection_set
+        };
 
-        self.save_without_formatting()
+        Ok((self.save_without_formatting()?, selection_set))
     }
 
+    /// The returned `SelectionSet` is `current_selection_set` mapped to the closest locations
+    /// in `new_content`, so that the selections stay on the same text after reformatting.
     fn update_content(
         &mut self,
         new_content: &str,
         current_selection_set: SelectionSet,
     ) -> anyhow::Result<SelectionSet> {
+        let selection_set =
+            map_selection_set(&self.content(), new_content, current_selection_set.clone());
         let edit_transaction = self.get_edit_transaction(new_content)?;
-        self.apply_edit_transaction(&edit_transaction, current_selection_set, true)
+        self.apply_edit_transaction(&edit_transaction, current_selection_set, true)?;
+        Ok(selection_set)
     }
 
     /// The resulting spans must be sorted by range
@@ -693,9 +1152,26 @@ impl Buffer {
     #[cfg(test)]
     pub(crate) fn set_language(&mut self, language: Language) -> anyhow::Result<()> {
         self.language = Some(language);
+        self.set_injection_query();
         self.reparse_tree()
     }
 
+    /// Compile the injection query of the language of this buffer,
+    /// and look up the injections of the whole content again with it.
+    fn set_injection_query(&mut self) {
+        self.injection_query = self
+            .language
+            .as_ref()
+            .zip(self.treesitter_language.as_ref())
+            .and_then(|(language, tree_sitter_language)| {
+                injection_query(language, tree_sitter_language)
+            })
+            .map(Arc::new);
+        self.injections.clear();
+        self.edited_byte_ranges = vec![0..self.rope.len_bytes()];
+        self.update_injections();
+    }
+
     pub(crate) fn treesitter_language(&self) -> Option<tree_sitter::Language> {
         self.treesitter_language.clone()
     }
@@ -728,6 +1204,86 @@ impl Buffer {
         self.marks.clone()
     }
 
+    pub(crate) fn folds(&self) -> &Vec<CharIndexRange> {
+        &self.folds
+    }
+
+    /// Unfolds the fold starting at the start of `range` if it exists, otherwise folds `range`.
+    pub(crate) fn toggle_fold(&mut self, range: CharIndexRange) {
+        if let Some(index) = self.folds.iter().position(|fold| fold.start == range.start) {
+            self.folds.remove(index);
+        } else {
+            self.folds.push(range)
+        }
+    }
+
+    /// The lines hidden by folds, that is every line of a fold except its first line.
+    pub(crate) fn folded_lines(&self) -> Vec<Range<usize>> {
+        self.folds
+            .iter()
+            .filter_map(|fold| self.lines_hidden_by(fold))
+            .collect()
+    }
+
+    fn lines_hidden_by(&self, fold: &CharIndexRange) -> Option<Range<usize>> {
+        Some(self.char_to_line(fold.start).ok()? + 1..self.char_to_line(fold.end).ok()? + 1)
+    }
+
+    /// Unfolds the folds hiding any of `lines`.
+    pub(crate) fn unfold_lines(&mut self, lines: &[usize]) {
+        let unfolded = self
+            .folds
+            .iter()
+            .map(|fold| {
+                self.lines_hidden_by(fold)
+                    .is_some_and(|hidden| lines.iter().any(|line| hidden.contains(line)))
+            })
+            .collect_vec();
+        let mut unfolded = unfolded.into_iter();
+        self.folds.retain(|_| !unfolded.next().unwrap_or(false))
+    }
+
+    /// Returns the ranges from the lines containing `start_marker` to the lines containing
+    /// `end_marker`, e.g. `// region` and `// endregion`. Regions can be nested.
+    /// A marker is compared literally with the first word of a line, or with the word following
+    /// the comment tokens, e.g. `// {{{` or `# region`.
+    pub(crate) fn marker_regions(
+        &self,
+        start_marker: &str,
+        end_marker: &str,
+    ) -> anyhow::Result<Vec<CharIndexRange>> {
+        let is_comment_token = |word: &str| !word.chars().any(char::is_alphanumeric);
+        let is_marker = |line: &str, marker: &str| {
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some(word) if word == marker => true,
+                Some(word) if is_comment_token(word) => words.next() == Some(marker),
+                _ => false,
+            }
+        };
+        let lines = self.rope.lines().map(|line| line.to_string()).collect_vec();
+        let mut start_lines = Vec::new();
+        let mut regions = Vec::new();
+        for (line_index, line) in lines.iter().enumerate() {
+            if is_marker(line, end_marker) {
+                if let Some(start_line) = start_lines.pop() {
+                    regions.push(start_line..line_index)
+                }
+            } else if is_marker(line, start_marker) {
+                start_lines.push(line_index)
+            }
+        }
+        regions
+            .into_iter()
+            .map(|line_range| -> anyhow::Result<CharIndexRange> {
+                let end_line = lines[line_range.end].trim_end_matches(['\n', '\r']);
+                Ok((self.line_to_char(line_range.start)?
+                    ..self.line_to_char(line_range.end)? + end_line.chars().count())
+                    .into())
+            })
+            .collect()
+    }
+
     pub(crate) fn byte_to_position(&self, byte_index: usize) -> anyhow::Result<Position> {
         let char_index = self.byte_to_char(byte_index)?;
         self.char_to_position(char_index)
@@ -939,6 +1495,19 @@ impl Buffer {
         self.selection_set_history.push(selection_set.clone());
     }
 
+    #[cfg(test)]
+    pub(crate) fn var(&self, key: &str) -> Option<&str> {
+        self.vars.get(key).map(|value| value.as_str())
+    }
+
+    pub(crate) fn set_var(&mut self, key: String, value: String) {
+        self.vars.insert(key, value);
+    }
+
+    pub(crate) fn vars(&self) -> &BTreeMap<String, String> {
+        &self.vars
+    }
+
     pub(crate) fn previous_selection_set(&mut self) -> Option<SelectionSet> {
         self.selection_set_history.undo()
     }
@@ -964,6 +1533,78 @@ impl Buffer {
     }
 }
 
+/// Beyond this duration, the changed lines are diffed more coarsely, refer `map_selection_set`
+const CHAR_DIFF_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Map each selection of `selection_set` from `old` to `new` using the diff between them.
+/// A selection boundary within unchanged text follows the text,
+/// while one within changed text moves to the start of its replacement.
+///
+/// Only the changed lines are diffed by character, since diffing whole files by character
+/// is too slow for large files.
+pub(crate) fn map_selection_set(
+    old: &str,
+    new: &str,
+    mut selection_set: SelectionSet,
+) -> SelectionSet {
+    let line_diff = similar::TextDiff::from_lines(old, new);
+    let chars_count = |lines: &[&str]| lines.iter().map(|line| line.chars().count()).sum::<usize>();
+    let deadline = Instant::now() + CHAR_DIFF_TIMEOUT;
+    let (mut old_start, mut new_start) = (0, 0);
+    let mut spans = Vec::new();
+    for op in line_diff.ops() {
+        let old_lines = &line_diff.old_slices()[op.old_range()];
+        let new_lines = &line_diff.new_slices()[op.new_range()];
+        let (old_len, new_len) = (chars_count(old_lines), chars_count(new_lines));
+        if op.tag() == similar::DiffTag::Equal {
+            spans.push((
+                similar::DiffTag::Equal,
+                old_start..old_start + old_len,
+                new_start..new_start + new_len,
+            ))
+        } else {
+            let (old_text, new_text) = (old_lines.concat(), new_lines.concat());
+            let char_diff = similar::TextDiff::configure()
+                .deadline(deadline)
+                .diff_chars(&old_text, &new_text);
+            spans.extend(char_diff.ops().iter().map(|op| {
+                let (tag, old_range, new_range) = op.as_tag_tuple();
+                (
+                    tag,
+                    old_start + old_range.start..old_start + old_range.end,
+                    new_start + new_range.start..new_start + new_range.end,
+                )
+            }))
+        }
+        old_start += old_len;
+        new_start += new_len;
+    }
+    let map_char_index = |char_index: CharIndex| {
+        spans
+            .iter()
+            .find(|(_, old_range, _)| old_range.contains(&char_index.0))
+            .map(|(tag, old_range, new_range)| match tag {
+                similar::DiffTag::Equal => new_range.start + (char_index.0 - old_range.start),
+                _ => new_range.start,
+            })
+            .map(CharIndex)
+            .unwrap_or(CharIndex(new.chars().count()))
+    };
+    selection_set.apply_mut(|selection| {
+        let range = selection.range();
+        *selection = selection
+            .clone()
+            .set_range((map_char_index(range.start)..map_char_index(range.end)).into())
+    });
+    selection_set
+}
+
+fn content_hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
 #[cfg(test)]
 mod test_buffer {
     use itertools::Itertools;
@@ -1217,6 +1858,242 @@ fn f(
         }
     }
 
+    #[test]
+    fn overlapping_edits_are_rejected() {
+        use crate::{
+            edit::{Action, ActionGroup, Edit, EditTransaction},
+            selection::CharIndex,
+        };
+        let mut buffer = Buffer::new(None, "foo bar");
+        let edit = |range: std::ops::Range<usize>, new: &str| {
+            ActionGroup::new(vec![Action::Edit(Edit {
+                range: (CharIndex(range.start)..CharIndex(range.end)).into(),
+                new: new.into(),
+            })])
+        };
+        let edit_transaction =
+            EditTransaction::from_action_groups(vec![edit(0..7, "x"), edit(4..7, "y")]);
+
+        let result =
+            buffer.apply_edit_transaction(&edit_transaction, SelectionSet::default(), false);
+
+        assert!(result.is_err());
+        assert_eq!(buffer.content(), "foo bar");
+    }
+
+    #[test]
+    fn typing_into_large_buffer_keeps_content_and_syntax_tree_correct() -> anyhow::Result<()> {
+        use crate::{
+            edit::{Action, ActionGroup, Edit, EditTransaction},
+            selection::CharIndex,
+        };
+        let line = "fn foo() { let x = 1; }\n";
+        let content = line.repeat(20_000);
+        let mut buffer = Buffer::new(Some(tree_sitter_rust::language()), &content);
+
+        // Type a new statement into a function in the middle of the buffer,
+        // one character at a time, like in insert mode
+        let typed = "let yü = 2; ";
+        let start = line.len() * 10_000 + "fn foo() { ".len();
+        buffer.begin_undo_group(SelectionSet::default());
+        for (offset, character) in typed.chars().enumerate() {
+            let index = CharIndex(start + offset);
+            let edit_transaction = EditTransaction::from_action_groups(vec![ActionGroup::new(
+                vec![Action::Edit(Edit {
+                    range: (index..index).into(),
+                    new: character.to_string().into(),
+                })],
+            )]);
+            buffer.apply_edit_transaction(&edit_transaction, SelectionSet::default(), true)?;
+        }
+        buffer.end_undo_group(SelectionSet::default());
+
+        let expected = format!(
+            "{}fn foo() {{ {typed}let x = 1; }}\n{}",
+            line.repeat(10_000),
+            line.repeat(9_999)
+        );
+        assert_eq!(buffer.content(), expected);
+
+        // Every reparse only reads around the edit, so all of them combined read
+        // much less than parsing the whole content even once
+        assert!(buffer.reparsed_bytes < content.len() / 2);
+
+        // The incrementally parsed tree should be the same as the tree parsed from scratch
+        let expected_tree = Buffer::new(Some(tree_sitter_rust::language()), &expected)
+            .tree
+            .map(|tree| tree.root_node().to_sexp());
+        assert_eq!(
+            buffer.tree.map(|tree| tree.root_node().to_sexp()),
+            expected_tree
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn current_node_descends_into_injected_language() -> anyhow::Result<()> {
+        use crate::selection::{CharIndex, Selection};
+        let prefix = "# Title\n\n```rust\n";
+        let mut buffer = Buffer::new(
+            Some(tree_sitter_md::language()),
+            &format!("{prefix}fn main() {{}}\nfn foo() {{}}\n```\n"),
+        );
+        buffer.set_language(language::from_extension("md").unwrap())?;
+        let start = CharIndex(prefix.len());
+        let selection = Selection::new((start..start + "fn main() {}".len()).into());
+        let node = buffer.get_current_node(&selection, false)?.unwrap();
+        assert_eq!(node.kind(), "function_item");
+        assert_eq!(
+            node.next_named_sibling().map(|node| node.kind()),
+            Some("function_item")
+        );
+
+        // Outside of the code block, the nodes of Markdown are resolved
+        let selection = Selection::new((CharIndex(0)..CharIndex(7)).into());
+        let node = buffer.get_current_node(&selection, false)?.unwrap();
+        assert_eq!(node.kind(), "atx_heading");
+        Ok(())
+    }
+
+    #[test]
+    fn only_edited_injections_are_parsed_again() -> anyhow::Result<()> {
+        use crate::{
+            edit::{Action, ActionGroup, Edit, EditTransaction},
+            selection::{CharIndex, Selection},
+        };
+        let block = |name: &str| format!("```rust\nfn {name}() {{}}\n```\n\n");
+        let markdown = |content: &str| -> anyhow::Result<Buffer> {
+            let mut buffer = Buffer::new(Some(tree_sitter_md::language()), content);
+            buffer.set_language(language::from_extension("md").unwrap())?;
+            Ok(buffer)
+        };
+        let mut buffer = markdown(&format!(
+            "{}{}{}",
+            block("foo"),
+            block("bar"),
+            block("spam")
+        ))?;
+        assert_eq!(buffer.injections.len(), 3);
+        let root_ids = |buffer: &Buffer| {
+            buffer
+                .injections
+                .iter()
+                .map(|injection| injection.tree.root_node().id())
+                .collect_vec()
+        };
+        let before = root_ids(&buffer);
+
+        // Rename `bar` to `baz`
+        let start = block("foo").len() + "```rust\nfn ba".len();
+        let edit_transaction =
+            EditTransaction::from_action_groups(vec![ActionGroup::new(vec![Action::Edit(Edit {
+                range: (CharIndex(start)..CharIndex(start + 1)).into(),
+                new: "z".into(),
+            })])]);
+        buffer.apply_edit_transaction(&edit_transaction, SelectionSet::default(), false)?;
+        buffer.reparse_tree()?;
+        assert_eq!(buffer.injections.len(), 3);
+        // Only the tree of the edited injection is replaced
+        let after = root_ids(&buffer);
+        assert_eq!(after[0], before[0]);
+        assert_ne!(after[1], before[1]);
+        assert_eq!(after[2], before[2]);
+
+        // The kept and the parsed injections are positioned correctly
+        let expected = markdown(&buffer.content())?;
+        let sexps = |buffer: &Buffer| {
+            buffer
+                .injections
+                .iter()
+                .map(|injection| {
+                    (
+                        injection.byte_range.clone(),
+                        injection.tree.root_node().to_sexp(),
+                    )
+                })
+                .collect_vec()
+        };
+        assert_eq!(sexps(&buffer), sexps(&expected));
+        let start = CharIndex(block("foo").len() + "```rust\n".len());
+        let selection = Selection::new((start..start + "fn baz() {}".len()).into());
+        let node = buffer.get_current_node(&selection, false)?.unwrap();
+        assert_eq!(node.kind(), "function_item");
+        Ok(())
+    }
+
+    #[test]
+    fn language_is_detected_from_shebang_when_path_has_no_extension() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let file_path = dir.path().join("script");
+        std::fs::write(&file_path, "#!/usr/bin/env python\nprint(1)\n")?;
+        let path = shared::canonicalized_path::CanonicalizedPath::try_from(file_path)?;
+
+        let buffer = Buffer::from_path(&path, true)?;
+
+        assert_eq!(
+            buffer
+                .language()
+                .and_then(|language| language.tree_sitter_grammar_id()),
+            Some("python".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn map_selection_set_within_changed_lines() {
+        use crate::selection::{CharIndex, Selection};
+        let selection = |range: std::ops::Range<usize>| {
+            Selection::new((CharIndex(range.start)..CharIndex(range.end)).into())
+        };
+        let selection_set = SelectionSet::new(
+            nonempty::NonEmpty::from_vec(
+                [
+                    // "a" on an unchanged line
+                    selection(3..4),
+                    // "()" on a changed line
+                    selection(14..16),
+                    // "c" on an unchanged line after the changed line
+                    selection(23..24),
+                ]
+                .to_vec(),
+            )
+            .unwrap(),
+        );
+        let actual = super::map_selection_set(
+            "fn a() {}\nfn b() {}\nfn c() {}\n",
+            "fn a() {}\nfn bb() {}\nfn c() {}\n",
+            selection_set,
+        )
+        .map(|selection| selection.range().start.0..selection.range().end.0)
+        .into_iter()
+        .collect_vec();
+        assert_eq!(actual, [3..4, 15..17, 24..25]);
+    }
+
+    #[test]
+    fn poll_external_modification() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let file_path = dir.path().join("main.rs");
+        std::fs::write(&file_path, "fn main() {}")?;
+        let path = shared::canonicalized_path::CanonicalizedPath::try_from(file_path)?;
+        let mut buffer = Buffer::from_path(&path, true)?;
+        assert!(!buffer.poll_external_modification());
+
+        // Rewriting the same content is not a modification
+        path.write("fn main() {}")?;
+        assert!(!buffer.poll_external_modification());
+
+        path.write("fn main() { }")?;
+        assert!(buffer.poll_external_modification());
+        // The modification is only reported once
+        assert!(!buffer.poll_external_modification());
+
+        // Saving is not an external modification
+        buffer.save_without_formatting()?;
+        assert!(!buffer.poll_external_modification());
+        Ok(())
+    }
+
     mod patch_edit {
         use crate::edit::EditTransaction;
 
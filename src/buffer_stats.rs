@@ -0,0 +1,92 @@
+/// The average silent reading speed of adults, in words per minute.
+pub(crate) const DEFAULT_WORDS_PER_MINUTE: usize = 238;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct BufferStats {
+    pub(crate) words: usize,
+    pub(crate) characters: usize,
+    pub(crate) lines: usize,
+}
+
+impl BufferStats {
+    /// If `exclude_markdown_syntax` is true, Markdown syntax such as headers, list markers,
+    /// quotes, rules and code fences are not counted as words.
+    pub(crate) fn new(content: &str, exclude_markdown_syntax: bool) -> Self {
+        let words = if exclude_markdown_syntax {
+            let line_prefix = lazy_regex::regex!(r"^\s*(#{1,6}|[-*+]|\d+[.)]|>)\s+");
+            content
+                .lines()
+                .map(|line| {
+                    line_prefix
+                        .replace(line, "")
+                        .split_whitespace()
+                        // Skip punctuation-only tokens such as `---`, "```" or `**`
+                        .filter(|word| word.chars().any(|c| c.is_alphanumeric()))
+                        .count()
+                })
+                .sum()
+        } else {
+            content.split_whitespace().count()
+        };
+        Self {
+            words,
+            characters: content.chars().count(),
+            lines: content.lines().count(),
+        }
+    }
+
+    /// Rounded up to the nearest minute.
+    pub(crate) fn reading_time_in_minutes(&self, words_per_minute: usize) -> usize {
+        self.words.div_ceil(words_per_minute.max(1))
+    }
+
+    pub(crate) fn display(&self, words_per_minute: usize) -> String {
+        format!(
+            "{} words, {} chars, {} lines, {} min read",
+            self.words,
+            self.characters,
+            self.lines,
+            self.reading_time_in_minutes(words_per_minute)
+        )
+    }
+}
+
+#[cfg(test)]
+mod test_buffer_stats {
+    use super::*;
+
+    const PROSE: &str = "# Title\n\nThe quick brown fox.\n\n- jumps over\n- the lazy dog\n\n---\n";
+
+    #[test]
+    fn counts() {
+        assert_eq!(
+            BufferStats::new(PROSE, false),
+            BufferStats {
+                words: 14,
+                characters: 64,
+                lines: 8,
+            }
+        )
+    }
+
+    #[test]
+    fn markdown_syntax_is_excluded() {
+        assert_eq!(BufferStats::new(PROSE, true).words, 10)
+    }
+
+    #[test]
+    fn reading_time() {
+        let stats = BufferStats::new(PROSE, true);
+        assert_eq!(stats.reading_time_in_minutes(5), 2);
+        assert_eq!(stats.reading_time_in_minutes(10), 1);
+        assert_eq!(stats.reading_time_in_minutes(DEFAULT_WORDS_PER_MINUTE), 1);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            BufferStats::new(PROSE, true).display(5),
+            "10 words, 64 chars, 8 lines, 2 min read"
+        )
+    }
+}
@@ -6,6 +6,13 @@ use shared::canonicalized_path::CanonicalizedPath;
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+    /// Persist the clipboard history in the cache directory across sessions
+    #[arg(long, global = true)]
+    persist_clipboard: bool,
+    /// Append every change applied to the content of any buffer to the given file,
+    /// one JSON object per line, for integrating with external tools
+    #[arg(long, global = true)]
+    buffer_change_log: Option<std::path::PathBuf>,
 }
 
 #[derive(Subcommand)]
@@ -50,6 +57,8 @@ enum HighlightQuery {
 
 pub(crate) fn cli() -> anyhow::Result<()> {
     let cli = Cli::parse();
+    let persist_clipboard = cli.persist_clipboard;
+    let buffer_change_log = cli.buffer_change_log;
 
     if let Some(command) = cli.command {
         match command {
@@ -76,6 +85,8 @@ pub(crate) fn cli() -> anyhow::Result<()> {
                 }
                 crate::run(crate::RunConfig {
                     entry_path: Some(args.path.try_into()?),
+                    persist_clipboard,
+                    buffer_change_log,
                     ..Default::default()
                 })
             }
@@ -88,10 +99,16 @@ pub(crate) fn cli() -> anyhow::Result<()> {
             }
             Commands::In(args) => crate::run(crate::RunConfig {
                 working_directory: Some(args.path.try_into()?),
+                persist_clipboard,
+                buffer_change_log,
                 ..Default::default()
             }),
         }
     } else {
-        crate::run(Default::default())
+        crate::run(crate::RunConfig {
+            persist_clipboard,
+            buffer_change_log,
+            ..Default::default()
+        })
     }
 }
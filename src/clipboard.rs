@@ -1,13 +1,48 @@
+use std::{collections::HashMap, path::PathBuf};
+
 use itertools::Itertools;
 use nonempty::NonEmpty;
 
-use crate::osc52;
+use crate::{components::editor::Direction, osc52};
 
+/// The in-process clipboard, shared by all editors of an `App`.
+///
+/// The system clipboard is only used when it is requested explicitly,
+/// so that copying in the editor does not override the system clipboard.
 #[derive(Clone)]
 pub(crate) struct Clipboard {
     history: RingHistory<CopiedTexts>,
+    /// Named registers, which are not affected by the unnamed clipboard above.
+    /// Deleted texts are also stored in the numbered registers and the small delete register,
+    /// refer `Clipboard::record_deletion`
+    registers: HashMap<char, CopiedTexts>,
+    /// If defined, the history is saved to this file whenever it is updated
+    persistence_path: Option<PathBuf>,
+    /// If defined, only the latest `history_depth` entries of the history are retained
+    history_depth: Option<usize>,
+    system_clipboard: SystemClipboard,
+}
+
+#[derive(Clone)]
+enum SystemClipboard {
+    Os,
+    /// Used by tests, so that they do not depend on, nor race for, the clipboard of the OS
+    #[cfg(test)]
+    Fake(std::rc::Rc<std::cell::RefCell<Option<String>>>),
 }
 
+/// Stores the latest deletion that spans less than one line
+pub(crate) const SMALL_DELETE_REGISTER: char = '-';
+
+/// Register `1` holds the latest deletion, register `2` the one before it, and so on
+const NUMBERED_REGISTERS: [char; 9] = ['1', '2', '3', '4', '5', '6', '7', '8', '9'];
+
+/// Only the latest entries are persisted, so that the persisted file does not grow forever
+const MAX_PERSISTED_ENTRIES_COUNT: usize = 100;
+
+/// Like Emacs' `kill-ring-max`, the oldest kill is dropped once the kill ring is full
+const MAX_KILL_RING_ENTRIES_COUNT: usize = 120;
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 /// Why is it a vector?  
 /// Because it needs to support multiple cursors.
@@ -21,10 +56,23 @@ impl CopiedTexts {
         Self { texts }
     }
 
+    /// True if none of the texts contains a newline
+    pub(crate) fn is_single_line(&self) -> bool {
+        self.texts.iter().all(|text| !text.contains('\n'))
+    }
+
     fn join(&self, separator: &str) -> String {
         self.texts.clone().into_iter().join(separator)
     }
 
+    /// The lines of the texts of all cursors
+    pub(crate) fn lines(&self) -> Vec<String> {
+        self.join("\n")
+            .lines()
+            .map(|line| line.to_string())
+            .collect()
+    }
+
     /// Returns the first element if no element is found at the given `index`
     pub(crate) fn get(&self, index: usize) -> String {
         self.texts
@@ -37,13 +85,165 @@ impl CopiedTexts {
     pub(crate) fn one(string: String) -> CopiedTexts {
         CopiedTexts::new(NonEmpty::singleton(string))
     }
+
+    /// Concatenate the texts of `other` to the texts of the same cursor.
+    fn append(self, other: CopiedTexts) -> CopiedTexts {
+        let texts = self
+            .texts
+            .iter()
+            .enumerate()
+            .map(|(index, text)| format!("{}{}", text, other.get(index)))
+            .collect_vec();
+        NonEmpty::from_vec(texts)
+            .map(CopiedTexts::new)
+            .unwrap_or(self)
+    }
+}
+
+/// An Emacs-style kill ring.
+///
+/// Unlike `Clipboard`, consecutive kills are accumulated into the latest entry,
+/// and the yanked entry can be cycled to the older entries with `KillRing::yank_pop`.
+#[derive(Clone, Default)]
+pub(crate) struct KillRing {
+    entries: Vec<CopiedTexts>,
+    /// The offset of the last yanked entry from the latest entry
+    yank_offset: usize,
+}
+
+impl KillRing {
+    /// If `accumulate` is `Some`, `copied_texts` is accumulated into the latest entry,
+    /// at its end or its start respectively, instead of being added as a new entry.
+    pub(crate) fn kill(&mut self, copied_texts: CopiedTexts, accumulate: Option<Direction>) {
+        let copied_texts = match (accumulate, self.entries.pop()) {
+            (Some(Direction::End), Some(last)) => last.append(copied_texts),
+            (Some(Direction::Start), Some(last)) => copied_texts.append(last),
+            (_, last) => {
+                self.entries.extend(last);
+                copied_texts
+            }
+        };
+        self.entries.push(copied_texts);
+        if self.entries.len() > MAX_KILL_RING_ENTRIES_COUNT {
+            self.entries.remove(0);
+        }
+    }
+
+    pub(crate) fn yank(&mut self) -> Option<CopiedTexts> {
+        self.yank_offset = 0;
+        self.entries.last().cloned()
+    }
+
+    /// Returns the entry before the last yanked entry, cycling to the latest entry
+    /// if the last yanked entry is the oldest.
+    pub(crate) fn yank_pop(&mut self) -> Option<CopiedTexts> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        self.yank_offset = (self.yank_offset + 1) % self.entries.len();
+        self.entries.iter().rev().nth(self.yank_offset).cloned()
+    }
 }
 
 impl Clipboard {
     pub(crate) fn new() -> Clipboard {
         Clipboard {
             history: RingHistory::new(),
+            registers: HashMap::new(),
+            persistence_path: None,
+            history_depth: None,
+            system_clipboard: SystemClipboard::Os,
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn use_fake_system_clipboard(&mut self) {
+        self.system_clipboard = SystemClipboard::Fake(Default::default())
+    }
+
+    /// Retain only the latest `depth` entries of the history, dropping the older entries.
+    ///
+    /// A `depth` of 0 is rejected, since it would drop every copied text.
+    pub(crate) fn history_depth(&self) -> Option<usize> {
+        self.history_depth
+    }
+
+    pub(crate) fn set_history_depth(&mut self, depth: usize) -> anyhow::Result<()> {
+        if depth == 0 {
+            return Err(anyhow::anyhow!(
+                "The clipboard history depth must be at least 1"
+            ));
+        }
+        self.history_depth = Some(depth);
+        self.history.retain_latest(depth);
+        Ok(())
+    }
+
+    /// Load the history persisted at `path` (if any) before the current history,
+    /// and persist the history to `path` from now on.
+    ///
+    /// The history is persisted to `path` even if loading it fails,
+    /// so that an unreadable file is replaced by the current history.
+    pub(crate) fn enable_persistence(&mut self, path: PathBuf) -> anyhow::Result<()> {
+        self.persistence_path = Some(path.clone());
+        if path.exists() {
+            let entries: Vec<Vec<String>> = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+            let persisted = entries
+                .into_iter()
+                .filter_map(NonEmpty::from_vec)
+                .map(CopiedTexts::new)
+                .collect_vec();
+            self.history.items.splice(0..0, persisted);
+            if let Some(depth) = self.history_depth {
+                self.history.retain_latest(depth)
+            }
+        }
+        Ok(())
+    }
+
+    fn persist(&self) -> anyhow::Result<()> {
+        let Some(path) = &self.persistence_path else {
+            return Ok(());
+        };
+        let entries = self
+            .history
+            .items
+            .iter()
+            .rev()
+            .take(MAX_PERSISTED_ENTRIES_COUNT)
+            .rev()
+            .map(|copied_texts| copied_texts.texts.iter().cloned().collect_vec())
+            .collect_vec();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string(&entries)?)?;
+        Ok(())
+    }
+
+    pub(crate) fn get_register(&self, register: char) -> Option<CopiedTexts> {
+        self.registers.get(&register).cloned()
+    }
+
+    pub(crate) fn set_register(&mut self, register: char, copied_texts: CopiedTexts) {
+        self.registers.insert(register, copied_texts);
+    }
+
+    /// Vim-style delete registers.
+    ///
+    /// Small deletions are stored in `SMALL_DELETE_REGISTER`,
+    /// otherwise the numbered registers are shifted down, dropping the oldest deletion,
+    /// and `copied_texts` is stored in register `1`.
+    pub(crate) fn record_deletion(&mut self, copied_texts: CopiedTexts, small: bool) {
+        if small {
+            return self.set_register(SMALL_DELETE_REGISTER, copied_texts);
         }
+        for (to, from) in NUMBERED_REGISTERS.iter().rev().tuple_windows() {
+            if let Some(previous) = self.registers.remove(from) {
+                self.registers.insert(*to, previous);
+            }
+        }
+        self.set_register(NUMBERED_REGISTERS[0], copied_texts)
     }
 
     pub(crate) fn get(&self, history_offset: isize) -> Option<CopiedTexts> {
@@ -51,7 +251,27 @@ impl Clipboard {
     }
 
     pub(crate) fn get_from_system_clipboard(&self) -> anyhow::Result<String> {
-        Ok(arboard::Clipboard::new()?.get_text()?)
+        match &self.system_clipboard {
+            SystemClipboard::Os => Ok(arboard::Clipboard::new()?.get_text()?),
+            #[cfg(test)]
+            SystemClipboard::Fake(text) => text
+                .borrow()
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("The system clipboard is empty")),
+        }
+    }
+
+    fn set_system_clipboard(&self, text: String) -> anyhow::Result<()> {
+        match &self.system_clipboard {
+            SystemClipboard::Os => arboard::Clipboard::new()
+                .and_then(|mut clipboard| clipboard.set_text(text.clone()))
+                .or_else(|_| osc52::copy_to_clipboard(&text)),
+            #[cfg(test)]
+            SystemClipboard::Fake(fake) => {
+                *fake.borrow_mut() = Some(text);
+                Ok(())
+            }
+        }
     }
 
     pub(crate) fn set(
@@ -60,10 +280,14 @@ impl Clipboard {
         use_system_clipboard: bool,
     ) -> anyhow::Result<()> {
         self.history.add(copied_texts.clone());
+        if let Some(depth) = self.history_depth {
+            self.history.retain_latest(depth)
+        }
+        if let Err(error) = self.persist() {
+            log::error!("Failed to persist the clipboard: {:?}", error)
+        }
         if use_system_clipboard {
-            arboard::Clipboard::new()
-                .and_then(|mut clipboard| clipboard.set_text(copied_texts.join("\n")))
-                .or_else(|_| osc52::copy_to_clipboard(&copied_texts.join("\n")))?
+            self.set_system_clipboard(copied_texts.join("\n"))?
         }
         Ok(())
     }
@@ -102,6 +326,10 @@ impl<T: Clone> RingHistory<T> {
         self.items.push(item)
     }
 
+    fn retain_latest(&mut self, count: usize) {
+        self.items.drain(..self.items.len().saturating_sub(count));
+    }
+
     fn new() -> Self {
         Self {
             items: Default::default(),
@@ -134,3 +362,25 @@ mod test_ring_history {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kill_ring_drops_the_oldest_kill_once_full() {
+        let mut kill_ring = KillRing::default();
+        for index in 0..=MAX_KILL_RING_ENTRIES_COUNT {
+            kill_ring.kill(CopiedTexts::one(index.to_string()), None)
+        }
+        assert_eq!(kill_ring.entries.len(), MAX_KILL_RING_ENTRIES_COUNT);
+        assert_eq!(
+            kill_ring.entries.first(),
+            Some(&CopiedTexts::one("1".to_string()))
+        );
+        assert_eq!(
+            kill_ring.yank(),
+            Some(CopiedTexts::one(MAX_KILL_RING_ENTRIES_COUNT.to_string()))
+        );
+    }
+}
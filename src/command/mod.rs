@@ -1,6 +1,12 @@
 use crate::{
-    app::{Dispatch, Dispatches},
-    components::{dropdown::DropdownItem, suggestive_editor::Info},
+    app::{Dispatch, Dispatches, RegisterOperation},
+    components::{
+        dropdown::DropdownItem,
+        editor::{Direction, DispatchEditor, IfCurrentNotFound, Motion, Movement, Operator},
+        suggestive_editor::Info,
+    },
+    selection::SelectionMode,
+    transformation::TransformKind,
 };
 
 pub(crate) struct Command {
@@ -38,7 +44,33 @@ pub const COMMANDS: &[Command] = &[
     Command {
         name: "quit-all",
         description: "Quit the editor",
-        dispatch: Dispatch::QuitAll,
+        dispatch: Dispatch::Quit { force: false },
+    },
+    Command {
+        name: "force-quit-all",
+        description: "Quit the editor without saving modified buffers",
+        dispatch: Dispatch::Quit { force: true },
+    },
+    Command {
+        name: "toggle-absolute-paths",
+        description:
+            "Toggle between relative and absolute paths in file pickers and quickfix lists",
+        dispatch: Dispatch::ToggleShowAbsolutePaths,
+    },
+    Command {
+        name: "toggle-confirm-quit",
+        description: "Toggle asking whether to save modified buffers before quitting",
+        dispatch: Dispatch::ToggleConfirmQuit,
+    },
+    Command {
+        name: "toggle-cursorline",
+        description: "Toggle highlighting the line of the cursor",
+        dispatch: Dispatch::ToggleHighlightCursorLine,
+    },
+    Command {
+        name: "toggle-selection-lines",
+        description: "Toggle highlighting the lines touched by any selection",
+        dispatch: Dispatch::ToggleHighlightSelectionLines,
     },
     Command {
         name: "write-quit-all",
@@ -50,4 +82,645 @@ pub const COMMANDS: &[Command] = &[
         description: "Save all buffers",
         dispatch: Dispatch::SaveAll,
     },
+    Command {
+        name: "reload-file",
+        description: "Replace the current buffer with its file, discarding unsaved changes",
+        dispatch: Dispatch::ReloadFile,
+    },
+    Command {
+        name: "open-file-explorer",
+        description: "Open the file explorer at the current file",
+        dispatch: Dispatch::OpenFileExplorer,
+    },
+    Command {
+        name: "save-session",
+        description: "Save the opened files and their selections to a file",
+        dispatch: Dispatch::OpenSaveSessionPrompt,
+    },
+    Command {
+        name: "load-session",
+        description: "Open the files of a saved session with their selections",
+        dispatch: Dispatch::OpenLoadSessionPrompt,
+    },
+    Command {
+        name: "toggle-file-explorer-hidden-files",
+        description: "Toggle whether the file explorer shows the paths starting with a dot",
+        dispatch: Dispatch::ToggleFileExplorerHiddenFiles,
+    },
+    Command {
+        name: "toggle-file-explorer-ignored-files",
+        description: "Toggle whether the file explorer shows the gitignored paths",
+        dispatch: Dispatch::ToggleFileExplorerIgnoredFiles,
+    },
+    Command {
+        name: "enable-autosave",
+        description: "Save the modified buffers once no edit is made for the given milliseconds",
+        dispatch: Dispatch::OpenEnableAutosavePrompt,
+    },
+    Command {
+        name: "disable-autosave",
+        description: "Stop saving the modified buffers automatically",
+        dispatch: Dispatch::DisableAutosave,
+    },
+    Command {
+        name: "toggle-autosave-in-insert-mode",
+        description: "Toggle whether autosave waits until insert mode is exited",
+        dispatch: Dispatch::ToggleAutosaveInInsertMode,
+    },
+    Command {
+        name: "sort-lines",
+        description: "Sort the selected lines in ascending order",
+        dispatch: Dispatch::ToEditor(DispatchEditor::SortLines {
+            ascending: true,
+            case_sensitive: true,
+        }),
+    },
+    Command {
+        name: "sort-lines-descending",
+        description: "Sort the selected lines in descending order",
+        dispatch: Dispatch::ToEditor(DispatchEditor::SortLines {
+            ascending: false,
+            case_sensitive: true,
+        }),
+    },
+    Command {
+        name: "sort-lines-case-insensitive",
+        description: "Sort the selected lines in ascending order, ignoring case",
+        dispatch: Dispatch::ToEditor(DispatchEditor::SortLines {
+            ascending: true,
+            case_sensitive: false,
+        }),
+    },
+    Command {
+        name: "dedup-lines",
+        description: "Remove the duplicated selected lines, keeping their first occurrences",
+        dispatch: Dispatch::ToEditor(DispatchEditor::DedupLines),
+    },
+    Command {
+        name: "goto-matching-bracket",
+        description: "Select the bracket matching the one under the cursor",
+        dispatch: Dispatch::ToEditor(DispatchEditor::GotoMatchingBracket),
+    },
+    Command {
+        name: "goto-next-diagnostic",
+        description: "Select the next diagnostic, wrapping around at the end of the file",
+        dispatch: Dispatch::ToEditor(DispatchEditor::GotoNextDiagnostic),
+    },
+    Command {
+        name: "goto-previous-diagnostic",
+        description: "Select the previous diagnostic, wrapping around at the start of the file",
+        dispatch: Dispatch::ToEditor(DispatchEditor::GotoPreviousDiagnostic),
+    },
+    Command {
+        name: "repeat-last-edit",
+        description: "Apply the last edit again on the current selections",
+        dispatch: Dispatch::ToEditor(DispatchEditor::RepeatLastEdit),
+    },
+    Command {
+        name: "strip-common-indent",
+        description: "Remove the common indentation of the lines covered by the selections",
+        dispatch: Dispatch::ToEditor(DispatchEditor::StripCommonIndent),
+    },
+    Command {
+        name: "format-json",
+        description: "Pretty-print the JSON of each selection",
+        dispatch: Dispatch::ToEditor(DispatchEditor::FormatJson { indent: None }),
+    },
+    Command {
+        name: "format-xml",
+        description: "Pretty-print the XML of each selection",
+        dispatch: Dispatch::ToEditor(DispatchEditor::FormatXml { indent: None }),
+    },
+    Command {
+        name: "reverse",
+        description: "Reverse the characters of each selection",
+        dispatch: Dispatch::ToEditor(DispatchEditor::ApplyTransform(TransformKind::Reverse)),
+    },
+    Command {
+        name: "trim-start",
+        description: "Remove the leading whitespaces of each selection",
+        dispatch: Dispatch::ToEditor(DispatchEditor::ApplyTransform(TransformKind::TrimStart)),
+    },
+    Command {
+        name: "trim-end",
+        description: "Remove the trailing whitespaces of each selection",
+        dispatch: Dispatch::ToEditor(DispatchEditor::ApplyTransform(TransformKind::TrimEnd)),
+    },
+    Command {
+        name: "base64-encode",
+        description: "Encode each selection as Base64",
+        dispatch: Dispatch::ToEditor(DispatchEditor::ApplyTransform(TransformKind::Base64Encode)),
+    },
+    Command {
+        name: "base64-decode",
+        description: "Decode each selection from Base64, invalid selections are left unchanged",
+        dispatch: Dispatch::ToEditor(DispatchEditor::ApplyTransform(TransformKind::Base64Decode)),
+    },
+    Command {
+        name: "url-encode",
+        description: "Percent-encode each selection",
+        dispatch: Dispatch::ToEditor(DispatchEditor::ApplyTransform(TransformKind::UrlEncode)),
+    },
+    Command {
+        name: "url-decode",
+        description: "Decode each percent-encoded selection, invalid selections are left unchanged",
+        dispatch: Dispatch::ToEditor(DispatchEditor::ApplyTransform(TransformKind::UrlDecode)),
+    },
+    Command {
+        name: "json-escape",
+        description: "Escape each selection as the content of a JSON string",
+        dispatch: Dispatch::ToEditor(DispatchEditor::ApplyTransform(TransformKind::JsonEscape)),
+    },
+    Command {
+        name: "refresh-diagnostics",
+        description: "Pull the diagnostics of the current file from the LSP servers",
+        dispatch: Dispatch::RefreshDiagnostics,
+    },
+    Command {
+        name: "show-error-diagnostics-only",
+        description: "Hide the diagnostics of the current buffer that are not errors",
+        dispatch: Dispatch::SetDiagnosticMinSeverity(lsp_types::DiagnosticSeverity::ERROR),
+    },
+    Command {
+        name: "show-warning-diagnostics-and-above",
+        description: "Hide the information and hint diagnostics of the current buffer",
+        dispatch: Dispatch::SetDiagnosticMinSeverity(lsp_types::DiagnosticSeverity::WARNING),
+    },
+    Command {
+        name: "show-all-diagnostics",
+        description: "Show all diagnostics of the current buffer",
+        dispatch: Dispatch::SetDiagnosticMinSeverity(lsp_types::DiagnosticSeverity::HINT),
+    },
+    Command {
+        name: "goto-next-misspelling",
+        description: "Go to the next misspelled word in comments and strings",
+        dispatch: Dispatch::ToEditor(DispatchEditor::GotoNextMisspelling),
+    },
+    Command {
+        name: "set-status-line",
+        description: "Set the segments shown in the status line, in order",
+        dispatch: Dispatch::OpenSetStatusLineSegmentsPrompt,
+    },
+    Command {
+        name: "set-extra-ignores",
+        description: "Set extra patterns to be skipped by the file picker and global search",
+        dispatch: Dispatch::OpenSetExtraIgnoresPrompt,
+    },
+    Command {
+        name: "export-selections-json",
+        description: "Copy the ranges and texts of the selections as JSON",
+        dispatch: Dispatch::ToEditor(DispatchEditor::ExportSelectionsJson),
+    },
+    Command {
+        name: "diff-with-opened-file",
+        description: "Show the diff between the current buffer and another opened buffer",
+        dispatch: Dispatch::OpenDiffBuffersPrompt,
+    },
+    Command {
+        name: "buffer-stats",
+        description: "Toggle showing the word count and the reading time in the status line",
+        dispatch: Dispatch::BufferStats {
+            exclude_markdown_syntax: false,
+        },
+    },
+    Command {
+        name: "buffer-stats-markdown",
+        description: "Same as buffer-stats, but Markdown syntax is not counted as words",
+        dispatch: Dispatch::BufferStats {
+            exclude_markdown_syntax: true,
+        },
+    },
+    Command {
+        name: "set-words-per-minute",
+        description: "Set the reading speed used for estimating the reading time",
+        dispatch: Dispatch::OpenSetWordsPerMinutePrompt,
+    },
+    Command {
+        name: "apply-patch",
+        description: "Apply the unified diff in the clipboard to the current buffer",
+        dispatch: Dispatch::ApplyPatchFromClipboard,
+    },
+    Command {
+        name: "apply-edits-json",
+        description: "Apply a JSON array of edits to the current buffer",
+        dispatch: Dispatch::OpenApplyEditsJsonPrompt,
+    },
+    Command {
+        name: "toggle-confirm-delete",
+        description: "Toggle previewing deletions before applying them",
+        dispatch: Dispatch::ToggleConfirmDelete,
+    },
+    Command {
+        name: "toggle-wrap-around-movements",
+        description: "Toggle whether moving past the last or first selection wraps around",
+        dispatch: Dispatch::ToggleWrapAroundMovements,
+    },
+    Command {
+        name: "set-max-line-length",
+        description: "Highlight the characters beyond the given column",
+        dispatch: Dispatch::OpenSetMaxLineLengthPrompt,
+    },
+    Command {
+        name: "goto-next-long-line",
+        description: "Go to the next line longer than the max line length",
+        dispatch: Dispatch::ToEditor(DispatchEditor::GotoNextLongLine),
+    },
+    Command {
+        name: "set-language-server",
+        description: "Set the LSP server command of a language, and restart its servers",
+        dispatch: Dispatch::OpenUpdateLanguageConfigPrompt,
+    },
+    Command {
+        name: "add-language-server",
+        description: "Add an additional LSP server to a language, and restart its servers",
+        dispatch: Dispatch::OpenAddLanguageServerPrompt,
+    },
+    Command {
+        name: "run-with-selection",
+        description: "Run a shell command with `{selection}` substituted by the selection",
+        dispatch: Dispatch::OpenRunWithSelectionPrompt,
+    },
+    Command {
+        name: "set-search",
+        description: "Set the search used by goto next/previous match",
+        dispatch: Dispatch::OpenSetSearchPrompt,
+    },
+    Command {
+        name: "cancel-global-search",
+        description: "Stop the running global search, keeping the results found so far",
+        dispatch: Dispatch::CancelGlobalSearch,
+    },
+    Command {
+        name: "replace-all-in-results",
+        description: "Replace the text of every quickfix list item, saving the unmodified files",
+        dispatch: Dispatch::OpenReplaceAllInResultsPrompt,
+    },
+    Command {
+        name: "jump-back",
+        description:
+            "Return to the location where the last jump, e.g. go to definition, was triggered",
+        dispatch: Dispatch::JumpBack,
+    },
+    Command {
+        name: "jump-forward",
+        description: "Redo the jump undone by jump-back",
+        dispatch: Dispatch::JumpForward,
+    },
+    Command {
+        name: "goto-next-match",
+        description: "Go to the next match of the last search",
+        dispatch: Dispatch::GotoNextMatch,
+    },
+    Command {
+        name: "goto-previous-match",
+        description: "Go to the previous match of the last search",
+        dispatch: Dispatch::GotoPreviousMatch,
+    },
+    Command {
+        name: "cycle-selection-mode-forward",
+        description: "Change to the next selection mode in the selection mode cycle",
+        dispatch: Dispatch::ToEditor(DispatchEditor::CycleSelectionMode(Direction::End)),
+    },
+    Command {
+        name: "cycle-selection-mode-backward",
+        description: "Change to the previous selection mode in the selection mode cycle",
+        dispatch: Dispatch::ToEditor(DispatchEditor::CycleSelectionMode(Direction::Start)),
+    },
+    Command {
+        name: "surround-with",
+        description:
+            "Surround the selections with the pair of a character, or the character itself",
+        dispatch: Dispatch::OpenSurroundWithPrompt,
+    },
+    Command {
+        name: "select-next-node-of-kind",
+        description: "Select the next syntax node of the given kind, such as `function_item`",
+        dispatch: Dispatch::OpenSelectNextNodeOfKindPrompt,
+    },
+    Command {
+        name: "set-buffer-var",
+        description: "Set a key/value metadata of the current buffer",
+        dispatch: Dispatch::OpenSetBufferVarPrompt,
+    },
+    Command {
+        name: "toggle-marker-fold",
+        description: "Fold or unfold the innermost region enclosing the cursor, delimited by the fold markers",
+        dispatch: Dispatch::ToEditor(DispatchEditor::ToggleMarkerFold),
+    },
+    Command {
+        name: "set-fold-markers",
+        description: "Set the start and end markers of the regions folded by toggle-marker-fold",
+        dispatch: Dispatch::OpenSetFoldMarkersPrompt,
+    },
+    Command {
+        name: "show-buffer-vars",
+        description: "Show the key/value metadata of the current buffer",
+        dispatch: Dispatch::ToEditor(DispatchEditor::ShowBufferVars),
+    },
+    Command {
+        name: "set-selection-mode-cycle",
+        description: "Set the selection modes to cycle through",
+        dispatch: Dispatch::OpenSetSelectionModeCyclePrompt,
+    },
+    Command {
+        name: "clear-search-highlight",
+        description: "Stop highlighting the matches of the last search",
+        dispatch: Dispatch::ClearSearchHighlight,
+    },
+    Command {
+        name: "cut-to-next-word",
+        description: "Cut from the cursor to the start of the next word",
+        dispatch: Dispatch::ToEditor(DispatchEditor::OperatorMotion {
+            operator: Operator::Cut {
+                use_system_clipboard: false,
+            },
+            motion: Motion::WordForward,
+        }),
+    },
+    Command {
+        name: "delete-to-line-start",
+        description: "Delete from the cursor to the start of the line",
+        dispatch: Dispatch::ToEditor(DispatchEditor::OperatorMotion {
+            operator: Operator::Delete,
+            motion: Motion::LineStart,
+        }),
+    },
+    Command {
+        name: "delete-to-line-end",
+        description: "Delete from the cursor to the end of the line",
+        dispatch: Dispatch::ToEditor(DispatchEditor::OperatorMotion {
+            operator: Operator::Delete,
+            motion: Motion::LineEnd,
+        }),
+    },
+    Command {
+        name: "select-same-indent",
+        description: "Select lines with the same indentation as the current line",
+        dispatch: Dispatch::ToEditor(DispatchEditor::SetSelectionMode(
+            IfCurrentNotFound::LookForward,
+            SelectionMode::SameIndent,
+        )),
+    },
+    Command {
+        name: "select-indent-block",
+        description: "Select the runs of lines indented at least as deep as the current line",
+        dispatch: Dispatch::ToEditor(DispatchEditor::SetSelectionMode(
+            IfCurrentNotFound::LookForward,
+            SelectionMode::IndentBlock,
+        )),
+    },
+    Command {
+        name: "toggle-column-selection",
+        description: "Start or stop a rectangular selection extended by moving up or down",
+        dispatch: Dispatch::ToEditor(DispatchEditor::ToggleColumnSelection),
+    },
+    Command {
+        name: "align-selections",
+        description: "Align the first occurrence of a delimiter on the selected lines",
+        dispatch: Dispatch::OpenAlignSelectionsPrompt,
+    },
+    Command {
+        name: "increment-number",
+        description: "Add 1 to the number of each selection",
+        dispatch: Dispatch::ToEditor(DispatchEditor::IncrementNumber(1)),
+    },
+    Command {
+        name: "decrement-number",
+        description: "Subtract 1 from the number of each selection",
+        dispatch: Dispatch::ToEditor(DispatchEditor::DecrementNumber(1)),
+    },
+    Command {
+        name: "increment-number-sequence",
+        description: "Add the index of each selection to its number, e.g. 0 0 0 becomes 0 1 2",
+        dispatch: Dispatch::ToEditor(DispatchEditor::IncrementNumberSequence(1)),
+    },
+    Command {
+        name: "add-selection-next",
+        description: "Add the next selection of the current selection mode",
+        dispatch: Dispatch::ToEditor(DispatchEditor::AddSelection(Movement::Next)),
+    },
+    Command {
+        name: "add-selection-previous",
+        description: "Add the previous selection of the current selection mode",
+        dispatch: Dispatch::ToEditor(DispatchEditor::AddSelection(Movement::Previous)),
+    },
+    Command {
+        name: "goto-next-blank-line",
+        description: "Go to the next blank line, consecutive blank lines are a single stop",
+        dispatch: Dispatch::ToEditor(DispatchEditor::GotoNextBlankLine),
+    },
+    Command {
+        name: "goto-previous-blank-line",
+        description: "Go to the previous blank line, consecutive blank lines are a single stop",
+        dispatch: Dispatch::ToEditor(DispatchEditor::GotoPreviousBlankLine),
+    },
+    Command {
+        name: "copy-to-system-clipboard",
+        description: "Copy the selections to the system clipboard",
+        dispatch: Dispatch::ToEditor(DispatchEditor::CopyToSystemClipboard),
+    },
+    Command {
+        name: "paste-from-system-clipboard",
+        description: "Paste the content of the system clipboard",
+        dispatch: Dispatch::ToEditor(DispatchEditor::PasteFromSystemClipboard),
+    },
+    Command {
+        name: "copy-to-register",
+        description: "Copy the selections to a named register",
+        dispatch: Dispatch::OpenRegisterPrompt(RegisterOperation::Copy),
+    },
+    Command {
+        name: "cut-to-register",
+        description: "Cut the selections to a named register",
+        dispatch: Dispatch::OpenRegisterPrompt(RegisterOperation::Cut),
+    },
+    Command {
+        name: "paste-from-register",
+        description: "Paste the content of a named register",
+        dispatch: Dispatch::OpenRegisterPrompt(RegisterOperation::Paste),
+    },
+    Command {
+        name: "split-vertical",
+        description: "Open the current buffer in a new pane beside the current pane",
+        dispatch: Dispatch::SplitVertical,
+    },
+    Command {
+        name: "split-horizontal",
+        description: "Open the current buffer in a new pane below the current pane",
+        dispatch: Dispatch::SplitHorizontal,
+    },
+    Command {
+        name: "set-mark",
+        description: "Remember the primary selection under a name",
+        dispatch: Dispatch::OpenRegisterPrompt(RegisterOperation::SetMark),
+    },
+    Command {
+        name: "go-to-mark",
+        description: "Go to the selection remembered under a name",
+        dispatch: Dispatch::OpenRegisterPrompt(RegisterOperation::GoToMark),
+    },
+    Command {
+        name: "record-macro",
+        description: "Record the following inputs into a named register",
+        dispatch: Dispatch::OpenRegisterPrompt(RegisterOperation::RecordMacro),
+    },
+    Command {
+        name: "stop-recording-macro",
+        description: "Stop recording the macro",
+        dispatch: Dispatch::StopRecordingMacro,
+    },
+    Command {
+        name: "replay-macro",
+        description: "Replay the inputs recorded in a named register",
+        dispatch: Dispatch::OpenRegisterPrompt(RegisterOperation::ReplayMacro),
+    },
+    Command {
+        name: "paste-cycle-older",
+        description: "Replace the text just pasted with the previous copied text",
+        dispatch: Dispatch::ToEditor(DispatchEditor::PasteCycleOlder),
+    },
+    Command {
+        name: "paste-cycle-newer",
+        description: "Replace the text just pasted with the next copied text",
+        dispatch: Dispatch::ToEditor(DispatchEditor::PasteCycleNewer),
+    },
+    Command {
+        name: "paste-as-multi-cursor",
+        description: "Replace each selection with the line of the clipboard of the same index",
+        dispatch: Dispatch::ToEditor(DispatchEditor::PasteAsMultiCursor {
+            use_system_clipboard: false,
+        }),
+    },
+    Command {
+        name: "copy-to-previous-word",
+        description: "Copy from the start of the previous word to the cursor",
+        dispatch: Dispatch::ToEditor(DispatchEditor::OperatorMotion {
+            operator: Operator::Copy {
+                use_system_clipboard: false,
+            },
+            motion: Motion::WordBackward,
+        }),
+    },
+    Command {
+        name: "goto-first-non-blank",
+        description: "Go to the first non-whitespace character of the line",
+        dispatch: Dispatch::ToEditor(DispatchEditor::GotoFirstNonBlank),
+    },
+    Command {
+        name: "goto-line-end-non-blank",
+        description: "Go to the last non-whitespace character of the line",
+        dispatch: Dispatch::ToEditor(DispatchEditor::GotoLineEndNonBlank),
+    },
+    Command {
+        name: "select-whole-function",
+        description: "Select the function or method enclosing the cursor",
+        dispatch: Dispatch::ToEditor(DispatchEditor::SelectWholeFunction),
+    },
+    Command {
+        name: "goto-matching-conditional",
+        description: "Go to the next directive of the enclosing preprocessor conditional",
+        dispatch: Dispatch::ToEditor(DispatchEditor::GotoMatchingConditional),
+    },
+    Command {
+        name: "insert-at-each-selection",
+        description: "Insert text at each cursor, where {i} is replaced by the cursor index",
+        dispatch: Dispatch::OpenInsertAtEachSelectionPrompt,
+    },
+    Command {
+        name: "split-selections-on-regex",
+        description: "Split each selection into the parts between the matches of a regex",
+        dispatch: Dispatch::OpenSplitSelectionsPrompt,
+    },
+    Command {
+        name: "set-undo-limit",
+        description: "Set the maximum number of undo entries of each buffer",
+        dispatch: Dispatch::OpenSetUndoLimitPrompt,
+    },
+    Command {
+        name: "set-clipboard-history-depth",
+        description: "Set the number of copied texts that pasting can cycle through",
+        dispatch: Dispatch::OpenSetClipboardHistoryDepthPrompt,
+    },
+    Command {
+        name: "toggle-ignored-files",
+        description: "Include or skip ignored files in the file picker and global search",
+        dispatch: Dispatch::ToggleIncludeIgnoredFiles,
+    },
+    Command {
+        name: "kill",
+        description: "Delete the selections and add them to the kill ring",
+        dispatch: Dispatch::ToEditor(DispatchEditor::Kill),
+    },
+    Command {
+        name: "move-node-up",
+        description: "Swap the selected syntax node with its previous sibling",
+        dispatch: Dispatch::ToEditor(DispatchEditor::MoveNodeUp),
+    },
+    Command {
+        name: "move-node-down",
+        description: "Swap the selected syntax node with its next sibling",
+        dispatch: Dispatch::ToEditor(DispatchEditor::MoveNodeDown),
+    },
+    Command {
+        name: "toggle-merge-overlapping-selections",
+        description: "Toggle merging of overlapping selections",
+        dispatch: Dispatch::ToEditor(DispatchEditor::ToggleMergeOverlappingSelections),
+    },
+    Command {
+        name: "peek-definition",
+        description: "Show the definition in a popup",
+        dispatch: Dispatch::PeekDefinition,
+    },
+    Command {
+        name: "goto-definition-in-split",
+        description: "Open the definition of the symbol under the cursor in a new split pane",
+        dispatch: Dispatch::GotoDefinitionInSplit,
+    },
+    Command {
+        name: "toggle-quickfix-preview",
+        description: "Toggle previewing the quickfix list items instead of jumping to them",
+        dispatch: Dispatch::ToggleQuickfixPreview,
+    },
+    Command {
+        name: "goto-current-quickfix-item",
+        description: "Jump to the current quickfix list item",
+        dispatch: Dispatch::GotoQuickfixListItem(Movement::Current(IfCurrentNotFound::LookForward)),
+    },
+    Command {
+        name: "goto-next-reference",
+        description: "Go to the next reference of the symbol under the cursor",
+        dispatch: Dispatch::ToEditor(DispatchEditor::GotoNextReference),
+    },
+    Command {
+        name: "goto-previous-reference",
+        description: "Go to the previous reference of the symbol under the cursor",
+        dispatch: Dispatch::ToEditor(DispatchEditor::GotoPreviousReference),
+    },
+    Command {
+        name: "expand-semantic-selection",
+        description:
+            "Expand the selection to the enclosing semantic range given by the language server",
+        dispatch: Dispatch::ToEditor(DispatchEditor::SelectSemanticRange(Direction::End)),
+    },
+    Command {
+        name: "shrink-semantic-selection",
+        description:
+            "Shrink the selection to the enclosed semantic range given by the language server",
+        dispatch: Dispatch::ToEditor(DispatchEditor::SelectSemanticRange(Direction::Start)),
+    },
+    Command {
+        name: "selections-to-quickfix",
+        description: "Put the current selections into a quickfix list",
+        dispatch: Dispatch::SelectionsToQuickfix,
+    },
+    Command {
+        name: "replace-all-matching",
+        description: "Replace every occurrence of a literal in the extended selections or the buffer",
+        dispatch: Dispatch::OpenReplaceAllMatchingPrompt { regex: false },
+    },
+    Command {
+        name: "replace-all-matching-regex",
+        description: "Replace every match of a regex in the extended selections or the buffer, `$1` refers to its first capture group",
+        dispatch: Dispatch::OpenReplaceAllMatchingPrompt { regex: true },
+    },
 ];
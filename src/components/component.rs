@@ -41,7 +41,7 @@ pub(crate) struct Cursor {
 }
 
 /// Why is this necessary? Because `crossterm::cursor::SetCursorStyle` does not implement `Debug`.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SetCursorStyle {
     DefaultUserShape,
     BlinkingBlock,
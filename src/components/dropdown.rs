@@ -1,4 +1,4 @@
-use std::cmp::Reverse;
+use std::{cmp::Reverse, path::PathBuf};
 
 use crate::{app::Dispatches, components::editor::Movement, position::Position};
 
@@ -68,28 +68,37 @@ impl DropdownItem {
     pub(crate) fn resolved(&self) -> bool {
         self.resolved
     }
-}
 
-impl From<CanonicalizedPath> for DropdownItem {
-    fn from(value: CanonicalizedPath) -> Self {
+    /// An item that opens `path`, grouped by its parent directory,
+    /// which is shown relative to `working_directory` unless `show_absolute_paths` is true.
+    pub(crate) fn from_path(
+        path: PathBuf,
+        working_directory: &CanonicalizedPath,
+        show_absolute_paths: bool,
+    ) -> DropdownItem {
         DropdownItem::new({
-            let name = value
-                .to_path_buf()
+            let name = path
                 .file_name()
                 .unwrap_or_default()
                 .to_string_lossy()
                 .to_string();
-            let icon = value.icon();
+            let icon = shared::canonicalized_path::get_path_icon(&path);
             format!("{icon} {name}")
         })
-        .set_group(value.parent().ok().flatten().map(|parent| {
-            format!(
-                "{} {}",
-                get_icon_config().folder,
-                parent.try_display_relative()
-            )
+        .set_group(path.parent().map(|parent| {
+            let parent = if show_absolute_paths {
+                parent.display().to_string()
+            } else {
+                parent
+                    .strip_prefix(working_directory.to_path_buf())
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_else(|_| parent.display().to_string())
+            };
+            format!("{} {}", get_icon_config().folder, parent)
         }))
-        .set_dispatches(Dispatches::one(crate::app::Dispatch::OpenFile(value)))
+        .set_dispatches(Dispatches::one(crate::app::Dispatch::OpenFileFromPathBuf(
+            path,
+        )))
     }
 }
 
@@ -4,10 +4,11 @@ use crate::{
     char_index_range::CharIndexRange,
     clipboard::CopiedTexts,
     context::{Context, GlobalMode, LocalSearchConfigMode, Search},
+    grid::{get_char_width, get_string_width},
     lsp::{completion::CompletionItemEdit, process::ResponseContext},
     selection_mode::{self, regex::get_regex},
     surround::EnclosureKind,
-    transformation::{MyRegex, Transformation},
+    transformation::{increment_number, MyRegex, TransformKind, Transformation},
 };
 
 use nonempty::NonEmpty;
@@ -23,6 +24,7 @@ use event::KeyEvent;
 use itertools::{Either, Itertools};
 use my_proc_macros::key;
 use ropey::Rope;
+use tree_sitter_traversal::Order;
 
 use crate::{
     app::{Dimension, Dispatch},
@@ -31,6 +33,7 @@ use crate::{
     edit::{Action, ActionGroup, Edit, EditTransaction},
     lsp::completion::PositionalEdit,
     position::Position,
+    quickfix_list::DiagnosticSeverityRange,
     rectangle::Rectangle,
     selection::{CharIndex, Selection, SelectionMode, SelectionSet},
 };
@@ -55,6 +58,8 @@ pub(crate) enum Mode {
     UndoTree,
     Replace,
     V,
+    /// Typed characters overwrite the characters under the cursors instead of being inserted.
+    Overtype,
 }
 
 #[derive(PartialEq, Clone, Debug)]
@@ -182,6 +187,79 @@ impl Component for Editor {
         &mut self,
         context: &mut Context,
         dispatch: DispatchEditor,
+    ) -> anyhow::Result<Dispatches> {
+        let is_kill_ring_dispatch = matches!(dispatch, Kill | KillLine(_) | Yank | YankPop);
+        let repeated_dispatch = match &dispatch {
+            RepeatLastEdit => self.last_edit.first(),
+            dispatch => Some(dispatch),
+        };
+        let is_delete_dispatch = matches!(
+            repeated_dispatch,
+            Some(
+                Delete(_)
+                    | DeleteCut { .. }
+                    | CutToRegister(_)
+                    | OperatorMotion {
+                        operator: Operator::Delete | Operator::Cut { .. },
+                        ..
+                    }
+            )
+        );
+        let last_edit = dispatch.is_repeatable_edit().then(|| dispatch.clone());
+        let was_inserting = self.mode == Mode::Insert;
+        let insertion_start = (!was_inserting).then(|| dispatch.clone());
+        let insert_mode_dispatch =
+            (was_inserting && dispatch.is_insert_mode_edit()).then(|| dispatch.clone());
+        let is_paste_dispatch = matches!(
+            dispatch,
+            Paste { .. }
+                | PasteBefore { .. }
+                | PasteAfter { .. }
+                | PasteCycleOlder
+                | PasteCycleNewer
+        );
+        // The rectangular selection is only extended, copied or pasted,
+        // any other dispatch ends it
+        if !matches!(
+            dispatch,
+            MoveSelection(Movement::Up | Movement::Down)
+                | Copy { .. }
+                | Paste { .. }
+                | PasteBefore { .. }
+                | PasteAfter { .. }
+                | ToggleColumnSelection
+        ) {
+            self.column_selection = None;
+        }
+        let dispatches = self.apply_dispatch_editor(context, dispatch)?;
+        if let Some(last_edit) = last_edit {
+            self.last_edit = vec![last_edit];
+        }
+        if let (Some(dispatch), Some(insertion)) = (insert_mode_dispatch, self.insertion.as_mut()) {
+            insertion.push(dispatch)
+        }
+        self.record_insertion(was_inserting, insertion_start);
+        if !is_kill_ring_dispatch {
+            self.last_kill_ring_command = None;
+        }
+        if !is_delete_dispatch {
+            self.pending_delete = None;
+        }
+        if !is_paste_dispatch {
+            self.cycling_pasted_text = false;
+        }
+        if self.merge_overlapping_selections {
+            self.selection_set.merge_overlapping();
+        }
+        Ok(dispatches)
+    }
+}
+
+impl Editor {
+    fn apply_dispatch_editor(
+        &mut self,
+        context: &mut Context,
+        dispatch: DispatchEditor,
     ) -> anyhow::Result<Dispatches> {
         match dispatch {
             #[cfg(test)]
@@ -189,18 +267,68 @@ impl Component for Editor {
             #[cfg(test)]
             AlignViewBottom => self.align_cursor_to_bottom(),
             Transform(transformation) => return self.transform_selection(transformation),
+            ApplyTransform(kind) => return self.apply_transform(kind),
+            FormatJson { indent } => {
+                return self.pretty_print_selections(crate::pretty_print::format_json, indent)
+            }
+            FormatXml { indent } => {
+                return self.pretty_print_selections(crate::pretty_print::format_xml, indent)
+            }
             SetSelectionMode(if_current_not_found, selection_mode) => {
                 return self.set_selection_mode(if_current_not_found, selection_mode);
             }
+            CycleSelectionMode(direction) => return self.cycle_selection_mode(context, direction),
+            SetBufferVar(key, value) => self.buffer_mut().set_var(key, value),
+            ShowBufferVars => return Ok(self.show_buffer_vars()),
 
             FindOneChar(if_current_not_found) => {
                 self.enter_single_character_mode(if_current_not_found)
             }
 
             MoveSelection(direction) => return self.handle_movement(context, direction),
+            GotoNextMisspelling => return self.goto_next_misspelling(context),
+            GotoNextLongLine => return self.goto_next_long_line(context),
+            GotoNextDiagnostic => return self.goto_diagnostic(Direction::End),
+            GotoPreviousDiagnostic => return self.goto_diagnostic(Direction::Start),
+            GotoMatchingConditional => return self.goto_matching_conditional(),
+            GotoMatchingBracket => return self.goto_matching_bracket(),
+            SelectWholeFunction => return self.select_whole_function(),
+            SelectNextNodeOfKind(kind) => return self.select_next_node_of_kind(&kind),
+            ToggleColumnSelection => return self.toggle_column_selection(),
+            AlignSelections(delimiter) => return self.align_selections(delimiter),
+            IncrementNumber(delta) => return self.increment_numbers(|_| Ok(delta)),
+            DecrementNumber(delta) => {
+                return self.increment_numbers(|_| {
+                    delta
+                        .checked_neg()
+                        .ok_or_else(|| anyhow::anyhow!("Cannot decrement by {delta}"))
+                })
+            }
+            IncrementNumberSequence(step) => {
+                return self.increment_numbers(|index| {
+                    step.checked_mul(index as i64).ok_or_else(|| {
+                        anyhow::anyhow!("The step {step} of selection {index} overflows")
+                    })
+                })
+            }
+            OperatorMotion { operator, motion } => {
+                return self.operator_motion(context, operator, motion)
+            }
             Copy {
                 use_system_clipboard,
             } => return self.copy(use_system_clipboard),
+            CopyToSystemClipboard => return self.copy(true),
+            PasteFromSystemClipboard => return self.paste(Direction::End, context, true),
+            CopyToRegister(register) => return Ok(self.copy_to_register(register)),
+            CutToRegister(register) => {
+                return Ok(self.copy_to_register(register).chain(self.delete(
+                    context,
+                    Direction::End,
+                    None,
+                )?))
+            }
+            PasteFromRegister(register) => return self.paste_from_register(context, register),
+            ExportSelectionsJson => return self.export_selections_json(),
             ReplaceWithCopiedText {
                 cut,
                 use_system_clipboard,
@@ -211,19 +339,31 @@ impl Component for Editor {
             EnterVMode => self.enter_v_mode(),
             EnterUndoTreeMode => return Ok(self.enter_undo_tree_mode()),
             EnterInsertMode(direction) => return self.enter_insert_mode(direction),
-            Delete(direction) => return self.delete(direction, None),
+            Delete(direction) => return self.delete(context, direction, None),
             DeleteCut {
                 direction,
                 use_system_clipboard,
-            } => return self.delete(direction, Some(use_system_clipboard)),
+            } => return self.delete(context, direction, Some(use_system_clipboard)),
             Insert(string) => return self.insert(&string),
+            InsertAtEachSelection(template) => return self.insert_at_each_selection(&template),
+            BreakLine => return self.break_line(),
             #[cfg(test)]
             MatchLiteral(literal) => return self.match_literal(&literal),
             ToggleMark => self.toggle_marks(),
             EnterNormalMode => self.enter_normal_mode()?,
             CursorAddToAllSelections => self.add_cursor_to_all_selections()?,
+            AddSelection(movement) => self.add_cursor(&movement)?,
             CursorKeepPrimaryOnly => self.cursor_keep_primary_only(),
+            AddCursorToNextMatch => self.add_cursor_to_next_match(true)?,
+            SkipCurrentMatch => self.add_cursor_to_next_match(false)?,
             EnterExchangeMode => self.enter_exchange_mode(),
+            MoveNodeUp => return self.move_node(Movement::Previous),
+            MoveNodeDown => return self.move_node(Movement::Next),
+            ReplaceAllMatching {
+                pattern,
+                replacement,
+                regex,
+            } => return self.replace_all_matching(&pattern, &replacement, regex),
             ReplacePattern { config } => {
                 let selection_set = self.selection_set.clone();
                 let (_, selection_set) = self.buffer_mut().replace(config, selection_set)?;
@@ -235,13 +375,20 @@ impl Component for Editor {
                 let dispatches = self.undo();
                 return dispatches;
             }
-            KillLine(direction) => return self.kill_line(direction),
+            KillLine(direction) => return self.kill_line(context, direction),
+            Kill => return self.kill(context),
+            Yank => return self.yank(context),
+            YankPop => return self.yank_pop(context),
             #[cfg(test)]
             Reset => self.reset(),
             DeleteWordBackward { short } => return self.delete_word_backward(short),
             Backspace => return self.backspace(),
             MoveToLineStart => return self.move_to_line_start(),
             MoveToLineEnd => return self.move_to_line_end(),
+            GotoNextBlankLine => return self.goto_blank_line(Direction::End),
+            GotoPreviousBlankLine => return self.goto_blank_line(Direction::Start),
+            GotoFirstNonBlank => return self.goto_non_blank(true),
+            GotoLineEndNonBlank => return self.goto_non_blank(false),
             SelectLine(movement) => return self.select_line(movement),
             Redo => return self.redo(),
             Change => return self.change(),
@@ -271,6 +418,12 @@ impl Component for Editor {
             SelectLineAt(index) => return Ok(self.select_line_at(index)?.into_vec().into()),
             EnterMultiCursorMode => self.enter_multicursor_mode(),
             Surround(open, close) => return self.enclose(open, close),
+            SurroundWith(c) => {
+                let (open, close) = EnclosureKind::from_char(c)
+                    .map(|enclosure| enclosure.open_close_symbols())
+                    .unwrap_or((c, c));
+                return self.enclose(open.to_string(), close.to_string());
+            }
             ShowKeymapLegendInsertMode => {
                 return Ok([Dispatch::ShowKeymapLegend(
                     self.insert_mode_keymap_legend_config(),
@@ -293,10 +446,22 @@ impl Component for Editor {
                 .into())
             }
             EnterReplaceMode => self.enter_replace_mode(),
+            EnterOvertypeMode => return self.enter_overtype_mode(),
             Paste {
                 direction,
                 use_system_clipboard,
             } => return self.paste(direction, context, use_system_clipboard),
+            PasteBefore {
+                use_system_clipboard,
+            } => return self.paste(Direction::Start, context, use_system_clipboard),
+            PasteAfter {
+                use_system_clipboard,
+            } => return self.paste(Direction::End, context, use_system_clipboard),
+            PasteCycleOlder => return self.cycle_pasted_text(context, Direction::Start),
+            PasteCycleNewer => return self.cycle_pasted_text(context, Direction::End),
+            PasteAsMultiCursor {
+                use_system_clipboard,
+            } => return self.paste_as_multi_cursor(context, use_system_clipboard),
             SwapCursorWithAnchor => self.swap_cursor_with_anchor(),
             SetDecorations(decorations) => self.buffer_mut().set_decorations(&decorations),
             MoveCharacterBack => self.selection_set.move_left(&self.cursor_direction),
@@ -317,7 +482,7 @@ impl Component for Editor {
             ReplaceWithPattern => return self.replace_with_pattern(context),
             Replace(movement) => return self.replace_with_movement(&movement),
             ApplyPositionalEdits(edits) => {
-                return self.apply_positional_edits(
+                return self.apply_edits(
                     edits
                         .into_iter()
                         .map(|edit| match edit {
@@ -335,12 +500,38 @@ impl Component for Editor {
                 return self.replace_with_copied_text(context, false, false, history_offset);
             }
             MoveToLastChar => return Ok(self.move_to_last_char()),
+            RepeatLastEdit => {
+                return self.last_edit.clone().into_iter().try_fold(
+                    Dispatches::default(),
+                    |dispatches, dispatch| {
+                        Ok(dispatches.chain(self.apply_dispatch_editor(context, dispatch)?))
+                    },
+                )
+            }
             PipeToShell { command } => return self.pipe_to_shell(command),
             ShowCurrentTreeSitterNodeSexp => return self.show_current_tree_sitter_node_sexp(),
             Indent => return self.indent(),
             Dedent => return self.dedent(),
+            StripCommonIndent => return self.strip_common_indent(),
+            SortLines {
+                ascending,
+                case_sensitive,
+            } => return self.sort_lines(ascending, case_sensitive),
+            DedupLines => return self.dedup_lines(),
+            ToggleMarkerFold => self.toggle_marker_fold(context)?,
             CyclePrimarySelection(direction) => self.cycle_primary_selection(direction),
+            SelectSemanticRange(direction) => return self.select_semantic_range(direction),
+            SetSemanticRanges(ranges) => return self.set_semantic_ranges(ranges),
+            GotoNextReference => {
+                return Ok(Dispatches::one(Dispatch::GotoReference(Direction::End)))
+            }
+            GotoPreviousReference => {
+                return Ok(Dispatches::one(Dispatch::GotoReference(Direction::Start)))
+            }
             SwapExtensionDirection => self.selection_set.swap_initial_range_direction(),
+            ToggleMergeOverlappingSelections => {
+                self.merge_overlapping_selections = !self.merge_overlapping_selections
+            }
             CollapseSelection(direction) => return self.collapse_selection(context, direction),
             EnterTillMode(if_current_not_found) => {
                 self.mode = Mode::TillOneChar(if_current_not_found)
@@ -352,6 +543,7 @@ impl Component for Editor {
                     keep,
                 ))
             }
+            SplitSelectionOnRegex(regex) => return self.split_selection_on_regex(&regex),
         }
         Ok(Default::default())
     }
@@ -372,6 +564,16 @@ impl Clone for Editor {
             current_view_alignment: None,
             regex_highlight_rules: Vec::new(),
             copied_text_history_offset: Default::default(),
+            merge_overlapping_selections: self.merge_overlapping_selections,
+            overtyped_chars: Vec::new(),
+            semantic_ranges: Vec::new(),
+            last_kill_ring_command: None,
+            pending_delete: None,
+            cycling_pasted_text: false,
+            last_edit: Vec::new(),
+            insertion: None,
+            pending_selections: None,
+            column_selection: None,
         }
     }
 }
@@ -394,6 +596,77 @@ pub(crate) struct Editor {
     id: ComponentId,
     pub(crate) current_view_alignment: Option<ViewAlignment>,
     copied_text_history_offset: Counter,
+    /// If true, selections that overlap each other are merged after every dispatch,
+    /// so that the same text is not edited more than once.
+    merge_overlapping_selections: bool,
+    /// The characters overwritten by each keystroke of the current overtype session,
+    /// one entry per selection, `None` means the typed character was appended instead.
+    /// Used for restoring the overwritten characters upon backspace.
+    overtyped_chars: Vec<Vec<Option<char>>>,
+    /// The semantic ranges enclosing the primary cursor, from the innermost to the outermost,
+    /// as provided by the LSP server.
+    semantic_ranges: Vec<Range<Position>>,
+    /// The kill ring command executed by the last dispatch,
+    /// used for accumulating consecutive kills and for yank-pop.
+    last_kill_ring_command: Option<KillRingCommand>,
+    /// The ranges to be removed by the pending deletion, which is only applied when
+    /// the deletion is issued again on the same ranges, refer `Context::confirm_delete`.
+    pending_delete: Option<Vec<CharIndexRange>>,
+    /// True if the last dispatch pasted the copied text of `copied_text_history_offset`,
+    /// which can then be replaced with an older or newer copied text.
+    cycling_pasted_text: bool,
+    /// The dispatches of the last edit, repeated by `DispatchEditor::RepeatLastEdit`.
+    /// It is a single dispatch, except for the text typed in insert mode, refer `Editor::record_insertion`.
+    /// Unlike the fields above, it is not cleared by other dispatches such as movements.
+    last_edit: Vec<DispatchEditor>,
+    /// The dispatch that entered insert mode, followed by the dispatches made in insert mode,
+    /// refer `Editor::record_insertion`
+    insertion: Option<Vec<DispatchEditor>>,
+    /// The selection mode, selection ranges and cursor index restored by
+    /// `Editor::restore_selections` while the buffer is still loading,
+    /// which are applied once the content is loaded, refer `Editor::handle_buffer_loaded`
+    pending_selections: Option<(SelectionMode, Vec<Range<usize>>, usize)>,
+    /// The rectangular selection being made, refer `DispatchEditor::ToggleColumnSelection`
+    column_selection: Option<ColumnSelection>,
+}
+
+/// A rectangle spanning from the line of the anchor to the line of the cursor,
+/// covering the same columns on every line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ColumnSelection {
+    anchor_line: usize,
+    cursor_line: usize,
+    /// Display columns, so that the rectangle is aligned on screen
+    /// even if the lines contain tabs or wide characters
+    columns: Range<usize>,
+}
+
+/// Returns the char range of `line` covering the display `columns`,
+/// or `None` if `line` ends before the start column.
+fn char_range_of_display_columns(line: &str, columns: &Range<usize>) -> Option<Range<usize>> {
+    let mut start = None;
+    let mut display_column = 0;
+    for (index, character) in line.chars().enumerate() {
+        let width = get_char_width(character);
+        if start.is_none() && columns.start < display_column + width {
+            start = Some(index)
+        }
+        if display_column >= columns.end {
+            return start.map(|start| start..index.max(start));
+        }
+        display_column += width;
+    }
+    let length = line.chars().count();
+    start
+        .or((display_column >= columns.start).then_some(length))
+        .map(|start| start..length)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum KillRingCommand {
+    Kill,
+    /// The ranges of the yanked texts, one per selection
+    Yank(Vec<CharIndexRange>),
 }
 
 #[derive(Default)]
@@ -557,6 +830,16 @@ impl Editor {
             current_view_alignment: None,
             regex_highlight_rules: Vec::new(),
             copied_text_history_offset: Default::default(),
+            merge_overlapping_selections: true,
+            overtyped_chars: Vec::new(),
+            semantic_ranges: Vec::new(),
+            last_kill_ring_command: None,
+            pending_delete: None,
+            cycling_pasted_text: false,
+            last_edit: Vec::new(),
+            insertion: None,
+            pending_selections: None,
+            column_selection: None,
         }
     }
 
@@ -574,6 +857,16 @@ impl Editor {
             current_view_alignment: None,
             regex_highlight_rules: Vec::new(),
             copied_text_history_offset: Default::default(),
+            merge_overlapping_selections: true,
+            overtyped_chars: Vec::new(),
+            semantic_ranges: Vec::new(),
+            last_kill_ring_command: None,
+            pending_delete: None,
+            cycling_pasted_text: false,
+            last_edit: Vec::new(),
+            insertion: None,
+            pending_selections: None,
+            column_selection: None,
         }
     }
 
@@ -762,14 +1055,26 @@ impl Editor {
 
     pub(crate) fn delete(
         &mut self,
+        context: &Context,
         direction: Direction,
         use_system_clipboard: Option<bool>,
     ) -> anyhow::Result<Dispatches> {
+        if context.confirm_delete() {
+            let ranges = self
+                .selection_set
+                .map(|selection| selection.extended_range())
+                .into();
+            if self.pending_delete.take().as_ref() != Some(&ranges) {
+                self.pending_delete = Some(ranges);
+                return Ok(Default::default());
+            }
+        }
         let copy_dispatches = if let Some(use_system_clipboard) = use_system_clipboard {
             self.copy(use_system_clipboard)?
         } else {
             Default::default()
-        };
+        }
+        .append(self.record_deletion());
         let edit_transaction = EditTransaction::from_action_groups({
             let buffer = self.buffer();
             self.selection_set
@@ -856,16 +1161,74 @@ impl Editor {
         Ok(copy_dispatches.chain(dispatches))
     }
 
+    pub(crate) fn pending_delete(&self) -> Option<&Vec<CharIndexRange>> {
+        self.pending_delete.as_ref()
+    }
+
     pub(crate) fn copy(&mut self, use_system_clipboard: bool) -> anyhow::Result<Dispatches> {
         Ok(Dispatches::one(Dispatch::SetClipboardContent {
             use_system_clipboard,
-            copied_texts: CopiedTexts::new(self.selection_set.map(|selection| {
-                self.buffer()
-                    .slice(&selection.extended_range())
-                    .ok()
-                    .map(|s| s.to_string())
-                    .unwrap_or_default()
-            })),
+            copied_texts: self.selected_copied_texts(),
+        }))
+    }
+
+    fn selected_copied_texts(&self) -> CopiedTexts {
+        CopiedTexts::new(self.selection_set.map(|selection| {
+            self.buffer()
+                .slice(&selection.extended_range())
+                .ok()
+                .map(|s| s.to_string())
+                .unwrap_or_default()
+        }))
+    }
+
+    /// Deletions of whole lines or multiple lines go to the numbered registers,
+    /// while smaller deletions go to the small delete register
+    fn record_deletion(&self) -> Dispatch {
+        let copied_texts = self.selected_copied_texts();
+        Dispatch::RecordDeletion {
+            small: self.selection_set.mode != SelectionMode::Line && copied_texts.is_single_line(),
+            copied_texts,
+        }
+    }
+
+    fn copy_to_register(&self, register: char) -> Dispatches {
+        Dispatches::one(Dispatch::SetRegisterContent {
+            register,
+            copied_texts: self.selected_copied_texts(),
+        })
+    }
+
+    /// Pastes nothing if the register is empty
+    fn paste_from_register(
+        &mut self,
+        context: &Context,
+        register: char,
+    ) -> anyhow::Result<Dispatches> {
+        let Some(copied_texts) = context.get_register_content(register) else {
+            return Ok(Default::default());
+        };
+        self.paste_text(Direction::End, copied_texts)
+    }
+
+    fn export_selections_json(&self) -> anyhow::Result<Dispatches> {
+        let buffer = self.buffer();
+        let selections = self
+            .selection_set
+            .selections()
+            .iter()
+            .map(|selection| -> anyhow::Result<_> {
+                let range = selection.extended_range();
+                Ok(SelectionJson {
+                    start: range.start.0,
+                    end: range.end.0,
+                    text: buffer.slice(&range)?.to_string(),
+                })
+            })
+            .try_collect::<_, Vec<_>, _>()?;
+        Ok(Dispatches::one(Dispatch::SetClipboardContent {
+            use_system_clipboard: false,
+            copied_texts: CopiedTexts::one(serde_json::to_string(&selections)?),
         }))
     }
 
@@ -1020,9 +1383,69 @@ impl Editor {
         let Some(copied_texts) = context.get_clipboard_content(use_system_clipboard, 0)? else {
             return Ok(Default::default());
         };
+        // The pasted text is only selected in Normal mode.
+        // The system clipboard is not part of the history,
+        // so the text pasted from it cannot be cycled.
+        self.cycling_pasted_text = !use_system_clipboard && self.mode == Mode::Normal;
+        if self.cycling_pasted_text {
+            self.copied_text_history_offset.reset();
+        }
         self.paste_text(direction, copied_texts)
     }
 
+    /// Like Emacs' `yank-pop`, replace the text pasted by the last dispatch
+    /// with the older (`Direction::Start`) or newer (`Direction::End`) copied text
+    /// in the clipboard history.
+    ///
+    /// Does nothing if the last dispatch is not a paste.
+    fn cycle_pasted_text(
+        &mut self,
+        context: &Context,
+        direction: Direction,
+    ) -> anyhow::Result<Dispatches> {
+        if !self.cycling_pasted_text {
+            return Ok(Default::default());
+        }
+        let history_offset = match direction {
+            Direction::Start => self.copied_text_history_offset.decrement(),
+            Direction::End => self.copied_text_history_offset.increment(),
+        };
+        self.replace_with_copied_text(context, false, false, history_offset)
+    }
+
+    /// Replace the i-th selection with the i-th line of the clipboard.
+    /// If the number of lines differs from the number of selections,
+    /// the clipboard is pasted as usual, with a warning.
+    fn paste_as_multi_cursor(
+        &mut self,
+        context: &Context,
+        use_system_clipboard: bool,
+    ) -> anyhow::Result<Dispatches> {
+        let Some(copied_texts) = context.get_clipboard_content(use_system_clipboard, 0)? else {
+            return Ok(Default::default());
+        };
+        let lines = copied_texts.lines();
+        let selections_count = self.selection_set.len();
+        match NonEmpty::from_vec(lines) {
+            Some(lines) if lines.len() == selections_count => {
+                self.transform_selection(Transformation::ReplaceWithCopiedText {
+                    copied_texts: CopiedTexts::new(lines),
+                })
+            }
+            lines => {
+                let lines_count = lines.map(|lines| lines.len()).unwrap_or_default();
+                Ok(self
+                    .paste_text(Direction::End, copied_texts)?
+                    .append(Dispatch::ShowEditorInfo(Info::new(
+                        "Warning".to_string(),
+                        format!(
+                            "The clipboard has {lines_count} line(s) but there are {selections_count} selection(s), pasted as usual instead"
+                        ),
+                    ))))
+            }
+        }
+    }
+
     /// If `cut` if true, the replaced text will override the clipboard.  
     ///
     /// If `history_offset` is 0, it means select the latest copied text;  
@@ -1069,7 +1492,16 @@ impl Editor {
         Ok(self.get_document_did_change_dispatch())
     }
 
+    /// Refer `App::register_buffer_change_observer`
+    fn buffer_changed_dispatch(&mut self) -> Option<Dispatch> {
+        let changes = self.buffer_mut().take_changes();
+        (!changes.is_empty()).then_some(Dispatch::BufferChanged(changes))
+    }
+
     pub(crate) fn get_document_did_change_dispatch(&mut self) -> Dispatches {
+        // The semantic ranges no longer match the edited content
+        self.semantic_ranges.clear();
+        let buffer_changed = self.buffer_changed_dispatch();
         [Dispatch::DocumentDidChange {
             component_id: self.id(),
             path: self.buffer().path(),
@@ -1077,6 +1509,7 @@ impl Editor {
             language: self.buffer().language(),
         }]
         .into_iter()
+        .chain(buffer_changed)
         .chain(if self.mode == Mode::UndoTree {
             Some(self.show_undo_tree_dispatch())
         } else {
@@ -1091,6 +1524,23 @@ impl Editor {
         [self.show_undo_tree_dispatch()].to_vec().into()
     }
 
+    fn show_buffer_vars(&self) -> Dispatches {
+        let content = self
+            .buffer()
+            .vars()
+            .iter()
+            .map(|(key, value)| format!("{key} = {value}"))
+            .join("\n");
+        Dispatches::one(Dispatch::ShowEditorInfo(Info::new(
+            "Buffer Variables".to_string(),
+            if content.is_empty() {
+                "[No buffer variables]".to_string()
+            } else {
+                content
+            },
+        )))
+    }
+
     pub(crate) fn show_undo_tree_dispatch(&self) -> Dispatch {
         Dispatch::ShowGlobalInfo(Info::new(
             "Undo Tree History".to_string(),
@@ -1166,6 +1616,7 @@ impl Editor {
                         Mode::UndoTree => self.handle_normal_mode(context, key_event),
                         Mode::Replace => self.handle_normal_mode(context, key_event),
                         Mode::V => self.handle_v_mode(context, key_event),
+                        Mode::Overtype => self.handle_overtype_mode(key_event),
                     }
                 }
             }
@@ -1248,14 +1699,86 @@ impl Editor {
     }
 
     pub(crate) fn change_cut(&mut self, use_system_clipboard: bool) -> anyhow::Result<Dispatches> {
-        Ok(self.copy(use_system_clipboard)?.chain(self.change()?))
+        Ok(self
+            .copy(use_system_clipboard)?
+            .append(self.record_deletion())
+            .chain(self.change()?))
     }
 
     pub(crate) fn insert(&mut self, s: &str) -> anyhow::Result<Dispatches> {
+        if self.mode == Mode::Insert {
+            if let Some(insertion) = self.insertion.as_mut() {
+                // Consecutive typed characters are recorded as one insertion,
+                // the first dispatch is the one that entered insert mode
+                let has_recorded_edits = insertion.len() > 1;
+                match insertion.last_mut() {
+                    Some(Insert(text)) if has_recorded_edits => text.push_str(s),
+                    _ => insertion.push(Insert(s.to_string())),
+                }
+            }
+        }
+        self.insert_per_selection(|_| s.to_string())
+    }
+
+    /// Typing in insert mode does not go through `handle_dispatch_editor`,
+    /// so the typed text is recorded by `Editor::insert`, while the other edits made in
+    /// insert mode, such as `Backspace` and `BreakLine`, are recorded by `handle_dispatch_editor`.
+    /// Once insert mode is exited, the dispatch that entered it and the recorded dispatches
+    /// become the last edit, unless nothing is edited.
+    fn record_insertion(&mut self, was_inserting: bool, insertion_start: Option<DispatchEditor>) {
+        match (was_inserting, self.mode == Mode::Insert) {
+            (false, true) => self.insertion = insertion_start.map(|dispatch| vec![dispatch]),
+            (true, false) => {
+                let Some(insertion) = self.insertion.take() else {
+                    return;
+                };
+                if insertion.len() == 1 && !insertion[0].is_repeatable_edit() {
+                    return;
+                }
+                self.last_edit = insertion
+                    .into_iter()
+                    .chain(Some(EnterNormalMode))
+                    .collect_vec()
+            }
+            _ => {}
+        }
+    }
+
+    /// Every `{i}` in `template` is replaced by the index of the selection,
+    /// the counter can start from a different number with `{i:start}`,
+    /// and increase by a different step with `{i:start:step}`.
+    fn insert_at_each_selection(&mut self, template: &str) -> anyhow::Result<Dispatches> {
+        let regex = regex::Regex::new(r"\{i(?::(-?\d+))?(?::(-?\d+))?\}")?;
+        self.insert_per_selection(|index| {
+            regex
+                .replace_all(template, |captures: &regex::Captures| {
+                    let parse = |group: usize, default: isize| {
+                        captures
+                            .get(group)
+                            .and_then(|m| m.as_str().parse::<isize>().ok())
+                            .unwrap_or(default)
+                    };
+                    (parse(1, 0) + index as isize * parse(2, 1)).to_string()
+                })
+                .to_string()
+        })
+    }
+
+    /// `get_text` receives the index of the selection
+    fn insert_per_selection(
+        &mut self,
+        get_text: impl Fn(usize) -> String,
+    ) -> anyhow::Result<Dispatches> {
+        // Typing breaks the sequence of consecutive kills
+        self.last_kill_ring_command = None;
         let edit_transaction =
             EditTransaction::from_action_groups(
                 self.selection_set
-                    .map(|selection| {
+                    .selections()
+                    .iter()
+                    .enumerate()
+                    .map(|(index, selection)| {
+                        let s = get_text(index);
                         let range = selection.extended_range();
                         ActionGroup::new(
                             [
@@ -1264,7 +1787,7 @@ impl Editor {
                                         let start = selection.to_char_index(&Direction::End);
                                         (start..start).into()
                                     },
-                                    new: Rope::from_str(s),
+                                    new: Rope::from_str(&s),
                                 }),
                                 Action::Select(selection.clone().set_range(
                                     (range.start + s.len()..range.start + s.len()).into(),
@@ -1273,24 +1796,131 @@ impl Editor {
                             .to_vec(),
                         )
                     })
-                    .into(),
+                    .collect(),
             );
 
         self.apply_edit_transaction(edit_transaction)
     }
 
+    /// Insert a newline after each cursor.
+    /// If the cursor is inside a line comment, the comment token is continued on the new line,
+    /// unless the comment is empty, which means the user wants to stop continuing the comment,
+    /// so the empty comment prefix is removed instead of leaving it dangling.
+    pub(crate) fn break_line(&mut self) -> anyhow::Result<Dispatches> {
+        if self.mode == Mode::Insert {
+            // The syntax tree is not reparsed on every edit in insert mode, but it is needed
+            // to tell trailing comments apart from comment prefixes in e.g. strings
+            self.buffer_mut().reparse_tree()?;
+        }
+        let edit_transaction = EditTransaction::from_action_groups({
+            let buffer = self.buffer();
+            let prefix = buffer
+                .language()
+                .and_then(|language| language.line_comment_prefix());
+            self.selection_set
+                .map(|selection| -> anyhow::Result<_> {
+                    let cursor = selection.to_char_index(&Direction::End);
+                    let line_index = buffer.char_to_line(cursor)?;
+                    let line_start = buffer.line_to_char(line_index)?;
+                    let line_before_cursor =
+                        buffer.slice(&(line_start..cursor).into())?.to_string();
+                    let is_shebang = line_index == 0 && line_before_cursor.starts_with("#!");
+                    let line_start_byte = buffer.char_to_byte(line_start)?;
+                    let is_comment_at = |byte: usize, prefix: &str| {
+                        let start = line_start_byte + byte;
+                        let mut node = buffer.tree().and_then(|tree| {
+                            tree.root_node()
+                                .descendant_for_byte_range(start, start + prefix.len())
+                        });
+                        while let Some(current) = node {
+                            if current.kind().contains("comment") {
+                                return true;
+                            }
+                            node = current.parent();
+                        }
+                        false
+                    };
+                    let (start, new) = match prefix.filter(|_| !is_shebang).and_then(|prefix| {
+                        let trailing_comment_start = line_before_cursor
+                            .match_indices(prefix)
+                            .map(|(byte, _)| byte)
+                            .find(|byte| is_comment_at(*byte, prefix));
+                        continue_line_comment(&line_before_cursor, prefix, trailing_comment_start)
+                    }) {
+                        Some(Either::Left(continuation)) => (cursor, format!("\n{continuation}")),
+                        Some(Either::Right(indentation_len)) => {
+                            (line_start + indentation_len, String::new())
+                        }
+                        None => (cursor, "\n".to_string()),
+                    };
+                    let new_cursor = start + new.chars().count();
+                    Ok(ActionGroup::new(
+                        [
+                            Action::Edit(Edit {
+                                range: (start..cursor).into(),
+                                new: Rope::from_str(&new),
+                            }),
+                            Action::Select(
+                                selection.clone().set_range((new_cursor..new_cursor).into()),
+                            ),
+                        ]
+                        .to_vec(),
+                    ))
+                })
+                .into_iter()
+                .flatten()
+                .collect()
+        });
+
+        self.apply_edit_transaction(edit_transaction)
+    }
+
     pub(crate) fn get_request_params(&self) -> Option<RequestParams> {
         let position = self.get_cursor_position().ok()?;
         self.path().map(|path| RequestParams {
             path,
             position,
-            context: ResponseContext {
-                scope: None,
-                description: None,
-            },
+            context: ResponseContext::default(),
+        })
+    }
+
+    /// Similar to `get_request_params`, but the position is the start of the primary selection,
+    /// so that it always lies within the selected symbol regardless of the cursor direction.
+    pub(crate) fn get_selection_request_params(&self) -> Option<RequestParams> {
+        let start = self
+            .selection_set
+            .primary_selection()
+            .extended_range()
+            .start;
+        let position = self.buffer().char_to_position(start).ok()?;
+        self.path().map(|path| RequestParams {
+            path,
+            position,
+            context: ResponseContext::default(),
         })
     }
 
+    fn cycle_selection_mode(
+        &mut self,
+        context: &Context,
+        direction: Direction,
+    ) -> anyhow::Result<Dispatches> {
+        let cycle = context.selection_mode_cycle();
+        if cycle.is_empty() {
+            return Ok(Default::default());
+        }
+        let current = cycle
+            .iter()
+            .position(|selection_mode| selection_mode == &self.selection_set.mode);
+        let index = match (current, direction) {
+            (Some(index), Direction::End) => (index + 1) % cycle.len(),
+            (Some(index), Direction::Start) => (index + cycle.len() - 1) % cycle.len(),
+            (None, Direction::End) => 0,
+            (None, Direction::Start) => cycle.len() - 1,
+        };
+        self.set_selection_mode(IfCurrentNotFound::LookForward, cycle[index].clone())
+    }
+
     pub(crate) fn set_selection_mode(
         &mut self,
         if_current_not_found: IfCurrentNotFound,
@@ -1313,6 +1943,154 @@ impl Editor {
         })
     }
 
+    fn goto_next_misspelling(&mut self, context: &Context) -> anyhow::Result<Dispatches> {
+        if self.selection_set.mode == SelectionMode::Misspelling {
+            self.handle_movement(context, Movement::Next)
+        } else {
+            self.set_selection_mode(IfCurrentNotFound::LookForward, SelectionMode::Misspelling)
+        }
+    }
+
+    /// Returns the ranges of the characters beyond `Context::max_line_length`
+    /// of the lines within `line_range`.
+    pub(crate) fn long_line_overflows(
+        &self,
+        context: &Context,
+        line_range: Range<usize>,
+    ) -> Vec<CharIndexRange> {
+        let Some(max_line_length) = context.max_line_length() else {
+            return Vec::new();
+        };
+        let buffer = self.buffer();
+        line_range
+            .filter_map(|line_index| {
+                let line = buffer.get_line_by_line_index(line_index)?;
+                let length = line
+                    .chars()
+                    .take_while(|c| !matches!(c, '\n' | '\r'))
+                    .count();
+                if length <= max_line_length {
+                    return None;
+                }
+                let start = buffer.line_to_char(line_index).ok()?;
+                Some((start + max_line_length..start + length).into())
+            })
+            .collect()
+    }
+
+    fn goto_next_long_line(&mut self, context: &Context) -> anyhow::Result<Dispatches> {
+        let cursor = self.get_cursor_char_index();
+        let Some(overflow) = self
+            .long_line_overflows(context, 0..self.buffer().len_lines())
+            .into_iter()
+            .find(|overflow| overflow.start > cursor)
+        else {
+            return Ok(Default::default());
+        };
+        Ok(self.update_selection_set(
+            self.selection_set
+                .clone()
+                .set_selections(NonEmpty::singleton(Selection::new(overflow))),
+            true,
+        ))
+    }
+
+    /// Select the next (`Direction::End`) or previous diagnostic relative to the cursor,
+    /// wrapping around at the end or the start of the buffer.
+    fn goto_diagnostic(&mut self, direction: Direction) -> anyhow::Result<Dispatches> {
+        let cursor = self.get_cursor_char_index();
+        let diagnostics = self
+            .buffer()
+            .diagnostics()
+            .into_iter()
+            .sorted_by_key(|diagnostic| diagnostic.range.start)
+            .collect_vec();
+        let diagnostic = match direction {
+            Direction::End => diagnostics
+                .iter()
+                .find(|diagnostic| diagnostic.range.start > cursor)
+                .or(diagnostics.first()),
+            Direction::Start => diagnostics
+                .iter()
+                .rev()
+                .find(|diagnostic| diagnostic.range.start < cursor)
+                .or(diagnostics.last()),
+        };
+        let Some(diagnostic) = diagnostic else {
+            return Ok(Default::default());
+        };
+        Ok(self.update_selection_set(
+            self.selection_set
+                .clone()
+                .set_mode(SelectionMode::Diagnostic(DiagnosticSeverityRange::All))
+                .set_selections(NonEmpty::singleton(
+                    Selection::new(diagnostic.range).set_info(Some(diagnostic.info())),
+                )),
+            true,
+        ))
+    }
+
+    fn goto_matching_conditional(&mut self) -> anyhow::Result<Dispatches> {
+        /// Tree-sitter node kinds of the branches of a preprocessor conditional,
+        /// where each `#elif` or `#else` branch is the `alternative` of its preceding branch
+        const BRANCH_KINDS: &[&str] = &[
+            "preproc_if",
+            "preproc_ifdef",
+            "preproc_elif",
+            "preproc_elifdef",
+            "preproc_else",
+        ];
+        let range = {
+            let buffer = self.buffer();
+            let Some(tree) = buffer.tree() else {
+                return Ok(Default::default());
+            };
+            let cursor = buffer.char_to_byte(self.get_cursor_char_index())?;
+            let Some(mut node) = tree.root_node().descendant_for_byte_range(cursor, cursor) else {
+                return Ok(Default::default());
+            };
+            while !BRANCH_KINDS.contains(&node.kind()) {
+                let Some(parent) = node.parent() else {
+                    return Ok(Default::default());
+                };
+                node = parent;
+            }
+            while let Some(parent) = node.parent().filter(|parent| {
+                parent
+                    .child_by_field_name("alternative")
+                    .is_some_and(|alternative| alternative.id() == node.id())
+            }) {
+                node = parent;
+            }
+            let root = node;
+            let mut directives = Vec::new();
+            let mut branch = Some(root);
+            while let Some(current) = branch {
+                directives.extend(current.child(0).map(|directive| directive.byte_range()));
+                branch = current.child_by_field_name("alternative");
+            }
+            directives.extend(
+                root.child(root.child_count().saturating_sub(1))
+                    .filter(|directive| directive.kind() == "#endif")
+                    .map(|directive| directive.byte_range()),
+            );
+            let Some(next) = directives
+                .iter()
+                .find(|directive| directive.start > cursor)
+                .or_else(|| directives.first())
+            else {
+                return Ok(Default::default());
+            };
+            buffer.byte_range_to_char_index_range(next)?
+        };
+        Ok(self.update_selection_set(
+            self.selection_set
+                .clone()
+                .set_selections(NonEmpty::singleton(Selection::new(range))),
+            true,
+        ))
+    }
+
     fn move_selection_with_selection_mode(
         &mut self,
         context: &Context,
@@ -1326,7 +2104,29 @@ impl Editor {
                 }
             }
         } else {
-            self.move_selection_with_selection_mode_without_global_mode(movement, selection_mode)
+            let selection_set = self.selection_set.clone();
+            let dispatches = self.move_selection_with_selection_mode_without_global_mode(
+                movement,
+                selection_mode.clone(),
+            )?;
+            // The selection stays unchanged when there is no next or previous selection,
+            // searches always wrap around regardless of `Context::wrap_around_movements`
+            let wraps_around = context.wrap_around_movements()
+                || matches!(selection_mode, SelectionMode::Find { .. });
+            let wrapped_movement = match movement {
+                Movement::Next => Some(Movement::First),
+                Movement::Previous => Some(Movement::Last),
+                _ => None,
+            };
+            match wrapped_movement {
+                Some(wrapped_movement) if wraps_around && self.selection_set == selection_set => {
+                    self.move_selection_with_selection_mode_without_global_mode(
+                        wrapped_movement,
+                        selection_mode,
+                    )
+                }
+                _ => Ok(dispatches),
+            }
         }
     }
 
@@ -1337,6 +2137,12 @@ impl Editor {
     ) -> anyhow::Result<Dispatches> {
         self.copied_text_history_offset.reset();
         match self.mode {
+            Mode::Normal
+                if self.column_selection.is_some()
+                    && matches!(movement, Movement::Up | Movement::Down) =>
+            {
+                self.move_column_selection(movement)
+            }
             Mode::Normal => self.move_selection_with_selection_mode(
                 context,
                 movement,
@@ -1362,31 +2168,126 @@ impl Editor {
     }
 
     pub(crate) fn enter_insert_mode(&mut self, direction: Direction) -> anyhow::Result<Dispatches> {
-        self.set_selection_set(self.selection_set.apply(
-            self.selection_set.mode.clone(),
-            |selection| {
-                let range = selection.extended_range();
-                let char_index = match direction {
-                    Direction::Start => range.start,
-                    Direction::End => range.end,
-                };
-                Ok(selection
-                    .clone()
-                    .set_range((char_index..char_index).into())
-                    .set_initial_range(None))
-            },
-        )?);
+        // Inserting on every line of a rectangular selection completes it
+        self.column_selection = None;
+        let selection_set =
+            self.selection_set
+                .apply(self.selection_set.mode.clone(), |selection| {
+                    let range = selection.extended_range();
+                    let char_index = match direction {
+                        Direction::Start => range.start,
+                        Direction::End => range.end,
+                    };
+                    Ok(selection
+                        .clone()
+                        .set_range((char_index..char_index).into())
+                        .set_initial_range(None))
+                })?;
+        // The undo group is ended when exiting insert mode, so it is only begun once per session.
+        // It is begun after everything that can fail, so that a failure never leaves it open.
+        if !matches!(self.mode, Mode::Insert | Mode::Overtype) {
+            let selection_set = self.selection_set.clone();
+            self.buffer_mut().begin_undo_group(selection_set);
+        }
+        self.set_selection_set(selection_set);
         self.mode = Mode::Insert;
         self.cursor_direction = Direction::Start;
         Ok(Dispatches::one(Dispatch::RequestSignatureHelp))
     }
 
+    fn enter_overtype_mode(&mut self) -> anyhow::Result<Dispatches> {
+        let dispatches = self.enter_insert_mode(Direction::Start)?;
+        self.mode = Mode::Overtype;
+        self.overtyped_chars.clear();
+        Ok(dispatches)
+    }
+
+    fn handle_overtype_mode(&mut self, key_event: KeyEvent) -> anyhow::Result<Dispatches> {
+        match key_event.code {
+            KeyCode::Esc => {
+                self.enter_normal_mode()?;
+                Ok(Default::default())
+            }
+            KeyCode::Backspace => self.overtype_backspace(),
+            KeyCode::Char(c) => self.overtype(c),
+            _ => Ok(Default::default()),
+        }
+    }
+
+    /// Overwrite the character after each cursor with `c`,
+    /// except at the end of a line, where `c` is inserted.
+    fn overtype(&mut self, c: char) -> anyhow::Result<Dispatches> {
+        let (action_groups, overtyped_chars): (Vec<_>, Vec<_>) = {
+            let buffer = self.buffer();
+            self.selection_set
+                .map(|selection| {
+                    let start = selection.extended_range().start;
+                    let overtyped_char =
+                        buffer.rope().get_char(start.0).filter(|char| *char != '\n');
+                    let end = start + usize::from(overtyped_char.is_some());
+                    (
+                        ActionGroup::new(
+                            [
+                                Action::Edit(Edit {
+                                    range: (start..end).into(),
+                                    new: Rope::from(c.to_string()),
+                                }),
+                                Action::Select(
+                                    selection.clone().set_range((start + 1..start + 1).into()),
+                                ),
+                            ]
+                            .to_vec(),
+                        ),
+                        overtyped_char,
+                    )
+                })
+                .into_iter()
+                .unzip()
+        };
+        self.overtyped_chars.push(overtyped_chars);
+        self.apply_edit_transaction(EditTransaction::from_action_groups(action_groups))
+    }
+
+    /// Restore the characters overwritten by the last keystroke of the current overtype session.
+    /// Beyond the session, the cursors are moved to the left without deleting anything.
+    fn overtype_backspace(&mut self) -> anyhow::Result<Dispatches> {
+        let Some(overtyped_chars) = self.overtyped_chars.pop() else {
+            self.selection_set.move_left(&self.cursor_direction);
+            return Ok(Default::default());
+        };
+        let edit_transaction = EditTransaction::from_action_groups(
+            self.selection_set
+                .map_with_index(|index, selection| {
+                    let end = selection.extended_range().start;
+                    let start = CharIndex(end.0.saturating_sub(1));
+                    let restored = overtyped_chars
+                        .get(index)
+                        .copied()
+                        .flatten()
+                        .map(|char| char.to_string())
+                        .unwrap_or_default();
+                    ActionGroup::new(
+                        [
+                            Action::Edit(Edit {
+                                range: (start..end).into(),
+                                new: Rope::from(restored),
+                            }),
+                            Action::Select(selection.clone().set_range((start..start).into())),
+                        ]
+                        .to_vec(),
+                    )
+                })
+                .into(),
+        );
+        self.apply_edit_transaction(edit_transaction)
+    }
+
     pub(crate) fn enter_normal_mode(&mut self) -> anyhow::Result<()> {
-        if self.mode == Mode::Insert {
+        if matches!(self.mode, Mode::Insert | Mode::Overtype) {
             // This is necessary for cursor to not overflow after exiting insert mode
-            self.set_selection_set(self.selection_set.apply(
-                self.selection_set.mode.clone(),
-                |selection| {
+            let result = self
+                .selection_set
+                .apply(self.selection_set.mode.clone(), |selection| {
                     let range = {
                         if let Ok(position) = self
                             .buffer()
@@ -1400,9 +2301,15 @@ impl Editor {
                         }
                     };
                     Ok(selection.clone().set_range(range))
-                },
-            )?);
-            self.clamp()?;
+                })
+                .and_then(|selection_set| {
+                    self.set_selection_set(selection_set);
+                    self.clamp()
+                });
+            // The undo group is ended even if the above failed, otherwise it would stay open
+            let selection_set = self.selection_set.clone();
+            self.buffer_mut().end_undo_group(selection_set);
+            result?;
             self.buffer_mut().reparse_tree()?
         }
         // TODO: continue from here, need to add test: upon exiting insert mode, should close all panels
@@ -1613,6 +2520,12 @@ impl Editor {
         self.replace_faultlessly(&mode, movement)
     }
 
+    /// Unlike exchanging lines, this moves the whole node even if it spans multiple lines,
+    /// regardless of the current selection mode.
+    fn move_node(&mut self, movement: Movement) -> anyhow::Result<Dispatches> {
+        self.replace_faultlessly(&SelectionMode::SyntaxNode, movement)
+    }
+
     pub(crate) fn add_cursor(&mut self, movement: &Movement) -> anyhow::Result<()> {
         self.selection_set.add_selection(
             &self.buffer.borrow(),
@@ -1933,36 +2846,227 @@ impl Editor {
             .append(Dispatch::ToEditor(EnterInsertMode(direction))))
     }
 
-    pub(crate) fn apply_positional_edits(
-        &mut self,
-        edits: Vec<PositionalEdit>,
-    ) -> anyhow::Result<Dispatches> {
-        let edit_transaction = EditTransaction::from_action_groups(
+    /// Apply edits whose ranges are relative to the current content, such as the
+    /// text edits of the LSP server used by rename, code actions and formatting.
+    ///
+    /// Every selection is shifted by the edits preceding it, while marks, diagnostics
+    /// and other positional spans are updated by the buffer.
+    pub(crate) fn apply_edits(&mut self, edits: Vec<PositionalEdit>) -> anyhow::Result<Dispatches> {
+        let edits = {
+            let buffer = self.buffer();
             edits
                 .into_iter()
                 .filter_map(|edit| {
-                    let range = edit.range.start.to_char_index(&self.buffer()).ok()?
-                        ..edit.range.end.to_char_index(&self.buffer()).ok()?;
-
-                    let action_edit = Action::Edit(Edit {
-                        range: range.clone().into(),
+                    Some(Edit {
+                        range: (edit.range.start.to_char_index(&buffer).ok()?
+                            ..edit.range.end.to_char_index(&buffer).ok()?)
+                            .into(),
                         new: edit.new_text.into(),
-                    });
+                    })
+                })
+                .collect_vec()
+        };
+        self.apply_char_index_edits(edits)
+    }
 
-                    Some(ActionGroup::new(vec![action_edit]))
+    /// Apply a JSON array of `{start, end, replacement}`, where `start` and `end` are char indices
+    /// relative to the current content.
+    /// Either all of the edits are applied or none, which is the case if any of them overlap.
+    pub(crate) fn apply_edits_json(&mut self, json: &str) -> anyhow::Result<Dispatches> {
+        let edits = serde_json::from_str::<Vec<EditJson>>(json)?
+            .into_iter()
+            .map(|edit| Edit {
+                range: (CharIndex(edit.start)..CharIndex(edit.end)).into(),
+                new: edit.replacement.into(),
+            })
+            .sorted_by_key(|edit| edit.range.start)
+            .collect_vec();
+        let len_chars = self.buffer().len_chars();
+        if let Some(edit) = edits
+            .iter()
+            .find(|edit| edit.range.start > edit.range.end || edit.range.end.0 > len_chars)
+        {
+            return Err(anyhow::anyhow!(
+                "Invalid edit range: {}..{}",
+                edit.range.start.0,
+                edit.range.end.0
+            ));
+        }
+        if let Some((left, right)) = edits
+            .iter()
+            .tuple_windows()
+            .find(|(left, right)| left.range.end > right.range.start)
+        {
+            return Err(anyhow::anyhow!(
+                "Overlapping edits: {}..{} and {}..{}",
+                left.range.start.0,
+                left.range.end.0,
+                right.range.start.0,
+                right.range.end.0
+            ));
+        }
+        self.apply_char_index_edits(edits)
+    }
+
+    /// Apply the hunks of a unified diff.
+    /// A hunk is still applied if its context is shifted by at most `PATCH_FUZZ` lines,
+    /// otherwise it is rejected and reported via the global info.
+    pub(crate) fn apply_patch(&mut self, patch: &str) -> anyhow::Result<Dispatches> {
+        const PATCH_FUZZ: isize = 3;
+        let patch = diffy::Patch::from_str(patch)?;
+        let content = self.buffer().content();
+        let lines = content.split_inclusive('\n').collect_vec();
+        // The char index of the start of each line, followed by the end of the content
+        let line_starts = std::iter::once(0)
+            .chain(lines.iter().scan(0, |char_index, line| {
+                *char_index += line.chars().count();
+                Some(*char_index)
+            }))
+            .collect_vec();
+        let mut edits = Vec::new();
+        let mut rejected = Vec::new();
+        // The shift between the actual and the expected position of the last applied hunk
+        let mut offset = 0;
+        // Hunks are not allowed to overlap with the previously applied hunks
+        let mut min_line = 0;
+        for hunk in patch.hunks() {
+            let old_range = hunk.old_range();
+            let old_lines = hunk
+                .lines()
+                .iter()
+                .filter_map(|line| match line {
+                    diffy::Line::Context(line) | diffy::Line::Delete(line) => Some(*line),
+                    diffy::Line::Insert(_) => None,
                 })
-                .chain(
-                    // This is necessary to retain the current selection set
-                    self.selection_set
-                        .map(|selection| ActionGroup::new(vec![Action::Select(selection.clone())])),
-                )
+                .collect_vec();
+            // For a pure insertion, the start of the range is the line *after* which to insert
+            let expected_start = if old_range.len() == 0 {
+                old_range.start()
+            } else {
+                old_range.start().saturating_sub(1)
+            } as isize;
+            let start = (0..=PATCH_FUZZ)
+                .flat_map(|distance| {
+                    [
+                        expected_start + offset - distance,
+                        expected_start + offset + distance,
+                    ]
+                })
+                .filter_map(|start| usize::try_from(start).ok())
+                .find(|&start| {
+                    start >= min_line
+                        && lines.get(start..start + old_lines.len()) == Some(&old_lines[..])
+                });
+            let Some(start) = start else {
+                let new_range = hunk.new_range();
+                rejected.push(format!(
+                    "@@ -{},{} +{},{} @@",
+                    old_range.start(),
+                    old_range.len(),
+                    new_range.start(),
+                    new_range.len()
+                ));
+                continue;
+            };
+            offset = start as isize - expected_start;
+            min_line = start + old_lines.len();
+            edits.push(Edit {
+                range: (CharIndex(line_starts[start])..CharIndex(line_starts[min_line])).into(),
+                new: hunk
+                    .lines()
+                    .iter()
+                    .filter_map(|line| match line {
+                        diffy::Line::Context(line) | diffy::Line::Insert(line) => Some(*line),
+                        diffy::Line::Delete(_) => None,
+                    })
+                    .collect::<String>()
+                    .into(),
+            });
+        }
+        let dispatches = self.apply_char_index_edits(edits)?;
+        Ok(dispatches.append_some((!rejected.is_empty()).then(|| {
+            Dispatch::ShowGlobalInfo(Info::new("Rejected hunks".to_string(), rejected.join("\n")))
+        })))
+    }
+
+    fn apply_char_index_edits(&mut self, edits: Vec<Edit>) -> anyhow::Result<Dispatches> {
+        let selection_set = self.selection_set.clone();
+        let dispatches = self.apply_edit_transaction(EditTransaction::from_action_groups(
+            edits
+                .iter()
+                .map(|edit| ActionGroup::new(vec![Action::Edit(edit.clone())]))
+                .collect(),
+        ))?;
+        let max_char_index = CharIndex(self.buffer().len_chars());
+        // Apply the edits from the last to the first,
+        // so that the ranges of the remaining edits are not invalidated by the applied ones
+        let selection_set = edits
+            .iter()
+            .sorted_by_key(|edit| edit.range.start)
+            .rev()
+            .fold(selection_set, |selection_set, edit| {
+                selection_set.apply_edit(edit, max_char_index)
+            });
+        self.set_selection_set(selection_set);
+        Ok(dispatches)
+    }
+
+    /// Replace the selections with the given char index `ranges`, clamped to the content,
+    /// where the selection at `cursor_index` becomes the primary selection
+    pub(crate) fn restore_selections(
+        &mut self,
+        mode: SelectionMode,
+        ranges: Vec<Range<usize>>,
+        cursor_index: usize,
+    ) -> anyhow::Result<()> {
+        // The selections would otherwise be clamped to the placeholder
+        if self.buffer().is_loading() {
+            self.pending_selections = Some((mode, ranges, cursor_index));
+            return Ok(());
+        }
+        let Some(selections) = NonEmpty::from_vec(
+            ranges
+                .into_iter()
+                .map(|range| Selection::new((CharIndex(range.start)..CharIndex(range.end)).into()))
                 .collect(),
+        ) else {
+            return Ok(());
+        };
+        self.set_selection_set(
+            self.selection_set
+                .clone()
+                .set_mode(mode)
+                .set_selections(selections)
+                .set_cursor_index(cursor_index),
         );
-        self.apply_edit_transaction(edit_transaction)
+        self.clamp()
+    }
+
+    /// Restore the selections passed to `Editor::restore_selections` while the buffer was loading
+    fn restore_pending_selections(&mut self) -> anyhow::Result<()> {
+        match self.pending_selections.take() {
+            Some((mode, ranges, cursor_index)) => {
+                self.restore_selections(mode, ranges, cursor_index)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Replace the content with the content of the file, with the selections mapped to it
+    pub(crate) fn reload(&mut self) -> anyhow::Result<Dispatches> {
+        let selection_set = self
+            .buffer
+            .borrow_mut()
+            .reload(self.selection_set.clone())?;
+        self.set_selection_set(selection_set);
+        self.clamp()?;
+        Ok(self.get_document_did_change_dispatch())
     }
 
     pub(crate) fn save(&mut self) -> anyhow::Result<Dispatches> {
-        let Some(path) = self.buffer.borrow_mut().save(self.selection_set.clone())? else {
+        let (path, selection_set) = self.buffer.borrow_mut().save(self.selection_set.clone())?;
+        self.set_selection_set(selection_set);
+        let Some(path) = path else {
             return Ok(Default::default());
         };
 
@@ -1982,6 +3086,27 @@ impl Editor {
             }))
     }
 
+    /// Map the selections to the current content of the buffer,
+    /// which was `old_content` before it is reloaded by another editor of the same buffer.
+    pub(crate) fn remap_selections(&mut self, old_content: &str) -> anyhow::Result<()> {
+        let selection_set = crate::buffer::map_selection_set(
+            old_content,
+            &self.buffer().content(),
+            self.selection_set.clone(),
+        );
+        self.set_selection_set(selection_set);
+        self.clamp()
+    }
+
+    /// Called once the content of the file replaces the placeholder of the loading buffer.
+    /// The selections made on the placeholder are reset, because they might be out of bound,
+    /// then the selections passed while the buffer was loading are restored.
+    pub(crate) fn handle_buffer_loaded(&mut self) -> anyhow::Result<()> {
+        self.set_selection_set(SelectionSet::default());
+        self.scroll_offset = 0;
+        self.restore_pending_selections()
+    }
+
     /// Clamp everything that might be out of bound after the buffer content is modified elsewhere
     fn clamp(&mut self) -> anyhow::Result<()> {
         let len_chars = self.buffer().len_chars();
@@ -1998,6 +3123,7 @@ impl Editor {
             self.selection_set
                 .map(|selection| -> anyhow::Result<_> {
                     let old = self.buffer().slice(&selection.extended_range())?;
+                    let added_chars_count = open.chars().count() + close.chars().count();
                     Ok(ActionGroup::new(
                         [
                             Action::Edit(Edit {
@@ -2007,7 +3133,7 @@ impl Editor {
                             Action::Select(
                                 selection.clone().set_range(
                                     (selection.extended_range().start
-                                        ..selection.extended_range().end + 2)
+                                        ..selection.extended_range().end + added_chars_count)
                                         .into(),
                                 ),
                             ),
@@ -2026,18 +3152,66 @@ impl Editor {
     fn transform_selection(
         &mut self,
         transformation: Transformation,
+    ) -> anyhow::Result<Dispatches> {
+        self.replace_selections_with(|index, text| transformation.apply(index, text))
+    }
+
+    /// Add `get_delta(selection_index)` to the number of each selection, refer `increment_number`
+    fn increment_numbers(
+        &mut self,
+        get_delta: impl Fn(usize) -> anyhow::Result<i64>,
+    ) -> anyhow::Result<Dispatches> {
+        self.replace_selections_with(|index, text| increment_number(&text, get_delta(index)?))
+    }
+
+    /// Selections that cannot be transformed, e.g. invalid Base64 for `TransformKind::Base64Decode`,
+    /// are left unchanged, and a warning is shown.
+    fn apply_transform(&mut self, kind: TransformKind) -> anyhow::Result<Dispatches> {
+        let errors = self
+            .get_selected_texts()
+            .iter()
+            .filter_map(|text| kind.apply(text).err())
+            .collect_vec();
+        let dispatches =
+            self.replace_selections_with(|_, text| Ok(kind.apply(&text).unwrap_or(text)))?;
+        Ok(match errors.first() {
+            Some(error) => dispatches.append(Dispatch::ShowGlobalInfo(Info::new(
+                format!("{kind} Warning"),
+                format!(
+                    "{} selection(s) are left unchanged because they cannot be transformed: {error}",
+                    errors.len()
+                ),
+            ))),
+            None => dispatches,
+        })
+    }
+
+    /// If any selection cannot be formatted, the buffer is left untouched.
+    fn pretty_print_selections(
+        &mut self,
+        format: fn(&str, usize) -> anyhow::Result<String>,
+        indent: Option<usize>,
+    ) -> anyhow::Result<Dispatches> {
+        let indent = indent.unwrap_or(self.buffer().options().tab_width);
+        self.replace_selections_with(|_, text| format(&text, indent))
+    }
+
+    /// Replace the text of each selection with `get_new_text(selection_index, text)`,
+    /// and select the replaced text.
+    fn replace_selections_with(
+        &mut self,
+        get_new_text: impl Fn(usize, String) -> anyhow::Result<String>,
     ) -> anyhow::Result<Dispatches> {
         let edit_transaction = EditTransaction::from_action_groups(
             self.selection_set
                 .map_with_index(|index, selection| -> anyhow::Result<_> {
-                    let new: Rope = transformation
-                        .apply(
-                            index,
-                            self.buffer()
-                                .slice(&selection.extended_range())?
-                                .to_string(),
-                        )?
-                        .into();
+                    let new: Rope = get_new_text(
+                        index,
+                        self.buffer()
+                            .slice(&selection.extended_range())?
+                            .to_string(),
+                    )?
+                    .into();
                     let new_char_count = new.chars().count();
                     let range = selection.extended_range();
                     Ok(ActionGroup::new(
@@ -2072,6 +3246,7 @@ impl Editor {
             Mode::Exchange => "EXCHANGE",
             Mode::UndoTree => "UNDO TREE",
             Mode::Replace => "REPLACE",
+            Mode::Overtype => "OVERTYPE",
             Mode::V => "V",
             Mode::TillOneChar(_) => "TILL",
         }
@@ -2090,11 +3265,17 @@ impl Editor {
         }
     }
 
+    /// Lines hidden by folds are not counted towards the height of this editor.
     pub(crate) fn visible_line_range(&self) -> Range<usize> {
-        let start = self.scroll_offset;
-        let end = (start as usize + self.rectangle.height as usize).min(self.buffer().len_lines());
+        let start = self.scroll_offset as usize;
+        let buffer = self.buffer();
+        let folded_lines = buffer.folded_lines();
+        let end = (start..buffer.len_lines())
+            .filter(|line| !folded_lines.iter().any(|range| range.contains(line)))
+            .nth(self.rectangle.height as usize)
+            .unwrap_or(buffer.len_lines());
 
-        start as usize..end
+        start..end
     }
 
     fn handle_multi_cursor_mode(
@@ -2123,6 +3304,54 @@ impl Editor {
         self.selection_set.only();
     }
 
+    /// Look for the next occurrence of the primary selection's text that is not selected yet,
+    /// wrapping around at the end of the buffer.
+    ///
+    /// If `add` is true, a new cursor is added at the occurrence,
+    /// otherwise the primary selection is moved to the occurrence.
+    fn add_cursor_to_next_match(&mut self, add: bool) -> anyhow::Result<()> {
+        let next_match = {
+            let buffer = self.buffer();
+            let range = self.selection_set.primary_selection().extended_range();
+            let search = buffer.slice(&range)?.to_string();
+            if search.is_empty() {
+                return Ok(());
+            }
+            let content = buffer.content();
+            let start_byte = buffer.char_to_byte(range.end)?;
+            let (after, before): (Vec<_>, Vec<_>) = content
+                .match_indices(&search)
+                .map(|(byte, _)| byte)
+                .partition(|byte| *byte >= start_byte);
+            after
+                .into_iter()
+                .chain(before)
+                .filter_map(|byte| -> Option<CharIndexRange> {
+                    Some(
+                        (buffer.byte_to_char(byte).ok()?
+                            ..buffer.byte_to_char(byte + search.len()).ok()?)
+                            .into(),
+                    )
+                })
+                .find(|range| {
+                    !self
+                        .selection_set
+                        .selections()
+                        .iter()
+                        .any(|selection| &selection.extended_range() == range)
+                })
+        };
+        if let Some(range) = next_match {
+            if add {
+                self.selection_set.add_primary(Selection::new(range));
+            } else {
+                self.selection_set.replace_primary(Selection::new(range));
+            }
+            self.recalculate_scroll_offset();
+        }
+        Ok(())
+    }
+
     fn enter_single_character_mode(&mut self, if_current_not_found: IfCurrentNotFound) {
         self.mode = Mode::FindOneChar(if_current_not_found);
     }
@@ -2263,6 +3492,134 @@ impl Editor {
         .into())
     }
 
+    /// Go to the first line of the next (if `direction` is `End`) or the previous
+    /// run of blank lines, where a run of consecutive blank lines is a single stop.
+    fn goto_blank_line(&mut self, direction: Direction) -> anyhow::Result<Dispatches> {
+        let target_line = {
+            let buffer = self.buffer();
+            let content = buffer.content();
+            let blanks = content
+                .lines()
+                .map(|line| line.trim().is_empty())
+                .collect_vec();
+            let is_stop = |line: usize| blanks[line] && (line == 0 || !blanks[line - 1]);
+            let cursor_line = buffer.char_to_line(self.get_cursor_char_index())?;
+            let target_line = match direction {
+                Direction::End => (cursor_line + 1..blanks.len()).find(|line| is_stop(*line)),
+                Direction::Start => {
+                    // Skip the run of blank lines where the cursor is at
+                    let mut run_start = cursor_line;
+                    while run_start > 0
+                        && blanks.get(run_start) == Some(&true)
+                        && blanks[run_start - 1]
+                    {
+                        run_start -= 1
+                    }
+                    (0..run_start.min(blanks.len()))
+                        .rev()
+                        .find(|line| is_stop(*line))
+                }
+            };
+            let Some(target_line) = target_line else {
+                return Ok(Default::default());
+            };
+            target_line
+        };
+        let start = self.buffer().line_to_char(target_line)?;
+        Ok(self.update_selection_set(
+            self.selection_set
+                .clone()
+                .set_selections(NonEmpty::singleton(Selection::new((start..start).into())))
+                .set_mode(SelectionMode::Line),
+            true,
+        ))
+    }
+
+    /// Select the first (if `first` is true) or the last non-whitespace character
+    /// of the line of each cursor, blank lines are left untouched.
+    fn goto_non_blank(&mut self, first: bool) -> anyhow::Result<Dispatches> {
+        let selections = {
+            let buffer = self.buffer();
+            self.selection_set
+                .selections()
+                .iter()
+                .map(|selection| -> anyhow::Result<Selection> {
+                    let cursor = selection.to_char_index(&self.cursor_direction);
+                    let line_index = buffer.char_to_line(cursor)?;
+                    let line_start = buffer.line_to_char(line_index)?;
+                    let line = buffer
+                        .get_line_by_line_index(line_index)
+                        .map(|line| line.to_string())
+                        .unwrap_or_default();
+                    let chars = line.trim_end_matches(['\n', '\r']).chars().collect_vec();
+                    let offset = if first {
+                        chars.iter().position(|c| !c.is_whitespace())
+                    } else {
+                        chars.iter().rposition(|c| !c.is_whitespace())
+                    };
+                    Ok(match offset {
+                        Some(offset) => selection
+                            .clone()
+                            .set_range((line_start + offset..line_start + offset + 1).into())
+                            .set_initial_range(None),
+                        None => selection.clone(),
+                    })
+                })
+                .try_collect::<_, Vec<_>, _>()?
+        };
+        let Some(selections) = NonEmpty::from_vec(selections) else {
+            return Ok(Default::default());
+        };
+        Ok(self.update_selection_set(
+            self.selection_set
+                .clone()
+                .set_selections(selections)
+                .set_mode(SelectionMode::Column),
+            true,
+        ))
+    }
+
+    fn operator_motion(
+        &mut self,
+        context: &Context,
+        operator: Operator,
+        motion: Motion,
+    ) -> anyhow::Result<Dispatches> {
+        let selections = {
+            let buffer = self.buffer();
+            self.selection_set
+                .selections()
+                .iter()
+                .map(|selection| -> anyhow::Result<Selection> {
+                    let cursor = selection.to_char_index(&self.cursor_direction);
+                    Ok(selection
+                        .clone()
+                        .set_range(motion.range(&buffer, cursor)?)
+                        .set_initial_range(None))
+                })
+                .try_collect::<_, Vec<_>, _>()?
+        };
+        let Some(selections) = NonEmpty::from_vec(selections) else {
+            return Ok(Default::default());
+        };
+        let dispatches = self.update_selection_set(
+            self.selection_set
+                .clone()
+                .set_selections(selections)
+                .set_mode(SelectionMode::Custom),
+            true,
+        );
+        Ok(dispatches.chain(match operator {
+            Operator::Delete => self.delete(context, Direction::End, None)?,
+            Operator::Cut {
+                use_system_clipboard,
+            } => self.delete(context, Direction::End, Some(use_system_clipboard))?,
+            Operator::Copy {
+                use_system_clipboard,
+            } => self.copy(use_system_clipboard)?,
+        }))
+    }
+
     pub(crate) fn select_all(&mut self, context: &mut Context) -> anyhow::Result<Dispatches> {
         self.handle_dispatch_editors(
             context,
@@ -2317,12 +3674,24 @@ impl Editor {
     }
 
     fn navigate_undo_tree(&mut self, movement: Movement) -> Result<Dispatches, anyhow::Error> {
-        let selection_set = self.buffer_mut().undo_tree_apply_movement(movement)?;
-
-        Ok(selection_set
-            .map(|selection_set| self.update_selection_set(selection_set, false))
-            .unwrap_or_default()
-            .chain(self.get_document_did_change_dispatch()))
+        // The edits of the current insert mode session should be undoable as well
+        let current_selection_set = self.selection_set.clone();
+        self.buffer_mut().end_undo_group(current_selection_set);
+        let dispatches =
+            self.buffer_mut()
+                .undo_tree_apply_movement(movement)
+                .map(|selection_set| {
+                    selection_set
+                        .map(|selection_set| self.update_selection_set(selection_set, false))
+                        .unwrap_or_default()
+                        .chain(self.get_document_did_change_dispatch())
+                });
+        // The group is begun again even if the movement failed, since insert mode is not exited
+        if matches!(self.mode, Mode::Insert | Mode::Overtype) {
+            let selection_set = self.selection_set.clone();
+            self.buffer_mut().begin_undo_group(selection_set);
+        }
+        dispatches
     }
 
     #[cfg(test)]
@@ -2376,34 +3745,39 @@ impl Editor {
         self.mode = Mode::Exchange
     }
 
-    fn kill_line(&mut self, direction: Direction) -> Result<Dispatches, anyhow::Error> {
-        let edit_transaction = EditTransaction::from_action_groups(
-            self.selection_set
-                .map(|selection| -> anyhow::Result<_> {
-                    let buffer = self.buffer();
-                    let cursor = selection.get_anchor(&self.cursor_direction);
-                    let line_range = buffer.get_line_range_by_char_index(cursor)?;
-                    let (delete_range, cursor_start) = match direction {
-                        Direction::Start => {
-                            let start = line_range.start();
-                            let range = (start..cursor).into();
-                            let slice = buffer.slice(&range)?.to_string();
-                            let start = if slice.is_empty() { start - 1 } else { start };
-                            let range = (start..cursor).into();
-                            (range, start)
-                        }
-                        Direction::End => {
-                            let range = (cursor..line_range.end()).into();
-                            let slice = buffer.slice(&range)?.to_string();
-                            let range = if slice == "\n" || range.end.0 == buffer.len_chars() {
-                                range
-                            } else {
-                                (cursor..(line_range.end() - 1)).into()
-                            };
-                            (range, cursor)
-                        }
-                    };
-                    Ok(ActionGroup::new(
+    fn kill_line(
+        &mut self,
+        context: &mut Context,
+        direction: Direction,
+    ) -> Result<Dispatches, anyhow::Error> {
+        let (action_groups, killed_texts): (Vec<_>, Vec<_>) = self
+            .selection_set
+            .map(|selection| -> anyhow::Result<_> {
+                let buffer = self.buffer();
+                let cursor = selection.get_anchor(&self.cursor_direction);
+                let line_range = buffer.get_line_range_by_char_index(cursor)?;
+                let (delete_range, cursor_start) = match direction {
+                    Direction::Start => {
+                        let start = line_range.start();
+                        let range = (start..cursor).into();
+                        let slice = buffer.slice(&range)?.to_string();
+                        let start = if slice.is_empty() { start - 1 } else { start };
+                        let range = (start..cursor).into();
+                        (range, start)
+                    }
+                    Direction::End => {
+                        let range = (cursor..line_range.end()).into();
+                        let slice = buffer.slice(&range)?.to_string();
+                        let range = if slice == "\n" || range.end.0 == buffer.len_chars() {
+                            range
+                        } else {
+                            (cursor..(line_range.end() - 1)).into()
+                        };
+                        (range, cursor)
+                    }
+                };
+                Ok((
+                    ActionGroup::new(
                         [
                             Action::Edit(Edit {
                                 range: delete_range,
@@ -2416,18 +3790,145 @@ impl Editor {
                             ),
                         ]
                         .to_vec(),
-                    ))
-                })
-                .into_iter()
-                .flatten()
-                .collect_vec(),
-        );
+                    ),
+                    buffer.slice(&delete_range)?.to_string(),
+                ))
+            })
+            .into_iter()
+            .flatten()
+            .unzip();
+        if let Some(killed_texts) = NonEmpty::from_vec(killed_texts) {
+            self.add_to_kill_ring(context, CopiedTexts::new(killed_texts), direction);
+        }
         let dispatches = self
-            .apply_edit_transaction(edit_transaction)?
+            .apply_edit_transaction(EditTransaction::from_action_groups(action_groups))?
             .chain(self.enter_insert_mode(Direction::Start)?);
         Ok(dispatches)
     }
 
+    /// Consecutive kills are accumulated into the same kill ring entry,
+    /// `direction` decides whether `copied_texts` is appended or prepended to it.
+    fn add_to_kill_ring(
+        &mut self,
+        context: &mut Context,
+        copied_texts: CopiedTexts,
+        direction: Direction,
+    ) {
+        let accumulate =
+            (self.last_kill_ring_command == Some(KillRingCommand::Kill)).then_some(direction);
+        context.kill_ring_mut().kill(copied_texts, accumulate);
+        self.last_kill_ring_command = Some(KillRingCommand::Kill);
+    }
+
+    fn kill(&mut self, context: &mut Context) -> anyhow::Result<Dispatches> {
+        let copied_texts = CopiedTexts::new(self.selection_set.map(|selection| {
+            self.buffer()
+                .slice(&selection.extended_range())
+                .map(|slice| slice.to_string())
+                .unwrap_or_default()
+        }));
+        self.add_to_kill_ring(context, copied_texts, Direction::End);
+        let edit_transaction = EditTransaction::from_action_groups(
+            self.selection_set
+                .map(|selection| {
+                    let range = selection.extended_range();
+                    ActionGroup::new(
+                        [
+                            Action::Edit(Edit {
+                                range,
+                                new: Rope::new(),
+                            }),
+                            Action::Select(
+                                selection
+                                    .clone()
+                                    .set_range((range.start..range.start).into())
+                                    .set_initial_range(None),
+                            ),
+                        ]
+                        .to_vec(),
+                    )
+                })
+                .into(),
+        );
+        self.apply_edit_transaction(edit_transaction)
+    }
+
+    fn yank(&mut self, context: &mut Context) -> anyhow::Result<Dispatches> {
+        let Some(copied_texts) = context.kill_ring_mut().yank() else {
+            return Ok(Default::default());
+        };
+        let ranges = self
+            .selection_set
+            .map(|selection| {
+                let cursor = selection.to_char_index(&self.cursor_direction);
+                CharIndexRange::from(cursor..cursor)
+            })
+            .into();
+        self.replace_yanked_texts(ranges, copied_texts)
+    }
+
+    fn yank_pop(&mut self, context: &mut Context) -> anyhow::Result<Dispatches> {
+        let Some(KillRingCommand::Yank(ranges)) = self.last_kill_ring_command.take() else {
+            return Ok(Default::default());
+        };
+        let Some(copied_texts) = context.kill_ring_mut().yank_pop() else {
+            return Ok(Default::default());
+        };
+        self.replace_yanked_texts(ranges, copied_texts)
+    }
+
+    /// Replace the given `ranges` (one per selection) with `copied_texts`,
+    /// and place each cursor at the end of its inserted text.
+    fn replace_yanked_texts(
+        &mut self,
+        ranges: Vec<CharIndexRange>,
+        copied_texts: CopiedTexts,
+    ) -> anyhow::Result<Dispatches> {
+        let replacements = ranges
+            .into_iter()
+            .enumerate()
+            .map(|(index, range)| (range, Rope::from_str(&copied_texts.get(index))))
+            .collect_vec();
+        let edit_transaction = EditTransaction::from_action_groups(
+            self.selection_set
+                .selections()
+                .iter()
+                .zip(replacements.iter())
+                .map(|(selection, (range, new))| {
+                    let end = range.start + new.len_chars();
+                    ActionGroup::new(
+                        [
+                            Action::Edit(Edit {
+                                range: *range,
+                                new: new.clone(),
+                            }),
+                            Action::Select(selection.clone().set_range((end..end).into())),
+                        ]
+                        .to_vec(),
+                    )
+                })
+                .collect(),
+        );
+        let dispatches = self.apply_edit_transaction(edit_transaction)?;
+
+        // Compute the ranges of the inserted texts after all the replacements are applied,
+        // so that they can be replaced again by the next `YankPop`
+        let yanked_ranges = replacements
+            .iter()
+            .map(|(range, new)| {
+                let offset: isize = replacements
+                    .iter()
+                    .filter(|(other, _)| other.start < range.start)
+                    .map(|(other, new)| new.len_chars() as isize - other.len() as isize)
+                    .sum();
+                let start = range.start.apply_offset(offset);
+                CharIndexRange::from(start..start + new.len_chars())
+            })
+            .collect_vec();
+        self.last_kill_ring_command = Some(KillRingCommand::Yank(yanked_ranges));
+        Ok(dispatches)
+    }
+
     fn enter_multicursor_mode(&mut self) {
         self.mode = Mode::MultiCursor
     }
@@ -2463,9 +3964,31 @@ impl Editor {
 
     fn set_selection_set(&mut self, selection_set: SelectionSet) {
         self.selection_set = selection_set;
+        self.unfold_selections();
         self.recalculate_scroll_offset()
     }
 
+    /// Unfold the folds hiding the first or the last line of any selection,
+    /// otherwise the cursor could be moved onto a line that is not rendered.
+    fn unfold_selections(&mut self) {
+        if self.buffer().folds().is_empty() {
+            return;
+        }
+        let lines = {
+            let buffer = self.buffer();
+            self.selection_set
+                .map(|selection| {
+                    let range = selection.extended_range();
+                    [range.start, (range.end - 1).max(range.start)]
+                })
+                .into_iter()
+                .flatten()
+                .filter_map(|char_index| buffer.char_to_line(char_index).ok())
+                .collect_vec()
+        };
+        self.buffer_mut().unfold_lines(&lines)
+    }
+
     pub(crate) fn set_position_range(
         &mut self,
         range: Range<Position>,
@@ -2474,6 +3997,117 @@ impl Editor {
         Ok(self.update_selection_set(selection_set, true))
     }
 
+    pub(crate) fn primary_selection_position_range(&self) -> anyhow::Result<Range<Position>> {
+        self.buffer().char_index_range_to_position_range(
+            self.selection_set.primary_selection().extended_range(),
+        )
+    }
+
+    pub(crate) fn selection_position_ranges(&self) -> anyhow::Result<Vec<Range<Position>>> {
+        let buffer = self.buffer();
+        self.selection_set
+            .map(|selection| buffer.char_index_range_to_position_range(selection.extended_range()))
+            .into_iter()
+            .try_collect()
+    }
+
+    fn select_semantic_range(&mut self, direction: Direction) -> anyhow::Result<Dispatches> {
+        let current = self.primary_selection_position_range()?;
+        let Some(index) = self
+            .semantic_ranges
+            .iter()
+            .position(|range| range == &current)
+        else {
+            return Ok(match direction {
+                Direction::End => Dispatches::one(Dispatch::RequestSelectionRanges),
+                Direction::Start => Default::default(),
+            });
+        };
+        let index = match direction {
+            Direction::Start => index.checked_sub(1),
+            Direction::End => Some(index + 1),
+        };
+        match index.and_then(|index| self.semantic_ranges.get(index).cloned()) {
+            Some(range) => self.set_position_range(range),
+            None => Ok(Default::default()),
+        }
+    }
+
+    /// Select the innermost of the given ranges that encloses the primary selection,
+    /// or the parent syntax node if there is none, e.g. the LSP server does not support it.
+    fn set_semantic_ranges(&mut self, ranges: Vec<Range<Position>>) -> anyhow::Result<Dispatches> {
+        let current = self.primary_selection_position_range()?;
+        self.semantic_ranges = ranges;
+        let enclosing_range = self
+            .semantic_ranges
+            .iter()
+            .find(|range| {
+                range.start <= current.start && current.end <= range.end && *range != &current
+            })
+            .cloned();
+        match enclosing_range {
+            Some(range) => self.set_position_range(range),
+            None => self.select(SelectionMode::SyntaxNode, Movement::Parent),
+        }
+    }
+
+    /// Returns the open and close indices of the innermost pair of `enclosure` that encloses
+    /// `selection`, including the pair spanned by `selection` itself.
+    ///
+    /// The syntax nodes delimited by the pair are preferred, so that the symbols within
+    /// strings or comments are not mistaken as part of the pair.
+    fn get_surrounding_indices(
+        &self,
+        selection: &Selection,
+        enclosure: EnclosureKind,
+    ) -> Option<(CharIndex, CharIndex)> {
+        let buffer = self.buffer();
+        buffer
+            .get_enclosing_pair(selection.extended_range(), enclosure.open_close_symbols())
+            .or_else(|| {
+                crate::surround::get_surrounding_indices_within_window(
+                    buffer.rope(),
+                    enclosure,
+                    selection.get_anchor(&self.cursor_direction),
+                )
+            })
+    }
+
+    /// Select the bracket matching the one under the cursor
+    fn goto_matching_bracket(&mut self) -> anyhow::Result<Dispatches> {
+        let cursor = self.get_cursor_char_index();
+        let Some(enclosure) = self
+            .buffer()
+            .rope()
+            .get_char(cursor.0)
+            .and_then(EnclosureKind::from_char)
+            .filter(|enclosure| {
+                let (open, close) = enclosure.open_close_symbols();
+                open != close
+            })
+        else {
+            return Ok(Default::default());
+        };
+        let Some((open_index, close_index)) =
+            self.get_surrounding_indices(&Selection::new((cursor..cursor + 1).into()), enclosure)
+        else {
+            return Ok(Default::default());
+        };
+        let target = if cursor == open_index {
+            close_index
+        } else {
+            open_index
+        };
+        Ok(self.update_selection_set(
+            self.selection_set
+                .clone()
+                .set_selections(NonEmpty::singleton(Selection::new(
+                    (target..target + 1).into(),
+                ))),
+            true,
+        ))
+    }
+
     fn select_surround(
         &mut self,
         enclosure: EnclosureKind,
@@ -2482,14 +4116,8 @@ impl Editor {
         let edit_transaction = EditTransaction::from_action_groups(
             self.selection_set
                 .map(|selection| -> anyhow::Result<_> {
-                    let buffer = self.buffer();
-                    let cursor_char_index = selection.get_anchor(&self.cursor_direction);
                     if let Some((open_index, close_index)) =
-                        crate::surround::get_surrounding_indices(
-                            &buffer.content(),
-                            enclosure,
-                            cursor_char_index,
-                        )
+                        self.get_surrounding_indices(selection, enclosure)
                     {
                         let offset = match kind {
                             SurroundKind::Inside => 1,
@@ -2523,14 +4151,8 @@ impl Editor {
         let edit_transaction = EditTransaction::from_action_groups(
             self.selection_set
                 .map(|selection| -> anyhow::Result<_> {
-                    let buffer = self.buffer();
-                    let cursor_char_index = selection.get_anchor(&self.cursor_direction);
                     if let Some((open_index, close_index)) =
-                        crate::surround::get_surrounding_indices(
-                            &buffer.content(),
-                            from,
-                            cursor_char_index,
-                        )
+                        self.get_surrounding_indices(selection, from)
                     {
                         let open_range = (open_index..open_index + 1).into();
                         let close_range = (close_index..close_index + 1).into();
@@ -2575,6 +4197,68 @@ impl Editor {
         self.apply_edit_transaction(edit_transaction)
     }
 
+    /// Replace every match of `pattern` within the selections if any of them is extended,
+    /// otherwise within the whole buffer.
+    ///
+    /// If `regex` is true, `pattern` is a regex and `replacement` can refer its capture groups,
+    /// e.g. `$1`, otherwise both are literal.
+    fn replace_all_matching(
+        &mut self,
+        pattern: &str,
+        replacement: &str,
+        regex: bool,
+    ) -> anyhow::Result<Dispatches> {
+        if pattern.is_empty() {
+            return Err(anyhow::anyhow!(
+                "The pattern to be replaced cannot be empty"
+            ));
+        }
+        let matcher = regex::RegexBuilder::new(&if regex {
+            pattern.to_string()
+        } else {
+            regex::escape(pattern)
+        })
+        .multi_line(true)
+        .size_limit(REPLACE_REGEX_SIZE_LIMIT)
+        .dfa_size_limit(REPLACE_REGEX_SIZE_LIMIT)
+        .build()?;
+        let buffer = self.buffer();
+        let ranges = if self.selection_set.is_extended() {
+            self.selection_set
+                .map(|selection| selection.extended_range())
+                .into_iter()
+                .collect_vec()
+        } else {
+            [(CharIndex(0)..CharIndex(buffer.len_chars())).into()].to_vec()
+        };
+        let mut edits = Vec::new();
+        for range in ranges {
+            let text = buffer.slice(&range)?.to_string();
+            // The byte index and char index of the end of the last match
+            let (mut byte_index, mut char_index) = (0, range.start);
+            for captures in matcher.captures_iter(&text) {
+                let Some(matched) = captures.get(0) else {
+                    continue;
+                };
+                let start = char_index + text[byte_index..matched.start()].chars().count();
+                let end = start + matched.as_str().chars().count();
+                let mut new = String::new();
+                if regex {
+                    captures.expand(replacement, &mut new);
+                } else {
+                    new.push_str(replacement);
+                }
+                edits.push(Edit {
+                    range: (start..end).into(),
+                    new: new.into(),
+                });
+                (byte_index, char_index) = (matched.end(), end);
+            }
+        }
+        drop(buffer);
+        self.apply_char_index_edits(edits)
+    }
+
     fn replace_with_pattern(&mut self, context: &Context) -> Result<Dispatches, anyhow::Error> {
         let config = context.local_search_config();
         match config.mode {
@@ -2681,9 +4365,181 @@ impl Editor {
         ))))
     }
 
+    /// Start a rectangular selection covering the columns of the primary selection,
+    /// which is then extended to the lines above or below with `Movement::Up` and `Movement::Down`.
+    ///
+    /// If the rectangular selection is already started, it is stopped with its selections kept.
+    fn toggle_column_selection(&mut self) -> anyhow::Result<Dispatches> {
+        if self.column_selection.take().is_some() {
+            return Ok(Default::default());
+        }
+        let (start, end, line) = {
+            let buffer = self.buffer();
+            let range = self.selection_set.primary_selection().extended_range();
+            let start = buffer.char_to_position(range.start)?;
+            (
+                start,
+                buffer.char_to_position(range.end)?,
+                buffer
+                    .get_line_by_line_index(start.line)
+                    .map(|line| line.to_string())
+                    .unwrap_or_default(),
+            )
+        };
+        // A selection spanning multiple lines is collapsed to its start
+        let end_column = if start.line == end.line {
+            end.column
+        } else {
+            start.column
+        };
+        let display_column =
+            |column: usize| get_string_width(&line.chars().take(column).collect::<String>());
+        self.column_selection = Some(ColumnSelection {
+            anchor_line: start.line,
+            cursor_line: start.line,
+            columns: display_column(start.column)..display_column(end_column),
+        });
+        self.update_column_selection()
+    }
+
+    fn move_column_selection(&mut self, movement: Movement) -> anyhow::Result<Dispatches> {
+        let last_line = self.buffer().len_lines().saturating_sub(1);
+        if let Some(column_selection) = &mut self.column_selection {
+            column_selection.cursor_line = match movement {
+                Movement::Up => column_selection.cursor_line.saturating_sub(1),
+                _ => (column_selection.cursor_line + 1).min(last_line),
+            };
+        }
+        self.update_column_selection()
+    }
+
+    /// Select the columns of the rectangular selection on every line it spans.
+    ///
+    /// Lines ending before the start column are skipped,
+    /// while lines ending within the columns are only selected until their end.
+    fn update_column_selection(&mut self) -> anyhow::Result<Dispatches> {
+        let Some(ColumnSelection {
+            anchor_line,
+            cursor_line,
+            columns,
+        }) = self.column_selection.clone()
+        else {
+            return Ok(Default::default());
+        };
+        let selections = {
+            let buffer = self.buffer();
+            (anchor_line.min(cursor_line)..=anchor_line.max(cursor_line))
+                .filter_map(|line_index| {
+                    let line = buffer.get_line_by_line_index(line_index)?.to_string();
+                    let chars = char_range_of_display_columns(
+                        line.trim_end_matches(['\n', '\r']),
+                        &columns,
+                    )?;
+                    let line_start = buffer.line_to_char(line_index).ok()?;
+                    let range = (line_start + chars.start..line_start + chars.end).into();
+                    Some((line_index, Selection::new(range)))
+                })
+                .collect_vec()
+        };
+        let cursor_index = selections
+            .iter()
+            .position(|(line_index, _)| *line_index == cursor_line)
+            .unwrap_or(if cursor_line < anchor_line {
+                0
+            } else {
+                selections.len().saturating_sub(1)
+            });
+        let Some(selections) = NonEmpty::from_vec(
+            selections
+                .into_iter()
+                .map(|(_, selection)| selection)
+                .collect(),
+        ) else {
+            return Ok(Default::default());
+        };
+        Ok(self.update_selection_set(
+            self.selection_set
+                .clone()
+                .set_selections(selections)
+                .set_cursor_index(cursor_index)
+                .set_mode(SelectionMode::Custom),
+            false,
+        ))
+    }
+
+    /// Select the first node of the given tree-sitter `kind` that starts after the primary cursor,
+    /// wrapping around to the first such node of the buffer.
+    fn select_next_node_of_kind(&mut self, kind: &str) -> anyhow::Result<Dispatches> {
+        let range = {
+            let buffer = self.buffer();
+            let cursor_byte =
+                buffer.char_to_byte(self.selection_set.primary_selection().range().start)?;
+            let nodes = buffer
+                .traverse(Order::Pre)
+                .ok_or_else(|| anyhow::anyhow!("The buffer has no syntax tree"))?
+                .filter(|node| node.kind() == kind)
+                .collect_vec();
+            let node = nodes
+                .iter()
+                .find(|node| node.start_byte() > cursor_byte)
+                .or_else(|| nodes.first())
+                .ok_or_else(|| anyhow::anyhow!("No node of kind {kind:?} is found"))?;
+            buffer.byte_range_to_char_index_range(&node.byte_range())?
+        };
+        Ok(self.update_selection_set(
+            self.selection_set
+                .clone()
+                .set_selections(NonEmpty::singleton(Selection::new(range)))
+                .set_mode(SelectionMode::SyntaxNode),
+            true,
+        ))
+    }
+
+    fn select_whole_function(&mut self) -> anyhow::Result<Dispatches> {
+        /// Tree-sitter node kinds of function and method definitions across languages
+        const FUNCTION_KINDS: &[&str] = &[
+            "function_item",
+            "function_definition",
+            "function_declaration",
+            "function_expression",
+            "arrow_function",
+            "method_definition",
+            "method_declaration",
+            "generator_function_declaration",
+        ];
+        let range = {
+            let buffer = self.buffer();
+            let mut node =
+                buffer.get_current_node(self.selection_set.primary_selection(), false)?;
+            while let Some(current) = node {
+                if FUNCTION_KINDS.contains(&current.kind()) {
+                    break;
+                }
+                node = current.parent();
+            }
+            match node {
+                Some(node) => buffer.byte_range_to_char_index_range(&node.byte_range())?,
+                None => {
+                    return Ok(Dispatches::one(Dispatch::ShowEditorInfo(Info::new(
+                        "Select whole function".to_string(),
+                        "The cursor is not inside a function".to_string(),
+                    ))))
+                }
+            }
+        };
+        Ok(self.update_selection_set(
+            self.selection_set
+                .clone()
+                .set_selections(NonEmpty::singleton(Selection::new(range)))
+                .set_mode(SelectionMode::SyntaxNode),
+            true,
+        ))
+    }
+
     fn indent(&mut self) -> Result<Dispatches, anyhow::Error> {
+        let indent_width = self.buffer().options().tab_width;
         let indentation: Rope = std::iter::repeat(INDENT_CHAR)
-            .take(INDENT_WIDTH)
+            .take(indent_width)
             .collect::<String>()
             .into();
         let edit_transaction = EditTransaction::from_action_groups(
@@ -2718,7 +4574,7 @@ impl Editor {
                         .join("")
                         .into();
                     let select_range = {
-                        let offset: isize = INDENT_WIDTH as isize;
+                        let offset: isize = indent_width as isize;
                         let start = original_range.start.apply_offset(offset);
                         let original_len = original_range.len();
                         let end =
@@ -2744,7 +4600,119 @@ impl Editor {
         self.apply_edit_transaction(edit_transaction)
     }
 
+    /// Remove the longest leading whitespace shared by the non-blank lines covered by the selections,
+    /// such that the shallowest of them is no longer indented, while the relative indentation
+    /// of the others is preserved.
+    fn strip_common_indent(&mut self) -> anyhow::Result<Dispatches> {
+        let buffer = self.buffer();
+        let lines = self
+            .selected_line_indices()?
+            .into_iter()
+            .filter_map(|line_index| {
+                let line = buffer.get_line_by_line_index(line_index)?.to_string();
+                Some((buffer.line_to_char(line_index).ok()?, line))
+            })
+            .collect_vec();
+        let common_prefix_length = |left: &str, right: &str| {
+            left.chars()
+                .zip(right.chars())
+                .take_while(|(left, right)| left == right)
+                .count()
+        };
+        let Some(common_indent) = lines
+            .iter()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(_, line)| {
+                line.chars()
+                    .take_while(|c| c.is_whitespace() && !matches!(c, '\n' | '\r'))
+                    .collect::<String>()
+            })
+            .reduce(|common, indent| {
+                common
+                    .chars()
+                    .take(common_prefix_length(&common, &indent))
+                    .collect()
+            })
+        else {
+            return Ok(Default::default());
+        };
+        let edits = lines
+            .into_iter()
+            .map(|(start, line)| {
+                // Blank lines might be indented less than the common indent
+                let length = common_prefix_length(&common_indent, &line);
+                Edit {
+                    range: (start..start + length).into(),
+                    new: Rope::new(),
+                }
+            })
+            .filter(|edit| edit.range.len() > 0)
+            .collect_vec();
+        drop(buffer);
+        self.apply_char_index_edits(edits)
+    }
+
+    /// Insert spaces before the first `delimiter` of each line covered by the selections,
+    /// so that the delimiters of the lines are at the same column.
+    /// Lines without `delimiter` are left untouched.
+    fn align_selections(&mut self, delimiter: char) -> anyhow::Result<Dispatches> {
+        let buffer = self.buffer();
+        // The columns are measured in display width, as the grid renders them,
+        // so that the delimiters are aligned even when the lines contain tabs or wide characters
+        let delimiters = self
+            .selected_line_indices()?
+            .into_iter()
+            .filter_map(|line_index| {
+                let line = buffer.get_line_by_line_index(line_index)?.to_string();
+                let char_column = line.chars().position(|c| c == delimiter)?;
+                let column = line
+                    .chars()
+                    .take(char_column)
+                    .map(get_char_width)
+                    .sum::<usize>();
+                Some((buffer.line_to_char(line_index).ok()? + char_column, column))
+            })
+            .collect_vec();
+        let Some(max_column) = delimiters.iter().map(|(_, column)| *column).max() else {
+            return Ok(Default::default());
+        };
+        let edits = delimiters
+            .into_iter()
+            .filter(|(_, column)| *column < max_column)
+            .map(|(char_index, column)| Edit {
+                range: (char_index..char_index).into(),
+                new: Rope::from_str(&" ".repeat(max_column - column)),
+            })
+            .collect_vec();
+        drop(buffer);
+        self.apply_char_index_edits(edits)
+    }
+
+    /// The indices of the lines covered by the selections, sorted and deduplicated
+    fn selected_line_indices(&self) -> anyhow::Result<Vec<usize>> {
+        let buffer = self.buffer();
+        Ok(self
+            .selection_set
+            .map(|selection| -> anyhow::Result<_> {
+                let range = selection.extended_range();
+                let last = if range.end > range.start {
+                    range.end - 1
+                } else {
+                    range.start
+                };
+                Ok(buffer.char_to_line(range.start)?..=buffer.char_to_line(last)?)
+            })
+            .into_iter()
+            .collect::<anyhow::Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .unique()
+            .sorted()
+            .collect_vec())
+    }
+
     fn dedent(&mut self) -> Result<Dispatches, anyhow::Error> {
+        let indent_width = self.buffer().options().tab_width;
         let edit_transaction = EditTransaction::from_action_groups(
             self.selection_set
                 .map(|selection| -> anyhow::Result<_> {
@@ -2759,7 +4727,7 @@ impl Editor {
                     let get_remove_leading_char_count = |line: &str| {
                         let leading_indent_count =
                             line.chars().take_while(|c| c == &INDENT_CHAR).count();
-                        leading_indent_count.min(INDENT_WIDTH)
+                        leading_indent_count.min(indent_width)
                     };
                     let modified_lines = content
                         .lines()
@@ -2818,6 +4786,123 @@ impl Editor {
         self.apply_edit_transaction(edit_transaction)
     }
 
+    /// Replace the lines covered by each selection with `transform(lines)`,
+    /// then select the resulting lines.
+    fn transform_selected_lines(
+        &mut self,
+        transform: impl Fn(Vec<&str>) -> Vec<&str>,
+    ) -> Result<Dispatches, anyhow::Error> {
+        let edit_transaction = EditTransaction::from_action_groups(
+            self.selection_set
+                .map(|selection| -> anyhow::Result<_> {
+                    let range = selection.extended_range();
+                    // A selection ending with a newline does not cover the line after it
+                    let last_char_index = if range.start < range.end {
+                        range.end.apply_offset(-1)
+                    } else {
+                        range.end
+                    };
+                    let line_range = self.buffer().char_to_line(range.start)?
+                        ..self.buffer().char_to_line(last_char_index)?;
+                    let linewise_range = self
+                        .buffer()
+                        .line_range_to_full_char_index_range(line_range)?;
+                    let content = self.buffer().slice(&linewise_range)?.to_string();
+                    // The trailing newline stays at the end, instead of moving along with the last line
+                    let (content, trailing_newline) = match content.strip_suffix('\n') {
+                        Some(content) => (content, "\n"),
+                        None => (content.as_str(), ""),
+                    };
+                    let new: Rope = format!(
+                        "{}{}",
+                        transform(content.split('\n').collect()).join("\n"),
+                        trailing_newline
+                    )
+                    .into();
+                    let select_range = linewise_range.start..linewise_range.start + new.len_chars();
+                    Ok(ActionGroup::new(
+                        [
+                            Action::Edit(Edit {
+                                range: linewise_range,
+                                new,
+                            }),
+                            Action::Select(selection.clone().set_range(select_range.into())),
+                        ]
+                        .to_vec(),
+                    ))
+                })
+                .into_iter()
+                .flatten()
+                .collect_vec(),
+        );
+        self.apply_edit_transaction(edit_transaction)
+    }
+
+    fn sort_lines(
+        &mut self,
+        ascending: bool,
+        case_sensitive: bool,
+    ) -> Result<Dispatches, anyhow::Error> {
+        self.transform_selected_lines(|mut lines| {
+            // `sort_by` is stable, so equal lines keep their original order
+            lines.sort_by(|a, b| {
+                let ordering = if case_sensitive {
+                    a.cmp(b)
+                } else {
+                    a.to_lowercase().cmp(&b.to_lowercase())
+                };
+                if ascending {
+                    ordering
+                } else {
+                    ordering.reverse()
+                }
+            });
+            lines
+        })
+    }
+
+    /// Remove the lines that are identical to a preceding line
+    fn dedup_lines(&mut self) -> Result<Dispatches, anyhow::Error> {
+        self.transform_selected_lines(|lines| {
+            let mut seen = std::collections::HashSet::new();
+            lines
+                .into_iter()
+                .filter(|line| seen.insert(*line))
+                .collect()
+        })
+    }
+
+    fn toggle_marker_fold(&mut self, context: &Context) -> anyhow::Result<()> {
+        let cursor = self.get_cursor_char_index();
+        let (start_marker, end_marker) = context.fold_markers();
+        let region = self
+            .buffer()
+            .marker_regions(start_marker, end_marker)?
+            .into_iter()
+            .filter(|region| region.contains(&cursor))
+            .min_by_key(|region| region.len());
+        if let Some(region) = region {
+            self.buffer_mut().toggle_fold(region);
+            // Move the cursor out of the hidden lines onto the first line of the fold
+            if self.buffer().folds().contains(&region) {
+                let selection_set =
+                    self.selection_set
+                        .apply(self.selection_set.mode.clone(), |selection| {
+                            Ok(if region.contains(&selection.extended_range().start) {
+                                selection
+                                    .clone()
+                                    .set_range((region.start..region.start + 1).into())
+                                    .set_initial_range(None)
+                            } else {
+                                selection.clone()
+                            })
+                        })?;
+                self.set_selection_set(selection_set)
+            }
+        }
+        Ok(())
+    }
+
     #[cfg(test)]
     pub(crate) fn primary_selection(&self) -> anyhow::Result<String> {
         println!(
@@ -2930,6 +5015,79 @@ impl Editor {
         };
         self.update_selection_set(self.selection_set.clone().set_selections(selections), true)
     }
+
+    fn split_selection_on_regex(&mut self, regex: &str) -> anyhow::Result<Dispatches> {
+        let regex = regex::Regex::new(regex)?;
+        let selections = {
+            let buffer = self.buffer();
+            self.selection_set
+                .selections()
+                .iter()
+                .map(|selection| -> anyhow::Result<Vec<Selection>> {
+                    let range = selection.extended_range();
+                    let haystack = buffer.slice(&range)?.to_string();
+                    let matches = regex.find_iter(&haystack).map(|m| m.range()).collect_vec();
+                    if matches.is_empty() {
+                        return Ok(vec![selection.clone()]);
+                    }
+                    let start_byte = buffer.char_to_byte(range.start)?;
+                    let starts = std::iter::once(0).chain(matches.iter().map(|m| m.end));
+                    let ends = matches
+                        .iter()
+                        .map(|m| m.start)
+                        .chain(std::iter::once(haystack.len()));
+                    starts
+                        .zip(ends)
+                        .filter(|(start, end)| start < end)
+                        .map(|(start, end)| {
+                            Ok(Selection::new(
+                                (buffer.byte_to_char(start_byte + start)?
+                                    ..buffer.byte_to_char(start_byte + end)?)
+                                    .into(),
+                            ))
+                        })
+                        .collect()
+                })
+                .flatten_ok()
+                .try_collect::<_, Vec<_>, _>()?
+        };
+        let Some(selections) = NonEmpty::from_vec(selections) else {
+            return Ok(Default::default());
+        };
+        Ok(self.update_selection_set(self.selection_set.clone().set_selections(selections), true))
+    }
+}
+
+/// If `line_before_cursor` is a line comment, or ends with a comment starting at the byte
+/// `trailing_comment_start`, returns either the indentation and comment token to be inserted
+/// on the next line, or the number of characters of the indentation if the comment is empty.
+///
+/// The comment token is the whole leading token, so that doc comments such as `///` and `//!`
+/// are continued as themselves instead of as a plain `//`.
+fn continue_line_comment(
+    line_before_cursor: &str,
+    prefix: &str,
+    trailing_comment_start: Option<usize>,
+) -> Option<Either<String, usize>> {
+    let content = line_before_cursor.trim_start();
+    let indentation = &line_before_cursor[..line_before_cursor.len() - content.len()];
+    let is_trailing = !content.starts_with(prefix);
+    let comment = if is_trailing {
+        line_before_cursor.get(trailing_comment_start?..)?
+    } else {
+        content
+    };
+    let text = comment
+        .strip_prefix(prefix)?
+        .trim_start_matches(|c: char| c == '!' || prefix.contains(c));
+    let token = &comment[..comment.len() - text.len()];
+    if text.trim().is_empty() {
+        // Decorations such as `//////` and trailing comments are left as they are
+        return (!is_trailing && token.len() <= prefix.len() + 1)
+            .then(|| Either::Right(indentation.chars().count()));
+    }
+    let spaces = &text[..text.len() - text.trim_start().len()];
+    Some(Either::Left(format!("{indentation}{token}{spaces}")))
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
@@ -2947,6 +5105,9 @@ pub(crate) enum HandleEventResult {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) enum DispatchEditor {
     Surround(String, String),
+    /// Surround with the pair of which the given character is either the open or close symbol,
+    /// or with the given character on both sides if there is no such pair.
+    SurroundWith(char),
     #[cfg(test)]
     SetScrollOffset(u16),
     ShowJumps {
@@ -2959,7 +5120,22 @@ pub(crate) enum DispatchEditor {
     #[cfg(test)]
     AlignViewBottom,
     Transform(Transformation),
+    /// Transform the text of each selection with a built-in transformation
+    ApplyTransform(TransformKind),
+    /// Pretty-print the JSON of each selection with `indent` spaces, defaulting to the tab width
+    FormatJson {
+        indent: Option<usize>,
+    },
+    /// Pretty-print the XML of each selection with `indent` spaces, defaulting to the tab width
+    FormatXml {
+        indent: Option<usize>,
+    },
     SetSelectionMode(IfCurrentNotFound, SelectionMode),
+    /// Change to the next (`Direction::End`) or previous selection mode in `Context::selection_mode_cycle`
+    CycleSelectionMode(Direction),
+    /// Set a key/value metadata of the current buffer, refer `Buffer::var`
+    SetBufferVar(String, String),
+    ShowBufferVars,
     Save,
     FindOneChar(IfCurrentNotFound),
     MoveSelection(Movement),
@@ -2967,6 +5143,19 @@ pub(crate) enum DispatchEditor {
     Copy {
         use_system_clipboard: bool,
     },
+    /// Same as `Copy`, but the selections are also copied to the system clipboard,
+    /// while `Copy` without `use_system_clipboard` only uses the internal clipboard of the editor
+    CopyToSystemClipboard,
+    /// Paste the content of the system clipboard after the selections
+    PasteFromSystemClipboard,
+    /// Copy the selections to the named register, without affecting the clipboard
+    CopyToRegister(char),
+    /// Same as `CopyToRegister`, but the selections are also deleted
+    CutToRegister(char),
+    /// Paste the content of the named register after the selections
+    PasteFromRegister(char),
+    /// Copy the range and text of every selection as a JSON array, for scripting
+    ExportSelectionsJson,
     GoBack,
     GoForward,
     SelectAll,
@@ -2995,8 +5184,20 @@ pub(crate) enum DispatchEditor {
         use_system_clipboard: bool,
     },
     Insert(String),
+    /// Same as `Insert`, but every `{i}` is replaced by the index of the selection,
+    /// refer `Editor::insert_at_each_selection`
+    InsertAtEachSelection(String),
+    BreakLine,
     MoveToLineStart,
     MoveToLineEnd,
+    /// Go to the next run of blank lines, like `}` in Vim
+    GotoNextBlankLine,
+    /// Go to the previous run of blank lines, like `{` in Vim
+    GotoPreviousBlankLine,
+    /// Select the first non-whitespace character of the current line, like `^` in Vim
+    GotoFirstNonBlank,
+    /// Select the last non-whitespace character of the current line, like `g_` in Vim
+    GotoLineEndNonBlank,
     #[cfg(test)]
     MatchLiteral(String),
     SelectSurround {
@@ -3007,17 +5208,48 @@ pub(crate) enum DispatchEditor {
     ToggleMark,
     EnterNormalMode,
     EnterExchangeMode,
+    /// Swap the selected syntax node with its previous sibling
+    MoveNodeUp,
+    /// Swap the selected syntax node with its next sibling
+    MoveNodeDown,
     EnterReplaceMode,
+    EnterOvertypeMode,
     EnterMultiCursorMode,
     CursorAddToAllSelections,
+    /// Keep the existing selections, and add the selection of the current selection mode
+    /// obtained by applying `Movement` to the primary selection, without entering the multi-cursor mode
+    AddSelection(Movement),
     CyclePrimarySelection(Direction),
+    /// Jump to the next reference of the symbol under the cursor, without showing them in a list
+    GotoNextReference,
+    GotoPreviousReference,
+    /// Grow (`Direction::End`) or shrink (`Direction::Start`) the primary selection
+    /// by the semantic ranges provided by the LSP server
+    SelectSemanticRange(Direction),
+    SetSemanticRanges(Vec<Range<Position>>),
     CursorKeepPrimaryOnly,
+    AddCursorToNextMatch,
+    SkipCurrentMatch,
+    ToggleMergeOverlappingSelections,
+    /// Refer `Editor::replace_all_matching`
+    ReplaceAllMatching {
+        pattern: String,
+        replacement: String,
+        regex: bool,
+    },
     ReplacePattern {
         config: crate::context::LocalSearchConfig,
     },
     Undo,
     Redo,
     KillLine(Direction),
+    /// Delete the selections and add them to the kill ring,
+    /// appending to the latest kill if the previous dispatch is also a kill
+    Kill,
+    /// Insert the latest kill at the cursor
+    Yank,
+    /// Replace the text inserted by the previous `Yank` or `YankPop` with the kill before it
+    YankPop,
     #[cfg(test)]
     Reset,
     DeleteWordBackward {
@@ -3036,6 +5268,25 @@ pub(crate) enum DispatchEditor {
         direction: Direction,
         use_system_clipboard: bool,
     },
+    /// Insert the copied text before the selections without replacing them,
+    /// the pasted text is selected
+    PasteBefore {
+        use_system_clipboard: bool,
+    },
+    /// Insert the copied text after the selections without replacing them,
+    /// the pasted text is selected
+    PasteAfter {
+        use_system_clipboard: bool,
+    },
+    /// Replace the text pasted by the last `Paste` with the previous copied text,
+    /// refer `Editor::cycle_pasted_text`
+    PasteCycleOlder,
+    /// Same as `PasteCycleOlder`, but with the next copied text
+    PasteCycleNewer,
+    /// Replace the i-th selection with the i-th line of the clipboard
+    PasteAsMultiCursor {
+        use_system_clipboard: bool,
+    },
     SwapCursorWithAnchor,
     MoveCharacterBack,
     MoveCharacterForward,
@@ -3056,6 +5307,17 @@ pub(crate) enum DispatchEditor {
     ShowCurrentTreeSitterNodeSexp,
     Indent,
     Dedent,
+    /// Remove the common indentation of the lines covered by the selections
+    StripCommonIndent,
+    /// Sort the lines covered by each selection
+    SortLines {
+        ascending: bool,
+        case_sensitive: bool,
+    },
+    /// Remove the duplicated lines covered by each selection, keeping their first occurrences
+    DedupLines,
+    /// Fold or unfold the innermost region enclosing the cursor, delimited by `Context::fold_markers`
+    ToggleMarkerFold,
     SwapExtensionDirection,
     CollapseSelection(Direction),
     EnterTillMode(IfCurrentNotFound),
@@ -3063,6 +5325,207 @@ pub(crate) enum DispatchEditor {
         search: String,
         keep: bool,
     },
+    /// Split each selection into the parts between the matches of the given regex,
+    /// selections without any match are left untouched.
+    SplitSelectionOnRegex(String),
+    GotoNextMisspelling,
+    /// Select the overflow of the next line longer than `Context::max_line_length`
+    GotoNextLongLine,
+    /// Select the next diagnostic after the cursor, wrapping around to the first one
+    GotoNextDiagnostic,
+    /// Select the previous diagnostic before the cursor, wrapping around to the last one
+    GotoPreviousDiagnostic,
+    /// Cycle through the directives (e.g. `#if`, `#elif`, `#else` and `#endif`)
+    /// of the innermost preprocessor conditional enclosing the cursor
+    GotoMatchingConditional,
+    /// Select the bracket matching the one under the cursor,
+    /// falling back to scanning the text around the cursor if there is no syntax tree
+    GotoMatchingBracket,
+    /// Select the innermost function or method enclosing the cursor, including its signature
+    SelectWholeFunction,
+    /// Select from each cursor to where `motion` moves it, then apply `operator` on the selections
+    OperatorMotion {
+        operator: Operator,
+        motion: Motion,
+    },
+    /// Apply the last edit again on the current selections, like `.` in Vim
+    RepeatLastEdit,
+    /// Select the next syntax node of the given tree-sitter kind, e.g. `function_item`,
+    /// refer `Editor::select_next_node_of_kind`
+    SelectNextNodeOfKind(String),
+    /// Start or stop a rectangular selection, refer `Editor::toggle_column_selection`
+    ToggleColumnSelection,
+    /// Refer `Editor::align_selections`
+    AlignSelections(char),
+    /// Add the given delta to the number of each selection
+    IncrementNumber(i64),
+    /// Subtract the given delta from the number of each selection
+    DecrementNumber(i64),
+    /// Add the given step multiplied by the index of the selection to the number of each selection,
+    /// e.g. `0`, `0`, `0` becomes `0`, `1`, `2` with a step of 1
+    IncrementNumberSequence(i64),
+}
+
+impl DispatchEditor {
+    /// Returns true if this dispatch is sent by the insert mode keymaps,
+    /// and is recorded as part of the insertion repeated by `RepeatLastEdit`.
+    /// `Insert` is excluded, because the typed text is recorded by `Editor::insert`.
+    fn is_insert_mode_edit(&self) -> bool {
+        matches!(
+            self,
+            Backspace
+                | BreakLine
+                | KillLine(_)
+                | Yank
+                | YankPop
+                | DeleteWordBackward { .. }
+                | MoveCharacterBack
+                | MoveCharacterForward
+                | MoveToLineStart
+                | MoveToLineEnd
+        )
+    }
+
+    /// Returns true if this dispatch edits the buffer, and can be repeated by `RepeatLastEdit`
+    fn is_repeatable_edit(&self) -> bool {
+        matches!(
+            self,
+            Surround(..)
+                | SurroundWith(_)
+                | Transform(_)
+                | ApplyTransform(_)
+                | FormatJson { .. }
+                | FormatXml { .. }
+                | CutToRegister(_)
+                | PasteFromRegister(_)
+                | PasteFromSystemClipboard
+                | ReplaceWithCopiedText { .. }
+                | Delete(_)
+                | DeleteCut { .. }
+                | Insert(_)
+                | Change
+                | ChangeCut { .. }
+                | InsertAtEachSelection(_)
+                | ReplaceAllMatching { .. }
+                | Kill
+                | KillLine(_)
+                | Paste { .. }
+                | PasteBefore { .. }
+                | PasteAfter { .. }
+                | DeleteSurround(_)
+                | ChangeSurround { .. }
+                | Indent
+                | Dedent
+                | StripCommonIndent
+                | AlignSelections(_)
+                | IncrementNumber(_)
+                | DecrementNumber(_)
+                | IncrementNumberSequence(_)
+                | SortLines { .. }
+                | DedupLines
+                | OperatorMotion {
+                    operator: Operator::Delete | Operator::Cut { .. },
+                    ..
+                }
+        )
+    }
+}
+
+/// An action applied on the range covered by a `Motion`, refer `DispatchEditor::OperatorMotion`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Operator {
+    Delete,
+    Cut { use_system_clipboard: bool },
+    Copy { use_system_clipboard: bool },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Motion {
+    /// To the start of the next word, like `w` in Vim
+    WordForward,
+    /// To the start of the current or previous word, like `b` in Vim
+    WordBackward,
+    LineStart,
+    /// To the end of the line, excluding the line break
+    LineEnd,
+}
+
+impl Motion {
+    /// Returns the range between `cursor` and the position where this motion moves `cursor` to.
+    fn range(&self, buffer: &Buffer, cursor: CharIndex) -> anyhow::Result<CharIndexRange> {
+        /// Consecutive characters of the same class form a word
+        fn class(c: char) -> u8 {
+            if c.is_whitespace() {
+                0
+            } else if c.is_alphanumeric() || c == '_' {
+                1
+            } else {
+                2
+            }
+        }
+        let rope = buffer.rope();
+        let cursor = CharIndex(cursor.0.min(rope.len_chars()));
+        Ok(match self {
+            Motion::WordForward => {
+                let mut chars = rope.chars_at(cursor.0).peekable();
+                let mut end = cursor.0;
+                if let Some(first_class) = chars.peek().map(|&c| class(c)) {
+                    while first_class != 0 && chars.next_if(|&c| class(c) == first_class).is_some()
+                    {
+                        end += 1
+                    }
+                    while chars.next_if(|c| c.is_whitespace()).is_some() {
+                        end += 1
+                    }
+                }
+                (cursor..CharIndex(end)).into()
+            }
+            Motion::WordBackward => {
+                let mut chars = rope.chars_at(cursor.0).reversed().peekable();
+                let mut start = cursor.0;
+                while chars.next_if(|c| c.is_whitespace()).is_some() {
+                    start -= 1
+                }
+                if let Some(last_class) = chars.peek().map(|&c| class(c)) {
+                    while chars.next_if(|&c| class(c) == last_class).is_some() {
+                        start -= 1
+                    }
+                }
+                (CharIndex(start)..cursor).into()
+            }
+            Motion::LineStart => {
+                (buffer.line_to_char(buffer.char_to_line(cursor)?)?..cursor).into()
+            }
+            Motion::LineEnd => {
+                let line_index = buffer.char_to_line(cursor)?;
+                let length = buffer
+                    .get_line_by_line_index(line_index)
+                    .map(|line| {
+                        line.chars()
+                            .take_while(|c| !matches!(c, '\n' | '\r'))
+                            .count()
+                    })
+                    .unwrap_or_default();
+                (cursor..buffer.line_to_char(line_index)? + length).into()
+            }
+        })
+    }
+}
+
+/// The JSON representation of a selection, where `start` and `end` are char indices
+#[derive(serde::Serialize)]
+struct SelectionJson {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+/// The JSON representation of an edit, where `start` and `end` are char indices
+#[derive(serde::Deserialize)]
+struct EditJson {
+    start: usize,
+    end: usize,
+    replacement: String,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -3081,4 +5544,7 @@ impl std::fmt::Display for SurroundKind {
 }
 
 const INDENT_CHAR: char = ' ';
-const INDENT_WIDTH: usize = 4;
+
+/// The maximum size of the compiled regex of `DispatchEditor::ReplaceAllMatching`,
+/// so that a pathological pattern fails to compile instead of exhausting the memory.
+const REPLACE_REGEX_SIZE_LIMIT: usize = 1 << 20;
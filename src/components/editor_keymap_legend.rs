@@ -227,6 +227,11 @@ impl Editor {
                         Direction::End.format_action("Insert"),
                         Dispatch::ToEditor(EnterInsertMode(Direction::End)),
                     ),
+                    Keymap::new(
+                        "I",
+                        "Overtype".to_string(),
+                        Dispatch::ToEditor(EnterOvertypeMode),
+                    ),
                     Keymap::new("c", "Change".to_string(), Dispatch::ToEditor(Change)),
                     Keymap::new(
                         "d",
@@ -501,6 +506,12 @@ impl Editor {
                                 Direction::Start.format_action("Kill line"),
                                 Dispatch::ToEditor(KillLine(Direction::Start)),
                             ),
+                            Keymap::new("ctrl+y", "Yank".to_string(), Dispatch::ToEditor(Yank)),
+                            Keymap::new(
+                                "alt+y",
+                                "Yank pop".to_string(),
+                                Dispatch::ToEditor(YankPop),
+                            ),
                             Keymap::new(
                                 "ctrl+w",
                                 "Delete word backward".to_string(),
@@ -539,7 +550,7 @@ impl Editor {
                             Keymap::new(
                                 "enter",
                                 "Enter new line".to_string(),
-                                Dispatch::ToEditor(Insert("\n".to_string())),
+                                Dispatch::ToEditor(BreakLine),
                             ),
                             Keymap::new(
                                 "tab",
@@ -878,7 +889,14 @@ impl Editor {
                             .chain(Some(Keymap::new(
                                 "s",
                                 "Pick Symbols".to_string(),
-                                Dispatch::RequestDocumentSymbols,
+                                Dispatch::RequestDocumentSymbols { kinds: Vec::new() },
+                            )))
+                            .chain(Some(Keymap::new(
+                                "S",
+                                "Pick Symbols of kind".to_string(),
+                                Dispatch::ShowKeymapLegend(
+                                    self.symbol_kinds_keymap_legend_config(),
+                                ),
                             )))
                             .chain(Some(Keymap::new(
                                 "t",
@@ -901,6 +919,16 @@ impl Editor {
                                 "Keep only primary cursor".to_string(),
                                 Dispatch::ToEditor(DispatchEditor::CursorKeepPrimaryOnly),
                             ),
+                            Keymap::new(
+                                "n",
+                                "Add cursor to next match".to_string(),
+                                Dispatch::ToEditor(DispatchEditor::AddCursorToNextMatch),
+                            ),
+                            Keymap::new(
+                                "N",
+                                "Skip current match".to_string(),
+                                Dispatch::ToEditor(DispatchEditor::SkipCurrentMatch),
+                            ),
                         ]),
                     }))
                     .chain(Some(KeymapLegendSection {
@@ -913,6 +941,11 @@ impl Editor {
                                     context.current_working_directory().clone()
                                 })),
                             ),
+                            Keymap::new(
+                                "A",
+                                "Go to alternate file".to_string(),
+                                Dispatch::GotoAlternateFile,
+                            ),
                             Keymap::new(
                                 "z",
                                 "Undo Tree".to_string(),
@@ -1056,6 +1089,14 @@ impl Editor {
                         )
                     }),
                 )
+                .chain(match scope {
+                    Scope::Global => None,
+                    Scope::Local => Some(Keymap::new(
+                        "s",
+                        "Misspelling".to_string(),
+                        Dispatch::ToEditor(SetSelectionMode(if_current_not_found, Misspelling)),
+                    )),
+                })
                 .collect_vec(),
             ),
         };
@@ -1214,6 +1255,76 @@ impl Editor {
         }
     }
 
+    fn symbol_kinds_keymap_legend_config(&self) -> KeymapLegendConfig {
+        use lsp_types::SymbolKind;
+        KeymapLegendConfig {
+            title: "Pick Symbols of kind".to_string(),
+
+            body: KeymapLegendBody::SingleSection {
+                keymaps: Keymaps::new(
+                    &[
+                        (
+                            "f",
+                            "Functions",
+                            [
+                                SymbolKind::FUNCTION,
+                                SymbolKind::METHOD,
+                                SymbolKind::CONSTRUCTOR,
+                            ]
+                            .to_vec(),
+                        ),
+                        (
+                            "t",
+                            "Types",
+                            [
+                                SymbolKind::STRUCT,
+                                SymbolKind::CLASS,
+                                SymbolKind::ENUM,
+                                SymbolKind::INTERFACE,
+                                SymbolKind::TYPE_PARAMETER,
+                            ]
+                            .to_vec(),
+                        ),
+                        (
+                            "v",
+                            "Variables",
+                            [
+                                SymbolKind::VARIABLE,
+                                SymbolKind::FIELD,
+                                SymbolKind::PROPERTY,
+                            ]
+                            .to_vec(),
+                        ),
+                        (
+                            "c",
+                            "Constants",
+                            [SymbolKind::CONSTANT, SymbolKind::ENUM_MEMBER].to_vec(),
+                        ),
+                        (
+                            "m",
+                            "Modules",
+                            [
+                                SymbolKind::MODULE,
+                                SymbolKind::NAMESPACE,
+                                SymbolKind::PACKAGE,
+                            ]
+                            .to_vec(),
+                        ),
+                    ]
+                    .into_iter()
+                    .map(|(key, description, kinds)| {
+                        Keymap::new(
+                            key,
+                            description.to_string(),
+                            Dispatch::RequestDocumentSymbols { kinds },
+                        )
+                    })
+                    .collect_vec(),
+                ),
+            },
+        }
+    }
+
     pub(crate) fn delete_surround_keymap_legend_config(&self) -> KeymapLegendConfig {
         KeymapLegendConfig {
             title: "Delete Surround".to_string(),
@@ -1231,10 +1342,21 @@ impl Editor {
             title: "Surround".to_string(),
 
             body: KeymapLegendBody::SingleSection {
-                keymaps: generate_enclosures_keymaps(|enclosure| {
-                    let (open, close) = enclosure.open_close_symbols_str();
-                    Dispatch::ToEditor(Surround(open.to_string(), close.to_string()))
-                }),
+                keymaps: Keymaps::new(
+                    &generate_enclosures_keymaps(|enclosure| {
+                        let (open, close) = enclosure.open_close_symbols_str();
+                        Dispatch::ToEditor(Surround(open.to_string(), close.to_string()))
+                    })
+                    .keymaps()
+                    .into_iter()
+                    .cloned()
+                    .chain(Some(Keymap::new(
+                        "space",
+                        "Other character".to_string(),
+                        Dispatch::OpenSurroundWithPrompt,
+                    )))
+                    .collect_vec(),
+                ),
             },
         }
     }
@@ -6,18 +6,19 @@ use shared::canonicalized_path::CanonicalizedPath;
 
 use super::{
     component::Component,
-    editor::Editor,
+    editor::{Editor, Mode},
     keymap_legend::{Keymap, Keymaps},
 };
 
 pub(crate) struct FileExplorer {
     editor: Editor,
     tree: Tree,
+    working_directory: CanonicalizedPath,
 }
 
 impl FileExplorer {
     pub(crate) fn new(path: &CanonicalizedPath) -> anyhow::Result<Self> {
-        let tree = Tree::new(path)?;
+        let tree = Tree::new(path, Filter::default())?;
         let text = tree.render();
         let mut editor = Editor::from_text(
             shared::language::from_extension("yaml")
@@ -25,7 +26,11 @@ impl FileExplorer {
             &format!("{}\n", text),
         );
         editor.set_title("File Explorer".to_string());
-        Ok(Self { editor, tree })
+        Ok(Self {
+            editor,
+            tree,
+            working_directory: path.clone(),
+        })
     }
 
     pub(crate) fn reveal(&mut self, path: &CanonicalizedPath) -> anyhow::Result<Dispatches> {
@@ -42,10 +47,21 @@ impl FileExplorer {
     pub(crate) fn refresh(&mut self, working_directory: &CanonicalizedPath) -> anyhow::Result<()> {
         let tree = std::mem::take(&mut self.tree);
         self.tree = tree.refresh(working_directory)?;
+        self.working_directory = working_directory.clone();
         self.refresh_editor()?;
         Ok(())
     }
 
+    pub(crate) fn toggle_hidden_files(&mut self) -> anyhow::Result<()> {
+        self.tree.filter.exclude_hidden = !self.tree.filter.exclude_hidden;
+        self.refresh(&self.working_directory.clone())
+    }
+
+    pub(crate) fn toggle_ignored_files(&mut self) -> anyhow::Result<()> {
+        self.tree.filter.exclude_ignored = !self.tree.filter.exclude_ignored;
+        self.refresh(&self.working_directory.clone())
+    }
+
     fn refresh_editor(&mut self) -> anyhow::Result<()> {
         let text = self.tree.render();
         self.editor_mut().set_content(&text)
@@ -57,13 +73,26 @@ impl FileExplorer {
     }
 }
 
-fn get_nodes(path: &CanonicalizedPath) -> anyhow::Result<Vec<Node>> {
-    let directory = std::fs::read_dir(path)?;
-    Ok(directory
+fn get_nodes(path: &CanonicalizedPath, filter: Filter) -> anyhow::Result<Vec<Node>> {
+    Ok(ignore::WalkBuilder::new(path)
+        .max_depth(Some(1))
+        .standard_filters(false)
+        .hidden(filter.exclude_hidden)
+        .ignore(filter.exclude_ignored)
+        .parents(filter.exclude_ignored)
+        .git_ignore(filter.exclude_ignored)
+        .git_global(filter.exclude_ignored)
+        .git_exclude(filter.exclude_ignored)
+        .build()
         .flatten()
+        // The entry of depth 0 is `path` itself
+        .filter(|entry| entry.depth() > 0)
         .flat_map(|entry| -> anyhow::Result<Node> {
             let path: CanonicalizedPath = entry.path().try_into()?;
-            let kind = if entry.file_type()?.is_dir() {
+            let kind = if entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_dir())
+            {
                 NodeKind::Directory {
                     open: false,
                     children: None,
@@ -81,9 +110,19 @@ fn get_nodes(path: &CanonicalizedPath) -> anyhow::Result<Vec<Node>> {
         .collect())
 }
 
+/// The paths excluded from the tree, every path is shown by default
+#[derive(Clone, Copy, Default)]
+struct Filter {
+    /// Paths starting with a dot
+    exclude_hidden: bool,
+    /// Paths ignored by `.gitignore` and the other ignore files
+    exclude_ignored: bool,
+}
+
 #[derive(Clone, Default)]
 struct Tree {
     nodes: Vec<Node>,
+    filter: Filter,
 }
 
 struct Continuation<T> {
@@ -97,9 +136,9 @@ enum ContinuationKind {
 }
 
 impl Tree {
-    fn new(working_directory: &CanonicalizedPath) -> anyhow::Result<Self> {
-        let nodes = get_nodes(working_directory)?;
-        Ok(Self { nodes })
+    fn new(working_directory: &CanonicalizedPath, filter: Filter) -> anyhow::Result<Self> {
+        let nodes = get_nodes(working_directory, filter)?;
+        Ok(Self { nodes, filter })
     }
 
     fn map<F>(self, f: F) -> Self
@@ -108,6 +147,7 @@ impl Tree {
     {
         Tree {
             nodes: self.nodes.into_iter().map(f).collect(),
+            filter: self.filter,
         }
     }
 
@@ -115,6 +155,7 @@ impl Tree {
     where
         F: Fn(bool) -> bool + Clone,
     {
+        let filter = self.filter;
         self.map(|node| {
             let kind = match node.kind {
                 NodeKind::File => node.kind,
@@ -124,13 +165,15 @@ impl Tree {
                     } else {
                         open
                     },
-                    children: children.or_else(|| Tree::new(&node.path).ok()).map(|tree| {
-                        if open {
-                            tree.toggle(path, change_open.clone())
-                        } else {
-                            tree
-                        }
-                    }),
+                    children: children
+                        .or_else(|| Tree::new(&node.path, filter).ok())
+                        .map(|tree| {
+                            if open {
+                                tree.toggle(path, change_open.clone())
+                            } else {
+                                tree
+                            }
+                        }),
                 },
             };
             Node { kind, ..node }
@@ -291,7 +334,7 @@ impl Tree {
                 }
             },
         });
-        let tree = Tree::new(working_directory)?;
+        let tree = Tree::new(working_directory, self.filter)?;
         log::info!("opened_paths = {:?}", opened_paths);
         let tree = opened_paths
             .into_iter()
@@ -331,27 +374,57 @@ impl Component for FileExplorer {
             .flatten()
             .map(|node| super::keymap_legend::KeymapLegendSection {
                 title: "File Explorer".to_string(),
-                keymaps: Keymaps::new(&[
-                    Keymap::new(
-                        "a",
-                        "Add file (or postfix with / for folder)".to_string(),
-                        Dispatch::OpenAddPathPrompt(node.path.clone()),
-                    ),
-                    Keymap::new(
-                        "d",
-                        "Delete path".to_string(),
-                        Dispatch::OpenYesNoPrompt(YesNoPrompt {
-                            title: format!("Delete \"{}\"?", node.path.display_absolute()),
-                            yes: Box::new(Dispatch::DeletePath(node.path.clone())),
-                        }),
-                    ),
-                    Keymap::new(
-                        "m",
-                        "Move path".to_string(),
-                        Dispatch::OpenMoveFilePrompt(node.path.clone()),
-                    ),
-                    Keymap::new("r", "Refresh".to_string(), Dispatch::RefreshFileExplorer),
-                ]),
+                keymaps: Keymaps::new(
+                    &[
+                        Keymap::new(
+                            "a",
+                            "Add file (or postfix with / for folder)".to_string(),
+                            Dispatch::OpenAddPathPrompt(node.path.clone()),
+                        ),
+                        Keymap::new(
+                            "d",
+                            "Delete path".to_string(),
+                            Dispatch::OpenYesNoPrompt(YesNoPrompt {
+                                title: format!("Delete \"{}\"?", node.path.display_absolute()),
+                                yes: Box::new(Dispatch::DeletePath(node.path.clone())),
+                            }),
+                        ),
+                        Keymap::new(
+                            "m",
+                            "Move path".to_string(),
+                            Dispatch::OpenMoveFilePrompt(node.path.clone()),
+                        ),
+                        Keymap::new("r", "Refresh".to_string(), Dispatch::RefreshFileExplorer),
+                        Keymap::new(
+                            ".",
+                            "Toggle hidden files".to_string(),
+                            Dispatch::ToggleFileExplorerHiddenFiles,
+                        ),
+                        Keymap::new(
+                            "i",
+                            "Toggle gitignored files".to_string(),
+                            Dispatch::ToggleFileExplorerIgnoredFiles,
+                        ),
+                    ]
+                    .into_iter()
+                    .chain(match node.kind {
+                        NodeKind::Directory { .. } => [
+                            Keymap::new(
+                                "w",
+                                "Add as workspace folder".to_string(),
+                                Dispatch::AddWorkspaceFolder(node.path.clone()),
+                            ),
+                            Keymap::new(
+                                "W",
+                                "Remove workspace folder".to_string(),
+                                Dispatch::RemoveWorkspaceFolder(node.path.clone()),
+                            ),
+                        ]
+                        .to_vec(),
+                        NodeKind::File => Vec::new(),
+                    })
+                    .collect_vec(),
+                ),
             })
             .into_iter()
             .collect()
@@ -383,6 +456,9 @@ impl Component for FileExplorer {
                     Ok(Vec::new().into())
                 }
             }
+            key!("esc") if self.editor.mode == Mode::Normal => {
+                Ok(Dispatches::one(Dispatch::CloseFileExplorer))
+            }
             _ => self.editor.handle_key_event(context, event),
         }
     }
@@ -392,7 +468,7 @@ impl Component for FileExplorer {
 mod test_file_explorer {
     use my_proc_macros::{key, keys};
 
-    use crate::test_app::*;
+    use crate::{test_app::*, ui_tree::ComponentKind};
 
     #[test]
     fn reveal() -> Result<(), anyhow::Error> {
@@ -438,6 +514,93 @@ mod test_file_explorer {
         })
     }
 
+    #[test]
+    fn close_with_esc() -> anyhow::Result<()> {
+        execute_test(|s| {
+            Box::new([
+                App(OpenFile(s.foo_rs())),
+                Expect(ComponentsOrder(vec![ComponentKind::SuggestiveEditor])),
+                App(OpenFileExplorer),
+                // The explorer takes the place of the editor
+                Expect(ComponentsOrder(vec![ComponentKind::FileExplorer])),
+                Expect(CurrentComponentTitle("File Explorer")),
+                Expect(CurrentSelectedTexts(&["   - 🦀  foo.rs\n"])),
+                Editor(MoveSelection(Down)),
+                App(HandleKeyEvent(key!("esc"))),
+                Expect(ComponentsOrder(vec![ComponentKind::SuggestiveEditor])),
+                Expect(CurrentComponentPath(Some(s.foo_rs()))),
+            ])
+        })
+    }
+
+    #[test]
+    fn close_with_esc_without_file_to_return_to() -> anyhow::Result<()> {
+        execute_test(|_| {
+            Box::new([
+                Expect(ComponentCount(0)),
+                App(OpenFileExplorer),
+                Expect(ComponentsOrder(vec![ComponentKind::FileExplorer])),
+                App(HandleKeyEvent(key!("esc"))),
+                Expect(ComponentCount(0)),
+            ])
+        })
+    }
+
+    #[test]
+    fn toggle_hidden_and_ignored_files() -> anyhow::Result<()> {
+        execute_test(|s| {
+            Box::new([
+                App(RevealInExplorer(s.main_rs())),
+                // `target/` is ignored by the `.gitignore`
+                App(AddPath(format!(
+                    "{}/target/",
+                    s.temp_dir().display_absolute()
+                ))),
+                App(ToggleFileExplorerHiddenFiles),
+                Expect(FileExplorerContent(
+                    "
+ - 🔒  Cargo.lock
+ - 📄  Cargo.toml
+ - 📂  src/ :
+   - 🦀  foo.rs
+   - 🦀  main.rs
+ - 📂  target/ :
+"
+                    .trim_matches('\n')
+                    .to_string(),
+                )),
+                App(ToggleFileExplorerIgnoredFiles),
+                Expect(FileExplorerContent(
+                    "
+ - 🔒  Cargo.lock
+ - 📄  Cargo.toml
+ - 📂  src/ :
+   - 🦀  foo.rs
+   - 🦀  main.rs
+"
+                    .trim_matches('\n')
+                    .to_string(),
+                )),
+                App(ToggleFileExplorerHiddenFiles),
+                App(ToggleFileExplorerIgnoredFiles),
+                Expect(FileExplorerContent(
+                    "
+ - 📁  .git/ :
+ - 🙈  .gitignore
+ - 🔒  Cargo.lock
+ - 📄  Cargo.toml
+ - 📂  src/ :
+   - 🦀  foo.rs
+   - 🦀  main.rs
+ - 📂  target/ :
+"
+                    .trim_matches('\n')
+                    .to_string(),
+                )),
+            ])
+        })
+    }
+
     #[test]
     fn open_file() -> anyhow::Result<()> {
         execute_test(|s| {
@@ -56,6 +56,29 @@ pub(crate) enum PromptHistoryKey {
     Null,
     Theme,
     PipeToShell,
+    StatusLineSegments,
+    ExtraIgnores,
+    UndoLimit,
+    ClipboardHistoryDepth,
+    SplitSelectionsOnRegex,
+    ApplyEditsJson,
+    DiffBuffers,
+    WordsPerMinute,
+    InsertAtEachSelection,
+    MaxLineLength,
+    LanguageConfig,
+    SurroundWith,
+    SelectNextNodeOfKind,
+    SelectionModeCycle,
+    BufferVar,
+    FoldMarkers,
+    Quit,
+    ReloadFile,
+    AutosaveInterval,
+    Session,
+    AlignSelections,
+    RunWithSelection,
+    Register,
     FilterSelectionsMatchingSearch {
         keep: bool,
     },
@@ -145,6 +168,7 @@ impl Component for Prompt {
         match event {
             key!("esc") if self.editor().mode == Mode::Normal => {
                 Ok(Dispatches::one(Dispatch::CloseCurrentWindow)
+                    .chain(self.on_enter.to_dispatches_on_close())
                     .chain(self.fire_dispatches_on_change.clone().unwrap_or_default()))
             }
             key!("ctrl+space") => {
@@ -173,6 +197,7 @@ impl Component for Prompt {
                 };
 
                 Ok(Dispatches::one(Dispatch::CloseCurrentWindow)
+                    .chain(self.on_enter.to_dispatches_on_close())
                     .chain(dispatches)
                     .append(Dispatch::PushPromptHistory {
                         key: self.prompt_history_key,
@@ -180,7 +205,14 @@ impl Component for Prompt {
                     }))
             }
             _ => {
+                let line_before = self.editor().current_line()?;
                 let dispatches = self.editor.handle_key_event(context, event)?;
+                let line = self.editor().current_line()?;
+                let dispatches = if line == line_before {
+                    dispatches
+                } else {
+                    dispatches.chain(self.on_enter.to_dispatches_on_change(&line))
+                };
                 Ok(if self.fire_dispatches_on_change.is_some() {
                     dispatches.chain(
                         self.editor
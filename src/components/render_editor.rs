@@ -38,12 +38,15 @@ impl Editor {
             self.get_parent_lines().unwrap_or_default();
         let top_offset = hidden_parent_lines.len() as u16;
         let scroll_offset = self.scroll_offset();
+        let folded_lines = buffer.folded_lines();
         let visible_lines = rope
             .lines()
             .enumerate()
             .skip(scroll_offset as usize)
+            .filter(|(line_index, _)| !folded_lines.iter().any(|range| range.contains(line_index)))
             .take(height as usize)
-            .map(|(line_index, slice)| (line_index, slice.to_string()));
+            .map(|(line_index, slice)| (line_index, slice.to_string()))
+            .collect_vec();
 
         let visible_lines_grid: Grid = Grid::new(Dimension { height, width });
 
@@ -71,6 +74,17 @@ impl Editor {
             source: Source::StyleKey(UiMark),
             range: HighlightSpanRange::CharIndexRange(mark),
         });
+        let pending_delete =
+            editor
+                .pending_delete()
+                .into_iter()
+                .flatten()
+                .map(|range| HighlightSpan {
+                    set_symbol: None,
+                    is_cursor: false,
+                    source: Source::StyleKey(StyleKey::HunkOld),
+                    range: HighlightSpanRange::CharIndexRange(*range),
+                });
         let secondary_selections = &editor.selection_set.secondary_selections();
         let primary_selection = HighlightSpan {
             set_symbol: None,
@@ -273,17 +287,78 @@ impl Editor {
             set_symbol: None,
             is_cursor: false,
         });
+        let cursor_line = buffer.char_to_line(editor.get_cursor_char_index()).ok();
+        let cursor_line_highlight =
+            cursor_line
+                .filter(|_| context.highlight_cursor_line())
+                .map(|line| HighlightSpan {
+                    source: Source::StyleKey(StyleKey::UiCursorLine),
+                    range: HighlightSpanRange::Line(line),
+                    set_symbol: None,
+                    is_cursor: false,
+                });
+        let selection_lines = if context.highlight_selection_lines() {
+            editor
+                .selection_set
+                .map(|selection| {
+                    let range = selection.extended_range();
+                    let last = if range.end > range.start {
+                        range.end - 1
+                    } else {
+                        range.start
+                    };
+                    Some(buffer.char_to_line(range.start).ok()?..=buffer.char_to_line(last).ok()?)
+                })
+                .into_iter()
+                .flatten()
+                .flatten()
+                .filter(|line| visible_line_range.contains(line))
+                .unique()
+                .collect_vec()
+        } else {
+            Vec::new()
+        };
+        let selection_lines = selection_lines.into_iter().map(|line| HighlightSpan {
+            source: Source::StyleKey(StyleKey::UiSelectionLine),
+            range: HighlightSpanRange::Line(line),
+            set_symbol: None,
+            is_cursor: false,
+        });
+        let long_line_overflows = editor
+            .long_line_overflows(context, visible_line_range.clone())
+            .into_iter()
+            .map(|range| HighlightSpan {
+                set_symbol: None,
+                is_cursor: false,
+                source: Source::StyleKey(StyleKey::UiLongLineOverflow),
+                range: HighlightSpanRange::CharIndexRange(range),
+            });
+        let search_matches = editor
+            .search_matches(context, visible_line_range.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|range| HighlightSpan {
+                set_symbol: None,
+                is_cursor: false,
+                source: Source::StyleKey(StyleKey::UiSearchMatch),
+                range: HighlightSpanRange::CharIndexRange(range),
+            });
         let updates = vec![]
             .into_iter()
             .chain(visible_parent_lines)
+            .chain(selection_lines)
+            .chain(cursor_line_highlight)
             .chain(filtered_highlighted_spans)
             .chain(extra_decorations)
+            .chain(search_matches)
             .chain(possible_selections)
             .chain(Some(primary_selection))
             .chain(secondary_selection)
             .chain(primary_selection_anchors)
             .chain(seconday_selection_anchors)
             .chain(marks)
+            .chain(pending_delete)
+            .chain(long_line_overflows)
             .chain(diagnostics)
             .chain(jumps)
             .chain(primary_selection_secondary_cursor)
@@ -300,23 +375,75 @@ impl Editor {
                 .collect_vec()
         };
 
-        let visible_lines_grid = visible_lines_grid.render_content(
-            &visible_lines.map(|(_, line)| line).join(""),
-            RenderContentLineNumber::LineNumber {
-                start_line_index: scroll_offset as usize,
-                max_line_number: len_lines as usize,
-            },
-            visible_lines_updates
-                .clone()
-                .into_iter()
-                .map(|cell_update| CellUpdate {
-                    position: cell_update.position.move_up(scroll_offset as usize),
-                    ..cell_update
-                })
-                .collect_vec(),
-            Vec::new(),
-            theme,
-        );
+        let visible_lines_grid = if folded_lines.is_empty() {
+            visible_lines_grid.render_content(
+                &visible_lines.iter().map(|(_, line)| line).join(""),
+                RenderContentLineNumber::LineNumber {
+                    start_line_index: scroll_offset as usize,
+                    max_line_number: len_lines as usize,
+                },
+                visible_lines_updates
+                    .clone()
+                    .into_iter()
+                    .map(|cell_update| CellUpdate {
+                        position: cell_update.position.move_up(scroll_offset as usize),
+                        ..cell_update
+                    })
+                    .collect_vec(),
+                Vec::new(),
+                theme,
+            )
+        } else {
+            // The lines around folds are not consecutive,
+            // so each run of consecutive lines is rendered with its own line numbers
+            let runs =
+                visible_lines
+                    .iter()
+                    .fold(Vec::<Vec<&(usize, String)>>::new(), |mut runs, line| {
+                        match runs.last_mut() {
+                            Some(run) if run.last().map(|(index, _)| index + 1) == Some(line.0) => {
+                                run.push(line)
+                            }
+                            _ => runs.push(vec![line]),
+                        }
+                        runs
+                    });
+            let grid =
+                runs.into_iter()
+                    .fold(Grid::new(Dimension { height: 0, width }), |grid, run| {
+                        let start_line_index =
+                            run.first().map(|(index, _)| *index).unwrap_or_default();
+                        let end_line_index = start_line_index + run.len();
+                        let updates = visible_lines_updates
+                            .iter()
+                            .filter(|update| {
+                                (start_line_index..end_line_index).contains(&update.position.line)
+                            })
+                            .map(|update| {
+                                update
+                                    .clone()
+                                    .set_position_line(update.position.line - start_line_index)
+                            })
+                            .collect_vec();
+                        grid.merge_vertical(
+                            Grid::new(Dimension { height: 0, width }).render_content(
+                                &run.iter().map(|(_, line)| line).join(""),
+                                RenderContentLineNumber::LineNumber {
+                                    start_line_index,
+                                    max_line_number: len_lines as usize,
+                                },
+                                updates,
+                                Vec::new(),
+                                theme,
+                            ),
+                        )
+                    });
+            let padding = height.saturating_sub(grid.dimension().height);
+            grid.merge_vertical(Grid::new(Dimension {
+                height: padding,
+                width,
+            }))
+        };
 
         let hidden_parent_lines_grid = {
             let boundaries = hidden_parent_line_ranges
@@ -409,17 +536,49 @@ impl Editor {
 
         let grid = title_grid.merge_vertical(grid);
         let cursor_position = grid.get_cursor_position();
-        let style = match self.mode {
+        GetGridResult {
+            cursor: cursor_position.map(|position| Cursor::new(position, self.cursor_style())),
+            grid,
+        }
+    }
+
+    /// The cursor shape that the frontend should display for the current mode.
+    pub(crate) fn cursor_style(&self) -> SetCursorStyle {
+        match self.mode {
             Mode::Normal => SetCursorStyle::BlinkingBlock,
             Mode::Insert => SetCursorStyle::BlinkingBar,
+            // Including `Mode::Overtype`, which overwrites the character under the cursor
             _ => SetCursorStyle::BlinkingUnderScore,
-        };
-        GetGridResult {
-            cursor: cursor_position.map(|position| Cursor::new(position, style)),
-            grid,
         }
     }
 
+    /// Returns the matches of `Context::highlighted_search` within `line_range`.
+    pub(crate) fn search_matches(
+        &self,
+        context: &Context,
+        line_range: Range<usize>,
+    ) -> anyhow::Result<Vec<CharIndexRange>> {
+        let Some(search) = context.highlighted_search() else {
+            return Ok(Vec::new());
+        };
+        let buffer = self.buffer();
+        let selection = self.selection_set.primary_selection();
+        let object = crate::selection::SelectionMode::Find { search }
+            .to_selection_mode_trait_object(&buffer, selection, &self.cursor_direction)?;
+        object
+            .selections_in_line_number_range(
+                &selection_mode::SelectionModeParams {
+                    buffer: &buffer,
+                    current_selection: selection,
+                    cursor_direction: &self.cursor_direction,
+                },
+                [line_range].to_vec(),
+            )?
+            .into_iter()
+            .map(|byte_range| buffer.byte_range_to_char_index_range(byte_range.range()))
+            .try_collect()
+    }
+
     pub(crate) fn possible_selections_in_line_number_range(
         &self,
         selection: &Selection,
@@ -26,6 +26,8 @@ use super::{
 pub(crate) struct SuggestiveEditor {
     editor: Editor,
     completion_dropdown: Dropdown,
+    /// The latest completion of each LSP server, which are merged into the dropdown
+    completions: Vec<Completion>,
 
     trigger_characters: Vec<String>,
     filter: SuggestiveEditorFilter,
@@ -42,9 +44,7 @@ impl From<CompletionItem> for DropdownItem {
         DropdownItem::new(format!("{} {}", item.emoji(), item.label()))
             .set_info(item.info())
             .set_dispatches(item.dispatches())
-            .set_on_focused(Dispatches::one(Dispatch::ResolveCompletionItem(
-                item.completion_item(),
-            )))
+            .set_on_focused(Dispatches::one(Dispatch::ResolveCompletionItem(item)))
     }
 }
 
@@ -62,9 +62,11 @@ impl Component for SuggestiveEditor {
         context: &mut Context,
         dispatch: DispatchEditor,
     ) -> anyhow::Result<Dispatches> {
+        let was_insert_mode = self.editor.mode == Mode::Insert;
         let dispatches = self
             .editor_mut()
             .handle_dispatch_editor(context, dispatch)?;
+        self.clear_completions_if_insert_mode_exited(was_insert_mode);
         let update_filter_result = self.update_filter();
         Ok(dispatches.chain(update_filter_result?))
     }
@@ -87,7 +89,7 @@ impl Component for SuggestiveEditor {
                 key!("ctrl+space") => {
                     let current_item = self.completion_dropdown.current_item();
                     if let Some(completion) = current_item {
-                        self.completion_dropdown.set_items(Vec::new());
+                        self.clear_completions();
                         return Ok(
                             Dispatches::one(Dispatch::CloseDropdown).chain(completion.dispatches)
                         );
@@ -100,7 +102,9 @@ impl Component for SuggestiveEditor {
 
         // Every other character typed in Insert mode should update the dropdown to show
         // relevant completions.
+        let was_insert_mode = self.editor.mode == Mode::Insert;
         let dispatches = self.editor.handle_key_event(context, event.clone())?;
+        self.clear_completions_if_insert_mode_exited(was_insert_mode);
 
         let render_dropdown_dispatch = self.update_filter()?;
         Ok(render_dropdown_dispatch
@@ -142,6 +146,7 @@ impl Component for SuggestiveEditor {
                             .collect_vec(),
                     }
                 }),
+                Keymap::new("f", "Format".to_string(), Dispatch::Format),
                 Keymap::new("h", "Hover".to_string(), Dispatch::RequestHover),
                 Keymap::new("r", "Rename".to_string(), Dispatch::PrepareRename),
             ]),
@@ -157,6 +162,7 @@ impl SuggestiveEditor {
             completion_dropdown: Dropdown::new(DropdownConfig {
                 title: "Completion".to_string(),
             }),
+            completions: Vec::new(),
             trigger_characters: vec![],
             filter,
         }
@@ -194,9 +200,37 @@ impl SuggestiveEditor {
         !self.completion_dropdown.items().is_empty()
     }
 
+    fn clear_completions(&mut self) {
+        self.completions.clear();
+        self.completion_dropdown.set_items(Vec::new());
+    }
+
+    /// The completions are only relevant to the current insertion,
+    /// so they should not reappear when entering insert mode again.
+    fn clear_completions_if_insert_mode_exited(&mut self, was_insert_mode: bool) {
+        if was_insert_mode && self.editor.mode != Mode::Insert {
+            self.clear_completions()
+        }
+    }
+
+    /// Replaces the previous completion of the same LSP server,
+    /// the completions of other servers are kept.
     pub(crate) fn set_completion(&mut self, completion: Completion) {
-        self.completion_dropdown.set_items(completion.items);
-        self.trigger_characters = completion.trigger_characters;
+        self.completions
+            .retain(|existing| existing.server_name != completion.server_name);
+        self.completions.push(completion);
+        self.completion_dropdown.set_items(
+            self.completions
+                .iter()
+                .flat_map(|completion| completion.items.clone())
+                .collect(),
+        );
+        self.trigger_characters = self
+            .completions
+            .iter()
+            .flat_map(|completion| completion.trigger_characters.clone())
+            .unique()
+            .collect();
     }
 
     pub(crate) fn render_completion_dropdown(&self, ignore_insert_mode: bool) -> Dispatches {
@@ -305,6 +339,7 @@ mod test_suggestive_editor {
             .into_iter()
             .map(|item| item.into())
             .collect(),
+            server_name: None,
         }
     }
 
@@ -388,6 +423,26 @@ mod test_suggestive_editor {
         })
     }
 
+    #[test]
+    fn completions_are_cleared_when_insert_mode_exits() -> Result<(), anyhow::Error> {
+        execute_test(|s| {
+            Box::new([
+                App(OpenFile(s.main_rs())),
+                Editor(SetContent("".to_string())),
+                Editor(EnterInsertMode(Direction::Start)),
+                SuggestiveEditor(CompletionFilter(SuggestiveEditorFilter::CurrentWord)),
+                SuggestiveEditor(Completion(dummy_completion())),
+                Expect(CompletionDropdownIsOpen(true)),
+                App(HandleKeyEvent(key!("esc"))),
+                Expect(CompletionDropdownIsOpen(false)),
+                // The stale completions should not reappear in the next insertion
+                Editor(EnterInsertMode(Direction::Start)),
+                App(HandleKeyEvents(keys!("p a").to_vec())),
+                Expect(CompletionDropdownIsOpen(false)),
+            ])
+        })
+    }
+
     #[test]
     /// Should not replace non-alphanumeric word
     fn completion_without_edit_2() -> Result<(), anyhow::Error> {
@@ -424,6 +479,7 @@ mod test_suggestive_editor {
                         .into_iter()
                         .map(|item| item.into())
                         .collect(),
+                    server_name: None,
                 })),
                 // Type in 'aBigCat'
                 Editor(Insert("aBigCat".to_string())),
@@ -451,6 +507,7 @@ mod test_suggestive_editor {
                         .into_iter()
                         .map(|item| item.into())
                         .collect(),
+                    server_name: None,
                 })),
                 Editor(EnterInsertMode(Direction::End)),
                 // Type in 'aBigCat',,
@@ -479,6 +536,7 @@ mod test_suggestive_editor {
                     .into_iter()
                     .map(|item| item.into())
                     .collect(),
+                    server_name: None,
                 })),
                 Editor(EnterInsertMode(Direction::End)),
                 Editor(Insert(" aBig".to_string())),
@@ -529,6 +587,7 @@ mod test_suggestive_editor {
                         .into_iter()
                         .map(|item: CompletionItem| item.into())
                         .collect(),
+                        server_name: None,
                     })),
                     Editor(EnterInsertMode(Direction::End)),
                     Editor(Insert(" aBig".to_string())),
@@ -570,6 +629,7 @@ mod test_suggestive_editor {
                     .into_iter()
                     .map(|item: CompletionItem| item.into())
                     .collect(),
+                    server_name: None,
                 })),
                 Editor(Insert(" a".to_string())),
                 App(HandleKeyEvent(key!("ctrl+n"))),
@@ -598,6 +658,7 @@ mod test_suggestive_editor {
             kind: None,
             detail: None,
             completion_item: Default::default(),
+            server_name: None,
         };
         execute_test(|s| {
             Box::new([
@@ -615,6 +676,7 @@ mod test_suggestive_editor {
                     .into_iter()
                     .map(|item| item.into())
                     .collect(),
+                    server_name: None,
                 })),
                 // Expect the "Completion Info" panel is shown, because "Spongebob" has doc
                 Expect(AppGridContains("Completion Info")),
@@ -651,10 +713,12 @@ mod test_suggestive_editor {
                         detail: None,
                         insert_text: None,
                         completion_item: Default::default(),
+                        server_name: None,
                     }]
                     .into_iter()
                     .map(|item| item.into())
                     .collect(),
+                    server_name: None,
                 })),
                 App(HandleKeyEvent(key!("ctrl+space"))),
                 Expect(CurrentComponentContent("Spongebob")),
@@ -755,6 +819,7 @@ mod test_suggestive_editor {
             .into_iter()
             .map(|item| item.into())
             .collect(),
+            server_name: None,
         };
         execute_test(|s| {
             Box::new([
@@ -830,11 +895,13 @@ mod test_suggestive_editor {
                         kind: Some(CompletionItemKind::FUNCTION),
                         detail: None,
                         completion_item: Default::default(),
+                        server_name: None,
                     }]
                     .into_iter()
                     .map(|item| item.into())
                     .collect(),
                     trigger_characters: Vec::new(),
+                    server_name: None,
                 })),
                 App(HandleKeyEvent(key!("s"))),
                 Expect(CompletionDropdownContent("💥 Spongebob")),
@@ -17,7 +17,7 @@ use crate::style::Style;
 use crate::test_app::*;
 
 use crate::{
-    components::editor::{Direction, Mode, ViewAlignment},
+    components::editor::{Direction, Mode, Motion, Operator, ViewAlignment},
     grid::StyleKey,
     position::Position,
     selection::SelectionMode,
@@ -119,6 +119,97 @@ fn delete_should_kill_if_possible_3() -> anyhow::Result<()> {
     })
 }
 
+#[test]
+fn select_next_node_of_kind() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("fn main() { let x = y; }".to_string())),
+            Editor(SelectNextNodeOfKind("identifier".to_string())),
+            Expect(CurrentSelectedTexts(&["main"])),
+            Editor(SelectNextNodeOfKind("identifier".to_string())),
+            Expect(CurrentSelectedTexts(&["x"])),
+            Editor(SelectNextNodeOfKind("identifier".to_string())),
+            Expect(CurrentSelectedTexts(&["y"])),
+            // Wraps around at the end of the file
+            Editor(SelectNextNodeOfKind("identifier".to_string())),
+            Expect(CurrentSelectedTexts(&["main"])),
+            Editor(SelectNextNodeOfKind("let_declaration".to_string())),
+            Expect(CurrentSelectedTexts(&["let x = y;"])),
+        ])
+    })
+}
+
+#[test]
+fn repeat_last_edit() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("fn main(a:A,b:B,c:C) {}".to_string())),
+            Editor(MatchLiteral("a:A".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, SyntaxNode)),
+            Editor(DeleteCut {
+                direction: Direction::End,
+                use_system_clipboard: false,
+            }),
+            Expect(CurrentComponentContent("fn main(b:B,c:C) {}")),
+            // Movements do not affect the last edit
+            Editor(MoveSelection(Next)),
+            Expect(CurrentSelectedTexts(&["c:C"])),
+            Editor(RepeatLastEdit),
+            Expect(CurrentComponentContent("fn main(b:B) {}")),
+            Expect(LocalClipboardContent("c:C")),
+        ])
+    })
+}
+
+#[test]
+fn repeat_last_edit_of_typed_insertion() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo spam bar baz".to_string())),
+            Editor(MatchLiteral("foo".to_string())),
+            Editor(Change),
+            App(HandleKeyEvents(keys!("x y").to_vec())),
+            Editor(EnterNormalMode),
+            Expect(CurrentComponentContent("xy spam bar baz")),
+            Editor(MatchLiteral("spam".to_string())),
+            Editor(RepeatLastEdit),
+            Expect(CurrentComponentContent("xy xy bar baz")),
+            Expect(CurrentMode(Mode::Normal)),
+            // Entering insert mode without changing the selection is repeated as well
+            Editor(MatchLiteral("bar".to_string())),
+            Editor(EnterInsertMode(Direction::End)),
+            App(HandleKeyEvents(keys!("z").to_vec())),
+            Editor(EnterNormalMode),
+            Expect(CurrentComponentContent("xy xy barz baz")),
+            Editor(MatchLiteral("baz".to_string())),
+            Editor(RepeatLastEdit),
+            Expect(CurrentComponentContent("xy xy barz bazz")),
+        ])
+    })
+}
+
+#[test]
+fn repeat_last_edit_of_insertion_with_backspace_and_enter() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo spam".to_string())),
+            Editor(MatchLiteral("foo".to_string())),
+            Editor(Change),
+            App(HandleKeyEvents(keys!("a b backspace c enter d").to_vec())),
+            Editor(EnterNormalMode),
+            Expect(CurrentComponentContent("ac\nd spam")),
+            Editor(MatchLiteral("spam".to_string())),
+            Editor(RepeatLastEdit),
+            Expect(CurrentComponentContent("ac\nd ac\nd")),
+            Expect(CurrentMode(Mode::Normal)),
+        ])
+    })
+}
+
 #[test]
 /// The selection mode is contiguous
 fn delete_should_kill_if_possible_4() -> anyhow::Result<()> {
@@ -182,6 +273,26 @@ fn delete_should_not_kill_if_not_possible_2() -> anyhow::Result<()> {
     })
 }
 
+#[test]
+fn word_and_sub_word_without_syntax_tree() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.gitignore())),
+            Editor(SetContent("let myVar = 1".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, SubWord)),
+            Expect(CurrentSelectedTexts(&["let"])),
+            Editor(MoveSelection(Next)),
+            Expect(CurrentSelectedTexts(&["my"])),
+            Editor(MoveSelection(Next)),
+            Expect(CurrentSelectedTexts(&["Var"])),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Word)),
+            Expect(CurrentSelectedTexts(&["myVar"])),
+            Editor(MoveSelection(Next)),
+            Expect(CurrentSelectedTexts(&["="])),
+        ])
+    })
+}
+
 #[test]
 fn toggle_untoggle_mark() -> anyhow::Result<()> {
     execute_test(|s| {
@@ -206,6 +317,296 @@ fn toggle_untoggle_mark() -> anyhow::Result<()> {
     })
 }
 
+#[test]
+fn add_cursor_to_next_match() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("y x y x x".to_string())),
+            Editor(MatchLiteral("x".to_string())),
+            Editor(AddCursorToNextMatch),
+            Editor(AddCursorToNextMatch),
+            Expect(CurrentSelectedTexts(&["x", "x", "x"])),
+            // Every occurrence is already selected
+            Editor(AddCursorToNextMatch),
+            Expect(CurrentSelectedTexts(&["x", "x", "x"])),
+        ])
+    })
+}
+
+#[test]
+fn skip_current_match_wraps_around() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("y x y x y".to_string())),
+            Editor(MatchLiteral("y".to_string())),
+            Editor(SkipCurrentMatch),
+            Editor(SkipCurrentMatch),
+            Expect(CurrentSelectedTexts(&["y"])),
+            Expect(EditorCursorPosition(Position { line: 0, column: 8 })),
+            Editor(AddCursorToNextMatch),
+            Expect(CurrentSelectedTexts(&["y", "y"])),
+            Expect(EditorCursorPosition(Position { line: 0, column: 0 })),
+        ])
+    })
+}
+
+#[test]
+fn add_selection() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo bar spam eggs".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Word)),
+            Editor(MoveSelection(Next)),
+            Editor(AddSelection(Next)),
+            Editor(AddSelection(Next)),
+            Expect(CurrentMode(Mode::Normal)),
+            Expect(CurrentSelectedTexts(&["bar", "spam", "eggs"])),
+            // The selected texts are in document order, regardless of the adding order
+            Editor(CursorKeepPrimaryOnly),
+            Editor(AddSelection(Previous)),
+            Expect(CurrentSelectedTexts(&["spam", "eggs"])),
+            // Edits are applied to all selections
+            Editor(EnterInsertMode(Direction::Start)),
+            Editor(Insert("x".to_string())),
+            Expect(CurrentComponentContent("foo bar xspam xeggs")),
+        ])
+    })
+}
+
+#[test]
+fn same_indent_selection_mode() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent(
+                "- a\n  - a1\n\n  - a2\n- b\n  - b1\n- c".to_string(),
+            )),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, SameIndent)),
+            Expect(CurrentSelectedTexts(&["- a"])),
+            // Deeper indented lines are skipped
+            Editor(MoveSelection(Next)),
+            Expect(CurrentSelectedTexts(&["- b"])),
+            Editor(MoveSelection(Next)),
+            Expect(CurrentSelectedTexts(&["- c"])),
+            Editor(MoveSelection(Previous)),
+            Expect(CurrentSelectedTexts(&["- b"])),
+            // Blank lines are skipped
+            Editor(MatchLiteral("- a1".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, SameIndent)),
+            Expect(CurrentSelectedTexts(&["- a1"])),
+            Editor(MoveSelection(Next)),
+            Expect(CurrentSelectedTexts(&["- a2"])),
+            // Moving stops at the parent, instead of jumping to the children of the next parent
+            Editor(MoveSelection(Next)),
+            Expect(CurrentSelectedTexts(&["- a2"])),
+        ])
+    })
+}
+
+#[test]
+fn indent_block_selection_mode() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent(
+                "def a():\n    x = 1\n\n\ty = 2\ndef b():\n    z = 3".to_string(),
+            )),
+            Editor(MatchLiteral("x = 1".to_string())),
+            Editor(SetSelectionMode(
+                IfCurrentNotFound::LookForward,
+                IndentBlock,
+            )),
+            // Blank lines do not terminate the block, and a tab is as deep as 4 spaces
+            Expect(CurrentSelectedTexts(&["x = 1\n\n\ty = 2"])),
+            Editor(MoveSelection(Next)),
+            Expect(CurrentSelectedTexts(&["z = 3"])),
+            Editor(MoveSelection(Previous)),
+            Expect(CurrentSelectedTexts(&["x = 1\n\n\ty = 2"])),
+        ])
+    })
+}
+
+#[test]
+fn increment_number() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("let x = 1".to_string())),
+            Editor(MatchLiteral("1".to_string())),
+            Editor(IncrementNumber(5)),
+            Expect(CurrentComponentContent("let x = 6")),
+            Expect(CurrentSelectedTexts(&["6"])),
+            Editor(DecrementNumber(7)),
+            Expect(CurrentComponentContent("let x = -1")),
+            Editor(SetContent("[0x00, 0x00, 0x00]".to_string())),
+            Editor(MatchLiteral("0x00".to_string())),
+            Editor(CursorAddToAllSelections),
+            Editor(IncrementNumberSequence(1)),
+            Expect(CurrentComponentContent("[0x00, 0x01, 0x02]")),
+        ])
+    })
+}
+
+#[test]
+fn column_selection() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo = 1\n\nbar = 2".to_string())),
+            Editor(MatchLiteral("oo".to_string())),
+            Editor(ToggleColumnSelection),
+            Expect(CurrentSelectedTexts(&["oo"])),
+            // Lines shorter than the start column are skipped
+            Editor(MoveSelection(Down)),
+            Expect(CurrentSelectedTexts(&["oo"])),
+            Editor(MoveSelection(Down)),
+            Expect(CurrentSelectedTexts(&["oo", "ar"])),
+            // Each line of the rectangle is copied and pasted separately
+            Editor(Copy {
+                use_system_clipboard: false,
+            }),
+            Editor(Paste {
+                direction: Direction::End,
+                use_system_clipboard: false,
+            }),
+            Expect(CurrentComponentContent("foooo = 1\n\nbarar = 2")),
+            Editor(ToggleColumnSelection),
+            Editor(SetContent("foo = 1\n\nbar = 2".to_string())),
+            Editor(MatchLiteral("oo".to_string())),
+            Editor(ToggleColumnSelection),
+            Editor(MoveSelection(Down)),
+            Editor(MoveSelection(Down)),
+            Editor(EnterInsertMode(Direction::Start)),
+            Editor(Insert("|".to_string())),
+            Expect(CurrentComponentContent("f|oo = 1\n\nb|ar = 2")),
+        ])
+    })
+}
+
+#[test]
+fn column_selection_is_aligned_by_display_columns() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            // A tab is 4 columns wide, and a CJK character is 2 columns wide
+            Editor(SetContent("\ta = 1\n    b = 2\n你c = 3".to_string())),
+            Editor(MatchLiteral("a".to_string())),
+            Editor(ToggleColumnSelection),
+            Editor(MoveSelection(Down)),
+            Expect(CurrentSelectedTexts(&["a", "b"])),
+            Editor(SetContent("你x\nabx".to_string())),
+            Editor(MatchLiteral("x".to_string())),
+            Editor(ToggleColumnSelection),
+            Editor(MoveSelection(Down)),
+            Expect(CurrentSelectedTexts(&["x", "x"])),
+        ])
+    })
+}
+
+#[test]
+fn column_selection_ends_on_other_dispatches() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo\nbar\nspam".to_string())),
+            Editor(MatchLiteral("o".to_string())),
+            Editor(ToggleColumnSelection),
+            Editor(MoveSelection(Down)),
+            Expect(CurrentSelectedTexts(&["o", "a"])),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Line)),
+            Expect(CurrentSelectedTexts(&["foo", "bar"])),
+            // Moving down moves the selections instead of extending the rectangle
+            Editor(MoveSelection(Down)),
+            Expect(CurrentSelectedTexts(&["bar", "spam"])),
+        ])
+    })
+}
+
+#[test]
+fn overlapping_selections_are_merged() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo bar\nspam".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Word)),
+            Editor(CursorAddToAllSelections),
+            Expect(CurrentSelectedTexts(&["foo", "bar", "spam"])),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Line)),
+            Expect(CurrentSelectedTexts(&["foo bar", "spam"])),
+            Editor(EnterInsertMode(Direction::Start)),
+            Editor(Insert("x".to_string())),
+            Expect(CurrentComponentContent("xfoo bar\nxspam")),
+        ])
+    })
+}
+
+#[test]
+fn overlapping_selections_are_kept_when_merging_is_disabled() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(ToggleMergeOverlappingSelections),
+            Editor(SetContent("foo bar\nspam".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Word)),
+            Editor(CursorAddToAllSelections),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Line)),
+            Expect(CurrentSelectedTexts(&["foo bar", "foo bar", "spam"])),
+        ])
+    })
+}
+
+#[test]
+fn cursor_style_follows_mode() -> anyhow::Result<()> {
+    use crate::components::component::SetCursorStyle;
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Expect(CurrentCursorStyle(SetCursorStyle::BlinkingBlock)),
+            Editor(EnterInsertMode(Direction::Start)),
+            Expect(CurrentCursorStyle(SetCursorStyle::BlinkingBar)),
+            Editor(EnterNormalMode),
+            Editor(EnterReplaceMode),
+            Expect(CurrentCursorStyle(SetCursorStyle::BlinkingUnderScore)),
+        ])
+    })
+}
+
+#[test]
+fn overtype_mode() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("abcd".to_string())),
+            Editor(EnterOvertypeMode),
+            Expect(CurrentMode(Mode::Overtype)),
+            App(HandleKeyEvents(keys!("X Y").to_vec())),
+            Expect(CurrentComponentContent("XYcd")),
+        ])
+    })
+}
+
+#[test]
+fn overtype_mode_backspace_restores_overwritten_chars() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("ab\ncd".to_string())),
+            Editor(EnterOvertypeMode),
+            // The last character is appended instead, because the line ends there
+            App(HandleKeyEvents(keys!("X Y Z").to_vec())),
+            Expect(CurrentComponentContent("XYZ\ncd")),
+            App(HandleKeyEvents(keys!("backspace backspace").to_vec())),
+            Expect(CurrentComponentContent("Xb\ncd")),
+            App(HandleKeyEvents(keys!("backspace backspace esc").to_vec())),
+            Expect(CurrentComponentContent("ab\ncd")),
+            Expect(CurrentMode(Mode::Normal)),
+        ])
+    })
+}
+
 #[test]
 fn test_delete_word_short_backward_from_end_of_file() -> anyhow::Result<()> {
     execute_test(|s| {
@@ -420,6 +821,47 @@ fn kill_line_to_start() -> anyhow::Result<()> {
     })
 }
 
+#[test]
+fn kill_ring_yank_pop() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("one two three end".to_string())),
+            Editor(MatchLiteral("one ".to_string())),
+            Editor(Kill),
+            Editor(MatchLiteral("two ".to_string())),
+            Editor(Kill),
+            Editor(MatchLiteral("three ".to_string())),
+            Editor(Kill),
+            Expect(CurrentComponentContent("end")),
+            Editor(Yank),
+            Expect(CurrentComponentContent("three end")),
+            Editor(YankPop),
+            Expect(CurrentComponentContent("two end")),
+            Editor(YankPop),
+            Expect(CurrentComponentContent("one end")),
+            Editor(YankPop),
+            Expect(CurrentComponentContent("three end")),
+            Expect(EditorCursorPosition(Position::new(0, 6))),
+        ])
+    })
+}
+
+#[test]
+fn consecutive_kills_are_accumulated() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo\nbar".to_string())),
+            Editor(KillLine(Direction::End)),
+            Editor(KillLine(Direction::End)),
+            Expect(CurrentComponentContent("bar")),
+            Editor(Yank),
+            Expect(CurrentComponentContent("foo\nbar")),
+        ])
+    })
+}
+
 #[test]
 #[ignore = "Undo tree should be removed soon, I don't use it at all."]
 fn undo_tree() -> anyhow::Result<()> {
@@ -568,6 +1010,30 @@ fn exchange_sibling_2() -> anyhow::Result<()> {
     })
 }
 
+#[test]
+fn move_node_down_multi_line() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent(
+                "fn main() {\n    if x {\n        a();\n    }\n    let y = 2;\n}".to_string(),
+            )),
+            Editor(MatchLiteral("if x".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, SyntaxNode)),
+            Expect(CurrentSelectedTexts(&["if x {\n        a();\n    }"])),
+            Editor(MoveNodeDown),
+            Expect(CurrentComponentContent(
+                "fn main() {\n    let y = 2;\n    if x {\n        a();\n    }\n}",
+            )),
+            Expect(CurrentSelectedTexts(&["if x {\n        a();\n    }"])),
+            Editor(MoveNodeUp),
+            Expect(CurrentComponentContent(
+                "fn main() {\n    if x {\n        a();\n    }\n    let y = 2;\n}",
+            )),
+        ])
+    })
+}
+
 #[test]
 fn select_character() -> anyhow::Result<()> {
     execute_test(|s| {
@@ -958,13 +1424,177 @@ fn smart_paste() -> anyhow::Result<()> {
 }
 
 #[test]
-fn paste_before() -> anyhow::Result<()> {
+fn paste_as_multi_cursor() -> anyhow::Result<()> {
     execute_test(|s| {
         Box::new([
             App(OpenFile(s.main_rs())),
             Editor(SetContent("foo bar spam".to_string())),
             App(SetClipboardContent {
-                copied_texts: CopiedTexts::one("haha".to_string()),
+                copied_texts: CopiedTexts::one("1\n2\n3".to_string()),
+                use_system_clipboard: false,
+            }),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Word)),
+            Editor(CursorAddToAllSelections),
+            Expect(CurrentSelectedTexts(&["foo", "bar", "spam"])),
+            Editor(PasteAsMultiCursor {
+                use_system_clipboard: false,
+            }),
+            // Each selection is replaced by its line
+            Expect(CurrentComponentContent("1 2 3")),
+            Expect(CurrentSelectedTexts(&["1", "2", "3"])),
+            // Fallback to the usual paste if the counts mismatch
+            Editor(CursorKeepPrimaryOnly),
+            Expect(CurrentSelectedTexts(&["1"])),
+            Editor(PasteAsMultiCursor {
+                use_system_clipboard: false,
+            }),
+            Expect(CurrentComponentContent("1 1\n2\n3 2 3")),
+            Expect(EditorInfoOpen(true)),
+        ])
+    })
+}
+
+#[test]
+fn paste_before_and_after_at_start_and_end_of_file() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo bar".to_string())),
+            App(SetClipboardContent {
+                copied_texts: CopiedTexts::one("x".to_string()),
+                use_system_clipboard: false,
+            }),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Word)),
+            Expect(CurrentSelectedTexts(&["foo"])),
+            Editor(PasteBefore {
+                use_system_clipboard: false,
+            }),
+            Expect(CurrentComponentContent("x foo bar")),
+            Expect(CurrentSelectedTexts(&["x"])),
+            Editor(MatchLiteral("bar".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Word)),
+            Editor(PasteAfter {
+                use_system_clipboard: false,
+            }),
+            Expect(CurrentComponentContent("x foo bar x")),
+            Expect(CurrentSelectedTexts(&["x"])),
+        ])
+    })
+}
+
+#[test]
+fn paste_after_duplicates_syntax_node() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("fn main(a:A, b:B) {}".to_string())),
+            Editor(MatchLiteral("a:A".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, SyntaxNode)),
+            Editor(Copy {
+                use_system_clipboard: false,
+            }),
+            Editor(PasteAfter {
+                use_system_clipboard: false,
+            }),
+            // The selected node is kept, instead of the next node being replaced
+            Expect(CurrentComponentContent("fn main(a:A, a:A, b:B) {}")),
+            Expect(CurrentSelectedTexts(&["a:A"])),
+            // Multi-line content is pasted as is
+            App(SetClipboardContent {
+                copied_texts: CopiedTexts::one("c:C,\nd:D".to_string()),
+                use_system_clipboard: false,
+            }),
+            Editor(PasteBefore {
+                use_system_clipboard: false,
+            }),
+            Expect(CurrentComponentContent(
+                "fn main(a:A, c:C,\nd:D, a:A, b:B) {}",
+            )),
+            Expect(CurrentSelectedTexts(&["c:C,\nd:D"])),
+        ])
+    })
+}
+
+#[test]
+fn named_registers() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("fn foo(a: A) -> B {}".to_string())),
+            App(SetClipboardContent {
+                copied_texts: CopiedTexts::one("unnamed".to_string()),
+                use_system_clipboard: false,
+            }),
+            Editor(MatchLiteral("a: A".to_string())),
+            Editor(CopyToRegister('a')),
+            Editor(MatchLiteral(" -> B".to_string())),
+            Editor(CutToRegister('b')),
+            Expect(FileContent(s.main_rs(), "fn foo(a: A) {}".to_string())),
+            Editor(MatchLiteral("{}".to_string())),
+            Editor(PasteFromRegister('a')),
+            Expect(FileContent(s.main_rs(), "fn foo(a: A) {}a: A".to_string())),
+            Editor(PasteFromRegister('b')),
+            Expect(FileContent(
+                s.main_rs(),
+                "fn foo(a: A) {}a: A -> B".to_string(),
+            )),
+            // An empty register pastes nothing
+            Editor(PasteFromRegister('c')),
+            Expect(FileContent(
+                s.main_rs(),
+                "fn foo(a: A) {}a: A -> B".to_string(),
+            )),
+            // The unnamed register is not affected by the named registers
+            Editor(Paste {
+                direction: Direction::End,
+                use_system_clipboard: false,
+            }),
+            Expect(FileContent(
+                s.main_rs(),
+                "fn foo(a: A) {}a: A -> Bunnamed".to_string(),
+            )),
+        ])
+    })
+}
+
+#[test]
+fn deletions_are_stored_in_delete_registers() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("first\nsecond\nthird\nfourth".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Line)),
+            Editor(Delete(Direction::End)),
+            Editor(Delete(Direction::End)),
+            Editor(Delete(Direction::End)),
+            Expect(CurrentComponentContent("fourth")),
+            Expect(RegisterContent('1', "third")),
+            Expect(RegisterContent('2', "second")),
+            Expect(RegisterContent('3', "first")),
+            // Deletions within a line go to the small delete register instead
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Word)),
+            Editor(Delete(Direction::End)),
+            Expect(RegisterContent('-', "fourth")),
+            Expect(RegisterContent('1', "third")),
+            Editor(PasteFromRegister('2')),
+            Expect(CurrentComponentContent("second")),
+            // Change-cut deletes as well
+            Editor(ChangeCut {
+                use_system_clipboard: false,
+            }),
+            Expect(RegisterContent('-', "second")),
+        ])
+    })
+}
+
+#[test]
+fn paste_before() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo bar spam".to_string())),
+            App(SetClipboardContent {
+                copied_texts: CopiedTexts::one("haha".to_string()),
                 use_system_clipboard: false,
             }),
             Editor(MatchLiteral("bar".to_string())),
@@ -1019,6 +1649,69 @@ fn enter_newline() -> anyhow::Result<()> {
     })
 }
 
+#[test]
+fn enter_continues_line_comment() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("".to_string())),
+            Editor(EnterInsertMode(Direction::Start)),
+            Editor(Insert("  // hello".to_string())),
+            App(HandleKeyEvent(key!("enter"))),
+            Expect(CurrentComponentContent("  // hello\n  // ")),
+            Editor(Insert("world".to_string())),
+            App(HandleKeyEvent(key!("enter"))),
+            Expect(CurrentComponentContent("  // hello\n  // world\n  // ")),
+            // Stop continuing the comment on an empty continuation, without leaving it dangling
+            App(HandleKeyEvent(key!("enter"))),
+            Expect(CurrentComponentContent("  // hello\n  // world\n  ")),
+            Editor(Insert("x".to_string())),
+            Expect(CurrentComponentContent("  // hello\n  // world\n  x")),
+            // Doc comments are continued as themselves
+            Editor(SetContent("".to_string())),
+            Editor(Insert("  /// doc".to_string())),
+            App(HandleKeyEvent(key!("enter"))),
+            Expect(CurrentComponentContent("  /// doc\n  /// ")),
+            App(HandleKeyEvent(key!("enter"))),
+            Expect(CurrentComponentContent("  /// doc\n  ")),
+            Editor(SetContent("".to_string())),
+            Editor(Insert("//! inner".to_string())),
+            App(HandleKeyEvent(key!("enter"))),
+            Expect(CurrentComponentContent("//! inner\n//! ")),
+            // Trailing comments are continued at the indentation of the line
+            Editor(SetContent("".to_string())),
+            Editor(Insert("  let x = 1; // note".to_string())),
+            App(HandleKeyEvent(key!("enter"))),
+            Expect(CurrentComponentContent("  let x = 1; // note\n  // ")),
+            // The comment prefix inside a string is not a comment
+            Editor(SetContent("".to_string())),
+            Editor(Insert("let x = \"a // b\";".to_string())),
+            App(HandleKeyEvent(key!("enter"))),
+            Expect(CurrentComponentContent("let x = \"a // b\";\n")),
+        ])
+    })
+}
+
+#[test]
+fn enter_does_not_continue_shebang() -> anyhow::Result<()> {
+    execute_test(|s| {
+        let path = s.temp_dir().join("script.py");
+        std::fs::write(path.to_path_buf(), "").unwrap();
+        Box::new([
+            App(OpenFile(path.clone())),
+            Editor(EnterInsertMode(Direction::Start)),
+            Editor(Insert("#!/usr/bin/env python".to_string())),
+            App(HandleKeyEvent(key!("enter"))),
+            Expect(CurrentComponentContent("#!/usr/bin/env python\n")),
+            Editor(Insert("# hello".to_string())),
+            App(HandleKeyEvent(key!("enter"))),
+            Expect(CurrentComponentContent(
+                "#!/usr/bin/env python\n# hello\n# ",
+            )),
+        ])
+    })
+}
+
 #[test]
 fn insert_mode_start() -> anyhow::Result<()> {
     execute_test(|s| {
@@ -1336,6 +2029,63 @@ fn switch_view_alignment() -> anyhow::Result<()> {
     })
 }
 
+#[test]
+fn highlight_cursor_line_and_selection_lines() -> anyhow::Result<()> {
+    let cursor_line_background = hex!("#badbad");
+    let selection_lines_background = hex!("#cebceb");
+    let theme = {
+        let mut theme = Theme::default();
+        theme.ui.cursor_line_background = cursor_line_background;
+        theme.ui.selection_lines_background = selection_lines_background;
+        theme
+    };
+    // The first row is the title, and the first two columns are the line number and its separator
+    let expect_line_background = |row_index: usize, color: crate::themes::Color| {
+        ExpectMulti(
+            [2, 3, 4, 5, 9, 10, 11, 12, 13]
+                .into_iter()
+                .map(|column_index| GridCellBackground(row_index, column_index, color))
+                .collect(),
+        )
+    };
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("fn main() {\n  let x = 1;\n}".to_string())),
+            Editor(SetRectangle(Rectangle {
+                origin: Position::default(),
+                width: 20,
+                height: 5,
+            })),
+            App(SetTheme(theme.clone())),
+            Editor(MatchLiteral("x".to_string())),
+            Expect(Not(Box::new(GridCellBackground(
+                2,
+                2,
+                cursor_line_background,
+            )))),
+            App(ToggleHighlightSelectionLines),
+            expect_line_background(2, selection_lines_background),
+            App(ToggleHighlightCursorLine),
+            expect_line_background(2, cursor_line_background),
+            // Other lines are not highlighted
+            Expect(Not(Box::new(GridCellBackground(
+                1,
+                2,
+                cursor_line_background,
+            )))),
+            Expect(Not(Box::new(GridCellBackground(
+                1,
+                2,
+                selection_lines_background,
+            )))),
+            // The content is not affected
+            Expect(CurrentComponentContent("fn main() {\n  let x = 1;\n}")),
+            Expect(CurrentSelectedTexts(&["x"])),
+        ])
+    })
+}
+
 #[test]
 fn get_grid_parent_line() -> anyhow::Result<()> {
     let parent_lines_background = hex!("#badbad");
@@ -1510,8 +2260,9 @@ fn diagnostics_range_updated_by_edit() -> anyhow::Result<()> {
         Box::new([
             App(OpenFile(s.main_rs())),
             Editor(SetContent("fn main() { let x = 123 }".trim().to_string())),
-            App(HandleLspNotification(LspNotification::PublishDiagnostics(
-                lsp_types::PublishDiagnosticsParams {
+            App(HandleLspNotification(LspNotification::PublishDiagnostics {
+                server_name: "rust-analyzer".to_string(),
+                params: lsp_types::PublishDiagnosticsParams {
                     uri: s.main_rs().to_url().unwrap(),
                     diagnostics: [lsp_types::Diagnostic {
                         range: lsp_types::Range::new(
@@ -1529,7 +2280,7 @@ fn diagnostics_range_updated_by_edit() -> anyhow::Result<()> {
                     .to_vec(),
                     version: None,
                 },
-            ))),
+            })),
             Expect(ExpectKind::DiagnosticsRanges(
                 [CharIndexRange::from(CharIndex(3)..CharIndex(7))].to_vec(),
             )),
@@ -2000,7 +2751,8 @@ fn main() {
     // The SyntaxNode selection mode is contiguous, thus it should select current after save
     test(SyntaxNode, &["let foo = 1;"])?;
 
-    // The Find selection mode is not contigouos, thus it should not select current after save
+    // The Find selection mode is not contigouos, thus it should not select current after save,
+    // but the selection should still follow the formatted text
     test(
         SelectionMode::Find {
             search: crate::context::Search {
@@ -2012,115 +2764,616 @@ fn main() {
                 }),
             },
         },
-        &["et foo = 1;\n"],
+        &["let foo = 1;"],
     )
 }
 
 #[test]
-fn undo_till_empty_should_not_crash_in_insert_mode() -> anyhow::Result<()> {
+fn selection_stays_on_the_same_token_after_format() -> anyhow::Result<()> {
     execute_test(|s| {
         Box::new([
             App(OpenFile(s.main_rs())),
-            Editor(SetContent("".to_string())),
-            App(SetClipboardContent {
-                copied_texts: CopiedTexts::one("foo".to_string()),
-                use_system_clipboard: false,
-            }),
+            Editor(SetContent(
+                "fn main() {\n     let   foo = 1;\n}\n".to_string(),
+            )),
+            Editor(SetLanguage(shared::language::from_extension("rs").unwrap())),
+            Editor(MatchLiteral("foo".to_string())),
+            Editor(Save),
+            Expect(CurrentComponentContent(
+                "fn main() {\n    let foo = 1;\n}\n",
+            )),
+            Expect(CurrentSelectedTexts(&["foo"])),
+        ])
+    })
+}
+
+#[test]
+fn undo_limit_drops_oldest_entry() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("x".to_string())),
+            App(SetUndoLimit(2)),
             Editor(EnterInsertMode(Direction::Start)),
-            Editor(Paste {
-                direction: Direction::End,
-                use_system_clipboard: false,
-            }),
-            Expect(CurrentComponentContent("foo")),
+            Editor(Insert("a".to_string())),
+            Editor(EnterNormalMode),
+            Editor(EnterInsertMode(Direction::Start)),
+            Editor(Insert("b".to_string())),
+            Editor(EnterNormalMode),
+            Editor(EnterInsertMode(Direction::Start)),
+            Editor(Insert("c".to_string())),
+            Editor(EnterNormalMode),
+            Expect(CurrentComponentContent("cbax")),
             Editor(Undo),
-            Expect(CurrentComponentContent("")),
+            Editor(Undo),
+            Expect(CurrentComponentContent("ax")),
+            // The entry of inserting "a" is dropped
+            Editor(Undo),
+            Expect(CurrentComponentContent("ax")),
+            // Raising the limit keeps the existing entries
+            App(SetUndoLimit(3)),
+            Editor(Redo),
+            Editor(Redo),
+            Editor(EnterInsertMode(Direction::Start)),
+            Editor(Insert("d".to_string())),
+            Editor(EnterNormalMode),
+            Expect(CurrentComponentContent("dcbax")),
+            // Lowering the limit after some edits keeps only the newest entries
+            App(SetUndoLimit(1)),
+            Expect(CurrentComponentContent("dcbax")),
+            Editor(Undo),
+            Expect(CurrentComponentContent("cbax")),
+            // The entries of inserting "b" and "c" are dropped
+            Editor(Undo),
+            Expect(CurrentComponentContent("cbax")),
         ])
     })
 }
 
 #[test]
-fn selection_set_history() -> Result<(), anyhow::Error> {
+fn split_selection_on_regex() -> anyhow::Result<()> {
     execute_test(|s| {
         Box::new([
             App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo,bar,,baz\nspam".to_string())),
             Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Line)),
-            Expect(CurrentSelectedTexts(&["mod foo;"])),
-            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Column)),
-            Expect(CurrentSelectedTexts(&["m"])),
-            App(ToEditor(GoBack)),
-            Expect(CurrentSelectedTexts(&["mod foo;"])),
-            App(ToEditor(GoForward)),
-            Expect(CurrentSelectedTexts(&["m"])),
+            Editor(SplitSelectionOnRegex(",+".to_string())),
+            Expect(CurrentSelectedTexts(&["foo", "bar", "baz"])),
         ])
     })
 }
 
 #[test]
-fn select_surround_inside_with_multiwidth_character() -> Result<(), anyhow::Error> {
+fn export_selections_json() -> anyhow::Result<()> {
     execute_test(|s| {
         Box::new([
             App(OpenFile(s.main_rs())),
-            Editor(SetContent("(hello (w🦀orld))".to_string())),
-            Editor(MatchLiteral("rl".to_string())),
-            Editor(SelectSurround {
-                enclosure: crate::surround::EnclosureKind::Parentheses,
-                kind: SurroundKind::Inside,
-            }),
-            Expect(CurrentSelectedTexts(&["w🦀orld"])),
-            Expect(CurrentSelectionMode(SelectionMode::Custom)),
+            Editor(SetContent("foo bar spam".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Word)),
+            Editor(CursorAddToAllSelections),
+            Editor(ExportSelectionsJson),
+            Expect(LocalClipboardContent(
+                r#"[{"start":0,"end":3,"text":"foo"},{"start":4,"end":7,"text":"bar"},{"start":8,"end":12,"text":"spam"}]"#,
+            )),
         ])
     })
 }
 
 #[test]
-fn select_surround_inside() -> Result<(), anyhow::Error> {
+fn insert_at_each_selection_with_counter() -> anyhow::Result<()> {
     execute_test(|s| {
         Box::new([
             App(OpenFile(s.main_rs())),
-            Editor(SetContent("(hello (world))".to_string())),
-            Editor(MatchLiteral("rl".to_string())),
-            App(HandleKeyEvents(keys!("v i (").to_vec())),
-            Expect(CurrentSelectedTexts(&["world"])),
-            Expect(CurrentSelectionMode(SelectionMode::Custom)),
+            Editor(SetContent("a\nb\nc".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Line)),
+            Editor(CursorAddToAllSelections),
+            Editor(EnterInsertMode(Direction::Start)),
+            Editor(InsertAtEachSelection("item{i} ".to_string())),
+            Expect(CurrentComponentContent("item0 a\nitem1 b\nitem2 c")),
+            Editor(InsertAtEachSelection("{i:10:-5},".to_string())),
+            Expect(CurrentComponentContent("item0 10,a\nitem1 5,b\nitem2 0,c")),
         ])
     })
 }
 
 #[test]
-fn select_surround_around() -> Result<(), anyhow::Error> {
+fn wrap_around_movements() -> anyhow::Result<()> {
     execute_test(|s| {
         Box::new([
             App(OpenFile(s.main_rs())),
-            Editor(SetContent("(hello (world))".to_string())),
-            Editor(MatchLiteral("rl".to_string())),
-            App(HandleKeyEvents(keys!("v a (").to_vec())),
-            Expect(CurrentSelectedTexts(&["(world)"])),
-            Expect(CurrentSelectionMode(SelectionMode::Custom)),
+            Editor(SetContent("fn f() {}\nfn g() {}".to_string())),
+            Editor(MatchLiteral("fn f() {}".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, SyntaxNode)),
+            Editor(MoveSelection(Next)),
+            Expect(CurrentSelectedTexts(&["fn g() {}"])),
+            // Stops at the last node by default
+            Editor(MoveSelection(Next)),
+            Expect(CurrentSelectedTexts(&["fn g() {}"])),
+            App(ToggleWrapAroundMovements),
+            Editor(MoveSelection(Next)),
+            Expect(CurrentSelectedTexts(&["fn f() {}"])),
+            Editor(MoveSelection(Previous)),
+            Expect(CurrentSelectedTexts(&["fn g() {}"])),
         ])
     })
 }
 
 #[test]
-fn select_surround_inside_same_symbols() -> Result<(), anyhow::Error> {
+fn confirm_delete() -> anyhow::Result<()> {
     execute_test(|s| {
         Box::new([
             App(OpenFile(s.main_rs())),
-            Editor(SetContent("hello 'world'".to_string())),
-            Editor(MatchLiteral("rl".to_string())),
-            Editor(DeleteSurround(crate::surround::EnclosureKind::SingleQuotes)),
-            Expect(CurrentSelectedTexts(&["world"])),
-            Expect(CurrentSelectionMode(SelectionMode::Custom)),
+            Editor(SetContent("fn main() {}".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Token)),
+            App(ToggleConfirmDelete),
+            Editor(Delete(Direction::End)),
+            // The deletion is only previewed
+            Expect(CurrentComponentContent("fn main() {}")),
+            Expect(PendingDeleteRanges(Some(
+                [(CharIndex(0)..CharIndex(2)).into()].to_vec(),
+            ))),
+            // Confirm the deletion
+            Editor(Delete(Direction::End)),
+            Expect(CurrentComponentContent("main() {}")),
+            Expect(PendingDeleteRanges(None)),
+            // Any other action cancels the pending deletion
+            Editor(Delete(Direction::End)),
+            Editor(MoveSelection(Next)),
+            Expect(PendingDeleteRanges(None)),
+            Expect(CurrentComponentContent("main() {}")),
         ])
     })
 }
 
 #[test]
-fn delete_surround() -> Result<(), anyhow::Error> {
+fn max_line_length() -> anyhow::Result<()> {
     execute_test(|s| {
         Box::new([
             App(OpenFile(s.main_rs())),
-            Editor(SetContent("(hello (world))".to_string())),
-            Editor(MatchLiteral("rl".to_string())),
+            Editor(SetContent("short\nthis line is too long\nok".to_string())),
+            Expect(LongLineOverflows(Vec::new())),
+            App(SetMaxLineLength(Some(10))),
+            Expect(LongLineOverflows(
+                [(CharIndex(16)..CharIndex(27)).into()].to_vec(),
+            )),
+            Editor(GotoNextLongLine),
+            Expect(CurrentSelectedTexts(&["is too long"])),
+            // There is no other long line
+            Editor(GotoNextLongLine),
+            Expect(CurrentSelectedTexts(&["is too long"])),
+        ])
+    })
+}
+
+#[test]
+fn moving_into_fold_unfolds_it() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent(
+                "fn main() {\n    // region setup\n    let a = 1;\n    // endregion\n    let c = 3;\n}"
+                    .to_string(),
+            )),
+            Editor(MatchLiteral("let a".to_string())),
+            Editor(ToggleMarkerFold),
+            Expect(FoldedTexts(&["    // region setup\n    let a = 1;\n    // endregion"])),
+            // The cursor is moved out of the hidden lines
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Line)),
+            Expect(CurrentSelectedTexts(&["// region setup"])),
+            Editor(MoveSelection(Next)),
+            Expect(FoldedTexts(&[])),
+            Expect(CurrentSelectedTexts(&["let a = 1;"])),
+            Expect(AppGridContains("let a = 1")),
+        ])
+    })
+}
+
+#[test]
+fn toggle_marker_fold() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent(
+                "
+fn main() {
+    // region setup
+    let a = 1;
+    // region inner
+    let b = 2;
+    // endregion
+    // endregion
+    let c = 3;
+}"
+                .trim()
+                .to_string(),
+            )),
+            Editor(MatchLiteral("let a".to_string())),
+            Editor(ToggleMarkerFold),
+            // The fold covers the innermost region enclosing the cursor, from its start marker
+            // to its end marker
+            Expect(FoldedTexts(&[
+                "    // region setup\n    let a = 1;\n    // region inner\n    let b = 2;\n    // endregion\n    // endregion",
+            ])),
+            Expect(Not(Box::new(AppGridContains("let a = 1")))),
+            Expect(AppGridContains("region setup")),
+            Expect(AppGridContains("let c = 3")),
+            Editor(ToggleMarkerFold),
+            Expect(FoldedTexts(&[])),
+            Expect(AppGridContains("let a = 1")),
+            Editor(MatchLiteral("let b".to_string())),
+            Editor(ToggleMarkerFold),
+            Expect(FoldedTexts(&["    // region inner\n    let b = 2;\n    // endregion"])),
+            Editor(ToggleMarkerFold),
+            //
+            // The markers are configurable
+            App(SetFoldMarkers {
+                start: "fold".to_string(),
+                end: "endfold".to_string(),
+            }),
+            Editor(ToggleMarkerFold),
+            Expect(FoldedTexts(&[])),
+            // Markers without alphanumeric characters are compared literally
+            App(SetFoldMarkers {
+                start: "{{{".to_string(),
+                end: "}}}".to_string(),
+            }),
+            Editor(SetContent(
+                "fn main() {\n    // {{{\n    let a = 1;\n    // }}}\n}".to_string(),
+            )),
+            Editor(MatchLiteral("let a".to_string())),
+            Editor(ToggleMarkerFold),
+            Expect(FoldedTexts(&["    // {{{\n    let a = 1;\n    // }}}"])),
+        ])
+    })
+}
+
+#[test]
+fn goto_blank_line() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("a\nb\n\nc\n\n  \n\nd\ne".to_string())),
+            Editor(GotoNextBlankLine),
+            Expect(EditorCursorPosition(Position::new(2, 0))),
+            // Consecutive blank lines are a single stop
+            Editor(GotoNextBlankLine),
+            Expect(EditorCursorPosition(Position::new(4, 0))),
+            // There is no blank line after
+            Editor(GotoNextBlankLine),
+            Expect(EditorCursorPosition(Position::new(4, 0))),
+            Editor(GotoPreviousBlankLine),
+            Expect(EditorCursorPosition(Position::new(2, 0))),
+            // There is no blank line before
+            Editor(GotoPreviousBlankLine),
+            Expect(EditorCursorPosition(Position::new(2, 0))),
+        ])
+    })
+}
+
+#[test]
+fn goto_matching_conditional() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent(
+                "#if DEBUG\nint x = 1;\n#else\nint x = 2;\n#endif\n".to_string(),
+            )),
+            Editor(SetLanguage(shared::language::from_extension("c").unwrap())),
+            Editor(GotoMatchingConditional),
+            Expect(CurrentSelectedTexts(&["#else"])),
+            Editor(GotoMatchingConditional),
+            Expect(CurrentSelectedTexts(&["#endif"])),
+            // Cycle back to the opening directive
+            Editor(GotoMatchingConditional),
+            Expect(CurrentSelectedTexts(&["#if"])),
+        ])
+    })
+}
+
+#[test]
+fn select_whole_function() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent(
+                "use std::fs;\n\nfn main() {\n    let x = 1;\n}\n".to_string(),
+            )),
+            Editor(SetLanguage(shared::language::from_extension("rs").unwrap())),
+            // Not inside a function
+            Editor(MatchLiteral("std".to_string())),
+            Editor(SelectWholeFunction),
+            Expect(CurrentSelectedTexts(&["std"])),
+            Expect(EditorInfoContent("The cursor is not inside a function")),
+            Editor(MatchLiteral("x".to_string())),
+            Editor(SelectWholeFunction),
+            Expect(CurrentSelectedTexts(&["fn main() {\n    let x = 1;\n}"])),
+        ])
+    })
+}
+
+#[test]
+fn goto_non_blank() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("   foo;   \nbar".to_string())),
+            Editor(GotoFirstNonBlank),
+            Expect(CurrentSelectedTexts(&["f"])),
+            Editor(GotoLineEndNonBlank),
+            Expect(CurrentSelectedTexts(&[";"])),
+        ])
+    })
+}
+
+#[test]
+fn operator_motion() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo bar".to_string())),
+            Editor(OperatorMotion {
+                operator: Operator::Cut {
+                    use_system_clipboard: false,
+                },
+                motion: Motion::WordForward,
+            }),
+            Expect(CurrentComponentContent("bar")),
+            Expect(LocalClipboardContent("foo ")),
+        ])
+    })
+}
+
+#[test]
+fn confirm_delete_cut_to_register() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("fn main() {}".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Token)),
+            App(ToggleConfirmDelete),
+            Editor(CutToRegister('a')),
+            Expect(CurrentComponentContent("fn main() {}")),
+            Editor(CutToRegister('a')),
+            Expect(CurrentComponentContent("main() {}")),
+            Expect(PendingDeleteRanges(None)),
+        ])
+    })
+}
+
+#[test]
+fn confirm_delete_operator_motion() -> anyhow::Result<()> {
+    let delete_word = || {
+        Editor(OperatorMotion {
+            operator: Operator::Delete,
+            motion: Motion::WordForward,
+        })
+    };
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo bar".to_string())),
+            App(ToggleConfirmDelete),
+            delete_word(),
+            Expect(CurrentComponentContent("foo bar")),
+            Expect(PendingDeleteRanges(Some(
+                [(CharIndex(0)..CharIndex(4)).into()].to_vec(),
+            ))),
+            delete_word(),
+            Expect(CurrentComponentContent("bar")),
+            Expect(PendingDeleteRanges(None)),
+        ])
+    })
+}
+
+#[test]
+fn cycle_selection_mode() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("fn main() {}".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Column)),
+            Editor(CycleSelectionMode(Direction::End)),
+            Editor(CycleSelectionMode(Direction::End)),
+            Expect(CurrentSelectionMode(Line)),
+            Editor(CycleSelectionMode(Direction::Start)),
+            Expect(CurrentSelectionMode(Word)),
+            // The cycle is configurable
+            App(SetSelectionModeCycle([Column, Line].to_vec())),
+            Editor(CycleSelectionMode(Direction::End)),
+            Expect(CurrentSelectionMode(Column)),
+            Editor(CycleSelectionMode(Direction::Start)),
+            Expect(CurrentSelectionMode(Line)),
+        ])
+    })
+}
+
+#[test]
+fn buffer_vars() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Expect(BufferVar("kind", None)),
+            Editor(SetBufferVar("kind".to_string(), "test".to_string())),
+            Expect(BufferVar("kind", Some("test"))),
+            Editor(SetBufferVar("kind".to_string(), "bench".to_string())),
+            Expect(BufferVar("kind", Some("bench"))),
+            // Buffer variables are not shared with other buffers
+            App(OpenFile(s.foo_rs())),
+            Expect(BufferVar("kind", None)),
+            Editor(ShowBufferVars),
+            Expect(EditorInfoContent("[No buffer variables]")),
+        ])
+    })
+}
+
+#[test]
+fn insert_mode_session_is_undone_as_one_entry() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo bar".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Word)),
+            Editor(Cut {
+                use_system_clipboard: false,
+            }),
+            Expect(CurrentComponentContent(" bar")),
+            Editor(EnterInsertMode(Direction::Start)),
+            Editor(Insert("x".to_string())),
+            Editor(Insert("y".to_string())),
+            Editor(Insert("z".to_string())),
+            Editor(EnterNormalMode),
+            Expect(CurrentComponentContent("xyz bar")),
+            Editor(Undo),
+            Expect(CurrentComponentContent(" bar")),
+            // Undoing the cut restores the cut text and its selection
+            Editor(Undo),
+            Expect(CurrentComponentContent("foo bar")),
+            Expect(CurrentSelectedTexts(&["foo"])),
+            Editor(Redo),
+            Expect(CurrentComponentContent(" bar")),
+            // Redo is not possible after a new edit
+            Editor(EnterInsertMode(Direction::Start)),
+            Editor(Insert("new".to_string())),
+            Editor(EnterNormalMode),
+            Editor(Redo),
+            Expect(CurrentComponentContent("new bar")),
+        ])
+    })
+}
+
+#[test]
+fn undo_till_empty_should_not_crash_in_insert_mode() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("".to_string())),
+            App(SetClipboardContent {
+                copied_texts: CopiedTexts::one("foo".to_string()),
+                use_system_clipboard: false,
+            }),
+            Editor(EnterInsertMode(Direction::Start)),
+            Editor(Paste {
+                direction: Direction::End,
+                use_system_clipboard: false,
+            }),
+            Expect(CurrentComponentContent("foo")),
+            Editor(Undo),
+            Expect(CurrentComponentContent("")),
+        ])
+    })
+}
+
+#[test]
+fn selection_set_history() -> Result<(), anyhow::Error> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Line)),
+            Expect(CurrentSelectedTexts(&["mod foo;"])),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Column)),
+            Expect(CurrentSelectedTexts(&["m"])),
+            App(ToEditor(GoBack)),
+            Expect(CurrentSelectedTexts(&["mod foo;"])),
+            App(ToEditor(GoForward)),
+            Expect(CurrentSelectedTexts(&["m"])),
+        ])
+    })
+}
+
+#[test]
+fn select_surround_inside_with_multiwidth_character() -> Result<(), anyhow::Error> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("(hello (w🦀orld))".to_string())),
+            Editor(MatchLiteral("rl".to_string())),
+            Editor(SelectSurround {
+                enclosure: crate::surround::EnclosureKind::Parentheses,
+                kind: SurroundKind::Inside,
+            }),
+            Expect(CurrentSelectedTexts(&["w🦀orld"])),
+            Expect(CurrentSelectionMode(SelectionMode::Custom)),
+        ])
+    })
+}
+
+#[test]
+fn surround_and_matching_bracket_without_syntax_tree() -> Result<(), anyhow::Error> {
+    execute_test(|s| {
+        Box::new([
+            // .gitignore has no tree-sitter grammar, so the pairs are found by scanning the text
+            App(OpenFile(s.gitignore())),
+            Editor(SetContent("{ a { b } c }".to_string())),
+            Editor(MatchLiteral("b".to_string())),
+            Editor(SelectSurround {
+                enclosure: crate::surround::EnclosureKind::CurlyBraces,
+                kind: SurroundKind::Inside,
+            }),
+            Expect(CurrentSelectedTexts(&[" b "])),
+            Editor(MatchLiteral("a".to_string())),
+            Editor(SelectSurround {
+                enclosure: crate::surround::EnclosureKind::CurlyBraces,
+                kind: SurroundKind::Inside,
+            }),
+            Expect(CurrentSelectedTexts(&[" a { b } c "])),
+            Editor(MatchLiteral("{ b".to_string())),
+            Editor(GotoMatchingBracket),
+            Expect(EditorCursorPosition(Position::new(0, 8))),
+            Editor(GotoMatchingBracket),
+            Expect(EditorCursorPosition(Position::new(0, 4))),
+        ])
+    })
+}
+
+#[test]
+fn select_surround_inside() -> Result<(), anyhow::Error> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("(hello (world))".to_string())),
+            Editor(MatchLiteral("rl".to_string())),
+            App(HandleKeyEvents(keys!("v i (").to_vec())),
+            Expect(CurrentSelectedTexts(&["world"])),
+            Expect(CurrentSelectionMode(SelectionMode::Custom)),
+        ])
+    })
+}
+
+#[test]
+fn select_surround_around() -> Result<(), anyhow::Error> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("(hello (world))".to_string())),
+            Editor(MatchLiteral("rl".to_string())),
+            App(HandleKeyEvents(keys!("v a (").to_vec())),
+            Expect(CurrentSelectedTexts(&["(world)"])),
+            Expect(CurrentSelectionMode(SelectionMode::Custom)),
+        ])
+    })
+}
+
+#[test]
+fn select_surround_inside_same_symbols() -> Result<(), anyhow::Error> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("hello 'world'".to_string())),
+            Editor(MatchLiteral("rl".to_string())),
+            Editor(DeleteSurround(crate::surround::EnclosureKind::SingleQuotes)),
+            Expect(CurrentSelectedTexts(&["world"])),
+            Expect(CurrentSelectionMode(SelectionMode::Custom)),
+        ])
+    })
+}
+
+#[test]
+fn delete_surround() -> Result<(), anyhow::Error> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("(hello (world))".to_string())),
+            Editor(MatchLiteral("rl".to_string())),
             App(HandleKeyEvents(keys!("v d (").to_vec())),
             Expect(CurrentSelectedTexts(&["world"])),
             Expect(CurrentSelectionMode(SelectionMode::Custom)),
@@ -2129,6 +3382,54 @@ fn delete_surround() -> Result<(), anyhow::Error> {
     })
 }
 
+#[test]
+fn surround_with_pair() -> Result<(), anyhow::Error> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("fn f(){ }".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Token)),
+            Editor(MoveSelection(Next)),
+            Expect(CurrentSelectedTexts(&["f"])),
+            Editor(SurroundWith('(')),
+            Expect(CurrentComponentContent("fn (f)(){ }")),
+            Expect(CurrentSelectedTexts(&["(f)"])),
+            // The close symbol surrounds with the same pair
+            Editor(SurroundWith(']')),
+            Expect(CurrentComponentContent("fn [(f)](){ }")),
+            Expect(CurrentSelectedTexts(&["[(f)]"])),
+            // The pair spanned by the selection itself is the innermost pair
+            Editor(DeleteSurround(
+                crate::surround::EnclosureKind::SquareBrackets,
+            )),
+            Expect(CurrentComponentContent("fn (f)(){ }")),
+            Expect(CurrentSelectedTexts(&["(f)"])),
+            Editor(ChangeSurround {
+                from: crate::surround::EnclosureKind::Parentheses,
+                to: crate::surround::EnclosureKind::DoubleQuotes,
+            }),
+            Expect(CurrentComponentContent("fn \"f\"(){ }")),
+            // Characters without a pair surround on both sides
+            Editor(SurroundWith('*')),
+            Expect(CurrentComponentContent("fn *\"f\"*(){ }")),
+        ])
+    })
+}
+
+#[test]
+fn delete_surround_ignores_symbols_in_strings() -> Result<(), anyhow::Error> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("fn f() { g(\")\", x) }".to_string())),
+            Editor(MatchLiteral("x".to_string())),
+            Editor(DeleteSurround(crate::surround::EnclosureKind::Parentheses)),
+            Expect(CurrentComponentContent("fn f() { g\")\", x }")),
+            Expect(CurrentSelectedTexts(&["\")\", x"])),
+        ])
+    })
+}
+
 #[test]
 fn change_surround() -> Result<(), anyhow::Error> {
     execute_test(|s| {
@@ -2523,6 +3824,240 @@ fn main() {
     })
 }
 
+#[test]
+fn indent_width_follows_modeline() -> anyhow::Result<()> {
+    execute_test(|s| {
+        let path = s.new_path("modeline.py");
+        std::fs::write(&path, "# ki: tab_width=2 wrap\nx = 1\n").unwrap();
+        let path: shared::canonicalized_path::CanonicalizedPath = path.try_into().unwrap();
+        Box::new([
+            App(OpenFile(path)),
+            Editor(MatchLiteral("x".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Line)),
+            Editor(Indent),
+            Expect(CurrentComponentContent("# ki: tab_width=2 wrap\n  x = 1\n")),
+        ])
+    })
+}
+
+#[test]
+fn sort_lines() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("b\na\nc".to_string())),
+            Editor(SelectAll),
+            Editor(SortLines {
+                ascending: true,
+                case_sensitive: true,
+            }),
+            Expect(CurrentComponentContent("a\nb\nc")),
+            Expect(CurrentSelectedTexts(&["a\nb\nc"])),
+            Editor(SortLines {
+                ascending: false,
+                case_sensitive: true,
+            }),
+            Expect(CurrentComponentContent("c\nb\na")),
+        ])
+    })
+}
+
+#[test]
+fn sort_lines_keeps_trailing_newline_at_the_end() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("B\na\nb\nA\n".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Line)),
+            Editor(EnableSelectionExtension),
+            Editor(MoveSelection(Last)),
+            Editor(SortLines {
+                ascending: true,
+                case_sensitive: false,
+            }),
+            // Equal lines keep their original order
+            Expect(CurrentComponentContent("a\nA\nB\nb\n")),
+        ])
+    })
+}
+
+#[test]
+fn dedup_lines() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("a\nb\na\nc\nb".to_string())),
+            Editor(SelectAll),
+            Editor(DedupLines),
+            Expect(CurrentComponentContent("a\nb\nc")),
+        ])
+    })
+}
+
+#[test]
+fn strip_common_indent() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent(
+                "        if x {\n            y();\n\n        }\n".to_string(),
+            )),
+            Editor(SelectAll),
+            Editor(StripCommonIndent),
+            Expect(CurrentComponentContent("if x {\n    y();\n\n}\n")),
+            // Nothing is removed if a line is not indented
+            Editor(StripCommonIndent),
+            Expect(CurrentComponentContent("if x {\n    y();\n\n}\n")),
+        ])
+    })
+}
+
+#[test]
+fn align_selections() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("a = 1\nbb = 2\nccc\nd = 3".to_string())),
+            Editor(SelectAll),
+            Editor(AlignSelections('=')),
+            // Lines without the delimiter are left untouched
+            Expect(CurrentComponentContent("a  = 1\nbb = 2\nccc\nd  = 3")),
+            // Tabs and wide characters are measured by their display width
+            Editor(SetContent("\ta = 1\n🦀 = 2\nbbbbbb = 3".to_string())),
+            Editor(SelectAll),
+            Editor(AlignSelections('=')),
+            Expect(CurrentComponentContent("\ta  = 1\n🦀     = 2\nbbbbbb = 3")),
+        ])
+    })
+}
+
+#[test]
+fn replace_all_matching() -> anyhow::Result<()> {
+    let replace = |pattern: &str, replacement: &str, regex: bool| {
+        Editor(ReplaceAllMatching {
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+            regex,
+        })
+    };
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent(
+                "fn f(){ let x = S(a); let y = S(b); }".to_string(),
+            )),
+            Editor(MatchLiteral("y".to_string())),
+            replace(r"S\((\w)\)", "T($1)", true),
+            Expect(CurrentComponentContent(
+                "fn f(){ let x = T(a); let y = T(b); }",
+            )),
+            // The selection is kept
+            Expect(CurrentSelectedTexts(&["y"])),
+            // Literal patterns and replacements are not interpreted
+            replace("T(", "$1(", false),
+            Expect(CurrentComponentContent(
+                "fn f(){ let x = $1(a); let y = $1(b); }",
+            )),
+            // Only the matches within the selection are replaced if it is extended
+            Editor(MatchLiteral("let y = $1(b)".to_string())),
+            Editor(EnableSelectionExtension),
+            replace(r"\$1", "S", true),
+            Expect(CurrentComponentContent(
+                "fn f(){ let x = $1(a); let y = S(b); }",
+            )),
+        ])
+    })
+}
+
+#[test]
+fn apply_transform() -> anyhow::Result<()> {
+    use crate::transformation::TransformKind;
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("hello world".to_string())),
+            Editor(MatchLiteral("hello".to_string())),
+            Editor(ApplyTransform(TransformKind::Base64Encode)),
+            Expect(CurrentComponentContent("aGVsbG8= world")),
+            Expect(CurrentSelectedTexts(&["aGVsbG8="])),
+            Editor(ApplyTransform(TransformKind::Base64Decode)),
+            Expect(CurrentComponentContent("hello world")),
+            Expect(CurrentSelectedTexts(&["hello"])),
+            Editor(ApplyTransform(TransformKind::Reverse)),
+            Expect(CurrentComponentContent("olleh world")),
+            // Invalid input is left unchanged
+            Editor(MatchLiteral("world".to_string())),
+            Editor(ApplyTransform(TransformKind::Base64Decode)),
+            Expect(CurrentComponentContent("olleh world")),
+            Expect(CurrentSelectedTexts(&["world"])),
+        ])
+    })
+}
+
+#[test]
+fn format_json() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent(r#"x = {"a":[1,2],"b":{"c":null}};"#.to_string())),
+            Editor(MatchLiteral(r#"{"a":[1,2],"b":{"c":null}}"#.to_string())),
+            Editor(FormatJson { indent: Some(2) }),
+            Expect(CurrentComponentContent(
+                r#"x = {
+  "a": [
+    1,
+    2
+  ],
+  "b": {
+    "c": null
+  }
+};"#,
+            )),
+        ])
+    })
+}
+
+#[test]
+fn goto_next_misspelling_in_comment() -> anyhow::Result<()> {
+    execute_test(|s| {
+        let word_list = s.new_path(crate::spell_check::CUSTOM_WORD_LIST_PATH);
+        std::fs::create_dir_all(word_list.parent().unwrap()).unwrap();
+        std::fs::write(&word_list, "this\nhas\n").unwrap();
+        let word_list: shared::canonicalized_path::CanonicalizedPath =
+            word_list.try_into().unwrap();
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent(
+                "// This commnt has a tpyo\nfn mian() {}".to_string(),
+            )),
+            Editor(GotoNextMisspelling),
+            Expect(CurrentSelectedTexts(&["commnt"])),
+            Editor(GotoNextMisspelling),
+            Expect(CurrentSelectedTexts(&["tpyo"])),
+            // Identifiers outside of comments and strings are not checked
+            Editor(GotoNextMisspelling),
+            Expect(CurrentSelectedTexts(&["tpyo"])),
+            // The misspellings are looked up again after the content is changed
+            Editor(SetContent("// This has a mistkae".to_string())),
+            Editor(GotoNextMisspelling),
+            Expect(CurrentSelectedTexts(&["mistkae"])),
+            // The custom word list is only read again after the file is saved or reloaded
+            Editor(SetContent("// This commnt has a mistkae".to_string())),
+            App(OpenFile(word_list.clone())),
+            Editor(SetContent("this\nhas\ncommnt\n".to_string())),
+            Editor(Save),
+            App(OpenFile(s.main_rs())),
+            Editor(MatchLiteral("This".to_string())),
+            Editor(GotoNextMisspelling),
+            Expect(CurrentSelectedTexts(&["commnt"])),
+            Editor(Save),
+            Editor(MatchLiteral("This".to_string())),
+            Editor(GotoNextMisspelling),
+            Expect(CurrentSelectedTexts(&["mistkae"])),
+        ])
+    })
+}
+
 #[test]
 fn test_dedent_in_column_mode() -> anyhow::Result<()> {
     execute_test(|s| {
@@ -2623,3 +4158,86 @@ fn cycle_primary_selection_should_based_on_range_order() -> anyhow::Result<()> {
         ])
     })
 }
+
+#[test]
+fn select_semantic_range() -> anyhow::Result<()> {
+    use crate::lsp::completion::{CompletionItemEdit, PositionalEdit};
+    execute_test(|s| {
+        let range = |start: usize, end: usize| Position::new(0, start)..Position::new(0, end);
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("fn main() { let x = z.b(); }".to_string())),
+            Editor(MatchLiteral("z".to_string())),
+            // Mock the response of the LSP server
+            App(HandleLspNotification(LspNotification::SelectionRanges(
+                [range(20, 21), range(20, 25), range(12, 26), range(0, 28)].to_vec(),
+            ))),
+            Expect(CurrentSelectedTexts(&["z.b()"])),
+            Editor(SelectSemanticRange(Direction::End)),
+            Expect(CurrentSelectedTexts(&["let x = z.b();"])),
+            Editor(SelectSemanticRange(Direction::Start)),
+            Expect(CurrentSelectedTexts(&["z.b()"])),
+            Editor(SelectSemanticRange(Direction::Start)),
+            Expect(CurrentSelectedTexts(&["z"])),
+            // The ranges are discarded once the buffer is edited,
+            // so the parent syntax node is selected since no LSP server is running in tests
+            Editor(ApplyPositionalEdits(
+                [CompletionItemEdit::PositionalEdit(PositionalEdit {
+                    range: Position::new(0, 24)..Position::new(0, 24),
+                    new_text: "1".to_string(),
+                })]
+                .to_vec(),
+            )),
+            Expect(CurrentSelectedTexts(&["z"])),
+            Editor(SelectSemanticRange(Direction::End)),
+            Expect(CurrentSelectedTexts(&["z.b"])),
+        ])
+    })
+}
+
+#[test]
+fn select_semantic_range_falls_back_to_syntax_tree() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("fn main() { let x = z.b(); }".to_string())),
+            Editor(MatchLiteral("z".to_string())),
+            // No LSP server is running in tests
+            Editor(SelectSemanticRange(Direction::End)),
+            Expect(CurrentSelectedTexts(&["z.b"])),
+        ])
+    })
+}
+
+#[test]
+fn apply_edits_shifts_selections() -> anyhow::Result<()> {
+    use crate::lsp::completion::{CompletionItemEdit, PositionalEdit};
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("fn main() { foo(); }".to_string())),
+            Editor(MatchLiteral("foo".to_string())),
+            Editor(ToggleMark),
+            Editor(MatchLiteral("main".to_string())),
+            Editor(ApplyPositionalEdits(
+                [
+                    // The ranges of both edits are relative to the content before the edits
+                    CompletionItemEdit::PositionalEdit(PositionalEdit {
+                        range: Position::new(0, 0)..Position::new(0, 0),
+                        new_text: "pub ".to_string(),
+                    }),
+                    CompletionItemEdit::PositionalEdit(PositionalEdit {
+                        range: Position::new(0, 8)..Position::new(0, 8),
+                        new_text: "x: usize".to_string(),
+                    }),
+                ]
+                .to_vec(),
+            )),
+            Expect(CurrentComponentContent("pub fn main(x: usize) { foo(); }")),
+            Expect(CurrentSelectedTexts(&["main"])),
+            Expect(EditorCursorPosition(Position::new(0, 7))),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Mark)),
+            Expect(CurrentSelectedTexts(&["foo"])),
+        ])
+    })
+}
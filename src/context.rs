@@ -8,9 +8,9 @@ use shared::canonicalized_path::CanonicalizedPath;
 
 use crate::{
     app::{GlobalSearchConfigUpdate, GlobalSearchFilterGlob, LocalSearchConfigUpdate, Scope},
-    clipboard::{Clipboard, CopiedTexts},
+    clipboard::{Clipboard, CopiedTexts, KillRing},
     components::{keymap_legend::KeymapLegendSection, prompt::PromptHistoryKey},
-    list::grep::RegexConfig,
+    list::{grep::RegexConfig, IgnoreConfig},
     quickfix_list::DiagnosticSeverityRange,
     selection::SelectionMode,
     themes::Theme,
@@ -18,6 +18,7 @@ use crate::{
 
 pub(crate) struct Context {
     clipboard: Clipboard,
+    kill_ring: KillRing,
     mode: Option<GlobalMode>,
     theme: Theme,
 
@@ -26,10 +27,36 @@ pub(crate) struct Context {
     current_working_directory: CanonicalizedPath,
     local_search_config: LocalSearchConfig,
     global_search_config: GlobalSearchConfig,
+    ignore_config: IgnoreConfig,
     quickfix_list_state: Option<QuickfixListState>,
     contextual_keymaps: Vec<KeymapLegendSection>,
     prompt_histories: HashMap<PromptHistoryKey, IndexSet<String>>,
     last_non_contiguous_selection_mode: Option<Either<SelectionMode, GlobalMode>>,
+    /// If true, deletions are previewed and only applied when they are issued again
+    confirm_delete: bool,
+    /// If true, moving to the next selection at the last selection wraps around to the first
+    /// selection and vice versa, otherwise the selection stays at the edge.
+    /// Searches always wrap around regardless of this setting.
+    wrap_around_movements: bool,
+    /// If true, quitting with modified buffers asks whether to save them first
+    confirm_quit: bool,
+    /// Characters beyond this column are highlighted
+    max_line_length: Option<usize>,
+    /// If true, all matches of the last local search are highlighted
+    highlight_search: bool,
+    /// The search being typed in the search prompt, whose matches are highlighted
+    /// instead of those of the last search until the prompt is closed
+    search_preview: Option<String>,
+    /// The selection modes cycled through by `DispatchEditor::CycleSelectionMode`, in order
+    selection_mode_cycle: Vec<SelectionMode>,
+    /// If true, file pickers and quickfix lists display absolute paths instead of relative paths
+    show_absolute_paths: bool,
+    /// The start and end markers of the regions folded by `DispatchEditor::ToggleMarkerFold`
+    fold_markers: (String, String),
+    /// If true, the line of the cursor is highlighted
+    highlight_cursor_line: bool,
+    /// If true, the lines touched by any selection are highlighted
+    highlight_selection_lines: bool,
 }
 
 pub(crate) struct QuickfixListState {
@@ -65,6 +92,7 @@ impl Default for Context {
     fn default() -> Self {
         Self {
             clipboard: Clipboard::new(),
+            kill_ring: KillRing::default(),
             theme: Theme::default(),
             mode: None,
             #[cfg(test)]
@@ -72,10 +100,28 @@ impl Default for Context {
             current_working_directory: CanonicalizedPath::try_from(".").unwrap(),
             local_search_config: LocalSearchConfig::default(),
             global_search_config: GlobalSearchConfig::default(),
+            ignore_config: IgnoreConfig::default(),
             quickfix_list_state: Default::default(),
             contextual_keymaps: Default::default(),
             prompt_histories: Default::default(),
             last_non_contiguous_selection_mode: None,
+            confirm_delete: false,
+            wrap_around_movements: false,
+            confirm_quit: true,
+            max_line_length: None,
+            highlight_search: false,
+            search_preview: None,
+            selection_mode_cycle: [
+                SelectionMode::Column,
+                SelectionMode::Word,
+                SelectionMode::Line,
+                SelectionMode::SyntaxNode,
+            ]
+            .to_vec(),
+            show_absolute_paths: false,
+            fold_markers: ("region".to_string(), "endregion".to_string()),
+            highlight_cursor_line: false,
+            highlight_selection_lines: false,
         }
     }
 }
@@ -110,6 +156,46 @@ impl Context {
     ) -> anyhow::Result<()> {
         self.clipboard.set(contents.clone(), use_system_clipboard)
     }
+
+    #[cfg(test)]
+    pub(crate) fn use_fake_system_clipboard(&mut self) {
+        self.clipboard.use_fake_system_clipboard()
+    }
+
+    /// Refer `Clipboard::enable_persistence`
+    pub(crate) fn enable_clipboard_persistence(
+        &mut self,
+        path: std::path::PathBuf,
+    ) -> anyhow::Result<()> {
+        self.clipboard.enable_persistence(path)
+    }
+
+    pub(crate) fn clipboard_history_depth(&self) -> Option<usize> {
+        self.clipboard.history_depth()
+    }
+
+    /// Refer `Clipboard::set_history_depth`
+    pub(crate) fn set_clipboard_history_depth(&mut self, depth: usize) -> anyhow::Result<()> {
+        self.clipboard.set_history_depth(depth)
+    }
+
+    pub(crate) fn get_register_content(&self, register: char) -> Option<CopiedTexts> {
+        self.clipboard.get_register(register)
+    }
+
+    pub(crate) fn set_register_content(&mut self, register: char, contents: CopiedTexts) {
+        self.clipboard.set_register(register, contents)
+    }
+
+    /// Refer `Clipboard::record_deletion`
+    pub(crate) fn record_deletion(&mut self, copied_texts: CopiedTexts, small: bool) {
+        self.clipboard.record_deletion(copied_texts, small)
+    }
+
+    pub(crate) fn kill_ring_mut(&mut self) -> &mut KillRing {
+        &mut self.kill_ring
+    }
+
     pub(crate) fn mode(&self) -> Option<GlobalMode> {
         self.mode.clone()
     }
@@ -149,11 +235,122 @@ impl Context {
         &self.global_search_config
     }
 
+    pub(crate) fn ignore_config(&self) -> &IgnoreConfig {
+        &self.ignore_config
+    }
+
+    pub(crate) fn set_extra_ignores(&mut self, extra_ignores: Vec<String>) {
+        self.ignore_config.extra_ignores = extra_ignores
+    }
+
+    pub(crate) fn toggle_include_ignored_files(&mut self) {
+        self.ignore_config.include_ignored = !self.ignore_config.include_ignored
+    }
+
+    pub(crate) fn confirm_delete(&self) -> bool {
+        self.confirm_delete
+    }
+
+    pub(crate) fn wrap_around_movements(&self) -> bool {
+        self.wrap_around_movements
+    }
+
+    pub(crate) fn confirm_quit(&self) -> bool {
+        self.confirm_quit
+    }
+
+    pub(crate) fn show_absolute_paths(&self) -> bool {
+        self.show_absolute_paths
+    }
+
+    pub(crate) fn toggle_show_absolute_paths(&mut self) {
+        self.show_absolute_paths = !self.show_absolute_paths
+    }
+
+    pub(crate) fn highlight_cursor_line(&self) -> bool {
+        self.highlight_cursor_line
+    }
+
+    pub(crate) fn toggle_highlight_cursor_line(&mut self) {
+        self.highlight_cursor_line = !self.highlight_cursor_line
+    }
+
+    pub(crate) fn highlight_selection_lines(&self) -> bool {
+        self.highlight_selection_lines
+    }
+
+    pub(crate) fn toggle_highlight_selection_lines(&mut self) {
+        self.highlight_selection_lines = !self.highlight_selection_lines
+    }
+
+    pub(crate) fn toggle_confirm_quit(&mut self) {
+        self.confirm_quit = !self.confirm_quit
+    }
+
+    pub(crate) fn toggle_confirm_delete(&mut self) {
+        self.confirm_delete = !self.confirm_delete
+    }
+
+    pub(crate) fn toggle_wrap_around_movements(&mut self) {
+        self.wrap_around_movements = !self.wrap_around_movements
+    }
+
+    pub(crate) fn selection_mode_cycle(&self) -> &[SelectionMode] {
+        &self.selection_mode_cycle
+    }
+
+    pub(crate) fn set_selection_mode_cycle(&mut self, selection_modes: Vec<SelectionMode>) {
+        self.selection_mode_cycle = selection_modes
+    }
+
+    /// The search whose matches are highlighted, which is the search preview if any,
+    /// otherwise the last local search if search highlighting is enabled.
+    pub(crate) fn highlighted_search(&self) -> Option<Search> {
+        match &self.search_preview {
+            Some(search) => Some(Search {
+                search: search.clone(),
+                mode: self.local_search_config.mode,
+            })
+            .filter(|search| !search.search.is_empty()),
+            None => self
+                .local_search_config
+                .last_search()
+                .filter(|_| self.highlight_search),
+        }
+    }
+
+    pub(crate) fn set_search_preview(&mut self, search_preview: Option<String>) {
+        self.search_preview = search_preview
+    }
+
+    pub(crate) fn clear_search_highlight(&mut self) {
+        self.highlight_search = false
+    }
+
+    pub(crate) fn max_line_length(&self) -> Option<usize> {
+        self.max_line_length
+    }
+
+    pub(crate) fn set_max_line_length(&mut self, max_line_length: Option<usize>) {
+        self.max_line_length = max_line_length
+    }
+
+    pub(crate) fn fold_markers(&self) -> (&str, &str) {
+        (&self.fold_markers.0, &self.fold_markers.1)
+    }
+
+    pub(crate) fn set_fold_markers(&mut self, start: String, end: String) {
+        self.fold_markers = (start, end)
+    }
+
     pub(crate) fn update_local_search_config(
         &mut self,
         update: LocalSearchConfigUpdate,
         scope: Scope,
     ) {
+        if scope == Scope::Local && matches!(update, LocalSearchConfigUpdate::Search(_)) {
+            self.highlight_search = true
+        }
         match scope {
             Scope::Local => &mut self.local_search_config,
             Scope::Global => &mut self.global_search_config.local_config,
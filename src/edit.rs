@@ -89,6 +89,10 @@ impl Action {
 pub(crate) struct EditTransaction {
     /// This `action_group` should be always normalized.
     action_group: ActionGroup,
+    /// The ranges of the first pair of edits from different action groups that overlap each other.
+    /// Applying such edits would corrupt the buffer, because each of them expects the text
+    /// within its range to be untouched by the other edits.
+    overlapping_edits: Option<(CharIndexRange, CharIndexRange)>,
 }
 
 impl EditTransaction {
@@ -122,15 +126,69 @@ impl EditTransaction {
 
     pub(crate) fn from_action_groups(action_groups: Vec<ActionGroup>) -> Self {
         Self {
+            overlapping_edits: Self::find_overlapping_edits(&action_groups),
             action_group: Self::normalize_action_groups(action_groups),
         }
     }
 
     #[cfg(test)]
     pub(crate) fn from_tuples(action_groups: Vec<ActionGroup>) -> Self {
-        Self {
-            action_group: Self::normalize_action_groups(action_groups),
+        Self::from_action_groups(action_groups)
+    }
+
+    pub(crate) fn overlapping_edits(&self) -> Option<(CharIndexRange, CharIndexRange)> {
+        self.overlapping_edits
+    }
+
+    /// Edits within the same action group are not checked,
+    /// because they are meant to be applied together.
+    fn find_overlapping_edits(
+        action_groups: &[ActionGroup],
+    ) -> Option<(CharIndexRange, CharIndexRange)> {
+        let edits = action_groups
+            .iter()
+            .enumerate()
+            .flat_map(|(group_index, group)| {
+                group.actions.iter().filter_map(move |action| match action {
+                    Action::Edit(edit) => Some((edit.range(), group_index)),
+                    _ => None,
+                })
+            })
+            .sorted();
+        // The preceding edit that ends the furthest, and the preceding edit that ends the furthest
+        // among the groups other than the group of the former, because an edit can overlap
+        // any preceding edit, not only the adjacent one.
+        let mut furthest: Option<(CharIndexRange, usize)> = None;
+        let mut furthest_of_other_groups: Option<(CharIndexRange, usize)> = None;
+        for (range, group) in edits {
+            let preceding = match furthest {
+                Some((_, furthest_group)) if furthest_group == group => furthest_of_other_groups,
+                _ => furthest,
+            };
+            if let Some((preceding, _)) =
+                preceding.filter(|(preceding, _)| range.start < preceding.end)
+            {
+                return Some((preceding, range));
+            }
+            match furthest {
+                Some((furthest_range, furthest_group)) if range.end <= furthest_range.end => {
+                    if furthest_group != group
+                        && furthest_of_other_groups
+                            .map_or(true, |(other_range, _)| range.end > other_range.end)
+                    {
+                        furthest_of_other_groups = Some((range, group))
+                    }
+                }
+                Some((furthest_range, furthest_group)) => {
+                    if furthest_group != group {
+                        furthest_of_other_groups = Some((furthest_range, furthest_group))
+                    }
+                    furthest = Some((range, group))
+                }
+                None => furthest = Some((range, group)),
+            }
         }
+        None
     }
 
     /// Normalized action groups will become one action group, as they no longer need to offset each other
@@ -256,6 +314,62 @@ impl ActionGroup {
     }
 }
 
+#[cfg(test)]
+mod test_overlapping_edits {
+    use super::*;
+
+    #[test]
+    fn overlapping_edits_of_different_groups() {
+        let transaction = EditTransaction::from_action_groups(vec![
+            ActionGroup::new(vec![Action::edit(0, "foo bar", "x")]),
+            ActionGroup::new(vec![Action::edit(4, "bar", "y")]),
+        ]);
+        assert_eq!(
+            transaction.overlapping_edits(),
+            Some((
+                (CharIndex(0)..CharIndex(7)).into(),
+                (CharIndex(4)..CharIndex(7)).into()
+            ))
+        );
+    }
+
+    #[test]
+    fn overlapping_edits_that_are_not_adjacent() {
+        let transaction = EditTransaction::from_action_groups(vec![
+            ActionGroup::new(vec![
+                Action::edit(0, "0123456789", "x"),
+                Action::edit(2, "2", "y"),
+            ]),
+            ActionGroup::new(vec![Action::edit(5, "5", "z")]),
+        ]);
+        assert_eq!(
+            transaction.overlapping_edits(),
+            Some((
+                (CharIndex(0)..CharIndex(10)).into(),
+                (CharIndex(5)..CharIndex(6)).into()
+            ))
+        );
+    }
+
+    #[test]
+    fn adjacent_edits_do_not_overlap() {
+        let transaction = EditTransaction::from_action_groups(vec![
+            ActionGroup::new(vec![Action::edit(0, "foo", "x")]),
+            ActionGroup::new(vec![Action::edit(3, " bar", "y")]),
+        ]);
+        assert_eq!(transaction.overlapping_edits(), None);
+    }
+
+    #[test]
+    fn edits_of_the_same_group_are_not_checked() {
+        let transaction = EditTransaction::from_action_groups(vec![ActionGroup::new(vec![
+            Action::edit(0, "foo bar", "x"),
+            Action::edit(4, "bar", "y"),
+        ])]);
+        assert_eq!(transaction.overlapping_edits(), None);
+    }
+}
+
 #[cfg(test)]
 mod test_normalize_actions {
     use ropey::Rope;
@@ -129,8 +129,14 @@ impl Hunk {
     }
 
     pub(crate) fn to_info(&self) -> Option<crate::components::suggestive_editor::Info> {
-        let info = Info::new("Git Hunk Diff".to_string(), self.content.clone())
-            .set_decorations(self.decorations.clone());
+        self.to_info_with_title("Git Hunk Diff".to_string())
+    }
+
+    pub(crate) fn to_info_with_title(
+        &self,
+        title: String,
+    ) -> Option<crate::components::suggestive_editor::Info> {
+        let info = Info::new(title, self.content.clone()).set_decorations(self.decorations.clone());
         Some(info)
     }
 }
@@ -234,6 +234,56 @@ impl DiffMode {
     }
 }
 
+/// The state of the repository that contains a given file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct GitStatus {
+    pub(crate) branch: String,
+    /// True if the working tree has uncommitted changes, including untracked files.
+    pub(crate) dirty: bool,
+}
+
+/// The working directory of the git repository that contains `path`,
+/// which is cheaper to find than the `GitStatus` of the repository.
+pub(crate) fn repository_root(path: &CanonicalizedPath) -> Option<CanonicalizedPath> {
+    let directory = if path.is_dir() {
+        path.to_path_buf().as_path()
+    } else {
+        path.to_path_buf().parent()?
+    };
+    Repository::discover(directory)
+        .ok()?
+        .workdir()?
+        .try_into()
+        .ok()
+}
+
+impl GitStatus {
+    /// Returns `None` if `path` is not within a git repository.
+    pub(crate) fn of(path: &CanonicalizedPath) -> Option<GitStatus> {
+        let directory = if path.is_dir() {
+            path.to_path_buf().as_path()
+        } else {
+            path.to_path_buf().parent()?
+        };
+        let repo = Repository::discover(directory).ok()?;
+        let branch = repo.head().ok()?.shorthand()?.to_string();
+        let dirty = repo
+            .statuses(Some(
+                git2::StatusOptions::new()
+                    .include_untracked(true)
+                    .include_ignored(false),
+            ))
+            .ok()?
+            .iter()
+            .any(|entry| entry.status() != git2::Status::CURRENT);
+        Some(GitStatus { branch, dirty })
+    }
+
+    pub(crate) fn display(&self) -> String {
+        format!("{}{}", self.branch, if self.dirty { "*" } else { "" })
+    }
+}
+
 #[cfg(test)]
 mod test_git {
     use std::process::Command;
@@ -339,4 +389,36 @@ mod test_git {
         )?;
         Ok(())
     }
+
+    #[test]
+    fn test_git_status() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let file = dir.path().join("file.txt");
+        run_command(&dir, "git", &["init"]);
+        run_command(&dir, "git", &["checkout", "-b", "feature"]);
+        std::fs::write(file.clone(), "hello\n")?;
+        run_command(&dir, "git", &["add", "."]);
+        run_command(&dir, "git", &["commit", "-m", "First commit"]);
+
+        let path = file.clone().try_into()?;
+        assert_eq!(
+            super::GitStatus::of(&path),
+            Some(super::GitStatus {
+                branch: "feature".to_string(),
+                dirty: false
+            })
+        );
+
+        std::fs::write(file, "hello\nworld\n")?;
+        assert_eq!(
+            super::GitStatus::of(&path),
+            Some(super::GitStatus {
+                branch: "feature".to_string(),
+                dirty: true
+            })
+        );
+        assert_eq!(super::GitStatus::of(&path).unwrap().display(), "feature*");
+        assert_eq!(super::repository_root(&path), Some(dir.path().try_into()?));
+        Ok(())
+    }
 }
@@ -594,6 +594,10 @@ pub(crate) enum StyleKey {
     KeymapKey,
     UiFuzzyMatchedChar,
     ParentLine,
+    UiLongLineOverflow,
+    UiSearchMatch,
+    UiCursorLine,
+    UiSelectionLine,
 }
 
 /// TODO: in the future, tab size should be configurable
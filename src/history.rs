@@ -22,6 +22,13 @@ impl<T: Eq + Clone + std::fmt::Debug> History<T> {
         self.forward_history.clear();
     }
 
+    /// Replace the latest item, without clearing the redo stack
+    pub(crate) fn replace_last(&mut self, item: T) {
+        if let Some(last) = self.backward_history.last_mut() {
+            *last = item
+        }
+    }
+
     pub(crate) fn undo(&mut self) -> Option<T> {
         let item = self.backward_history.pop();
         if let Some(item) = &item {
@@ -89,6 +96,19 @@ mod test_history {
         assert_eq!(history.undo(), Some(1));
     }
 
+    #[test]
+    fn replace_last_should_keep_redo_stack() {
+        let mut history = History::new();
+        history.push(1);
+        history.push(2);
+        history.push(3);
+        assert_eq!(history.undo(), Some(2));
+        history.replace_last(4);
+        assert_eq!(history.undo(), Some(1));
+        assert_eq!(history.redo(), Some(4));
+        assert_eq!(history.redo(), Some(3));
+    }
+
     #[test]
     fn push_should_not_allow_consecutive_duplicates() {
         let mut history = History::new();
@@ -0,0 +1,177 @@
+//! Languages embedded in another language, e.g. the fenced code blocks of Markdown,
+//! or the script elements of HTML.
+
+use std::{collections::HashMap, ops::Range, sync::Mutex};
+
+use once_cell::sync::Lazy;
+use ropey::Rope;
+use tree_sitter::{InputEdit, Node, Parser, Point, Query, QueryCursor, Tree};
+
+/// The grammars of the injected languages by their injection name,
+/// so that a grammar is only loaded once instead of on every edit.
+static LANGUAGES: Lazy<Mutex<HashMap<String, Option<tree_sitter::Language>>>> =
+    Lazy::new(Default::default);
+
+#[derive(Clone)]
+pub(crate) struct Injection {
+    pub(crate) byte_range: Range<usize>,
+    /// The nodes of this tree are positioned relative to the whole buffer,
+    /// not relative to the injected content.
+    pub(crate) tree: Tree,
+}
+
+impl Injection {
+    /// Shift this injection by `edit`, which is applied to the content of the whole buffer
+    pub(crate) fn edit(&mut self, edit: &InputEdit) {
+        self.tree.edit(edit);
+        self.byte_range =
+            shift_byte(self.byte_range.start, edit)..shift_byte(self.byte_range.end, edit)
+    }
+}
+
+/// Returns `byte` after `edit` is applied, a byte within the replaced range is clamped
+/// to the end of the new text.
+pub(crate) fn shift_byte(byte: usize, edit: &InputEdit) -> usize {
+    if byte >= edit.old_end_byte {
+        byte - edit.old_end_byte + edit.new_end_byte
+    } else {
+        byte.min(edit.new_end_byte)
+    }
+}
+
+fn language(name: &str) -> Option<tree_sitter::Language> {
+    LANGUAGES
+        .lock()
+        .unwrap()
+        .entry(name.to_string())
+        .or_insert_with(|| shared::language::from_injection_name(name)?.tree_sitter_language())
+        .clone()
+}
+
+/// Compile the injection query of `language`, whose grammar is `tree_sitter_language`,
+/// refer `shared::language::Language::injection_query`.
+pub(crate) fn injection_query(
+    language: &shared::language::Language,
+    tree_sitter_language: &tree_sitter::Language,
+) -> Option<Query> {
+    Query::new(tree_sitter_language, language.injection_query()?).ok()
+}
+
+/// Returns the injected language name, the injected content, and the byte range of
+/// every match of `query` within `byte_range`.
+///
+/// The content is captured by `@injection.content`, and the language name is either
+/// captured by `@injection.language`, or set with `(#set! injection.language "...")`.
+fn injected_contents<'a>(
+    query: &Query,
+    tree: &'a Tree,
+    rope: &Rope,
+    byte_range: Range<usize>,
+) -> Vec<(String, Node<'a>, Range<usize>)> {
+    let Some(content_index) = query.capture_index_for_name("injection.content") else {
+        return Vec::new();
+    };
+    let language_index = query.capture_index_for_name("injection.language");
+    let text = |node: Node| {
+        rope.get_byte_slice(node.byte_range())
+            .into_iter()
+            .flat_map(|slice| slice.chunks())
+            .map(str::as_bytes)
+    };
+    let mut cursor = QueryCursor::new();
+    cursor.set_byte_range(byte_range);
+    cursor
+        .matches(query, tree.root_node(), text)
+        .filter_map(|query_match| {
+            let captured = |index: u32| {
+                query_match
+                    .captures
+                    .iter()
+                    .find(|capture| capture.index == index)
+                    .map(|capture| capture.node)
+            };
+            let content = captured(content_index)?;
+            let language = match language_index.and_then(captured) {
+                Some(node) => rope.get_byte_slice(node.byte_range())?.to_string(),
+                None => query
+                    .property_settings(query_match.pattern_index)
+                    .iter()
+                    .find(|property| &*property.key == "injection.language")?
+                    .value
+                    .as_deref()?
+                    .to_string(),
+            };
+            let start = query_match
+                .captures
+                .iter()
+                .map(|capture| capture.node.start_byte())
+                .min()?;
+            let end = query_match
+                .captures
+                .iter()
+                .map(|capture| capture.node.end_byte())
+                .max()?;
+            Some((language.trim().to_lowercase(), content, start..end))
+        })
+        .collect()
+}
+
+fn parse(language: &str, content: Node, rope: &Rope) -> Option<Injection> {
+    let mut parser = Parser::new();
+    parser.set_language(&self::language(language)?).ok()?;
+    parser.set_included_ranges(&[content.range()]).ok()?;
+    let tree = parser.parse_with(
+        &mut |byte: usize, _: Point| -> &[u8] {
+            if byte >= rope.len_bytes() {
+                return &[];
+            }
+            let (chunk, chunk_byte_index, _, _) = rope.chunk_at_byte(byte);
+            &chunk.as_bytes()[byte - chunk_byte_index..]
+        },
+        None,
+    )?;
+    Some(Injection {
+        byte_range: content.byte_range(),
+        tree,
+    })
+}
+
+/// Update `injections` of `tree` after the content within `edited_ranges` is changed.
+///
+/// The injections not touching any of `edited_ranges` are kept as they are,
+/// and only the matches of the injection `query` touching them are parsed again.
+pub(crate) fn update_injections(
+    injections: &mut Vec<Injection>,
+    tree: &Tree,
+    rope: &Rope,
+    edited_ranges: &[Range<usize>],
+    query: &Query,
+) {
+    let touches_edit = |range: &Range<usize>| {
+        edited_ranges
+            .iter()
+            .any(|edited| range.start <= edited.end && edited.start <= range.end)
+    };
+    let mut parsed: Vec<Injection> = Vec::new();
+    for edited in edited_ranges {
+        // Widened, so that the matches only touching the edited range are also found
+        let byte_range = edited.start.saturating_sub(1)..edited.end + 1;
+        for (language, content, range) in injected_contents(query, tree, rope, byte_range) {
+            if touches_edit(&range)
+                && parsed
+                    .iter()
+                    .all(|parsed| parsed.byte_range != content.byte_range())
+            {
+                parsed.extend(parse(&language, content, rope))
+            }
+        }
+    }
+    injections.retain(|injection| {
+        !touches_edit(&injection.byte_range)
+            && parsed
+                .iter()
+                .all(|parsed| parsed.byte_range != injection.byte_range)
+    });
+    injections.extend(parsed);
+    injections.sort_by_key(|injection| injection.byte_range.start);
+}
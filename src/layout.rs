@@ -20,7 +20,7 @@ use indexmap::IndexMap;
 use itertools::Itertools;
 use nary_tree::NodeId;
 use shared::canonicalized_path::CanonicalizedPath;
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 /// The layout of the app is split into multiple sections: the main panel, info panel, quickfix
 /// lists, prompts, and etc.
@@ -36,6 +36,9 @@ pub(crate) struct Layout {
 
     terminal_dimension: Dimension,
     tree: UiTree,
+    /// The pane split by each split made with `Layout::add_and_focus_split_with_layout`
+    /// and the orientation of the split, by the id of the editor of the split
+    splits: HashMap<ComponentId, (ComponentId, LayoutKind)>,
 }
 
 impl Layout {
@@ -54,6 +57,7 @@ impl Layout {
             borders,
             terminal_dimension,
             tree,
+            splits: HashMap::new(),
         })
     }
 
@@ -74,7 +78,23 @@ impl Layout {
 
     pub(crate) fn remove_current_component(&mut self) {
         let node = self.tree.get_current_node();
-        if let Some(path) = node.data().component().borrow().path() {
+        let (path, is_split) = {
+            let component = node.data().component();
+            let component = component.borrow();
+            let path = component.path();
+            // Split editors are not stored in the background editors,
+            // so closing them should not close the file
+            let is_split = path.as_ref().is_some_and(|path| {
+                self.background_suggestive_editors
+                    .get(path)
+                    .is_some_and(|editor| editor.borrow().id() != component.id())
+            });
+            (path, is_split)
+        };
+        if is_split {
+            self.tree.remove(node.node_id(), false);
+            self.tree.cycle_component();
+        } else if let Some(path) = path {
             self.background_suggestive_editors.shift_remove(&path);
             if let Some((_, editor)) = self
                 .background_suggestive_editors
@@ -115,12 +135,44 @@ impl Layout {
     pub(crate) fn recalculate_layout(&mut self) {
         let (layout_kind, ratio) = layout_kind(&self.terminal_dimension);
 
-        let (rectangles, borders) = Rectangle::generate(
+        let ids = self
+            .components()
+            .into_iter()
+            .map(|component| component.component().borrow().id())
+            .collect_vec();
+        self.splits.retain(|id, _| ids.contains(id));
+        // A split shares the rectangle of the pane it splits,
+        // instead of taking a rectangle of its own
+        let splits = ids
+            .iter()
+            .enumerate()
+            .map(|(index, id)| {
+                let (split_id, layout_kind) = self.splits.get(id)?;
+                let split_index = ids.iter().position(|id| id == split_id)?;
+                (split_index < index).then_some((split_index, *layout_kind))
+            })
+            .collect_vec();
+        let (generated_rectangles, mut borders) = Rectangle::generate(
             layout_kind,
-            self.components().len(),
+            splits.iter().filter(|split| split.is_none()).count(),
             ratio,
             self.terminal_dimension,
         );
+        let mut generated_rectangles = generated_rectangles.into_iter();
+        let mut rectangles: Vec<Rectangle> = Vec::new();
+        for split in splits {
+            let rectangle = match split {
+                Some((split_index, layout_kind)) => {
+                    let (split_rectangle, rectangle, border) =
+                        rectangles[split_index].split(layout_kind == LayoutKind::Tall);
+                    rectangles[split_index] = split_rectangle;
+                    borders.push(border);
+                    rectangle
+                }
+                None => generated_rectangles.next().unwrap_or_default(),
+            };
+            rectangles.push(rectangle)
+        }
         self.rectangles = rectangles;
         self.borders = borders;
 
@@ -254,6 +306,18 @@ impl Layout {
             .refresh(working_directory)
     }
 
+    pub(crate) fn toggle_file_explorer_hidden_files(&self) -> anyhow::Result<()> {
+        self.background_file_explorer
+            .borrow_mut()
+            .toggle_hidden_files()
+    }
+
+    pub(crate) fn toggle_file_explorer_ignored_files(&self) -> anyhow::Result<()> {
+        self.background_file_explorer
+            .borrow_mut()
+            .toggle_ignored_files()
+    }
+
     pub(crate) fn open_file_explorer(&mut self) {
         self.tree.remove_all_root_children();
         self.tree.replace_root_node_child(
@@ -292,22 +356,59 @@ impl Layout {
             .collect_vec()
     }
 
+    /// The selections of the editors of the reloaded buffers are mapped to the new content
     pub(crate) fn reload_buffers(
         &self,
         affected_paths: Vec<CanonicalizedPath>,
-    ) -> anyhow::Result<()> {
-        for buffer in self.buffers() {
-            let mut buffer = buffer.borrow_mut();
-            if let Some(path) = buffer.path() {
-                if affected_paths
-                    .iter()
-                    .any(|affected_path| affected_path == &path)
-                {
-                    buffer.reload()?;
-                }
-            }
+    ) -> anyhow::Result<Dispatches> {
+        affected_paths
+            .iter()
+            .map(|path| self.reload_buffer(path))
+            .try_fold(Dispatches::default(), |dispatches, reloaded| {
+                Ok(dispatches.chain(reloaded?))
+            })
+    }
+
+    /// Reload the buffer of `path`, the selections of every editor of the buffer,
+    /// including the editors of its splits, are mapped to the new content.
+    pub(crate) fn reload_buffer(&self, path: &CanonicalizedPath) -> anyhow::Result<Dispatches> {
+        let editors = self.get_existing_editors(path);
+        let Some((editor, other_editors)) = editors.split_first() else {
+            return Ok(Dispatches::default());
+        };
+        let old_content = editor.borrow().editor().buffer().content();
+        let dispatches = editor.borrow_mut().editor_mut().reload()?;
+        for other_editor in other_editors {
+            other_editor
+                .borrow_mut()
+                .editor_mut()
+                .remap_selections(&old_content)?
         }
-        Ok(())
+        Ok(dispatches)
+    }
+
+    /// Unlike `get_existing_editor`, the editors of the splits of the buffer of `path`
+    /// are also returned, after the editor of `path`.
+    pub(crate) fn get_existing_editors(
+        &self,
+        path: &CanonicalizedPath,
+    ) -> Vec<Rc<RefCell<dyn Component>>> {
+        let editor = self
+            .get_existing_editor(path)
+            .map(|editor| editor as Rc<RefCell<dyn Component>>);
+        let editor_id = editor.as_ref().map(|editor| editor.borrow().id());
+        let splits = self
+            .tree
+            .components()
+            .into_iter()
+            .filter(|component| component.kind() == ComponentKind::SuggestiveEditor)
+            .map(|component| component.component())
+            .filter(|component| {
+                let component = component.borrow();
+                Some(component.id()) != editor_id && component.path().as_ref() == Some(path)
+            })
+            .collect_vec();
+        editor.into_iter().chain(splits).collect_vec()
     }
 
     #[cfg(test)]
@@ -376,6 +477,7 @@ impl Layout {
     pub(crate) fn show_quickfix_list(
         &mut self,
         quickfix_list: QuickfixList,
+        show_preview: bool,
     ) -> anyhow::Result<Dispatches> {
         let render = quickfix_list.render();
         let editor = self.background_quickfix_list.get_or_insert_with(|| {
@@ -398,6 +500,18 @@ impl Layout {
         if let Some(info) = render.info {
             self.show_info_on(node_id, info, ComponentKind::QuickfixListInfo)?;
         }
+        match show_preview
+            .then(|| quickfix_list.current_item_preview())
+            .flatten()
+        {
+            Some(preview) => {
+                self.show_info_on(node_id, preview, ComponentKind::QuickfixListPreview)?
+            }
+            // Otherwise the preview of the previous item would stay visible
+            None => {
+                self.remove_node_child(node_id, ComponentKind::QuickfixListPreview);
+            }
+        }
         Ok(dispatches)
     }
 
@@ -473,10 +587,7 @@ impl Layout {
                                     path: buffer.path()?,
                                     range: position_range,
                                 },
-                                Some(Info::new(
-                                    "Diagnostics".to_string(),
-                                    diagnostic.message.clone(),
-                                )),
+                                Some(diagnostic.info()),
                             ))
                         })
                         .collect_vec(),
@@ -520,6 +631,29 @@ impl Layout {
         );
     }
 
+    /// Unlike `replace_and_focus_current_suggestive_editor`, the existing editor remains visible.
+    pub(crate) fn add_and_focus_split(&mut self, editor: Rc<RefCell<SuggestiveEditor>>) {
+        self.tree.append_component(
+            self.tree.root_id(),
+            KindedComponent::new(ComponentKind::SuggestiveEditor, editor),
+            true,
+        );
+        self.recalculate_layout();
+    }
+
+    /// Same as `add_and_focus_split`, but the rectangle of the current pane is split in half,
+    /// side by side for `LayoutKind::Tall`, and stacked for `LayoutKind::Wide`.
+    pub(crate) fn add_and_focus_split_with_layout(
+        &mut self,
+        editor: Rc<RefCell<SuggestiveEditor>>,
+        layout_kind: LayoutKind,
+    ) {
+        let current_id = self.get_current_component().borrow().id();
+        self.splits
+            .insert(editor.borrow().id(), (current_id, layout_kind));
+        self.add_and_focus_split(editor)
+    }
+
     pub(crate) fn close_current_window_and_focus_parent(&mut self) {
         self.tree.close_current_and_focus_parent()
     }
@@ -534,6 +668,16 @@ impl Layout {
         )
     }
 
+    #[cfg(test)]
+    pub(crate) fn quickfix_list_preview(&self) -> Option<String> {
+        Some(
+            self.tree
+                .get_component_by_kind(ComponentKind::QuickfixListPreview)?
+                .borrow()
+                .content(),
+        )
+    }
+
     pub(crate) fn get_component_by_kind(
         &self,
         kind: ComponentKind,
@@ -1,14 +1,18 @@
+use crossbeam::channel::Sender;
+
 use crate::{
     quickfix_list::Location,
     selection_mode::{AstGrep, ByteRange},
 };
 
-use super::WalkBuilderConfig;
+use super::{ShouldStop, WalkBuilderConfig};
 
 pub(crate) fn run(
     pattern: String,
     walk_builder_config: WalkBuilderConfig,
-) -> anyhow::Result<Vec<Location>> {
+    sender: Sender<Location>,
+    should_stop: &ShouldStop,
+) -> anyhow::Result<()> {
     walk_builder_config.run_with_search(
         true,
         Box::new(move |buffer| {
@@ -18,5 +22,7 @@ pub(crate) fn run(
                 .map(|node_match| ByteRange::new(node_match.range()))
                 .collect())
         }),
+        sender,
+        should_stop,
     )
 }
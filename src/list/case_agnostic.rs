@@ -1,11 +1,15 @@
+use crossbeam::channel::Sender;
+
 use crate::{quickfix_list::Location, selection_mode::CaseAgnostic};
 
-use super::WalkBuilderConfig;
+use super::{ShouldStop, WalkBuilderConfig};
 
 pub(crate) fn run(
     pattern: String,
     walk_builder_config: WalkBuilderConfig,
-) -> anyhow::Result<Vec<Location>> {
+    sender: Sender<Location>,
+    should_stop: &ShouldStop,
+) -> anyhow::Result<()> {
     walk_builder_config.run_with_search(
         false,
         Box::new(move |buffer| {
@@ -16,5 +20,7 @@ pub(crate) fn run(
                 .map(|(range, _)| range)
                 .collect())
         }),
+        sender,
+        should_stop,
     )
 }
@@ -1,3 +1,4 @@
+use crossbeam::channel::Sender;
 use grep_regex::RegexMatcher;
 use grep_searcher::{sinks, SearcherBuilder};
 
@@ -9,7 +10,7 @@ use crate::{
 };
 use shared::canonicalized_path::CanonicalizedPath;
 
-use super::WalkBuilderConfig;
+use super::{ShouldStop, WalkBuilderConfig};
 
 #[derive(Clone, Debug, PartialEq, Eq, Copy)]
 pub(crate) struct RegexConfig {
@@ -56,17 +57,20 @@ pub(crate) fn replace(
         .collect())
 }
 
+/// The found locations are sent to `sender`, refer `WalkBuilderConfig::run_with_sender`
 pub(crate) fn run(
     pattern: &str,
     walk_builder_config: WalkBuilderConfig,
     grep_config: RegexConfig,
-) -> anyhow::Result<Vec<Location>> {
+    sender: Sender<Location>,
+    should_stop: &ShouldStop,
+) -> anyhow::Result<()> {
     let pattern = get_regex(pattern, grep_config)?.as_str().to_string();
     let matcher = RegexMatcher::new_line_matcher(&pattern)?;
     let regex = Regex::new(&pattern)?;
 
-    Ok(walk_builder_config
-        .run(Box::new(move |path, sender| {
+    walk_builder_config.run_with_sender(
+        Box::new(move |path, sender| {
             let path = path.try_into()?;
             let buffer = Buffer::from_path(&path, false)?;
             // Tree-sitter should be disabled whenever possible during
@@ -77,25 +81,27 @@ pub(crate) fn run(
                 &matcher,
                 path.clone(),
                 sinks::UTF8(|line_number, line| {
-                    if let Ok(location) = to_location(
+                    if let Ok(locations) = to_location(
                         &buffer,
                         path.clone(),
                         line_number as usize,
                         line,
                         regex.clone(),
                     ) {
-                        let _ = sender.send(location).map_err(|error| {
-                            log::error!("sender.send {:?}", error);
-                        });
+                        for location in locations {
+                            let _ = sender.send(location).map_err(|error| {
+                                log::error!("sender.send {:?}", error);
+                            });
+                        }
                     }
                     Ok(true)
                 }),
             )?;
             Ok(())
-        }))?
-        .into_iter()
-        .flatten()
-        .collect())
+        }),
+        sender,
+        should_stop,
+    )
 }
 
 fn to_location(
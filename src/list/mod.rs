@@ -1,8 +1,11 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crossbeam::channel::Sender;
 use globset::Glob;
-use ignore::{WalkBuilder, WalkState};
+use ignore::{
+    overrides::{Override, OverrideBuilder},
+    WalkBuilder, WalkState,
+};
 use shared::canonicalized_path::CanonicalizedPath;
 
 use crate::{buffer::Buffer, quickfix_list::Location, selection_mode::ByteRange};
@@ -16,55 +19,104 @@ pub(crate) struct WalkBuilderConfig {
     pub(crate) root: PathBuf,
     pub(crate) include: Option<Glob>,
     pub(crate) exclude: Option<Glob>,
+    pub(crate) ignore_config: IgnoreConfig,
+}
+
+/// Controls which files are skipped when walking a directory.
+///
+/// By default, files matched by `.gitignore`, `.ignore` or `extra_ignores` are skipped.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct IgnoreConfig {
+    /// Additional patterns to be ignored, in the `.gitignore` format.
+    pub(crate) extra_ignores: Vec<String>,
+    /// If true, no files are skipped except the `.git` folder.
+    pub(crate) include_ignored: bool,
+}
+
+impl IgnoreConfig {
+    fn overrides(&self, root: &Path) -> anyhow::Result<Override> {
+        let mut builder = OverrideBuilder::new(root);
+        if !self.include_ignored {
+            for pattern in &self.extra_ignores {
+                // Override globs are whitelists, a `!` prefix turns it into an ignore glob
+                builder.add(&format!("!{pattern}"))?;
+            }
+        }
+        Ok(builder.build()?)
+    }
 }
 
 type SearchFn = dyn Fn(&Buffer) -> anyhow::Result<Vec<ByteRange>> + Send + Sync;
+/// Called before each file is searched, the walk stops once it returns true
+pub(crate) type ShouldStop = dyn Fn(&Path) -> bool + Sync;
 impl WalkBuilderConfig {
+    /// The locations found by `f` are sent to `sender` as soon as they are found,
+    /// refer `WalkBuilderConfig::run_with_sender`.
     pub(crate) fn run_with_search(
         self,
         enable_tree_sitter: bool,
         f: Box<SearchFn>,
-    ) -> anyhow::Result<Vec<Location>> {
-        self.run(Box::new(move |path, sender| {
-            let path = path.try_into()?;
-            let buffer = Buffer::from_path(&path, enable_tree_sitter)?;
-            // Tree-sitter should be disabled whenever possible during
-            // global search, because it will slow down the operation tremendously
-            if !enable_tree_sitter {
-                debug_assert!(buffer.tree().is_none())
-            }
-            let _ = f(&buffer)?
-                .into_iter()
-                .flat_map(move |node_match| -> anyhow::Result<_> {
-                    let range = node_match.range();
-                    let range = buffer.byte_to_position(range.start)?
-                        ..buffer.byte_to_position(range.end)?;
-
-                    let _ = sender
-                        .send(Location {
-                            path: path.clone(),
-                            range,
-                        })
-                        .map_err(|error| {
-                            log::error!("sender.send {:?}", error);
-                        });
-
-                    Ok(())
-                })
-                .collect::<Vec<_>>();
-            Ok(())
-        }))
+        sender: Sender<Location>,
+        should_stop: &ShouldStop,
+    ) -> anyhow::Result<()> {
+        self.run_with_sender(
+            Box::new(move |path, sender| {
+                let path = path.try_into()?;
+                let buffer = Buffer::from_path(&path, enable_tree_sitter)?;
+                // Tree-sitter should be disabled whenever possible during
+                // global search, because it will slow down the operation tremendously
+                if !enable_tree_sitter {
+                    debug_assert!(buffer.tree().is_none())
+                }
+                let _ = f(&buffer)?
+                    .into_iter()
+                    .flat_map(move |node_match| -> anyhow::Result<_> {
+                        let range = node_match.range();
+                        let range = buffer.byte_to_position(range.start)?
+                            ..buffer.byte_to_position(range.end)?;
+
+                        let _ = sender
+                            .send(Location {
+                                path: path.clone(),
+                                range,
+                            })
+                            .map_err(|error| {
+                                log::error!("sender.send {:?}", error);
+                            });
+
+                        Ok(())
+                    })
+                    .collect::<Vec<_>>();
+                Ok(())
+            }),
+            sender,
+            should_stop,
+        )
     }
     pub(crate) fn run<T: Send>(
         self,
         f: Box<dyn Fn(PathBuf, Sender<T>) -> anyhow::Result<()> + Send + Sync>,
     ) -> anyhow::Result<Vec<T>> {
+        let (sender, receiver) = crossbeam::channel::unbounded::<T>();
+        self.run_with_sender(f, sender, &|_| false)?;
+        Ok(receiver.into_iter().collect::<Vec<_>>())
+    }
+
+    /// Same as `run`, but the outputs of `f` are sent to `sender` as soon as they are produced,
+    /// instead of being collected after walking every file.
+    /// The walk stops early once `should_stop` returns true.
+    pub(crate) fn run_with_sender<T: Send>(
+        self,
+        f: Box<dyn Fn(PathBuf, Sender<T>) -> anyhow::Result<()> + Send + Sync>,
+        sender: Sender<T>,
+        should_stop: &ShouldStop,
+    ) -> anyhow::Result<()> {
         let WalkBuilderConfig {
             root,
             include,
             exclude,
+            ignore_config,
         } = self;
-        let (sender, receiver) = crossbeam::channel::unbounded::<T>();
         let build_matcher = |glob: Option<&Glob>| -> anyhow::Result<_> {
             let pattern = if let Some(glob) = glob {
                 Some(Glob::new(&root.join(glob.glob()).to_string_lossy())?.compile_matcher())
@@ -77,7 +129,10 @@ impl WalkBuilderConfig {
         };
         let include_match = build_matcher(include.as_ref())?;
         let exclude_match = build_matcher(exclude.as_ref())?;
+        let overrides = ignore_config.overrides(&root)?;
         WalkBuilder::new(root)
+            .standard_filters(!ignore_config.include_ignored)
+            .overrides(overrides)
             .filter_entry(move |entry| {
                 let path = entry.path().display().to_string();
 
@@ -97,6 +152,9 @@ impl WalkBuilderConfig {
                             .file_type()
                             .map_or(false, |file_type| file_type.is_file())
                         {
+                            if should_stop(path.path()) {
+                                return WalkState::Quit;
+                            }
                             let path = path.path().into();
                             if let Err(error) = f(path, sender.clone()) {
                                 log::error!("sender.send {:?}", error)
@@ -114,22 +172,26 @@ impl WalkBuilderConfig {
             drop(sender);
         }
 
-        Ok(receiver.into_iter().collect::<Vec<_>>())
+        Ok(())
     }
 
-    fn new(root: PathBuf) -> Self {
+    fn new(root: PathBuf, ignore_config: IgnoreConfig) -> Self {
         Self {
             root,
             include: None,
             exclude: None,
+            ignore_config,
         }
     }
 
     /// This method returns `PathBuf` instead of `CanonicalizedPath`
     /// because constructing `CanonicalizedPath` is expensive.
     /// For reference: read https://blobfolio.com/2021/faster-path-canonicalization-rust/
-    pub(crate) fn non_git_ignored_files(root: CanonicalizedPath) -> anyhow::Result<Vec<PathBuf>> {
-        WalkBuilderConfig::new(root.to_path_buf().clone())
+    pub(crate) fn non_git_ignored_files(
+        root: CanonicalizedPath,
+        ignore_config: &IgnoreConfig,
+    ) -> anyhow::Result<Vec<PathBuf>> {
+        WalkBuilderConfig::new(root.to_path_buf().clone(), ignore_config.clone())
             .run(Box::new(|path, sender| Ok(sender.send(path)?)))
     }
 }
@@ -149,6 +211,7 @@ mod test_walk_builder_config {
             root: "./tests/mock_repos/rust1".into(),
             include: None,
             exclude: Some(Glob::new("src/*.rs")?),
+            ignore_config: Default::default(),
         };
         let paths = config.run(Box::new(|path, sender| {
             sender.send(path).unwrap();
@@ -171,6 +234,7 @@ mod test_walk_builder_config {
             root: "./tests/mock_repos/rust1".into(),
             include: Some(Glob::new("src/*.rs")?),
             exclude: None,
+            ignore_config: Default::default(),
         };
         let paths = config.run(Box::new(|path, sender| {
             sender.send(path).unwrap();
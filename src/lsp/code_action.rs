@@ -1,6 +1,9 @@
 use itertools::Itertools;
 
-use crate::{app::Dispatch, components::dropdown::DropdownItem};
+use crate::{
+    app::Dispatch,
+    components::{dropdown::DropdownItem, suggestive_editor::Info},
+};
 
 use super::workspace_edit::WorkspaceEdit;
 
@@ -11,6 +14,17 @@ pub(crate) struct CodeAction {
     pub(crate) kind: Option<String>,
     pub(crate) edit: Option<WorkspaceEdit>,
     pub(crate) command: Option<Command>,
+    /// The LSP server that provides this code action
+    pub(crate) server_name: Option<String>,
+}
+
+impl CodeAction {
+    pub(crate) fn set_server_name(self, server_name: &str) -> CodeAction {
+        CodeAction {
+            server_name: Some(server_name.to_string()),
+            ..self
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -41,7 +55,14 @@ impl Eq for Command {}
 
 impl From<CodeAction> for DropdownItem {
     fn from(value: CodeAction) -> DropdownItem {
+        let server_name = value.server_name;
         DropdownItem::new(value.title)
+            .set_info(server_name.clone().map(|server_name| {
+                Info::new(
+                    "Code Action".to_string(),
+                    format!("Source: {}", server_name),
+                )
+            }))
             .set_group(Some(
                 value
                     .kind
@@ -57,11 +78,10 @@ impl From<CodeAction> for DropdownItem {
                     // provides an edit and a command, first the edit is
                     // executed and then the command.
                     // Refer https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#codeAction
-                    .chain(
-                        value
-                            .command
-                            .map(|command| Dispatch::LspExecuteCommand { command }),
-                    )
+                    .chain(value.command.map(|command| Dispatch::LspExecuteCommand {
+                        command,
+                        server_name: server_name.clone(),
+                    }))
                     .collect_vec()
                     .into(),
             )
@@ -80,6 +100,19 @@ impl Ord for CodeAction {
     }
 }
 
+/// A bare command returned by `textDocument/codeAction`, which is executed when chosen.
+impl From<lsp_types::Command> for CodeAction {
+    fn from(value: lsp_types::Command) -> Self {
+        CodeAction {
+            title: value.title.clone(),
+            kind: None,
+            edit: None,
+            command: Some(Command(value)),
+            server_name: None,
+        }
+    }
+}
+
 impl TryFrom<lsp_types::CodeAction> for CodeAction {
     type Error = anyhow::Error;
 
@@ -92,6 +125,7 @@ impl TryFrom<lsp_types::CodeAction> for CodeAction {
             kind: value.kind.map(|kind| kind.as_str().to_string()),
             edit: value.edit.map(WorkspaceEdit::try_from).transpose()?,
             command: value.command.map(Command),
+            server_name: None,
         })
     }
 }
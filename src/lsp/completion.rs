@@ -16,6 +16,9 @@ use super::documentation::Documentation;
 pub(crate) struct Completion {
     pub(crate) items: Vec<DropdownItem>,
     pub(crate) trigger_characters: Vec<String>,
+    /// The LSP server that provides this completion,
+    /// the completions of different servers are merged.
+    pub(crate) server_name: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -28,6 +31,7 @@ pub(crate) struct CompletionItem {
     pub(crate) insert_text: Option<String>,
     pub(crate) edit: Option<CompletionItemEdit>,
     pub(crate) completion_item: lsp_types::CompletionItem,
+    pub(crate) server_name: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -77,11 +81,16 @@ impl CompletionItem {
         });
         let detail = self.detail.clone();
         let documentation = self.documentation().map(|d| d.content);
+        let server_name = self
+            .server_name
+            .as_ref()
+            .map(|server_name| format!("Source: {}", server_name));
         let result = []
             .into_iter()
             .chain(kind)
             .chain(detail)
             .chain(documentation)
+            .chain(server_name)
             .collect_vec()
             .join("\n==========\n");
         if result.is_empty() {
@@ -101,6 +110,7 @@ impl CompletionItem {
             edit: None,
             insert_text: None,
             completion_item: Default::default(),
+            server_name: None,
         }
     }
 
@@ -146,6 +156,13 @@ impl CompletionItem {
         }
     }
 
+    pub(crate) fn set_server_name(self, server_name: &str) -> CompletionItem {
+        CompletionItem {
+            server_name: Some(server_name.to_string()),
+            ..self
+        }
+    }
+
     pub(crate) fn insert_text(&self) -> Option<String> {
         self.insert_text.clone()
     }
@@ -174,6 +191,7 @@ impl CompletionItem {
                 .clone()
                 .map(|command| Dispatch::LspExecuteCommand {
                     command: command.into(),
+                    server_name: self.server_name.clone(),
                 }),
         )
     }
@@ -206,6 +224,7 @@ impl From<lsp_types::CompletionItem> for CompletionItem {
                 lsp_types::CompletionTextEdit::InsertAndReplace(_) => None,
             }),
             completion_item: item,
+            server_name: None,
         }
     }
 }
@@ -1,8 +1,13 @@
 use crate::{
-    buffer::Buffer, char_index_range::CharIndexRange, position::Position, quickfix_list::Location,
+    buffer::Buffer, char_index_range::CharIndexRange, components::suggestive_editor::Info,
+    position::Position, quickfix_list::Location,
 };
 
-use lsp_types::DiagnosticSeverity;
+use lsp_types::{
+    DiagnosticSeverity, DocumentDiagnosticReport, DocumentDiagnosticReportKind,
+    DocumentDiagnosticReportResult,
+};
+use shared::canonicalized_path::CanonicalizedPath;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct Diagnostic {
@@ -12,10 +17,20 @@ pub(crate) struct Diagnostic {
     pub(crate) related_information: Option<Vec<DiagnosticRelatedInformation>>,
     pub(crate) code_description: Option<lsp_types::CodeDescription>,
     pub(crate) original_value: Option<lsp_types::Diagnostic>,
+    /// The LSP server that publishes this diagnostic
+    pub(crate) server_name: String,
+    /// True if this diagnostic is pulled with `textDocument/diagnostic`,
+    /// instead of being pushed with `textDocument/publishDiagnostics`
+    pub(crate) pulled: bool,
 }
 
 impl Diagnostic {
-    pub(crate) fn try_from(buffer: &Buffer, value: lsp_types::Diagnostic) -> anyhow::Result<Self> {
+    pub(crate) fn try_from(
+        buffer: &Buffer,
+        server_name: &str,
+        pulled: bool,
+        value: lsp_types::Diagnostic,
+    ) -> anyhow::Result<Self> {
         Ok(Self {
             range: buffer.position_range_to_char_index_range(
                 &(Position::from(value.range.start)..Position::from(value.range.end)),
@@ -36,8 +51,36 @@ impl Diagnostic {
                 None
             },
             original_value: Some(value),
+            server_name: server_name.to_string(),
+            pulled,
         })
     }
+
+    /// Returns true if this diagnostic is at least as severe as `min_severity`.
+    /// Diagnostics without severity are always included.
+    pub(crate) fn is_at_least(&self, min_severity: DiagnosticSeverity) -> bool {
+        match self.severity {
+            Some(severity) => severity_rank(severity) <= severity_rank(min_severity),
+            None => true,
+        }
+    }
+
+    pub(crate) fn info(&self) -> Info {
+        Info::new(
+            format!("Diagnostics ({})", self.server_name),
+            self.message.clone(),
+        )
+    }
+}
+
+/// Lower is more severe
+fn severity_rank(severity: DiagnosticSeverity) -> u8 {
+    match severity {
+        DiagnosticSeverity::ERROR => 0,
+        DiagnosticSeverity::WARNING => 1,
+        DiagnosticSeverity::INFORMATION => 2,
+        _ => 3,
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -56,3 +99,62 @@ impl TryFrom<lsp_types::DiagnosticRelatedInformation> for DiagnosticRelatedInfor
         })
     }
 }
+
+/// The diagnostics of a document pulled from an LSP server with `textDocument/diagnostic`
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PulledDiagnostics {
+    pub(crate) path: CanonicalizedPath,
+    /// `None` if the diagnostics are unchanged since the previous pull
+    pub(crate) diagnostics: Option<Vec<lsp_types::Diagnostic>>,
+    /// To be sent as the previous result ID of the next pull of the same document
+    pub(crate) result_id: Option<String>,
+}
+
+impl PulledDiagnostics {
+    /// Returns the diagnostics of `path`, followed by the diagnostics of its related documents.
+    pub(crate) fn from_report(
+        path: CanonicalizedPath,
+        report: DocumentDiagnosticReportResult,
+    ) -> Vec<PulledDiagnostics> {
+        let (kind, related_documents) = match report {
+            DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Full(report)) => (
+                Some(DocumentDiagnosticReportKind::Full(
+                    report.full_document_diagnostic_report,
+                )),
+                report.related_documents,
+            ),
+            DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Unchanged(report)) => {
+                (
+                    Some(DocumentDiagnosticReportKind::Unchanged(
+                        report.unchanged_document_diagnostic_report,
+                    )),
+                    report.related_documents,
+                )
+            }
+            DocumentDiagnosticReportResult::Partial(partial) => (None, partial.related_documents),
+        };
+        kind.map(|kind| (path, kind))
+            .into_iter()
+            .chain(
+                related_documents
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|(uri, kind)| {
+                        Some((uri.to_file_path().ok()?.try_into().ok()?, kind))
+                    }),
+            )
+            .map(|(path, kind)| match kind {
+                DocumentDiagnosticReportKind::Full(report) => PulledDiagnostics {
+                    path,
+                    diagnostics: Some(report.items),
+                    result_id: report.result_id,
+                },
+                DocumentDiagnosticReportKind::Unchanged(report) => PulledDiagnostics {
+                    path,
+                    diagnostics: None,
+                    result_id: Some(report.result_id),
+                },
+            })
+            .collect()
+    }
+}
@@ -9,6 +9,55 @@ impl Documentation {
             content: content.to_string(),
         }
     }
+
+    /// Renders Markdown to plain text, e.g. `**bold**` becomes `bold` and `[link](url)` becomes `link`.
+    /// Fenced code blocks are kept as they are, including the fences.
+    pub(crate) fn from_markdown(markdown: &str) -> Documentation {
+        let heading = lazy_regex::regex!(r"^\s{0,3}#{1,6}\s+");
+        let mut in_code_block = false;
+        let content = markdown
+            .lines()
+            .map(|line| {
+                let trimmed = line.trim_start();
+                if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                    in_code_block = !in_code_block;
+                    line.to_string()
+                } else if in_code_block {
+                    line.to_string()
+                } else {
+                    render_inline(&heading.replace(line, ""))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Documentation { content }
+    }
+}
+
+/// Code spans are unwrapped but their content is not rendered.
+fn render_inline(line: &str) -> String {
+    let code_span = lazy_regex::regex!(r"`([^`]+)`");
+    let mut result = String::new();
+    let mut last_end = 0;
+    for captures in code_span.captures_iter(line) {
+        let span = captures.get(0).unwrap();
+        result.push_str(&render_emphasis_and_links(&line[last_end..span.start()]));
+        result.push_str(&captures[1]);
+        last_end = span.end();
+    }
+    result.push_str(&render_emphasis_and_links(&line[last_end..]));
+    result
+}
+
+fn render_emphasis_and_links(text: &str) -> String {
+    let link = lazy_regex::regex!(r"!?\[([^\]]*)\]\([^)]*\)");
+    let strong = lazy_regex::regex!(r"\*\*([^*]+)\*\*");
+    let emphasis = lazy_regex::regex!(r"\*([^*\s][^*]*)\*");
+    let escape = lazy_regex::regex!(r"\\([!-/:-@\[-`{-~])");
+    let text = link.replace_all(text, "$1");
+    let text = strong.replace_all(&text, "$1");
+    let text = emphasis.replace_all(&text, "$1");
+    escape.replace_all(&text, "$1").to_string()
 }
 
 impl From<lsp_types::Documentation> for Documentation {
@@ -21,3 +70,27 @@ impl From<lsp_types::Documentation> for Documentation {
         }
     }
 }
+
+#[cfg(test)]
+mod test_documentation {
+    use super::Documentation;
+
+    #[test]
+    fn markdown_is_rendered_to_plain_text() {
+        let markdown = "# Title\n\nReturns **the** *first* `*item*` of [Vec](https://doc.rust-lang.org/std/vec/struct.Vec.html) \\_or\\_ none";
+        assert_eq!(
+            Documentation::from_markdown(markdown).content,
+            "Title\n\nReturns the first *item* of Vec _or_ none"
+        )
+    }
+
+    #[test]
+    fn code_fences_are_preserved() {
+        let markdown =
+            "```rust\n// **not bold**\nfn first(&self) -> Option<&T>\n```\n\n**Example**";
+        assert_eq!(
+            Documentation::from_markdown(markdown).content,
+            "```rust\n// **not bold**\nfn first(&self) -> Option<&T>\n```\n\nExample"
+        )
+    }
+}
@@ -1,27 +1,49 @@
+use super::documentation::Documentation;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct Hover {
     pub(crate) contents: Vec<String>,
+    /// Only Markdown contents are rendered, plain text is shown as it is.
+    pub(crate) kind: lsp_types::MarkupKind,
+}
+
+impl From<Hover> for Documentation {
+    fn from(hover: Hover) -> Self {
+        let contents = hover.contents.join("\n\n");
+        match hover.kind {
+            lsp_types::MarkupKind::Markdown => Documentation::from_markdown(&contents),
+            lsp_types::MarkupKind::PlainText => Documentation { content: contents },
+        }
+    }
 }
 
 impl From<lsp_types::Hover> for Hover {
     fn from(hover: lsp_types::Hover) -> Self {
-        let contents = match hover.contents {
-            lsp_types::HoverContents::Scalar(marked_string) => {
-                vec![marked_string_to_string(marked_string)]
-            }
-            lsp_types::HoverContents::Array(contents) => contents
-                .into_iter()
-                .map(marked_string_to_string)
-                .collect::<Vec<_>>(),
-            lsp_types::HoverContents::Markup(content) => vec![content.value],
+        // Marked strings are always in Markdown
+        let (contents, kind) = match hover.contents {
+            lsp_types::HoverContents::Scalar(marked_string) => (
+                vec![marked_string_to_string(marked_string)],
+                lsp_types::MarkupKind::Markdown,
+            ),
+            lsp_types::HoverContents::Array(contents) => (
+                contents
+                    .into_iter()
+                    .map(marked_string_to_string)
+                    .collect::<Vec<_>>(),
+                lsp_types::MarkupKind::Markdown,
+            ),
+            lsp_types::HoverContents::Markup(content) => (vec![content.value], content.kind),
         };
-        Hover { contents }
+        Hover { contents, kind }
     }
 }
 
 pub(crate) fn marked_string_to_string(marked_string: lsp_types::MarkedString) -> String {
     match marked_string {
         lsp_types::MarkedString::String(string) => string,
-        lsp_types::MarkedString::LanguageString(language_string) => language_string.value,
+        lsp_types::MarkedString::LanguageString(language_string) => format!(
+            "```{}\n{}\n```",
+            language_string.language, language_string.value
+        ),
     }
 }
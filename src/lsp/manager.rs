@@ -1,17 +1,50 @@
 use std::{collections::HashMap, sync::mpsc::Sender};
 
+use itertools::Itertools;
+
 use crate::app::AppMessage;
 
 use super::process::{FromEditor, LspServerProcessChannel};
 use shared::{
     canonicalized_path::CanonicalizedPath,
     language::{self, Language, LanguageId},
+    process_command::ProcessCommand,
 };
 
+/// The LSP server command of a language, which overrides the default
+/// command defined in `shared::languages`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LanguageConfig {
+    pub(crate) language_id: LanguageId,
+    pub(crate) command: String,
+    pub(crate) args: Vec<String>,
+    /// The commands (with their args) of the additional LSP servers, such as ESLint.
+    /// `None` keeps the default additional servers of the language,
+    /// while an empty list disables them.
+    pub(crate) additional_commands: Option<Vec<(String, Vec<String>)>>,
+}
+
+impl LanguageConfig {
+    fn process_command(&self) -> ProcessCommand {
+        to_process_command(&self.command, &self.args)
+    }
+}
+
+fn to_process_command(command: &str, args: &[String]) -> ProcessCommand {
+    ProcessCommand::new(
+        command,
+        &args.iter().map(|arg| arg.as_str()).collect::<Vec<_>>(),
+    )
+}
+
 pub(crate) struct LspManager {
-    lsp_server_process_channels: HashMap<LanguageId, LspServerProcessChannel>,
+    /// The main LSP server of a language comes first, followed by its additional servers
+    lsp_server_process_channels: HashMap<LanguageId, Vec<LspServerProcessChannel>>,
+    language_configs: HashMap<LanguageId, LanguageConfig>,
     sender: Sender<AppMessage>,
     current_working_directory: CanonicalizedPath,
+    /// Additional project roots besides the current working directory
+    workspace_folders: Vec<CanonicalizedPath>,
     #[cfg(test)]
     /// Used for testing the correctness of LSP requests
     /// We use HashMap instead of Vec because we only one to store the latest
@@ -32,39 +65,61 @@ impl LspManager {
     ) -> LspManager {
         LspManager {
             lsp_server_process_channels: HashMap::new(),
+            language_configs: HashMap::new(),
             sender,
             current_working_directory,
+            workspace_folders: Vec::new(),
             #[cfg(test)]
             history: Default::default(),
         }
     }
 
-    fn invoke_channels(
-        &self,
-        path: &CanonicalizedPath,
-        _error: &str,
-        f: impl Fn(&LspServerProcessChannel) -> anyhow::Result<()>,
-    ) -> anyhow::Result<()> {
+    fn channels(&self, path: &CanonicalizedPath) -> &[LspServerProcessChannel] {
         language::from_path(path)
             .and_then(|language| self.lsp_server_process_channels.get(&language.id()?))
-            .map(f)
-            .unwrap_or_else(|| Ok(()))
+            .map(|channels| channels.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Returns true if there is an LSP server handling the given file.
+    pub(crate) fn is_running(&self, path: &CanonicalizedPath) -> bool {
+        language::from_path(path)
+            .and_then(|language| language.id())
+            .is_some_and(|id| self.lsp_server_process_channels.contains_key(&id))
     }
 
     pub(crate) fn send_message(
         &mut self,
         path: CanonicalizedPath,
         from_editor: FromEditor,
+    ) -> anyhow::Result<()> {
+        self.send_message_to_server(path, None, from_editor)
+    }
+
+    /// If `server_name` is defined, the message is only sent to that server,
+    /// otherwise refer `FromEditor::is_for_every_server`.
+    pub(crate) fn send_message_to_server(
+        &mut self,
+        path: CanonicalizedPath,
+        server_name: Option<&str>,
+        from_editor: FromEditor,
     ) -> anyhow::Result<()> {
         #[cfg(test)]
         self.history
             .insert(from_editor.variant(), from_editor.clone());
 
-        self.invoke_channels(
-            &path,
-            &format!("Failed to send message '{}'", from_editor.variant()),
-            |channel| channel.send_from_editor(from_editor.clone()),
-        )
+        let channels = self.channels(&path);
+        let channels = match server_name {
+            Some(server_name) => channels
+                .iter()
+                .filter(|channel| channel.server_name() == server_name)
+                .collect_vec(),
+            None if from_editor.is_for_every_server() => channels.iter().collect_vec(),
+            None => channels.iter().take(1).collect_vec(),
+        };
+        channels
+            .into_iter()
+            .try_for_each(|channel| channel.send_from_editor(from_editor.clone()))
     }
 
     /// Open file can do one of the following:
@@ -80,32 +135,167 @@ impl LspManager {
             return Ok(());
         };
 
-        if let Some(channel) = self.lsp_server_process_channels.get(&language_id) {
-            if channel.is_initialized() {
-                channel.document_did_open(path.clone())
-            } else {
-                Ok(())
-            }
+        if let Some(channels) = self.lsp_server_process_channels.get(&language_id) {
+            channels
+                .iter()
+                .filter(|channel| channel.is_initialized())
+                .try_for_each(|channel| channel.document_did_open(path.clone()))
         } else {
-            LspServerProcessChannel::new(
+            self.start(language)
+        }
+    }
+
+    /// The additional servers are only started if the main server is started.
+    fn start(&mut self, language: Language) -> anyhow::Result<()> {
+        let Some(language_id) = language.id() else {
+            return Ok(());
+        };
+        // Each server is started independently, so that the additional servers
+        // are still started when the main server is not installed or fails to start
+        let mut channels = Vec::new();
+        for process_command in self
+            .process_command(&language)
+            .into_iter()
+            .chain(self.additional_process_commands(&language))
+        {
+            match LspServerProcessChannel::new(
                 language.clone(),
+                Some(process_command),
                 self.sender.clone(),
                 self.current_working_directory.clone(),
+                self.workspace_folders.clone(),
+            ) {
+                Ok(Some(channel)) => channels.push(channel),
+                Ok(None) => {}
+                Err(error) => log::error!("{:?}", error),
+            }
+        }
+        if !channels.is_empty() {
+            self.lsp_server_process_channels
+                .insert(language_id, channels);
+        }
+        Ok(())
+    }
+
+    fn process_command(&self, language: &Language) -> Option<ProcessCommand> {
+        match language
+            .id()
+            .and_then(|language_id| self.language_configs.get(&language_id))
+        {
+            Some(config) => Some(config.process_command()),
+            None => language.lsp_process_command(),
+        }
+    }
+
+    fn additional_process_commands(&self, language: &Language) -> Vec<ProcessCommand> {
+        match language
+            .id()
+            .and_then(|language_id| self.language_configs.get(&language_id))
+            .and_then(|config| config.additional_commands.as_ref())
+        {
+            Some(commands) => commands
+                .iter()
+                .map(|(command, args)| to_process_command(command, args))
+                .collect(),
+            None => language.additional_lsp_process_commands(),
+        }
+    }
+
+    /// Apply the given configs, and restart the running LSP servers of the languages
+    /// whose main or additional commands are changed.
+    /// The restarted servers re-open the documents once they are initialized.
+    pub(crate) fn update_language_configs(
+        &mut self,
+        configs: Vec<LanguageConfig>,
+    ) -> anyhow::Result<()> {
+        for config in configs {
+            let language_id = config.language_id;
+            let Some(language) = language::from_lsp_language_id(&language_id.to_string()) else {
+                continue;
+            };
+            let previous = (
+                self.process_command(&language),
+                self.additional_process_commands(&language),
+            );
+            self.language_configs.insert(language_id, config);
+            let changed = previous
+                != (
+                    self.process_command(&language),
+                    self.additional_process_commands(&language),
+                );
+            if !changed {
+                continue;
+            }
+            if let Some(channels) = self.lsp_server_process_channels.remove(&language_id) {
+                for channel in channels {
+                    channel.shutdown()?;
+                }
+                self.start(language)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Set the main LSP server command of `language_id`, or add an additional one
+    /// if `additional` is true, while keeping the other servers of the language.
+    pub(crate) fn set_language_server(
+        &mut self,
+        language_id: LanguageId,
+        command: String,
+        args: Vec<String>,
+        additional: bool,
+    ) -> anyhow::Result<()> {
+        let language = language::from_lsp_language_id(&language_id.to_string())
+            .ok_or_else(|| anyhow::anyhow!("Unknown language: {language_id}"))?;
+        let to_command = |process_command: ProcessCommand| {
+            (
+                process_command.command().to_string(),
+                process_command.args().to_vec(),
             )
-            .map(|channel| {
-                if let Some(channel) = channel {
-                    self.lsp_server_process_channels
-                        .insert(language.id()?, channel);
+        };
+        let mut config = match self.language_configs.get(&language_id) {
+            Some(config) => config.clone(),
+            None => {
+                let (command, args) = match language.lsp_process_command() {
+                    Some(process_command) => to_command(process_command),
+                    None if !additional => (command.clone(), args.clone()),
+                    None => {
+                        return Err(anyhow::anyhow!(
+                            "Unable to add a language server to {language_id}, \
+                             which has no main language server"
+                        ))
+                    }
+                };
+                LanguageConfig {
+                    language_id,
+                    command,
+                    args,
+                    additional_commands: None,
                 }
-                Some(())
-            })?;
-            Ok(())
+            }
+        };
+        if additional {
+            config
+                .additional_commands
+                .get_or_insert_with(|| {
+                    language
+                        .additional_lsp_process_commands()
+                        .into_iter()
+                        .map(to_command)
+                        .collect()
+                })
+                .push((command, args));
+        } else {
+            config.command = command;
+            config.args = args;
         }
+        self.update_language_configs([config].to_vec())
     }
 
     pub(crate) fn initialized(
         &mut self,
         language: Language,
+        server_name: &str,
         opened_documents: Vec<CanonicalizedPath>,
     ) {
         let Some(language_id) = language.id() else {
@@ -113,20 +303,79 @@ impl LspManager {
         };
         self.lsp_server_process_channels
             .get_mut(&language_id)
+            .and_then(|channels| {
+                channels
+                    .iter_mut()
+                    .find(|channel| channel.server_name() == server_name)
+            })
             .map(|channel| {
                 channel.initialized();
                 channel.documents_did_open(opened_documents)
             });
     }
 
+    pub(crate) fn add_workspace_folder(&mut self, path: CanonicalizedPath) -> anyhow::Result<()> {
+        self.workspace_folders.push(path.clone());
+        self.broadcast(FromEditor::WorkspaceDidChangeWorkspaceFolders {
+            added: [path].to_vec(),
+            removed: Vec::new(),
+        })
+    }
+
+    pub(crate) fn remove_workspace_folder(
+        &mut self,
+        path: CanonicalizedPath,
+    ) -> anyhow::Result<()> {
+        self.workspace_folders.retain(|folder| folder != &path);
+        self.broadcast(FromEditor::WorkspaceDidChangeWorkspaceFolders {
+            added: Vec::new(),
+            removed: [path].to_vec(),
+        })
+    }
+
+    /// Send the given message to every running LSP server, regardless of language.
+    fn broadcast(&mut self, from_editor: FromEditor) -> anyhow::Result<()> {
+        #[cfg(test)]
+        self.history
+            .insert(from_editor.variant(), from_editor.clone());
+
+        self.lsp_server_process_channels
+            .values()
+            .flatten()
+            .try_for_each(|channel| channel.send_from_editor(from_editor.clone()))
+    }
+
     pub(crate) fn shutdown(&mut self) {
-        for (_, channel) in self.lsp_server_process_channels.drain() {
+        for channel in self
+            .lsp_server_process_channels
+            .drain()
+            .flat_map(|(_, channels)| channels)
+        {
             channel
                 .shutdown()
                 .unwrap_or_else(|error| log::error!("{:?}", error));
         }
     }
 
+    #[cfg(test)]
+    /// Pretend that an LSP server named `server_name` of `language` is running,
+    /// the first mocked server of a language is its main server.
+    pub(crate) fn mock_lsp_server(&mut self, language: Language, server_name: &str) {
+        if let Some(language_id) = language.id() {
+            self.lsp_server_process_channels
+                .entry(language_id)
+                .or_default()
+                .push(LspServerProcessChannel::mock(language, server_name));
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn has_initialized_lsp_server(&self, language_id: LanguageId) -> bool {
+        self.lsp_server_process_channels
+            .get(&language_id)
+            .is_some_and(|channels| channels.iter().any(|channel| channel.is_initialized()))
+    }
+
     #[cfg(test)]
     pub(crate) fn lsp_request_sent(&self, from_editor: &FromEditor) -> bool {
         self.history.get(from_editor.variant()) == Some(from_editor)
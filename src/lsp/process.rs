@@ -1,4 +1,5 @@
 use crate::app::{RequestParams, Scope};
+use crate::components::editor::Direction;
 use anyhow::Context;
 use debounce::EventDebouncer;
 use lsp_types::notification::Notification;
@@ -9,7 +10,7 @@ use lsp_types::*;
 use name_variant::NamedVariant;
 use shared::canonicalized_path::CanonicalizedPath;
 use shared::language::Language;
-use shared::process_command::SpawnCommandResult;
+use shared::process_command::{ProcessCommand, SpawnCommandResult};
 use std::collections::HashMap;
 use std::io::{BufRead, BufReader, Read, Write};
 
@@ -22,7 +23,8 @@ use crate::app::AppMessage;
 use crate::utils::consolidate_errors;
 
 use super::code_action::CodeAction;
-use super::completion::{Completion, CompletionItem};
+use super::completion::{Completion, CompletionItem, PositionalEdit};
+use super::diagnostic::PulledDiagnostics;
 use super::goto_definition_response::GotoDefinitionResponse;
 use super::hover::Hover;
 use super::prepare_rename_response::PrepareRenameResponse;
@@ -33,6 +35,8 @@ use crate::quickfix_list::Location;
 
 struct LspServerProcess {
     language: Language,
+    /// The command of this server, used to tell apart the servers of the same language
+    server_name: String,
     stdin: process::ChildStdin,
 
     /// This is hacky, but we need to keep the stdout around so that it doesn't get dropped
@@ -41,8 +45,14 @@ struct LspServerProcess {
 
     server_capabilities: Option<ServerCapabilities>,
     current_working_directory: CanonicalizedPath,
+    /// Additional project roots besides the current working directory
+    workspace_folders: Vec<CanonicalizedPath>,
     next_request_id: RequestId,
     pending_response_requests: HashMap<RequestId, PendingResponseRequest>,
+    /// The documents of the pending `textDocument/diagnostic` requests
+    pending_diagnostic_paths: HashMap<RequestId, CanonicalizedPath>,
+    /// The result ID of the latest pulled diagnostics of each document
+    diagnostic_result_ids: HashMap<CanonicalizedPath, String>,
     app_message_sender: Sender<AppMessage>,
 
     sender: Sender<LspServerProcessMessage>,
@@ -58,8 +68,18 @@ struct PendingResponseRequest {
 
 #[derive(Debug, Clone, PartialEq)]
 pub(crate) enum LspNotification {
-    Initialized(Language),
-    PublishDiagnostics(PublishDiagnosticsParams),
+    Initialized {
+        language: Language,
+        server_name: String,
+    },
+    PublishDiagnostics {
+        server_name: String,
+        params: PublishDiagnosticsParams,
+    },
+    PulledDiagnostics {
+        server_name: String,
+        pulled: Vec<PulledDiagnostics>,
+    },
     Completion(ResponseContext, Completion),
     Hover(Hover),
     Definition(ResponseContext, GotoDefinitionResponse),
@@ -69,14 +89,36 @@ pub(crate) enum LspNotification {
     WorkspaceEdit(WorkspaceEdit),
     CodeAction(Vec<CodeAction>),
     SignatureHelp(Option<SignatureHelp>),
-    Symbols(Symbols),
-    CompletionItemResolve(lsp_types::CompletionItem),
+    Symbols(ResponseContext, Symbols),
+    CompletionItemResolve(CompletionItem),
+    /// Ranges enclosing the cursor, from the innermost to the outermost.
+    /// Empty if the server does not support `textDocument/selectionRange`.
+    SelectionRanges(Vec<std::ops::Range<crate::position::Position>>),
+    /// Edits that format the whole document of `path`
+    Formatting {
+        path: CanonicalizedPath,
+        edits: Vec<PositionalEdit>,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub(crate) struct ResponseContext {
     pub(crate) scope: Option<Scope>,
     pub(crate) description: Option<String>,
+    /// If defined, the references in the response are jumped through directly
+    /// in the given direction, instead of being shown in the quickfix list.
+    pub(crate) goto_reference: Option<Direction>,
+    /// If true, a single definition in the response is opened in a new split pane,
+    /// instead of replacing the current editor.
+    pub(crate) open_in_split: bool,
+    /// If true, the surroundings of a definition in the response are shown in a popup,
+    /// without leaving the current editor.
+    pub(crate) peek: bool,
+    /// If not empty, only the document symbols of these kinds are shown.
+    pub(crate) symbol_kinds: Vec<lsp_types::SymbolKind>,
+    /// The file of the request, for a response that applies to that file,
+    /// which might no longer be the current file when the response arrives.
+    pub(crate) path: Option<CanonicalizedPath>,
 }
 impl ResponseContext {
     pub(crate) fn set_description(self, descrption: &str) -> Self {
@@ -127,6 +169,8 @@ pub(crate) enum FromEditor {
     },
     TextDocumentCodeAction {
         params: RequestParams,
+        /// The range of the primary selection
+        range: std::ops::Range<crate::position::Position>,
         diagnostics: Vec<lsp_types::Diagnostic>,
     },
     TextDocumentSignatureHelp(RequestParams),
@@ -134,10 +178,22 @@ pub(crate) enum FromEditor {
     TextDocumentImplementation(RequestParams),
     TextDocumentTypeDefinition(RequestParams),
     TextDocumentDocumentSymbol(RequestParams),
+    TextDocumentSelectionRange(RequestParams),
+    TextDocumentFormatting {
+        params: RequestParams,
+        tab_size: usize,
+    },
+    TextDocumentDiagnostic {
+        file_path: CanonicalizedPath,
+    },
     WorkspaceDidRenameFiles {
         old: CanonicalizedPath,
         new: CanonicalizedPath,
     },
+    WorkspaceDidChangeWorkspaceFolders {
+        added: Vec<CanonicalizedPath>,
+        removed: Vec<CanonicalizedPath>,
+    },
     WorkspaceExecuteCommand {
         params: RequestParams,
         command: super::code_action::Command,
@@ -152,10 +208,28 @@ impl FromEditor {
     pub(crate) fn variant(&self) -> &'static str {
         self.variant_name()
     }
+
+    /// Returns true if this message should be sent to every LSP server of a language,
+    /// otherwise it is only sent to the main server of the language.
+    pub(crate) fn is_for_every_server(&self) -> bool {
+        matches!(
+            self,
+            FromEditor::TextDocumentDidOpen { .. }
+                | FromEditor::TextDocumentDidChange { .. }
+                | FromEditor::TextDocumentDidSave { .. }
+                | FromEditor::TextDocumentCompletion(_)
+                | FromEditor::TextDocumentCodeAction { .. }
+                | FromEditor::TextDocumentDiagnostic { .. }
+                | FromEditor::WorkspaceDidRenameFiles { .. }
+                | FromEditor::WorkspaceDidChangeWorkspaceFolders { .. }
+                | FromEditor::Shutdown
+        )
+    }
 }
 
 pub(crate) struct LspServerProcessChannel {
     language: Language,
+    server_name: String,
     join_handle: JoinHandle<JoinHandle<()>>,
     sender: Sender<LspServerProcessMessage>,
     is_initialized: bool,
@@ -164,10 +238,40 @@ pub(crate) struct LspServerProcessChannel {
 impl LspServerProcessChannel {
     pub(crate) fn new(
         language: Language,
+        process_command: Option<ProcessCommand>,
         screen_message_sender: Sender<AppMessage>,
         current_working_directory: CanonicalizedPath,
+        workspace_folders: Vec<CanonicalizedPath>,
     ) -> Result<Option<LspServerProcessChannel>, anyhow::Error> {
-        LspServerProcess::start(language, screen_message_sender, current_working_directory)
+        LspServerProcess::start(
+            language,
+            process_command,
+            screen_message_sender,
+            current_working_directory,
+            workspace_folders,
+        )
+    }
+
+    #[cfg(test)]
+    /// A channel that is not backed by any LSP server process,
+    /// all messages sent to it are ignored.
+    pub(crate) fn mock(language: Language, server_name: &str) -> LspServerProcessChannel {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let join_handle = std::thread::spawn(move || {
+            while let Ok(message) = receiver.recv() {
+                if let LspServerProcessMessage::FromEditor(FromEditor::Shutdown) = message {
+                    break;
+                }
+            }
+            std::thread::spawn(|| {})
+        });
+        LspServerProcessChannel {
+            language,
+            server_name: server_name.to_string(),
+            join_handle,
+            sender,
+            is_initialized: true,
+        }
     }
 
     pub(crate) fn shutdown(self) -> anyhow::Result<()> {
@@ -213,6 +317,10 @@ impl LspServerProcessChannel {
         ))
     }
 
+    pub(crate) fn server_name(&self) -> &str {
+        &self.server_name
+    }
+
     pub(crate) fn is_initialized(&self) -> bool {
         self.is_initialized
     }
@@ -229,10 +337,12 @@ impl LspServerProcessChannel {
 impl LspServerProcess {
     fn start(
         language: Language,
+        process_command: Option<ProcessCommand>,
         app_message_sender: Sender<AppMessage>,
         current_working_directory: CanonicalizedPath,
+        workspace_folders: Vec<CanonicalizedPath>,
     ) -> anyhow::Result<Option<LspServerProcessChannel>> {
-        let process_command = match language.lsp_process_command() {
+        let process_command = match process_command {
             Some(result) => result,
             None => return Ok(None),
         };
@@ -258,14 +368,19 @@ impl LspServerProcess {
             .take()
             .ok_or_else(|| anyhow::anyhow!("Unable to obtain stdout"))?;
         let (sender, receiver) = std::sync::mpsc::channel::<LspServerProcessMessage>();
+        let server_name = process_command.command().to_string();
         let mut lsp_server_process = LspServerProcess {
             language: language.clone(),
+            server_name: server_name.clone(),
             stdin,
             stdout: Some(stdout),
             stderr: Some(stderr),
             current_working_directory,
+            workspace_folders,
             next_request_id: 0,
             pending_response_requests: HashMap::new(),
+            pending_diagnostic_paths: HashMap::new(),
+            diagnostic_result_ids: HashMap::new(),
             server_capabilities: None,
             app_message_sender,
             sender: sender.clone(),
@@ -277,6 +392,7 @@ impl LspServerProcess {
 
         Ok(Some(LspServerProcessChannel {
             language,
+            server_name,
             join_handle,
             sender,
             is_initialized: false,
@@ -317,6 +433,7 @@ impl LspServerProcess {
                         execute_command: Some(DynamicRegistrationClientCapabilities {
                             dynamic_registration: None,
                         }),
+                        workspace_folders: Some(true),
                         ..WorkspaceClientCapabilities::default()
                     }),
                     text_document: Some(TextDocumentClientCapabilities {
@@ -331,6 +448,10 @@ impl LspServerProcess {
                             code_description_support: Some(true),
                             ..PublishDiagnosticsClientCapabilities::default()
                         }),
+                        diagnostic: Some(DiagnosticClientCapabilities {
+                            related_document_support: Some(true),
+                            ..DiagnosticClientCapabilities::default()
+                        }),
                         completion: Some(CompletionClientCapabilities {
                             completion_item: Some(CompletionItemCapability {
                                 resolve_support: Some(CompletionItemCapabilityResolveSupport {
@@ -391,7 +512,12 @@ impl LspServerProcess {
                     }),
                     ..ClientCapabilities::default()
                 },
-                workspace_folders: None,
+                workspace_folders: Some(
+                    std::iter::once(self.current_working_directory.clone())
+                        .chain(self.workspace_folders.clone())
+                        .map(path_to_workspace_folder)
+                        .collect::<Result<Vec<_>, _>>()?,
+                ),
                 ..InitializeParams::default()
             },
         )?;
@@ -536,6 +662,9 @@ impl LspServerProcess {
                 // Get the method of the request
                 let pending_response_request =
                     self.pending_response_requests.remove(&request_id).unwrap();
+                // Removed before the response is parsed, so that it is not left behind
+                // if the response is an error
+                let diagnostic_path = self.pending_diagnostic_paths.remove(&request_id);
 
                 // Parse the reply as a Response
                 let response = serde_json::from_value::<
@@ -583,7 +712,10 @@ impl LspServerProcess {
                         )?;
 
                         self.app_message_sender.send(AppMessage::LspNotification(
-                            LspNotification::Initialized(self.language.clone()),
+                            LspNotification::Initialized {
+                                language: self.language.clone(),
+                                server_name: self.server_name.clone(),
+                            },
                         ))?;
                     }
                     "textDocument/completion" => {
@@ -601,9 +733,13 @@ impl LspServerProcess {
                                             CompletionResponse::List(list) => list.items,
                                         }
                                         .into_iter()
-                                        .map(CompletionItem::from)
-                                        .map(|item| item.into())
+                                        .map(|item| {
+                                            CompletionItem::from(item)
+                                                .set_server_name(&self.server_name)
+                                                .into()
+                                        })
                                         .collect(),
+                                        server_name: Some(self.server_name.clone()),
                                     },
                                 )))
                                 .unwrap();
@@ -723,9 +859,17 @@ impl LspServerProcess {
                                     payload
                                         .into_iter()
                                         .map(|r| match r {
-                                            CodeActionOrCommand::Command(_) => todo!(),
+                                            CodeActionOrCommand::Command(command) => {
+                                                Ok(CodeAction::from(command)
+                                                    .set_server_name(&self.server_name))
+                                            }
                                             CodeActionOrCommand::CodeAction(code_action) => {
-                                                code_action.try_into()
+                                                CodeAction::try_from(code_action).map(
+                                                    |code_action| {
+                                                        code_action
+                                                            .set_server_name(&self.server_name)
+                                                    },
+                                                )
                                             }
                                         })
                                         .collect::<Result<Vec<_>, _>>()?,
@@ -750,18 +894,77 @@ impl LspServerProcess {
                         if let Some(payload) = payload {
                             self.app_message_sender
                                 .send(AppMessage::LspNotification(LspNotification::Symbols(
+                                    response_context,
                                     payload.try_into()?,
                                 )))
                                 .unwrap();
                         }
                     }
+                    "textDocument/formatting" => {
+                        let payload: <lsp_request!("textDocument/formatting") as Request>::Result =
+                            serde_json::from_value(response)?;
+
+                        if let (Some(payload), Some(path)) = (payload, response_context.path) {
+                            self.app_message_sender
+                                .send(AppMessage::LspNotification(LspNotification::Formatting {
+                                    path,
+                                    edits: payload
+                                        .into_iter()
+                                        .map(PositionalEdit::try_from)
+                                        .collect::<Result<Vec<_>, _>>()?,
+                                }))
+                                .unwrap();
+                        }
+                    }
+                    "textDocument/selectionRange" => {
+                        let payload: <lsp_request!("textDocument/selectionRange") as Request>::Result =
+                            serde_json::from_value(response)?;
+
+                        // Only one position is requested, so there is at most one selection range
+                        let ranges = payload
+                            .into_iter()
+                            .flatten()
+                            .next()
+                            .map(flatten_selection_range)
+                            .unwrap_or_default();
+                        self.app_message_sender
+                            .send(AppMessage::LspNotification(
+                                LspNotification::SelectionRanges(ranges),
+                            ))
+                            .unwrap();
+                    }
+                    "textDocument/diagnostic" => {
+                        let payload: <lsp_request!("textDocument/diagnostic") as Request>::Result =
+                            serde_json::from_value(response)?;
+
+                        let path = diagnostic_path
+                            .ok_or_else(|| anyhow::anyhow!("Unknown diagnostic request"))?;
+                        let pulled = PulledDiagnostics::from_report(path, payload);
+                        for pulled in &pulled {
+                            if let Some(result_id) = &pulled.result_id {
+                                self.diagnostic_result_ids
+                                    .insert(pulled.path.clone(), result_id.clone());
+                            }
+                        }
+                        self.app_message_sender
+                            .send(AppMessage::LspNotification(
+                                LspNotification::PulledDiagnostics {
+                                    server_name: self.server_name.clone(),
+                                    pulled,
+                                },
+                            ))
+                            .unwrap();
+                    }
                     "completionItem/resolve" => {
                         let payload: <lsp_request!("completionItem/resolve") as Request>::Result =
                             serde_json::from_value(response)?;
 
                         self.app_message_sender
                             .send(AppMessage::LspNotification(
-                                LspNotification::CompletionItemResolve(payload),
+                                LspNotification::CompletionItemResolve(
+                                    CompletionItem::from(payload)
+                                        .set_server_name(&self.server_name),
+                                ),
                             ))
                             .unwrap();
                     }
@@ -793,7 +996,10 @@ impl LspServerProcess {
 
                         self.app_message_sender
                             .send(AppMessage::LspNotification(
-                                LspNotification::PublishDiagnostics(params),
+                                LspNotification::PublishDiagnostics {
+                                    server_name: self.server_name.clone(),
+                                    params,
+                                },
                             ))
                             .unwrap();
                     }
@@ -814,11 +1020,7 @@ impl LspServerProcess {
                         self.send_reply(request.id, serde_json::Value::Null)?;
                     }
                     "window/logMessage" => {
-                        let command = self
-                            .language
-                            .lsp_process_command()
-                            .map(|command| command.to_string())
-                            .unwrap_or_default();
+                        let command = &self.server_name;
                         let params: <lsp_notification!("window/logMessage") as Notification>::Params =
                             serde_json::from_value(request.params.ok_or_else(|| anyhow::anyhow!("Missing params"))?)?;
                         let typ = match params.typ {
@@ -1012,6 +1214,39 @@ impl LspServerProcess {
         })
     }
 
+    fn workspace_did_change_workspace_folders(
+        &mut self,
+        added: Vec<CanonicalizedPath>,
+        removed: Vec<CanonicalizedPath>,
+    ) -> Result<(), anyhow::Error> {
+        // A string means the server registers for the notification dynamically
+        if !self.has_capability(|c| {
+            matches!(
+                c.workspace
+                    .as_ref()
+                    .and_then(|workspace| workspace.workspace_folders.as_ref())
+                    .and_then(|workspace_folders| workspace_folders.change_notifications.as_ref()),
+                Some(OneOf::Left(true) | OneOf::Right(_))
+            )
+        }) {
+            return Ok(());
+        }
+        self.send_notification::<lsp_notification!("workspace/didChangeWorkspaceFolders")>(
+            DidChangeWorkspaceFoldersParams {
+                event: WorkspaceFoldersChangeEvent {
+                    added: added
+                        .into_iter()
+                        .map(path_to_workspace_folder)
+                        .collect::<Result<_, _>>()?,
+                    removed: removed
+                        .into_iter()
+                        .map(path_to_workspace_folder)
+                        .collect::<Result<_, _>>()?,
+                },
+            },
+        )
+    }
+
     fn has_capability(&self, f: impl Fn(&ServerCapabilities) -> bool) -> bool {
         self.server_capabilities.as_ref().map(f).unwrap_or(false)
     }
@@ -1215,6 +1450,7 @@ impl LspServerProcess {
     fn text_document_code_action(
         &mut self,
         params: RequestParams,
+        range: std::ops::Range<crate::position::Position>,
         diagnostics: Vec<Diagnostic>,
     ) -> Result<(), anyhow::Error> {
         if !self.has_capability(|c| c.code_action_provider.is_some()) {
@@ -1230,8 +1466,8 @@ impl LspServerProcess {
                 },
                 partial_result_params: Default::default(),
                 range: Range {
-                    start: params.position.into(),
-                    end: params.position.into(),
+                    start: range.start.into(),
+                    end: range.end.into(),
                 },
                 text_document: path_buf_to_text_document_identifier(params.path)?,
                 work_done_progress_params: Default::default(),
@@ -1276,6 +1512,31 @@ impl LspServerProcess {
         )
     }
 
+    fn text_document_formatting(
+        &mut self,
+        params: RequestParams,
+        tab_size: usize,
+    ) -> Result<(), anyhow::Error> {
+        if !self.has_capability(|c| c.document_formatting_provider.is_some()) {
+            return Ok(());
+        }
+        self.send_request::<lsp_request!("textDocument/formatting")>(
+            ResponseContext {
+                path: Some(params.path.clone()),
+                ..params.context
+            },
+            DocumentFormattingParams {
+                text_document: path_buf_to_text_document_identifier(params.path)?,
+                options: FormattingOptions {
+                    tab_size: tab_size as u32,
+                    insert_spaces: true,
+                    ..Default::default()
+                },
+                work_done_progress_params: Default::default(),
+            },
+        )
+    }
+
     fn workspace_execute_command(
         &mut self,
         params: RequestParams,
@@ -1312,6 +1573,48 @@ impl LspServerProcess {
         self.send_request::<lsp_request!("completionItem/resolve")>(params.context, completion_item)
     }
 
+    fn text_document_diagnostic(&mut self, file_path: CanonicalizedPath) -> anyhow::Result<()> {
+        if !self.has_capability(|c| c.diagnostic_provider.is_some()) {
+            return Ok(());
+        }
+        let params = DocumentDiagnosticParams {
+            previous_result_id: self.diagnostic_result_ids.get(&file_path).cloned(),
+            text_document: path_buf_to_text_document_identifier(file_path.clone())?,
+            identifier: None,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        let request_id = self.next_request_id;
+        self.pending_diagnostic_paths.insert(request_id, file_path);
+        let result = self.send_request::<lsp_request!("textDocument/diagnostic")>(
+            ResponseContext::default(),
+            params,
+        );
+        if result.is_err() {
+            self.pending_diagnostic_paths.remove(&request_id);
+        }
+        result
+    }
+
+    fn text_document_selection_range(&mut self, params: RequestParams) -> anyhow::Result<()> {
+        if !self.has_capability(|c| c.selection_range_provider.is_some()) {
+            // Reply with no ranges, so that the editor can fall back to the syntax tree
+            self.app_message_sender.send(AppMessage::LspNotification(
+                LspNotification::SelectionRanges(Vec::new()),
+            ))?;
+            return Ok(());
+        }
+        self.send_request::<lsp_request!("textDocument/selectionRange")>(
+            params.context,
+            SelectionRangeParams {
+                positions: [params.position.into()].to_vec(),
+                text_document: path_buf_to_text_document_identifier(params.path)?,
+                partial_result_params: Default::default(),
+                work_done_progress_params: Default::default(),
+            },
+        )
+    }
+
     fn handle_from_editor(&mut self, from_editor: &FromEditor) {
         log::info!(
             "LspServerProcess::handle_from_editor = {}",
@@ -1340,11 +1643,21 @@ impl LspServerProcess {
             }
             FromEditor::TextDocumentCodeAction {
                 params,
+                range,
                 diagnostics,
-            } => self.text_document_code_action(params, diagnostics),
+            } => self.text_document_code_action(params, range, diagnostics),
             FromEditor::TextDocumentDocumentSymbol(params) => {
                 self.text_document_document_symbol(params)
             }
+            FromEditor::TextDocumentSelectionRange(params) => {
+                self.text_document_selection_range(params)
+            }
+            FromEditor::TextDocumentFormatting { params, tab_size } => {
+                self.text_document_formatting(params, tab_size)
+            }
+            FromEditor::TextDocumentDiagnostic { file_path } => {
+                self.text_document_diagnostic(file_path)
+            }
 
             FromEditor::TextDocumentDidOpen {
                 file_path,
@@ -1365,6 +1678,9 @@ impl LspServerProcess {
             FromEditor::WorkspaceDidRenameFiles { old, new } => {
                 self.workspace_did_rename_files(old, new)
             }
+            FromEditor::WorkspaceDidChangeWorkspaceFolders { added, removed } => {
+                self.workspace_did_change_workspace_folders(added, removed)
+            }
             FromEditor::WorkspaceExecuteCommand { params, command } => {
                 self.workspace_execute_command(params, command)
             }
@@ -1383,6 +1699,24 @@ fn path_buf_to_url(path: CanonicalizedPath) -> Result<Url, anyhow::Error> {
     Ok(Url::parse(&format!("file://{}", path.display_absolute()))?)
 }
 
+/// Returns the range of the given selection range followed by the ranges of its ancestors.
+fn flatten_selection_range(
+    selection_range: SelectionRange,
+) -> Vec<std::ops::Range<crate::position::Position>> {
+    std::iter::successors(Some(selection_range), |selection_range| {
+        selection_range.parent.as_deref().cloned()
+    })
+    .map(|selection_range| selection_range.range.start.into()..selection_range.range.end.into())
+    .collect()
+}
+
+fn path_to_workspace_folder(path: CanonicalizedPath) -> Result<WorkspaceFolder, anyhow::Error> {
+    Ok(WorkspaceFolder {
+        name: path.file_name().unwrap_or_else(|| path.display_absolute()),
+        uri: path_buf_to_url(path)?,
+    })
+}
+
 fn path_buf_to_text_document_identifier(
     path: CanonicalizedPath,
 ) -> Result<TextDocumentIdentifier, anyhow::Error> {
@@ -11,6 +11,22 @@ pub(crate) struct Symbols {
     pub(crate) symbols: Vec<Symbol>,
 }
 
+impl Symbols {
+    /// Keeps only the symbols of the given kinds, or every symbol if `kinds` is empty.
+    pub(crate) fn filter_kinds(self, kinds: &[SymbolKind]) -> Self {
+        if kinds.is_empty() {
+            return self;
+        }
+        Self {
+            symbols: self
+                .symbols
+                .into_iter()
+                .filter(|symbol| kinds.contains(&symbol.kind))
+                .collect(),
+        }
+    }
+}
+
 impl TryFrom<DocumentSymbolResponse> for Symbols {
     type Error = anyhow::Error;
 
@@ -1,4 +1,6 @@
+mod alternate_file;
 mod buffer;
+mod buffer_stats;
 mod git;
 
 pub(crate) mod char_index_range;
@@ -10,13 +12,16 @@ mod context;
 mod edit;
 pub(crate) mod frontend;
 mod grid;
+mod injection;
 #[cfg(test)]
 mod integration_test;
 
 mod layout;
 pub(crate) mod list;
 mod lsp;
+mod modeline;
 mod position;
+mod pretty_print;
 
 mod app;
 #[cfg(test)]
@@ -32,7 +37,9 @@ mod screen;
 mod selection;
 pub(crate) mod selection_mode;
 pub(crate) mod selection_range;
+mod session;
 pub(crate) mod soft_wrap;
+mod spell_check;
 pub(crate) mod style;
 pub(crate) mod surround;
 pub(crate) mod syntax_highlight;
@@ -45,7 +52,7 @@ pub(crate) mod ui_tree;
 pub(crate) mod undo_tree;
 mod utils;
 
-use std::{rc::Rc, sync::Mutex};
+use std::{io::Write, rc::Rc, sync::Mutex};
 
 use anyhow::Context;
 use frontend::crossterm::Crossterm;
@@ -64,6 +71,10 @@ fn main() {
 pub(crate) struct RunConfig {
     pub(crate) entry_path: Option<CanonicalizedPath>,
     pub(crate) working_directory: Option<CanonicalizedPath>,
+    /// If true, the clipboard history is persisted in the cache directory across sessions
+    pub(crate) persist_clipboard: bool,
+    /// If defined, every buffer change is appended to this file as a line of JSON
+    pub(crate) buffer_change_log: Option<std::path::PathBuf>,
 }
 
 pub(crate) fn run(config: RunConfig) -> anyhow::Result<()> {
@@ -86,6 +97,35 @@ pub(crate) fn run(config: RunConfig) -> anyhow::Result<()> {
         .to_vec(),
     )?;
     app.set_syntax_highlight_request_sender(syntax_highlighter_sender);
+    if config.persist_clipboard {
+        // A corrupted clipboard history should not prevent the editor from starting
+        app.enable_clipboard_persistence(grammar::cache_dir().join("clipboard.json"))
+            .unwrap_or_else(|error| {
+                log::error!("Failed to load the clipboard history: {:?}", error)
+            });
+    }
+
+    if let Some(path) = config.buffer_change_log {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        app.register_buffer_change_observer(Box::new(move |change| {
+            let position = |position: &position::Position| {
+                serde_json::json!({"line": position.line, "column": position.column})
+            };
+            let line = serde_json::json!({
+                "path": change.path.as_ref().map(|path| path.display_absolute()),
+                "start": position(&change.range.start),
+                "end": position(&change.range.end),
+                "new_text": change.new_text,
+            });
+            if let Err(error) = writeln!(file, "{}", line) {
+                log::error!("Failed to log the buffer change: {:?}", error)
+            }
+        }));
+    }
 
     let sender = app.sender();
 
@@ -0,0 +1,125 @@
+use itertools::Itertools;
+use regex::Regex;
+
+/// Similar to Vim's default `modelines` setting,
+/// only this number of lines at the start and at the end of a file are scanned.
+const SCANNED_LINES_COUNT: usize = 5;
+
+pub(crate) const DEFAULT_TAB_WIDTH: usize = 4;
+
+/// Options that can be set per buffer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct BufferOptions {
+    /// The number of spaces inserted by one level of indentation.
+    pub(crate) tab_width: usize,
+}
+
+impl Default for BufferOptions {
+    fn default() -> Self {
+        Self {
+            tab_width: DEFAULT_TAB_WIDTH,
+        }
+    }
+}
+
+impl BufferOptions {
+    /// Returns the options declared by the modeline of `content`,
+    /// or the default options if there's no modeline.
+    ///
+    /// A modeline is a comment like `# ki: tab_width=2 wrap`, located within the first or last
+    /// few lines of a file.
+    pub(crate) fn from_modeline(content: &str) -> Self {
+        let lines = content.lines().collect_vec();
+        let tail_start = lines
+            .len()
+            .saturating_sub(SCANNED_LINES_COUNT)
+            .max(SCANNED_LINES_COUNT);
+        lines
+            .iter()
+            .take(SCANNED_LINES_COUNT)
+            .chain(lines.iter().skip(tail_start))
+            .filter_map(|line| parse_modeline(line))
+            .flatten()
+            .fold(Self::default(), |options, (key, value)| {
+                options.set(&key, value.as_deref())
+            })
+    }
+
+    /// Only whitelisted options are honored,
+    /// unknown options or invalid values are silently ignored,
+    /// because a modeline comes from the file content, which is not trusted.
+    fn set(self, key: &str, value: Option<&str>) -> Self {
+        match (key, value) {
+            ("tab_width" | "tabwidth" | "indent", Some(value)) => value
+                .parse::<usize>()
+                .ok()
+                .filter(|tab_width| (1..=16).contains(tab_width))
+                .map(|tab_width| Self { tab_width })
+                .unwrap_or(self),
+            _ => self,
+        }
+    }
+}
+
+/// Returns the list of `(key, value)` declared in the given line, if it is a modeline.
+fn parse_modeline(line: &str) -> Option<Vec<(String, Option<String>)>> {
+    let regex = Regex::new(r"(?:^|\s)ki:\s*(?P<options>.*)$").ok()?;
+    let options = regex.captures(line)?.name("options")?.as_str();
+    Some(
+        options
+            .split(|c: char| c.is_whitespace() || c == ':')
+            // Ignore comment terminators such as `*/` or `-->`
+            .filter(|option| option.chars().any(|c| c.is_alphanumeric()))
+            .map(|option| match option.split_once('=') {
+                Some((key, value)) => (key.to_string(), Some(value.to_string())),
+                None => (option.to_string(), None),
+            })
+            .collect_vec(),
+    )
+}
+
+#[cfg(test)]
+mod test_modeline {
+    use super::*;
+
+    #[test]
+    fn leading_modeline() {
+        let content = "# ki: tab_width=2 wrap\nfoo: bar\n";
+        assert_eq!(BufferOptions::from_modeline(content).tab_width, 2)
+    }
+
+    #[test]
+    fn trailing_modeline_with_comment_terminator() {
+        let content = (0..20)
+            .map(|index| format!("line {index}"))
+            .chain(Some("/* ki: indent=8 */".to_string()))
+            .join("\n");
+        assert_eq!(BufferOptions::from_modeline(&content).tab_width, 8)
+    }
+
+    #[test]
+    fn modeline_in_the_middle_is_ignored() {
+        let content = (0..20)
+            .map(|index| {
+                if index == 10 {
+                    "// ki: tab_width=2".to_string()
+                } else {
+                    format!("line {index}")
+                }
+            })
+            .join("\n");
+        assert_eq!(
+            BufferOptions::from_modeline(&content).tab_width,
+            DEFAULT_TAB_WIDTH
+        )
+    }
+
+    #[test]
+    fn non_whitelisted_or_invalid_options_are_ignored() {
+        let content = "// ki: shell=rm tab_width=abc tab_width=1000";
+        assert_eq!(
+            BufferOptions::from_modeline(content),
+            BufferOptions::default()
+        )
+    }
+}
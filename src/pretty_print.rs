@@ -0,0 +1,231 @@
+use anyhow::{anyhow, bail};
+
+/// Re-indent `json` with `indent` spaces per level.
+///
+/// Unlike going through `serde_json::Value`, the order of keys and the formatting of numbers
+/// are preserved.
+pub(crate) fn format_json(json: &str, indent: usize) -> anyhow::Result<String> {
+    serde_json::from_str::<serde::de::IgnoredAny>(json)
+        .map_err(|error| anyhow!("Invalid JSON: {error}"))?;
+    let newline = |result: &mut String, depth: usize| {
+        result.push('\n');
+        result.push_str(&" ".repeat(depth * indent))
+    };
+    let mut result = String::new();
+    let mut depth = 0;
+    let mut chars = json.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '"' => {
+                result.push(char);
+                while let Some(char) = chars.next() {
+                    result.push(char);
+                    match char {
+                        '\\' => result.extend(chars.next()),
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '{' | '[' => {
+                result.push(char);
+                while chars.next_if(|char| char.is_whitespace()).is_some() {}
+                // Empty objects and arrays are kept on one line
+                match chars.next_if(|char| matches!(char, '}' | ']')) {
+                    Some(close) => result.push(close),
+                    None => {
+                        depth += 1;
+                        newline(&mut result, depth)
+                    }
+                }
+            }
+            '}' | ']' => {
+                depth = depth.saturating_sub(1);
+                newline(&mut result, depth);
+                result.push(char)
+            }
+            ',' => {
+                result.push(char);
+                newline(&mut result, depth)
+            }
+            ':' => result.push_str(": "),
+            char if char.is_whitespace() => {}
+            char => result.push(char),
+        }
+    }
+    Ok(result)
+}
+
+#[derive(Debug, PartialEq)]
+enum XmlToken<'a> {
+    Open(&'a str),
+    Close(&'a str),
+    Text(&'a str),
+    /// Self-closing tags, comments, CDATA sections, declarations and processing instructions
+    Other(&'a str),
+}
+
+/// Re-indent `xml` with `indent` spaces per level.
+///
+/// Elements containing only text are kept on one line, and tags are kept verbatim.
+pub(crate) fn format_xml(xml: &str, indent: usize) -> anyhow::Result<String> {
+    let tokens = tokenize_xml(xml)?;
+    let mut lines = Vec::new();
+    let mut depth = 0;
+    let mut index = 0;
+    while let Some(token) = tokens.get(index) {
+        let indentation = " ".repeat(depth * indent);
+        match token {
+            XmlToken::Open(open) => match (tokens.get(index + 1), tokens.get(index + 2)) {
+                (Some(XmlToken::Text(text)), Some(XmlToken::Close(close))) => {
+                    lines.push(format!("{indentation}{open}{text}{close}"));
+                    index += 2;
+                }
+                (Some(XmlToken::Close(close)), _) => {
+                    lines.push(format!("{indentation}{open}{close}"));
+                    index += 1;
+                }
+                _ => {
+                    lines.push(format!("{indentation}{open}"));
+                    depth += 1;
+                }
+            },
+            XmlToken::Close(close) => {
+                depth = depth.saturating_sub(1);
+                lines.push(format!("{}{close}", " ".repeat(depth * indent)));
+            }
+            XmlToken::Text(raw) | XmlToken::Other(raw) => {
+                lines.push(format!("{indentation}{raw}"));
+            }
+        }
+        index += 1;
+    }
+    Ok(lines.join("\n"))
+}
+
+/// Split `xml` into tags and trimmed texts, and check that every tag is closed in order.
+fn tokenize_xml(xml: &str) -> anyhow::Result<Vec<XmlToken>> {
+    let mut tokens = Vec::new();
+    let mut open_tags = Vec::new();
+    let mut rest = xml;
+    while !rest.is_empty() {
+        if rest.starts_with('<') {
+            let end = tag_end(rest).ok_or_else(|| anyhow!("Invalid XML: unterminated tag"))?;
+            let (raw, remaining) = rest.split_at(end);
+            rest = remaining;
+            let name = |prefix_length: usize| {
+                raw[prefix_length..]
+                    .split(|char: char| char.is_whitespace() || char == '/' || char == '>')
+                    .next()
+                    .unwrap_or_default()
+            };
+            if raw.starts_with("</") {
+                let name = name(2);
+                match open_tags.pop() {
+                    Some(open) if open == name => {}
+                    Some(open) => bail!("Invalid XML: expected </{open}> but got </{name}>"),
+                    None => bail!("Invalid XML: unexpected </{name}>"),
+                }
+                tokens.push(XmlToken::Close(raw))
+            } else if raw.starts_with("<?") || raw.starts_with("<!") || raw.ends_with("/>") {
+                tokens.push(XmlToken::Other(raw))
+            } else {
+                let name = name(1);
+                if name.is_empty() {
+                    bail!("Invalid XML: missing tag name")
+                }
+                open_tags.push(name);
+                tokens.push(XmlToken::Open(raw))
+            }
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            let (text, remaining) = rest.split_at(end);
+            rest = remaining;
+            let text = text.trim();
+            if !text.is_empty() {
+                tokens.push(XmlToken::Text(text))
+            }
+        }
+    }
+    if let Some(open) = open_tags.last() {
+        bail!("Invalid XML: <{open}> is not closed")
+    }
+    if !tokens
+        .iter()
+        .any(|token| matches!(token, XmlToken::Open(_) | XmlToken::Other(_)))
+    {
+        bail!("Invalid XML: no element found")
+    }
+    Ok(tokens)
+}
+
+/// Returns the byte index right after the end of the tag that `xml` starts with.
+fn tag_end(xml: &str) -> Option<usize> {
+    for (start, end) in [("<!--", "-->"), ("<![CDATA[", "]]>")] {
+        if xml.starts_with(start) {
+            return xml[start.len()..]
+                .find(end)
+                .map(|index| start.len() + index + end.len());
+        }
+    }
+    let mut quote = None;
+    for (index, char) in xml.char_indices() {
+        match (quote, char) {
+            (None, '"' | '\'') => quote = Some(char),
+            (Some(opening), char) if opening == char => quote = None,
+            (None, '>') => return Some(index + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test_pretty_print {
+    use super::*;
+
+    #[test]
+    fn format_json_preserves_key_order() {
+        assert_eq!(
+            format_json(r#"{"b":[1,2.50],"a":{},"c":"x,\"y\":{"}"#, 2).unwrap(),
+            r#"{
+  "b": [
+    1,
+    2.50
+  ],
+  "a": {},
+  "c": "x,\"y\":{"
+}"#
+        )
+    }
+
+    #[test]
+    fn format_json_invalid() {
+        assert!(format_json(r#"{"a":}"#, 2).is_err())
+    }
+
+    #[test]
+    fn format_xml() {
+        assert_eq!(
+            super::format_xml(
+                r#"<?xml version="1.0"?><a x="1>2"><b>hello</b><c/><!-- <d> --><e></e></a>"#,
+                2
+            )
+            .unwrap(),
+            r#"<?xml version="1.0"?>
+<a x="1>2">
+  <b>hello</b>
+  <c/>
+  <!-- <d> -->
+  <e></e>
+</a>"#
+        )
+    }
+
+    #[test]
+    fn format_xml_invalid() {
+        assert!(super::format_xml("<a><b></a></b>", 2).is_err());
+        assert!(super::format_xml("<a>", 2).is_err());
+        assert!(super::format_xml("hello", 2).is_err())
+    }
+}
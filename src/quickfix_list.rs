@@ -2,9 +2,10 @@ use std::{cell::RefCell, ops::Range, rc::Rc};
 
 use itertools::Itertools;
 use lsp_types::DiagnosticSeverity;
+use ropey::Rope;
 
 use crate::{
-    app::Dispatches,
+    app::{Dispatch, Dispatches},
     buffer::Buffer,
     components::{
         dropdown::{Dropdown, DropdownConfig, DropdownItem},
@@ -16,7 +17,11 @@ use crate::{
 use shared::canonicalized_path::CanonicalizedPath;
 
 impl QuickfixListItem {
-    fn into_dropdown_item(self: QuickfixListItem, buffers: &[Rc<RefCell<Buffer>>]) -> DropdownItem {
+    fn into_dropdown_item(
+        self: QuickfixListItem,
+        buffers: &[Rc<RefCell<Buffer>>],
+        show_absolute_paths: bool,
+    ) -> DropdownItem {
         let location = self.location();
         let Position { line, column } = location.range.start;
         DropdownItem::new({
@@ -30,10 +35,12 @@ impl QuickfixListItem {
         .set_info(self.info.clone())
         .set_group({
             let path = self.location().path.clone();
-            Some(
+            Some(if show_absolute_paths {
+                path.display_absolute()
+            } else {
                 path.display_relative()
-                    .unwrap_or_else(|_| path.display_absolute()),
-            )
+                    .unwrap_or_else(|_| path.display_absolute())
+            })
         })
         .set_dispatches(Dispatches::one(crate::app::Dispatch::GotoLocation(
             self.location().to_owned(),
@@ -55,6 +62,7 @@ impl QuickfixListItem {
 
 pub(crate) struct QuickfixList {
     dropdown: Dropdown,
+    buffers: Vec<Rc<RefCell<Buffer>>>,
     #[cfg(test)]
     items: Vec<QuickfixListItem>,
 }
@@ -63,6 +71,7 @@ impl QuickfixList {
     pub(crate) fn new(
         items: Vec<QuickfixListItem>,
         buffers: Vec<Rc<RefCell<Buffer>>>,
+        show_absolute_paths: bool,
     ) -> QuickfixList {
         let mut dropdown = Dropdown::new(DropdownConfig {
             title: "Quickfix".to_string(),
@@ -85,7 +94,10 @@ impl QuickfixList {
         dropdown.set_items(
             items
                 .iter()
-                .map(|item| item.to_owned().into_dropdown_item(&buffers))
+                .map(|item| {
+                    item.to_owned()
+                        .into_dropdown_item(&buffers, show_absolute_paths)
+                })
                 .collect(),
         );
 
@@ -93,6 +105,7 @@ impl QuickfixList {
             #[cfg(test)]
             items,
             dropdown,
+            buffers,
         }
     }
 
@@ -118,6 +131,22 @@ impl QuickfixList {
         self.dropdown.set_current_item_index(item_index);
         self
     }
+
+    /// Returns the lines surrounding the location of the current item,
+    /// which are read from the file if it is not opened.
+    pub(crate) fn current_item_preview(&self) -> Option<Info> {
+        let location = self
+            .dropdown
+            .current_item()?
+            .dispatches
+            .into_vec()
+            .into_iter()
+            .find_map(|dispatch| match dispatch {
+                Dispatch::GotoLocation(location) => Some(location),
+                _ => None,
+            })?;
+        location.preview("Preview", &self.buffers).ok()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -173,7 +202,7 @@ pub(crate) struct Location {
 }
 
 impl Location {
-    fn read_from_buffers(&self, buffers: &[Rc<RefCell<Buffer>>]) -> Option<String> {
+    fn find_buffer(&self, buffers: &[Rc<RefCell<Buffer>>]) -> Option<Rc<RefCell<Buffer>>> {
         buffers
             .iter()
             .find(|buffer| {
@@ -183,14 +212,44 @@ impl Location {
                     false
                 }
             })
-            .and_then(|buffer| {
-                Some(
-                    buffer
-                        .borrow()
-                        .get_line_by_line_index(self.range.start.line)?
-                        .to_string(),
-                )
-            })
+            .cloned()
+    }
+
+    /// Returns the lines surrounding the start of this location titled with `title`,
+    /// which are read from the file if it is not opened in any of `buffers`.
+    pub(crate) fn preview(
+        &self,
+        title: &str,
+        buffers: &[Rc<RefCell<Buffer>>],
+    ) -> anyhow::Result<Info> {
+        const CONTEXT_LINES_COUNT: usize = 3;
+        let rope = match self.find_buffer(buffers) {
+            Some(buffer) => buffer.borrow().rope().clone(),
+            None => Rope::from_str(&self.path.read()?),
+        };
+        let line = self.range.start.line;
+        let content = (line.saturating_sub(CONTEXT_LINES_COUNT)..=line + CONTEXT_LINES_COUNT)
+            .filter_map(|line_index| rope.get_line(line_index))
+            .map(|line| line.to_string())
+            .collect::<String>();
+        Ok(Info::new(
+            format!(
+                "{title} ({})",
+                self.path
+                    .display_relative()
+                    .unwrap_or_else(|_| self.path.display_absolute())
+            ),
+            content.trim_end().to_string(),
+        ))
+    }
+
+    fn read_from_buffers(&self, buffers: &[Rc<RefCell<Buffer>>]) -> Option<String> {
+        Some(
+            self.find_buffer(buffers)?
+                .borrow()
+                .get_line_by_line_index(self.range.start.line)?
+                .to_string(),
+        )
     }
 }
 
@@ -295,8 +354,11 @@ mod test_quickfix_list {
             },
             info: None,
         };
-        let quickfix_list =
-            QuickfixList::new(vec![foo.clone(), bar.clone(), spam.clone()], Vec::new());
+        let quickfix_list = QuickfixList::new(
+            vec![foo.clone(), bar.clone(), spam.clone()],
+            Vec::new(),
+            false,
+        );
         assert_eq!(quickfix_list.items(), vec![spam, foo, bar])
     }
 
@@ -320,7 +382,7 @@ mod test_quickfix_list {
         ]
         .to_vec();
 
-        let quickfix_list = QuickfixList::new(items, Vec::new());
+        let quickfix_list = QuickfixList::new(items, Vec::new(), false);
 
         assert_eq!(
             quickfix_list.items(),
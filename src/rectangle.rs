@@ -162,8 +162,7 @@ fn split(length: usize, count: usize) -> Vec<Split> {
 
 impl Rectangle {
     // A method to split a rectangle into two smaller ones based on a fixed ratio of 0.5 and return a border between them
-    #[cfg(test)]
-    fn split(&self, vertical: bool) -> (Rectangle, Rectangle, Border) {
+    pub(crate) fn split(&self, vertical: bool) -> (Rectangle, Rectangle, Border) {
         if vertical {
             // Split vertically
             let width1 = self.width / 2;
@@ -169,6 +169,73 @@ impl SelectionSet {
         Ok(())
     }
 
+    /// Merge selections that overlap each other into one selection that spans all of them.
+    /// Selections that merely touch each other are not merged.
+    pub(crate) fn merge_overlapping(&mut self) {
+        let sorted = self
+            .selections
+            .iter()
+            .enumerate()
+            .sorted_by_key(|(_, selection)| selection.extended_range().start)
+            .collect_vec();
+        let mut groups: Vec<(CharIndexRange, Vec<usize>)> = Vec::new();
+        for (index, selection) in sorted {
+            let range = selection.extended_range();
+            match groups.last_mut() {
+                Some((group_range, indices))
+                    if range.start < group_range.end || range == *group_range =>
+                {
+                    group_range.end = group_range.end.max(range.end);
+                    indices.push(index);
+                }
+                _ => groups.push((range, vec![index])),
+            }
+        }
+        if groups.len() == self.selections.len() {
+            return;
+        }
+        let cursor_index = groups
+            .iter()
+            .position(|(_, indices)| indices.contains(&self.cursor_index))
+            .unwrap_or_default();
+        // A merged selection keeps the initial range and info of the primary selection,
+        // or of its first selection if the primary selection is not one of them
+        let selections = groups
+            .into_iter()
+            .map(|(range, indices)| {
+                let index = indices
+                    .iter()
+                    .find(|index| **index == self.cursor_index)
+                    .or(indices.first());
+                match (index, indices.len()) {
+                    (Some(index), 1) => self.selections.get(*index).cloned(),
+                    (Some(index), _) => self
+                        .selections
+                        .get(*index)
+                        .map(|selection| selection.clone().set_range(range)),
+                    (None, _) => None,
+                }
+                .unwrap_or_else(|| Selection::new(range))
+            })
+            .collect_vec();
+        if let Some(selections) = NonEmpty::from_vec(selections) {
+            self.selections = selections;
+            self.cursor_index = cursor_index;
+        }
+    }
+
+    /// Add `selection` and make it the primary selection.
+    pub(crate) fn add_primary(&mut self, selection: Selection) {
+        self.selections.push(selection);
+        self.cursor_index = self.selections.len() - 1;
+    }
+
+    pub(crate) fn replace_primary(&mut self, selection: Selection) {
+        if let Some(primary) = self.selections.get_mut(self.cursor_index) {
+            *primary = selection
+        }
+    }
+
     pub(crate) fn add_all(
         &mut self,
         buffer: &Buffer,
@@ -268,6 +335,14 @@ impl SelectionSet {
         Self { mode, ..self }
     }
 
+    /// `cursor_index` is clamped to the last selection
+    pub(crate) fn set_cursor_index(self, cursor_index: usize) -> SelectionSet {
+        Self {
+            cursor_index: cursor_index.min(self.selections.len() - 1),
+            ..self
+        }
+    }
+
     pub(crate) fn secondary_selections(&self) -> Vec<&Selection> {
         self.selections
             .iter()
@@ -347,6 +422,10 @@ impl SelectionSet {
     pub(crate) fn selections(&self) -> &NonEmpty<Selection> {
         &self.selections
     }
+
+    pub(crate) fn cursor_index(&self) -> usize {
+        self.cursor_index
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -356,6 +435,10 @@ pub(crate) enum SelectionMode {
     SubWord,
     Word,
     Line,
+    /// Lines with the same indentation as the current line
+    SameIndent,
+    /// Runs of lines indented at least as deep as the current line
+    IndentBlock,
     Column,
     Custom,
     Find {
@@ -386,6 +469,9 @@ pub(crate) enum SelectionMode {
     // Mark
     Mark,
     LineFull,
+
+    // Spell check
+    Misspelling,
 }
 impl SelectionMode {
     pub(crate) fn is_node(&self) -> bool {
@@ -393,6 +479,44 @@ impl SelectionMode {
         matches!(self, SyntaxNode | SyntaxNodeFine)
     }
 
+    /// Parse the name of a selection mode that does not require any parameters,
+    /// e.g. `word` or `syntax-node`.
+    pub(crate) fn from_name(name: &str) -> anyhow::Result<SelectionMode> {
+        Ok(match name {
+            "column" | "character" => SelectionMode::Column,
+            "subword" => SelectionMode::SubWord,
+            "word" => SelectionMode::Word,
+            "line" => SelectionMode::Line,
+            "line-full" => SelectionMode::LineFull,
+            "same-indent" => SelectionMode::SameIndent,
+            "indent-block" => SelectionMode::IndentBlock,
+            "empty-line" => SelectionMode::EmptyLine,
+            "syntax-node" => SelectionMode::SyntaxNode,
+            "syntax-node-fine" => SelectionMode::SyntaxNodeFine,
+            "mark" => SelectionMode::Mark,
+            _ => return Err(anyhow::anyhow!("Unknown selection mode: {:?}", name)),
+        })
+    }
+
+    /// The name parsed by `SelectionMode::from_name`,
+    /// or `None` if this selection mode requires parameters.
+    pub(crate) fn name(&self) -> Option<&'static str> {
+        Some(match self {
+            SelectionMode::Column => "column",
+            SelectionMode::SubWord => "subword",
+            SelectionMode::Word => "word",
+            SelectionMode::Line => "line",
+            SelectionMode::LineFull => "line-full",
+            SelectionMode::SameIndent => "same-indent",
+            SelectionMode::IndentBlock => "indent-block",
+            SelectionMode::EmptyLine => "empty-line",
+            SelectionMode::SyntaxNode => "syntax-node",
+            SelectionMode::SyntaxNodeFine => "syntax-node-fine",
+            SelectionMode::Mark => "mark",
+            _ => return None,
+        })
+    }
+
     pub(crate) fn display(&self) -> String {
         match self {
             SelectionMode::SubWord => "SUB WORD".to_string(),
@@ -400,6 +524,8 @@ impl SelectionMode {
             SelectionMode::EmptyLine => "EMPTY LINE".to_string(),
             SelectionMode::Line => "LINE".to_string(),
             SelectionMode::LineFull => "FULL LINE".to_string(),
+            SelectionMode::SameIndent => "SAME INDENT".to_string(),
+            SelectionMode::IndentBlock => "INDENT BLOCK".to_string(),
             SelectionMode::Column => "COLUMN".to_string(),
             SelectionMode::Custom => "CUSTOM".to_string(),
             #[cfg(test)]
@@ -417,6 +543,7 @@ impl SelectionMode {
                 format!("GIT HUNK ({})", diff_mode.display()).to_string()
             }
             SelectionMode::Mark => "MARK".to_string(),
+            SelectionMode::Misspelling => "MISSPELLING".to_string(),
             SelectionMode::LocalQuickfix { title } => title.to_string(),
             SelectionMode::Till {
                 character,
@@ -441,6 +568,8 @@ impl SelectionMode {
             SelectionMode::Word => Box::new(selection_mode::WordLong::as_regex(buffer)?),
             SelectionMode::Line => Box::new(selection_mode::LineTrimmed),
             SelectionMode::LineFull => Box::new(selection_mode::LineFull),
+            SelectionMode::SameIndent => Box::new(selection_mode::SameIndent),
+            SelectionMode::IndentBlock => Box::new(selection_mode::IndentBlock),
             SelectionMode::Column => {
                 let current_column = buffer
                     .char_to_position(current_selection.to_char_index(cursor_direction))?
@@ -472,6 +601,7 @@ impl SelectionMode {
                 Box::new(selection_mode::GitHunk::new(diff_mode, buffer)?)
             }
             SelectionMode::Mark => Box::new(selection_mode::Mark),
+            SelectionMode::Misspelling => Box::new(selection_mode::Misspelling::new(buffer)),
             SelectionMode::EmptyLine => Box::new(selection_mode::Regex::new(buffer, r"(?m)^\s*$")?),
             SelectionMode::LocalQuickfix { .. } => {
                 Box::new(selection_mode::LocalQuickfix::new(params))
@@ -751,3 +881,33 @@ impl CharIndex {
         }
     }
 }
+
+#[cfg(test)]
+mod test_selection_set {
+    use super::*;
+
+    #[test]
+    fn merged_selections_keep_the_initial_range_and_info_of_the_primary_selection() {
+        let info = Info::new("Title".to_string(), "Content".to_string());
+        let mut selection_set = SelectionSet {
+            cursor_index: 1,
+            selections: NonEmpty {
+                head: Selection::new((CharIndex(0)..CharIndex(3)).into()),
+                tail: vec![Selection::new((CharIndex(2)..CharIndex(5)).into())
+                    .set_initial_range(Some((CharIndex(2)..CharIndex(4)).into()))
+                    .set_info(Some(info.clone()))],
+            },
+            mode: SelectionMode::Word,
+        };
+        selection_set.merge_overlapping();
+        assert_eq!(
+            selection_set.selections,
+            NonEmpty::singleton(
+                Selection::new((CharIndex(0)..CharIndex(5)).into())
+                    .set_initial_range(Some((CharIndex(2)..CharIndex(4)).into()))
+                    .set_info(Some(info))
+            )
+        );
+        assert_eq!(selection_set.cursor_index, 0);
+    }
+}
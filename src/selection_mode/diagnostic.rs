@@ -1,4 +1,4 @@
-use crate::{components::suggestive_editor::Info, quickfix_list::DiagnosticSeverityRange};
+use crate::quickfix_list::DiagnosticSeverityRange;
 
 use super::SelectionMode;
 
@@ -34,7 +34,7 @@ impl SelectionMode for Diagnostic {
                 .flat_map(|diagnostic| -> anyhow::Result<_> {
                     Ok(super::ByteRange::with_info(
                         buffer.char_index_range_to_byte_range(diagnostic.range)?,
-                        Info::new("Diagnostics".to_string(), diagnostic.message.clone()),
+                        diagnostic.info(),
                     ))
                 }),
         ))
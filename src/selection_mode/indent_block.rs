@@ -0,0 +1,77 @@
+use itertools::Itertools;
+
+use super::{ByteRange, SelectionMode, SelectionModeParams};
+
+/// The runs of lines indented at least as deep as the line of the current selection,
+/// like the bodies of the functions of a Python file.
+///
+/// Blank lines do not terminate a run, and tabs are counted as `tab_width` spaces.
+pub(crate) struct IndentBlock;
+
+fn indent_width(line: &str, tab_width: usize) -> usize {
+    line.chars()
+        .take_while(|char| char.is_whitespace() && !matches!(char, '\n' | '\r'))
+        .map(|char| if char == '\t' { tab_width } else { 1 })
+        .sum()
+}
+
+impl SelectionMode for IndentBlock {
+    fn iter<'a>(
+        &'a self,
+        SelectionModeParams {
+            buffer,
+            current_selection,
+            cursor_direction,
+            ..
+        }: SelectionModeParams<'a>,
+    ) -> anyhow::Result<Box<dyn Iterator<Item = ByteRange> + 'a>> {
+        let current_line =
+            buffer.char_to_line(current_selection.to_char_index(cursor_direction))?;
+        let tab_width = buffer.options().tab_width;
+        let lines = buffer
+            .content()
+            .lines()
+            .map(|line| line.to_string())
+            .collect_vec();
+        // `None` means the line is blank
+        let widths = lines
+            .iter()
+            .map(|line| (!line.trim().is_empty()).then(|| indent_width(line, tab_width)))
+            .collect_vec();
+        let level = widths
+            .iter()
+            .skip(current_line)
+            .flatten()
+            .next()
+            .copied()
+            .unwrap_or_default();
+
+        // The first and last non-blank lines of each block
+        let mut blocks = Vec::new();
+        let mut current_block: Option<(usize, usize)> = None;
+        for (line_index, width) in widths.into_iter().enumerate() {
+            match width {
+                None => {}
+                Some(width) if width >= level => {
+                    current_block = Some(match current_block {
+                        Some((first, _)) => (first, line_index),
+                        None => (line_index, line_index),
+                    })
+                }
+                Some(_) => blocks.extend(current_block.take()),
+            }
+        }
+        blocks.extend(current_block);
+
+        Ok(Box::new(blocks.into_iter().filter_map(
+            move |(first, last)| {
+                let first_line = lines.get(first)?;
+                let last_line = lines.get(last)?;
+                let start = buffer.line_to_byte(first).ok()? + first_line.len()
+                    - first_line.trim_start().len();
+                let end = buffer.line_to_byte(last).ok()? + last_line.trim_end().len();
+                Some(ByteRange::new(start..end))
+            },
+        )))
+    }
+}
@@ -0,0 +1,69 @@
+use itertools::Itertools;
+
+use crate::{buffer::Buffer, components::suggestive_editor::Info, spell_check::Dictionary};
+
+use super::{ByteRange, SelectionMode};
+
+/// Words within comments and strings that are not found in the dictionary.
+pub(crate) struct Misspelling {
+    ranges: Vec<ByteRange>,
+}
+
+impl Misspelling {
+    pub(crate) fn new(buffer: &Buffer) -> Misspelling {
+        Misspelling {
+            ranges: buffer.misspellings().to_vec(),
+        }
+    }
+
+    /// Prefer `Buffer::misspellings`, which caches the result of this function.
+    pub(crate) fn find(buffer: &Buffer, dictionary: &Dictionary) -> Vec<ByteRange> {
+        if dictionary.is_empty() {
+            return Vec::new();
+        }
+        let content = buffer.content();
+        let word_regex = lazy_regex::regex!(r"\p{Alphabetic}{2,}");
+        buffer
+            .traverse(tree_sitter_traversal::Order::Pre)
+            .into_iter()
+            .flatten()
+            .filter(|node| {
+                let kind = node.kind();
+                kind.contains("comment") || kind.contains("string")
+            })
+            .flat_map(|node| {
+                let range = node.byte_range();
+                word_regex
+                    .find_iter(&content[range.clone()])
+                    .map(move |word| {
+                        (
+                            range.start + word.start()..range.start + word.end(),
+                            word.as_str().to_string(),
+                        )
+                    })
+                    .collect_vec()
+            })
+            .filter(|(_, word)| !dictionary.contains(word))
+            .map(|(range, word)| {
+                ByteRange::with_info(
+                    range,
+                    Info::new(
+                        "Misspelling".to_string(),
+                        format!("{word:?} is not in the dictionary"),
+                    ),
+                )
+            })
+            .unique()
+            .sorted_by_key(|range| range.range.start)
+            .collect_vec()
+    }
+}
+
+impl SelectionMode for Misspelling {
+    fn iter<'a>(
+        &'a self,
+        _: super::SelectionModeParams<'a>,
+    ) -> anyhow::Result<Box<dyn Iterator<Item = super::ByteRange> + 'a>> {
+        Ok(Box::new(self.ranges.clone().into_iter()))
+    }
+}
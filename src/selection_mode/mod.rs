@@ -4,13 +4,16 @@ pub(crate) mod column;
 pub(crate) mod custom;
 pub(crate) mod diagnostic;
 pub(crate) mod git_hunk;
+pub(crate) mod indent_block;
 pub(crate) mod mark;
+pub(crate) mod misspelling;
 pub(crate) mod token;
 
 pub(crate) mod line_full;
 pub(crate) mod line_trimmed;
 pub(crate) mod local_quickfix;
 pub(crate) mod regex;
+pub(crate) mod same_indent;
 pub(crate) mod syntax_node;
 mod till;
 pub(crate) mod top_node;
@@ -23,11 +26,14 @@ pub(crate) use column::Column;
 pub(crate) use custom::Custom;
 pub(crate) use diagnostic::Diagnostic;
 pub(crate) use git_hunk::GitHunk;
+pub(crate) use indent_block::IndentBlock;
 use itertools::Itertools;
 pub(crate) use line_full::LineFull;
 pub(crate) use line_trimmed::LineTrimmed;
 pub(crate) use local_quickfix::LocalQuickfix;
 pub(crate) use mark::Mark;
+pub(crate) use misspelling::Misspelling;
+pub(crate) use same_indent::SameIndent;
 use std::ops::Range;
 pub(crate) use syntax_node::SyntaxNode;
 pub(crate) use till::Till;
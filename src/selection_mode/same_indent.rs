@@ -0,0 +1,69 @@
+use ropey::RopeSlice;
+
+use super::{ByteRange, SelectionMode, SelectionModeParams};
+
+/// The trimmed lines with the same indentation as the line of the current selection,
+/// so that moving skips the lines that are indented deeper, like the children of a list item.
+///
+/// The lines are looked up within the block of the current line only,
+/// which ends at the lines indented shallower, like the parent of a list item.
+pub(crate) struct SameIndent;
+
+/// Returns `None` if `line` is blank
+fn indentation(line: RopeSlice) -> Option<String> {
+    let indentation = line
+        .chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .collect::<String>();
+    line.chars()
+        .skip(indentation.chars().count())
+        .any(|c| !c.is_whitespace())
+        .then_some(indentation)
+}
+
+impl SelectionMode for SameIndent {
+    fn iter<'a>(
+        &'a self,
+        SelectionModeParams {
+            buffer,
+            current_selection,
+            cursor_direction,
+            ..
+        }: SelectionModeParams<'a>,
+    ) -> anyhow::Result<Box<dyn Iterator<Item = ByteRange> + 'a>> {
+        let rope = buffer.rope();
+        let current_line =
+            buffer.char_to_line(current_selection.to_char_index(cursor_direction))?;
+        let Some(indent) = rope.get_line(current_line).and_then(indentation) else {
+            return Ok(Box::new(std::iter::empty()));
+        };
+        let is_within_block = |line_index: &usize| {
+            rope.get_line(*line_index).map_or(false, |line| {
+                indentation(line).map_or(true, |line_indent| line_indent.len() >= indent.len())
+            })
+        };
+        let first_line = (0..current_line)
+            .rev()
+            .take_while(is_within_block)
+            .last()
+            .unwrap_or(current_line);
+        let last_line = (current_line + 1..rope.len_lines())
+            .take_while(is_within_block)
+            .last()
+            .unwrap_or(current_line);
+        let ranges = (first_line..=last_line)
+            .filter_map(|line_index| {
+                let line = rope.get_line(line_index)?;
+                if indentation(line)? != indent {
+                    return None;
+                }
+                let line_start = rope.try_line_to_byte(line_index).ok()?;
+                let content_length = line.to_string().trim_end().len();
+                Some(ByteRange::new(
+                    line_start + indent.len()..line_start + content_length,
+                ))
+            })
+            .collect::<Vec<_>>();
+        Ok(Box::new(ranges.into_iter()))
+    }
+}
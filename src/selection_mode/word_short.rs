@@ -6,7 +6,7 @@ impl WordShort {
     pub(crate) fn as_regex(buffer: &Buffer) -> anyhow::Result<super::Regex> {
         super::Regex::from_config(
             buffer,
-            r"((([a-z]+)|(([A-Z]{2,})+)|([A-Z][a-z]*))_*)|([^\w\s]|_)|[0-9]+",
+            r"(((\p{Ll}+)|((\p{Lu}{2,})+)|(\p{Lu}\p{Ll}*)|([\p{Lo}\p{Lm}]+))_*)|([^\w\s]|_)|[0-9]+",
             crate::list::grep::RegexConfig {
                 escaped: false,
                 case_sensitive: true,
@@ -51,4 +51,20 @@ mod test_word_short {
             ],
         );
     }
+
+    #[test]
+    fn non_ascii_words() {
+        let buffer = Buffer::new(None, "éclairÉtoile 日本語 naïve_über");
+        WordShort::as_regex(&buffer).unwrap().assert_all_selections(
+            &buffer,
+            Selection::default(),
+            &[
+                (0..7, "éclair"),
+                (7..14, "Étoile"),
+                (15..24, "日本語"),
+                (25..32, "naïve_"),
+                (32..37, "über"),
+            ],
+        );
+    }
 }
@@ -0,0 +1,106 @@
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// Incremented whenever the format of `Session` changes,
+/// so that a session of another version is rejected instead of being misread,
+/// refer `Session::read`
+pub(crate) const SESSION_VERSION: u64 = 1;
+
+/// The opened files and their selections, refer `App::save_session` and `App::load_session`
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct Session {
+    pub(crate) version: u64,
+    pub(crate) files: Vec<SessionFile>,
+    /// The file of the focused editor
+    pub(crate) current_file: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct SessionFile {
+    pub(crate) path: PathBuf,
+    /// The name of the selection mode, refer `SelectionMode::name`,
+    /// which is `None` for selection modes that require parameters
+    pub(crate) selection_mode: Option<String>,
+    /// The char index ranges of the selections
+    pub(crate) selections: Vec<Range<usize>>,
+    /// The index of the primary selection in `selections`
+    pub(crate) cursor_index: usize,
+}
+
+impl Session {
+    pub(crate) fn new(files: Vec<SessionFile>, current_file: Option<PathBuf>) -> Self {
+        Self {
+            version: SESSION_VERSION,
+            files,
+            current_file,
+        }
+    }
+
+    /// Only sessions of the current version can be read, the version is checked
+    /// before the rest of the session is parsed.
+    pub(crate) fn read(path: &Path) -> anyhow::Result<Session> {
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        let version = json
+            .get("version")
+            .and_then(|version| version.as_u64())
+            .ok_or_else(|| anyhow::anyhow!("The session has no version"))?;
+        match version {
+            SESSION_VERSION => Ok(serde_json::from_value(json)?),
+            version => Err(anyhow::anyhow!(
+                "Unable to read session of version {version}, \
+                 the latest supported version is {SESSION_VERSION}"
+            )),
+        }
+    }
+
+    pub(crate) fn write(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_session {
+    use super::*;
+
+    #[test]
+    fn read_written_session() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join("session.json");
+        let session = Session::new(
+            [SessionFile {
+                path: "src/main.rs".into(),
+                selection_mode: Some("word".to_string()),
+                selections: [0..3, 5..5].to_vec(),
+                cursor_index: 1,
+            }]
+            .to_vec(),
+            Some("src/main.rs".into()),
+        );
+        session.write(&path)?;
+        assert_eq!(Session::read(&path)?, session);
+        Ok(())
+    }
+
+    #[test]
+    fn reject_other_versions() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join("session.json");
+        std::fs::write(
+            &path,
+            r#"{"version": 2, "files": [], "current_file": null}"#,
+        )?;
+        assert!(Session::read(&path).is_err());
+        std::fs::write(
+            &path,
+            r#"{"version": 0, "files": [], "current_file": null}"#,
+        )?;
+        assert!(Session::read(&path).is_err());
+        Ok(())
+    }
+}
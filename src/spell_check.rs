@@ -0,0 +1,59 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use once_cell::sync::Lazy;
+use shared::canonicalized_path::CanonicalizedPath;
+
+/// The dictionary of the system, which can be overridden by the `KI_DICTIONARY` environment variable.
+const DEFAULT_DICTIONARY_PATH: &str = "/usr/share/dict/words";
+
+/// The path, relative to the project root, of the custom word list.
+/// Each line of this file is a word that should not be reported as misspelled.
+pub(crate) const CUSTOM_WORD_LIST_PATH: &str = ".ki/words.txt";
+
+static SYSTEM_DICTIONARY: Lazy<HashSet<String>> = Lazy::new(|| {
+    let path = std::env::var("KI_DICTIONARY")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(DEFAULT_DICTIONARY_PATH));
+    std::fs::read_to_string(path)
+        .map(|content| parse_words(&content))
+        .unwrap_or_default()
+});
+
+#[derive(Clone)]
+pub(crate) struct Dictionary {
+    custom_words: HashSet<String>,
+}
+
+impl Dictionary {
+    /// Loads the custom word list of the nearest ancestor directory of `path` that has one.
+    pub(crate) fn new(path: Option<&CanonicalizedPath>) -> Self {
+        let custom_words = path
+            .and_then(|path| {
+                path.to_path_buf().ancestors().find_map(|directory| {
+                    std::fs::read_to_string(directory.join(CUSTOM_WORD_LIST_PATH)).ok()
+                })
+            })
+            .map(|content| parse_words(&content))
+            .unwrap_or_default();
+        Self { custom_words }
+    }
+
+    /// Spell checking is disabled when there's no word to check against,
+    /// otherwise every word would be reported as misspelled.
+    pub(crate) fn is_empty(&self) -> bool {
+        SYSTEM_DICTIONARY.is_empty() && self.custom_words.is_empty()
+    }
+
+    pub(crate) fn contains(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        SYSTEM_DICTIONARY.contains(&word) || self.custom_words.contains(&word)
+    }
+}
+
+fn parse_words(content: &str) -> HashSet<String> {
+    content
+        .lines()
+        .map(|line| line.trim().to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
@@ -1,7 +1,12 @@
 use itertools::Itertools;
+use ropey::Rope;
 
 use crate::selection::CharIndex;
 
+/// The number of characters scanned on each side of the cursor by
+/// `get_surrounding_indices_within_window`, so that large files are not scanned entirely.
+const SCAN_WINDOW: usize = 10_000;
+
 #[derive(Clone, Debug, PartialEq, Eq, Copy)]
 pub(crate) enum EnclosureKind {
     Parentheses,
@@ -83,7 +88,40 @@ pub(crate) fn get_surrounding_indices(
     Some((open_index, close_index))
 }
 
+/// Same as `get_surrounding_indices`, but only the characters within `SCAN_WINDOW`
+/// of the cursor are scanned, so the pair is not found if it is farther away.
+pub(crate) fn get_surrounding_indices_within_window(
+    rope: &Rope,
+    kind: EnclosureKind,
+    cursor_char_index: CharIndex,
+) -> Option<(CharIndex, CharIndex)> {
+    let start = cursor_char_index.0.saturating_sub(SCAN_WINDOW);
+    let end = (cursor_char_index.0 + SCAN_WINDOW).min(rope.len_chars());
+    let content = rope.get_slice(start..end)?.to_string();
+    let (open_index, close_index) =
+        get_surrounding_indices(&content, kind, cursor_char_index - start)?;
+    Some((open_index + start, close_index + start))
+}
+
 impl EnclosureKind {
+    /// Returns the enclosure of which `c` is either the open or close symbol
+    pub(crate) fn from_char(c: char) -> Option<EnclosureKind> {
+        [
+            EnclosureKind::Parentheses,
+            EnclosureKind::CurlyBraces,
+            EnclosureKind::AngularBrackets,
+            EnclosureKind::SquareBrackets,
+            EnclosureKind::DoubleQuotes,
+            EnclosureKind::SingleQuotes,
+            EnclosureKind::Backticks,
+        ]
+        .into_iter()
+        .find(|enclosure| {
+            let (open, close) = enclosure.open_close_symbols();
+            c == open || c == close
+        })
+    }
+
     pub(crate) const fn open_close_symbols(&self) -> (char, char) {
         match self {
             EnclosureKind::Parentheses => ('(', ')'),
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, ops::Range, sync::mpsc::Sender, time::Duration};
+use std::{cell::RefCell, collections::HashMap, ops::Range, sync::mpsc::Sender, time::Duration};
 
 use tree_sitter_highlight::{HighlightConfiguration, HighlightEvent, Highlighter};
 
@@ -50,14 +50,24 @@ impl GetHighlightConfig for Language {
 }
 
 pub trait Highlight {
-    fn highlight(&self, source_code: &str) -> anyhow::Result<HighlighedSpans>;
+    /// `injection_callback` returns the highlight configuration of the given injected language
+    fn highlight<'a>(
+        &'a self,
+        source_code: &'a str,
+        injection_callback: impl FnMut(&str) -> Option<&'a HighlightConfiguration> + 'a,
+    ) -> anyhow::Result<HighlighedSpans>;
 }
 
 impl Highlight for HighlightConfiguration {
-    fn highlight(&self, source_code: &str) -> anyhow::Result<HighlighedSpans> {
+    fn highlight<'a>(
+        &'a self,
+        source_code: &'a str,
+        injection_callback: impl FnMut(&str) -> Option<&'a HighlightConfiguration> + 'a,
+    ) -> anyhow::Result<HighlighedSpans> {
         let mut highlighter = Highlighter::new();
 
-        let highlights = highlighter.highlight(self, source_code.as_bytes(), None, |_| None)?;
+        let highlights =
+            highlighter.highlight(self, source_code.as_bytes(), None, injection_callback)?;
 
         let mut highlight = None;
 
@@ -156,23 +166,62 @@ impl HighlightConfigs {
         language: Language,
         source_code: &str,
     ) -> Result<HighlighedSpans, anyhow::Error> {
-        let Some(grammar_id) = language.tree_sitter_grammar_id() else {
+        let Some(grammar_id) = self.load(&language)? else {
             return Ok(Default::default());
         };
-        let config = match self.0.get(&grammar_id) {
-            Some(config) => config,
-            None => {
-                if let Some(highlight_config) = language.get_highlight_config()? {
-                    self.0.insert(grammar_id.clone(), highlight_config);
-                    let get_error = || {
-                        anyhow::anyhow!("Unreachable: should be able to obtain a value that is inserted to the HashMap")
-                    };
-                    self.0.get(&grammar_id).ok_or_else(get_error)?
-                } else {
-                    return Ok(Default::default());
-                }
+        // The injected languages are only known while highlighting,
+        // so the missing ones are loaded afterwards, and the source code is highlighted again.
+        let missing_languages = RefCell::new(Vec::new());
+        let highlighted_spans =
+            self.highlight_loaded(&grammar_id, source_code, &missing_languages)?;
+        let missing_languages = missing_languages.into_inner();
+        if missing_languages.is_empty() {
+            return Ok(highlighted_spans);
+        }
+        for language in missing_languages {
+            if let Err(error) = self.load(&language) {
+                log::info!(
+                    "Failed to load the highlight config of injection: {:?}",
+                    error
+                )
             }
+        }
+        self.highlight_loaded(&grammar_id, source_code, &Default::default())
+    }
+
+    /// Returns the grammar ID of `language` if its highlight configuration is available.
+    fn load(&mut self, language: &Language) -> anyhow::Result<Option<TreeSitterGrammarId>> {
+        let Some(grammar_id) = language.tree_sitter_grammar_id() else {
+            return Ok(None);
         };
-        config.highlight(source_code)
+        if !self.0.contains_key(&grammar_id) {
+            let Some(highlight_config) = language.get_highlight_config()? else {
+                return Ok(None);
+            };
+            self.0.insert(grammar_id.clone(), highlight_config);
+        }
+        Ok(Some(grammar_id))
+    }
+
+    /// The injected languages which are not loaded yet are added to `missing_languages`.
+    fn highlight_loaded(
+        &self,
+        grammar_id: &str,
+        source_code: &str,
+        missing_languages: &RefCell<Vec<Language>>,
+    ) -> anyhow::Result<HighlighedSpans> {
+        let config = self.0.get(grammar_id).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unreachable: should be able to obtain a value that is inserted to the HashMap"
+            )
+        })?;
+        config.highlight(source_code, |name| {
+            let language = shared::language::from_injection_name(name)?;
+            let config = self.0.get(&language.tree_sitter_grammar_id()?);
+            if config.is_none() {
+                missing_languages.borrow_mut().push(language)
+            }
+            config
+        })
     }
 }
@@ -7,9 +7,12 @@ use itertools::Itertools;
 use lsp_types::Url;
 use my_proc_macros::{hex, key, keys};
 
-use serial_test::serial;
-
-use std::{ops::Range, path::PathBuf, rc::Rc, sync::Mutex};
+use std::{
+    ops::Range,
+    path::PathBuf,
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
 pub(crate) use Dispatch::*;
 pub(crate) use DispatchEditor::*;
 
@@ -23,10 +26,11 @@ use crate::{
         App, Dimension, Dispatch, LocalSearchConfigUpdate, RequestParams, Scope,
         StatusLineComponent,
     },
+    buffer::LOADING_PLACEHOLDER,
     char_index_range::CharIndexRange,
     clipboard::CopiedTexts,
     components::{
-        component::Component,
+        component::{Component, SetCursorStyle},
         editor::{Direction, DispatchEditor, IfCurrentNotFound, Mode, Movement, ViewAlignment},
         suggestive_editor::{DispatchSuggestiveEditor, Info, SuggestiveEditorFilter},
     },
@@ -38,14 +42,17 @@ use crate::{
     lsp::{
         code_action::CodeAction,
         completion::{Completion, CompletionItem, CompletionItemEdit, PositionalEdit},
+        diagnostic::PulledDiagnostics,
         documentation::Documentation,
+        goto_definition_response::GotoDefinitionResponse,
+        manager::LanguageConfig,
         process::FromEditor,
         signature_help::SignatureInformation,
         workspace_edit::{TextDocumentEdit, WorkspaceEdit},
     },
     position::Position,
     quickfix_list::{DiagnosticSeverityRange, Location, QuickfixListItem},
-    selection::SelectionMode,
+    selection::{CharIndex, SelectionMode},
     style::Style,
     themes::Theme,
     ui_tree::ComponentKind,
@@ -61,6 +68,8 @@ pub(crate) enum Step {
     SuggestiveEditor(DispatchSuggestiveEditor),
     ExpectLater(Box<dyn Fn() -> ExpectKind>),
     ExpectCustom(Box<dyn Fn()>),
+    /// Wait until the running global search completes
+    WaitForGlobalSearch,
 }
 
 #[derive(Debug, Clone)]
@@ -102,16 +111,30 @@ pub(crate) enum ExpectKind {
     GridCellStyleKey(Position, Option<StyleKey>),
     HighlightSpans(std::ops::Range<usize>, StyleKey),
     DiagnosticsRanges(Vec<CharIndexRange>),
+    CurrentDiagnosticMessages(&'static [&'static str]),
+    FoldedTexts(&'static [&'static str]),
     BufferQuickfixListItems(Vec<Range<Position>>),
     ComponentCount(usize),
     CurrentComponentPath(Option<CanonicalizedPath>),
     OpenedFilesCount(usize),
     QuickfixListInfo(&'static str),
+    QuickfixListPreview(&'static str),
+    QuickfixListPreviewOpen(bool),
     ComponentsOrder(Vec<ComponentKind>),
     CurrentComponentTitle(&'static str),
     CurrentSelectionMode(SelectionMode),
     LspRequestSent(FromEditor),
     CurrentCopiedTextHistoryOffset(isize),
+    LocalClipboardContent(&'static str),
+    RegisterContent(char, &'static str),
+    PendingDeleteRanges(Option<Vec<CharIndexRange>>),
+    LongLineOverflows(Vec<CharIndexRange>),
+    SearchHighlights(Vec<CharIndexRange>),
+    HighlightedSearch(Option<&'static str>),
+    BufferVar(&'static str, Option<&'static str>),
+    QuitRequested(bool),
+    StatusLineSegments(&'static [&'static str]),
+    CurrentCursorStyle(SetCursorStyle),
 }
 fn log<T: std::fmt::Debug>(s: T) {
     println!("===========\n{s:?}",);
@@ -313,6 +336,30 @@ impl ExpectKind {
                     .map(|d| d.range)
                     .collect_vec(),
             ),
+            CurrentDiagnosticMessages(expected) => contextualize(
+                to_vec(expected),
+                app.current_component()
+                    .borrow()
+                    .editor()
+                    .buffer()
+                    .diagnostics()
+                    .into_iter()
+                    .map(|d| d.message)
+                    .collect_vec(),
+            ),
+            FoldedTexts(expected) => {
+                let component = app.current_component();
+                let borrow = component.borrow();
+                let buffer = borrow.editor().buffer();
+                contextualize(
+                    to_vec(expected),
+                    buffer
+                        .folds()
+                        .iter()
+                        .map(|fold| buffer.slice(fold).map(|text| text.to_string()))
+                        .collect::<anyhow::Result<Vec<_>>>()?,
+                )
+            }
             BufferQuickfixListItems(expected) => contextualize(
                 expected,
                 &app.current_component()
@@ -332,6 +379,60 @@ impl ExpectKind {
             QuickfixListInfo(expected) => {
                 contextualize(*expected, &app.quickfix_list_info().unwrap())
             }
+            PendingDeleteRanges(expected) => contextualize(
+                expected.as_ref(),
+                component.borrow().editor().pending_delete(),
+            ),
+            LongLineOverflows(expected) => {
+                let editor = component.borrow();
+                let editor = editor.editor();
+                contextualize(
+                    expected.clone(),
+                    editor.long_line_overflows(context, 0..editor.buffer().len_lines()),
+                )
+            }
+            QuitRequested(expected) => contextualize(*expected, app.take_quit_requested()),
+            BufferVar(key, expected) => contextualize(
+                expected.map(|value| value.to_string()),
+                component
+                    .borrow()
+                    .editor()
+                    .buffer()
+                    .var(key)
+                    .map(|value| value.to_string()),
+            ),
+            SearchHighlights(expected) => {
+                let editor = component.borrow();
+                let editor = editor.editor();
+                contextualize(
+                    expected.clone(),
+                    editor.search_matches(context, 0..editor.buffer().len_lines())?,
+                )
+            }
+            HighlightedSearch(expected) => contextualize(
+                expected.map(|search| search.to_string()),
+                context.highlighted_search().map(|search| search.search),
+            ),
+            LocalClipboardContent(expected) => contextualize(
+                expected.to_string(),
+                context
+                    .get_clipboard_content(false, 0)?
+                    .map(|copied_texts| copied_texts.get(0))
+                    .unwrap_or_default(),
+            ),
+            RegisterContent(register, expected) => contextualize(
+                expected.to_string(),
+                context
+                    .get_register_content(*register)
+                    .map(|copied_texts| copied_texts.get(0))
+                    .unwrap_or_default(),
+            ),
+            QuickfixListPreview(expected) => {
+                contextualize(*expected, &app.quickfix_list_preview().unwrap())
+            }
+            QuickfixListPreviewOpen(expected) => {
+                contextualize(*expected, &app.quickfix_list_preview().is_some())
+            }
             ComponentsOrder(expected) => contextualize(expected, &app.components_order()),
             CurrentComponentTitle(expected) => {
                 contextualize(*expected, &app.current_component().borrow().title(context))
@@ -355,6 +456,13 @@ impl ExpectKind {
                     .editor()
                     .primary_selection()?,
             ),
+            StatusLineSegments(expected) => {
+                contextualize(to_vec(expected), app.status_line_segments())
+            }
+            CurrentCursorStyle(expected) => contextualize(
+                expected,
+                &app.current_component().borrow().editor().cursor_style(),
+            ),
         })
     }
 }
@@ -463,6 +571,7 @@ fn execute_test_helper(
                     log(dispatch);
                     app.handle_dispatch_suggestive_editor(dispatch.to_owned())?
                 }
+                WaitForGlobalSearch => app.wait_for_global_search()?,
             };
         }
 
@@ -488,6 +597,7 @@ fn run_test(
             status_line_components.clone(),
         )?;
         app.disable_lsp();
+        app.use_fake_system_clipboard();
         callback(app, temp_dir)?;
         use std::borrow::Borrow;
         let output = frontend.lock().unwrap().borrow().string_content();
@@ -756,6 +866,233 @@ fn multi_paste() -> anyhow::Result<()> {
     })
 }
 
+#[test]
+fn go_to_definition_and_jump_back() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("fn main() { foo::Foo::default(); }".to_string())),
+            Editor(MatchLiteral("Foo".to_string())),
+            App(RequestDefinitions(Scope::Local)),
+            Expect(LspRequestSent(FromEditor::TextDocumentDefinition(
+                RequestParams {
+                    path: s.main_rs(),
+                    position: Position::new(0, 17),
+                    context: Default::default(),
+                }
+                .set_kind(Some(Scope::Local))
+                .set_description("Definitions"),
+            ))),
+            App(HandleLspNotification(LspNotification::Definition(
+                crate::lsp::process::ResponseContext::default(),
+                GotoDefinitionResponse::Single(Location {
+                    path: s.foo_rs(),
+                    range: Position::new(0, 18)..Position::new(0, 21),
+                }),
+            ))),
+            Expect(CurrentPath(s.foo_rs())),
+            Expect(CurrentSelectedTexts(&["Foo"])),
+            App(JumpBack),
+            Expect(CurrentPath(s.main_rs())),
+            Expect(CurrentSelectedTexts(&["Foo"])),
+            Expect(EditorCursorPosition(Position::new(0, 17))),
+            App(JumpForward),
+            Expect(CurrentPath(s.foo_rs())),
+            Expect(CurrentSelectedTexts(&["Foo"])),
+        ])
+    })
+}
+
+#[test]
+fn jump_back_records_the_current_location() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("fn main() { foo::Foo::default(); }".to_string())),
+            Editor(MatchLiteral("Foo".to_string())),
+            App(HandleLspNotification(LspNotification::Definition(
+                crate::lsp::process::ResponseContext::default(),
+                GotoDefinitionResponse::Single(Location {
+                    path: s.foo_rs(),
+                    range: Position::new(0, 18)..Position::new(0, 21),
+                }),
+            ))),
+            // Move away from the jumped location
+            Editor(MatchLiteral("foo()".to_string())),
+            App(JumpBack),
+            Expect(CurrentPath(s.main_rs())),
+            Expect(CurrentSelectedTexts(&["Foo"])),
+            // Jumping forward returns to where the cursor was before jumping back
+            App(JumpForward),
+            Expect(CurrentPath(s.foo_rs())),
+            Expect(CurrentSelectedTexts(&["foo()"])),
+        ])
+    })
+}
+
+#[test]
+fn document_symbols_filtered_by_kind() -> anyhow::Result<()> {
+    execute_test(|s| {
+        let symbol =
+            |name: &str, kind: lsp_types::SymbolKind, line: usize| crate::lsp::symbols::Symbol {
+                name: name.to_string(),
+                kind,
+                location: Location {
+                    path: s.main_rs(),
+                    range: Position::new(line, 0)..Position::new(line, 1),
+                },
+                container_name: None,
+            };
+        let context = crate::lsp::process::ResponseContext {
+            symbol_kinds: [
+                lsp_types::SymbolKind::FUNCTION,
+                lsp_types::SymbolKind::METHOD,
+                lsp_types::SymbolKind::CONSTRUCTOR,
+            ]
+            .to_vec(),
+            ..Default::default()
+        };
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            // Pick functions
+            App(HandleKeyEvents(keys!("space S f").to_vec())),
+            Expect(LspRequestSent(FromEditor::TextDocumentDocumentSymbol(
+                RequestParams {
+                    path: s.main_rs(),
+                    position: Position::new(0, 0),
+                    context: context.clone(),
+                }
+                .set_description("Document Symbols"),
+            ))),
+            App(HandleLspNotification(LspNotification::Symbols(
+                context.set_description("Document Symbols"),
+                crate::lsp::symbols::Symbols {
+                    symbols: [
+                        symbol("spongebob_function", lsp_types::SymbolKind::FUNCTION, 0),
+                        symbol("patrick_variable", lsp_types::SymbolKind::VARIABLE, 1),
+                        symbol("squidward_function", lsp_types::SymbolKind::FUNCTION, 2),
+                    ]
+                    .to_vec(),
+                },
+            ))),
+            Expect(AppGridContains("spongebob_function")),
+            Expect(AppGridContains("squidward_function")),
+            Expect(Not(Box::new(AppGridContains("patrick_variable")))),
+        ])
+    })
+}
+
+#[test]
+fn hover() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("let x = foo.bar();".to_string())),
+            Editor(MatchLiteral("bar".to_string())),
+            Editor(SwapCursorWithAnchor),
+            App(RequestHover),
+            // Hover is requested for the symbol of the selection, not the cursor
+            Expect(LspRequestSent(FromEditor::TextDocumentHover(
+                RequestParams {
+                    path: s.main_rs(),
+                    position: Position::new(0, 12),
+                    context: Default::default(),
+                }
+                .set_description("Hover"),
+            ))),
+            App(HandleLspNotification(LspNotification::Hover(
+                crate::lsp::hover::Hover {
+                    contents: [
+                        "```rust\nfn bar(&self) -> **Bar**\n```".to_string(),
+                        "Returns the **bar** of `Foo`".to_string(),
+                    ]
+                    .to_vec(),
+                    kind: lsp_types::MarkupKind::Markdown,
+                },
+            ))),
+            Expect(ComponentsLength(2)),
+            Expect(EditorInfoContent(
+                "```rust\nfn bar(&self) -> **Bar**\n```\n\nReturns the bar of Foo",
+            )),
+            App(HandleKeyEvent(key!("esc"))),
+            Expect(ComponentsLength(1)),
+            // Plain text is not rendered as Markdown
+            App(HandleLspNotification(LspNotification::Hover(
+                crate::lsp::hover::Hover {
+                    contents: ["Returns the **bar** of `Foo`".to_string()].to_vec(),
+                    kind: lsp_types::MarkupKind::PlainText,
+                },
+            ))),
+            Expect(EditorInfoContent("Returns the **bar** of `Foo`")),
+        ])
+    })
+}
+
+#[test]
+fn format_with_lsp() -> anyhow::Result<()> {
+    execute_test(|s| {
+        let edit = |start: Position, end: Position, new_text: &str| {
+            crate::lsp::completion::PositionalEdit {
+                range: start..end,
+                new_text: new_text.to_string(),
+            }
+        };
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("fn main() {\nlet x  =  1;\n}".to_string())),
+            Editor(MatchLiteral("1".to_string())),
+            App(Format),
+            Expect(LspRequestSent(FromEditor::TextDocumentFormatting {
+                params: RequestParams {
+                    path: s.main_rs(),
+                    position: Position::new(1, 10),
+                    context: Default::default(),
+                },
+                tab_size: 4,
+            })),
+            App(HandleLspNotification(LspNotification::Formatting {
+                path: s.main_rs(),
+                edits: [
+                    edit(Position::new(1, 0), Position::new(1, 0), "    "),
+                    edit(Position::new(1, 5), Position::new(1, 7), " "),
+                    edit(Position::new(1, 8), Position::new(1, 10), " "),
+                ]
+                .to_vec(),
+            })),
+            Expect(CurrentComponentContent("fn main() {\n    let x = 1;\n}")),
+            // The cursor is shifted by the edits preceding it
+            Expect(CurrentSelectedTexts(&["1"])),
+        ])
+    })
+}
+
+#[test]
+fn format_with_lsp_applies_to_formatted_file() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("fn main() {\nlet x = 1;\n}".to_string())),
+            App(Format),
+            // Another file is opened before the response arrives
+            App(OpenFile(s.foo_rs())),
+            App(HandleLspNotification(LspNotification::Formatting {
+                path: s.main_rs(),
+                edits: [crate::lsp::completion::PositionalEdit {
+                    range: Position::new(1, 0)..Position::new(1, 0),
+                    new_text: "    ".to_string(),
+                }]
+                .to_vec(),
+            })),
+            Expect(CurrentPath(s.foo_rs())),
+            Expect(FileContent(
+                s.main_rs(),
+                "fn main() {\n    let x = 1;\n}".to_string(),
+            )),
+            Expect(FileContent(s.foo_rs(), s.foo_rs().read().unwrap())),
+        ])
+    })
+}
+
 #[test]
 fn signature_help() -> anyhow::Result<()> {
     execute_test(|s| {
@@ -825,6 +1162,38 @@ fn signature_help() -> anyhow::Result<()> {
     })
 }
 
+#[test]
+fn surround_with_prompted_character() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo bar".to_string())),
+            Editor(MatchLiteral("bar".to_string())),
+            App(OpenSurroundWithPrompt),
+            App(HandleKeyEvents(keys!("! enter").to_vec())),
+            Expect(CurrentComponentContent("foo !bar!")),
+            App(OpenSurroundWithPrompt),
+            App(HandleKeyEvents(keys!("( enter").to_vec())),
+            Expect(CurrentComponentContent("foo (!bar!)")),
+        ])
+    })
+}
+
+#[test]
+fn select_next_node_of_prompted_kind() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("fn f() { let x = 1; }".to_string())),
+            App(OpenSelectNextNodeOfKindPrompt),
+            App(HandleKeyEvents(
+                keys!("l e t _ d e c l a r a t i o n enter").to_vec(),
+            )),
+            Expect(CurrentSelectedTexts(&["let x = 1;"])),
+        ])
+    })
+}
+
 #[test]
 pub(crate) fn repo_git_hunks() -> Result<(), anyhow::Error> {
     execute_test(|s| {
@@ -902,8 +1271,11 @@ pub(crate) fn non_git_ignored_files() -> Result<(), anyhow::Error> {
             // Add a new Rust file
             App(AddPath(s.new_path("src/rust.rs").display().to_string())),
             ExpectCustom(Box::new(move || {
-                let paths = crate::list::WalkBuilderConfig::non_git_ignored_files(temp_dir.clone())
-                    .unwrap();
+                let paths = crate::list::WalkBuilderConfig::non_git_ignored_files(
+                    temp_dir.clone(),
+                    &Default::default(),
+                )
+                .unwrap();
 
                 // Expect all the paths are files, not directory for example
                 assert!(paths.iter().all(|file| file.is_file()));
@@ -1050,7 +1422,7 @@ fn local_lsp_references() -> anyhow::Result<()> {
             App(HandleLspNotification(LspNotification::References(
                 crate::lsp::process::ResponseContext {
                     scope: Some(Scope::Local),
-                    description: None,
+                    ..Default::default()
                 },
                 [
                     Location {
@@ -1070,11 +1442,115 @@ fn local_lsp_references() -> anyhow::Result<()> {
     })
 }
 
+#[test]
+fn goto_next_and_previous_reference() -> anyhow::Result<()> {
+    execute_test(|s| {
+        let location = |column: usize| Location {
+            path: s.main_rs(),
+            range: Position { line: 0, column }..Position {
+                line: 0,
+                column: column + 1,
+            },
+        };
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("let x = 1; x + x;".to_string())),
+            Editor(MatchLiteral("x".to_string())),
+            Editor(GotoNextReference),
+            // Mock the response of the LSP server, in an unsorted order
+            App(HandleLspNotification(LspNotification::References(
+                crate::lsp::process::ResponseContext {
+                    goto_reference: Some(Direction::End),
+                    ..Default::default()
+                },
+                [location(15), location(4), location(11)].to_vec(),
+            ))),
+            Expect(EditorCursorPosition(Position::new(0, 11))),
+            Editor(GotoNextReference),
+            Expect(EditorCursorPosition(Position::new(0, 15))),
+            // Wrap around
+            Editor(GotoNextReference),
+            Expect(EditorCursorPosition(Position::new(0, 4))),
+            Editor(GotoPreviousReference),
+            Expect(EditorCursorPosition(Position::new(0, 15))),
+            Editor(GotoPreviousReference),
+            Expect(EditorCursorPosition(Position::new(0, 11))),
+            // The cached references are not used once the document is changed
+            Editor(EnterInsertMode(Direction::Start)),
+            Editor(Insert("y".to_string())),
+            Editor(EnterNormalMode),
+            Editor(MatchLiteral("yx".to_string())),
+            Editor(GotoNextReference),
+            Expect(EditorCursorPosition(Position::new(0, 11))),
+        ])
+    })
+}
+
+#[test]
+fn goto_definition_in_split() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Expect(ComponentsLength(1)),
+            App(GotoDefinitionInSplit),
+            // Mock the response of the LSP server
+            App(HandleLspNotification(LspNotification::Definition(
+                crate::lsp::process::ResponseContext {
+                    open_in_split: true,
+                    ..Default::default()
+                },
+                GotoDefinitionResponse::Single(Location {
+                    path: s.foo_rs(),
+                    range: Position::new(0, 0)..Position::new(0, 3),
+                }),
+            ))),
+            // The original editor remains, and the definition is shown in a new pane
+            Expect(ComponentsLength(2)),
+            Expect(CurrentPath(s.foo_rs())),
+            Expect(CurrentSelectedTexts(&["pub"])),
+            App(CloseCurrentWindow),
+            Expect(ComponentsLength(1)),
+            Expect(CurrentPath(s.main_rs())),
+        ])
+    })
+}
+
+#[test]
+fn peek_definition() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            App(PeekDefinition),
+            // Mock the response of the LSP server
+            App(HandleLspNotification(LspNotification::Definition(
+                crate::lsp::process::ResponseContext {
+                    peek: true,
+                    ..Default::default()
+                },
+                GotoDefinitionResponse::Single(Location {
+                    path: s.foo_rs(),
+                    range: Position::new(0, 0)..Position::new(0, 3),
+                }),
+            ))),
+            // The current editor is not changed
+            Expect(CurrentPath(s.main_rs())),
+            Expect(EditorInfoOpen(true)),
+            Expect(EditorInfoContent(
+                "pub(crate) struct Foo {\n    a: (),\n    b: (),\n}",
+            )),
+            App(HandleKeyEvent(key!("esc"))),
+            Expect(EditorInfoOpen(false)),
+            Expect(CurrentPath(s.main_rs())),
+        ])
+    })
+}
+
 #[test]
 fn global_diagnostics() -> Result<(), anyhow::Error> {
     execute_test(|s| {
-        let publish_diagnostics = |path: CanonicalizedPath| {
-            LspNotification::PublishDiagnostics(lsp_types::PublishDiagnosticsParams {
+        let publish_diagnostics = |path: CanonicalizedPath| LspNotification::PublishDiagnostics {
+            server_name: "rust-analyzer".to_string(),
+            params: lsp_types::PublishDiagnosticsParams {
                 uri: path.to_url().unwrap(),
                 diagnostics: [lsp_types::Diagnostic {
                     range: lsp_types::Range::new(
@@ -1092,7 +1568,7 @@ fn global_diagnostics() -> Result<(), anyhow::Error> {
                 }]
                 .to_vec(),
                 version: None,
-            })
+            },
         };
         Box::new([
             App(OpenFile(s.main_rs())),
@@ -1108,7 +1584,7 @@ fn global_diagnostics() -> Result<(), anyhow::Error> {
                         range: Position { line: 0, column: 0 }..Position { line: 0, column: 3 },
                     },
                     Some(Info::new(
-                        "Diagnostics".to_string(),
+                        "Diagnostics (rust-analyzer)".to_string(),
                         "To err is normal, but to err again is not.".to_string(),
                     )),
                 ),
@@ -1118,7 +1594,7 @@ fn global_diagnostics() -> Result<(), anyhow::Error> {
                         range: Position { line: 0, column: 0 }..Position { line: 0, column: 3 },
                     },
                     Some(Info::new(
-                        "Diagnostics".to_string(),
+                        "Diagnostics (rust-analyzer)".to_string(),
                         "To err is normal, but to err again is not.".to_string(),
                     )),
                 ),
@@ -1178,6 +1654,37 @@ fn test_global_search_replace(
         ])
     })
 }
+#[test]
+fn global_replace_keeps_the_selections_of_reloaded_editors() -> anyhow::Result<()> {
+    execute_test(|s| {
+        let new_dispatch = |update: LocalSearchConfigUpdate| -> Dispatch {
+            UpdateLocalSearchConfig {
+                update,
+                scope: Scope::Global,
+                show_config_after_enter: false,
+                if_current_not_found: IfCurrentNotFound::LookForward,
+            }
+        };
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo bar".to_string())),
+            App(SaveAll),
+            Editor(MatchLiteral("bar".to_string())),
+            App(new_dispatch(LocalSearchConfigUpdate::Search(
+                "foo".to_string(),
+            ))),
+            App(new_dispatch(LocalSearchConfigUpdate::Replacement(
+                "spam".to_string(),
+            ))),
+            App(Dispatch::Replace {
+                scope: Scope::Global,
+            }),
+            Expect(FileContent(s.main_rs(), "spam bar".to_string())),
+            Expect(CurrentSelectedTexts(&["bar"])),
+        ])
+    })
+}
+
 struct TestGlobalSearchReplaceArgs {
     mode: LocalSearchConfigMode,
     main_content: &'static str,
@@ -1268,6 +1775,7 @@ foo a // Line 10
             App(new_dispatch(LocalSearchConfigUpdate::Search(
                 "foo".to_string(),
             ))),
+            WaitForGlobalSearch,
             Expect(QuickfixListContent(
                 // Line 10 should be placed below Line 2 (sorted numerically, not lexicograhically)
                 format!(
@@ -1315,26 +1823,74 @@ foo a // Line 10
 }
 
 #[test]
-fn quickfix_list_show_info_if_possible() -> anyhow::Result<()> {
+fn references_are_sorted_and_deduplicated_in_quickfix_list() -> anyhow::Result<()> {
     execute_test(|s| {
+        let location = |path: CanonicalizedPath, line: usize| Location {
+            path,
+            range: Position::new(line, 0)..Position::new(line, 3),
+        };
         Box::new([
+            App(OpenFile(s.foo_rs())),
+            Editor(SetContent("foo x\nfoo y".to_string())),
             App(OpenFile(s.main_rs())),
-            Editor(SetContent(
-                "
-fn main() { 
-  let x = 123 
-}
-"
+            Editor(SetContent("foo d\nfoo c".to_string())),
+            App(HandleLspNotification(LspNotification::References(
+                crate::lsp::process::ResponseContext::default().set_description("References"),
+                [
+                    location(s.main_rs(), 1),
+                    location(s.foo_rs(), 1),
+                    location(s.main_rs(), 0),
+                    location(s.main_rs(), 1),
+                ]
+                .to_vec(),
+            ))),
+            Expect(QuickfixListContent(
+                format!(
+                    "
+■┬ {}
+ └─ 2:1  foo y
+
+■┬ {}
+ ├─ 1:1  foo d
+ └─ 2:1  foo c",
+                    s.foo_rs().display_absolute(),
+                    s.main_rs().display_absolute()
+                )
                 .trim()
                 .to_string(),
             )),
-            App(SetQuickfixList(
-                crate::quickfix_list::QuickfixListType::Items(
-                    [QuickfixListItem::new(
-                        Location {
-                            path: s.main_rs(),
-                            range: Position { line: 1, column: 2 }..Position { line: 1, column: 5 },
-                        },
+            Expect(CurrentPath(s.foo_rs())),
+            Expect(CurrentLine("foo y")),
+            Expect(CurrentSelectedTexts(&["foo"])),
+            Editor(MoveSelection(Next)),
+            Expect(CurrentPath(s.main_rs())),
+            Expect(CurrentLine("foo d")),
+            Expect(CurrentSelectedTexts(&["foo"])),
+        ])
+    })
+}
+
+#[test]
+fn quickfix_list_show_info_if_possible() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent(
+                "
+fn main() { 
+  let x = 123 
+}
+"
+                .trim()
+                .to_string(),
+            )),
+            App(SetQuickfixList(
+                crate::quickfix_list::QuickfixListType::Items(
+                    [QuickfixListItem::new(
+                        Location {
+                            path: s.main_rs(),
+                            range: Position { line: 1, column: 2 }..Position { line: 1, column: 5 },
+                        },
                         Some(Info::new(
                             "Hello world".to_string(),
                             "This is fine".to_string(),
@@ -1361,18 +1917,21 @@ fn diagnostic_info() -> Result<(), anyhow::Error> {
         Box::new([
             App(OpenFile(s.foo_rs())),
             App(Dispatch::HandleLspNotification(
-                LspNotification::PublishDiagnostics(lsp_types::PublishDiagnosticsParams {
-                    uri: Url::from_file_path(s.foo_rs()).unwrap(),
-                    diagnostics: [lsp_types::Diagnostic::new_simple(
-                        lsp_types::Range::new(
-                            lsp_types::Position::new(0, 1),
-                            lsp_types::Position::new(0, 2),
-                        ),
-                        "Hello world".to_string(),
-                    )]
-                    .to_vec(),
-                    version: None,
-                }),
+                LspNotification::PublishDiagnostics {
+                    server_name: "rust-analyzer".to_string(),
+                    params: lsp_types::PublishDiagnosticsParams {
+                        uri: Url::from_file_path(s.foo_rs()).unwrap(),
+                        diagnostics: [lsp_types::Diagnostic::new_simple(
+                            lsp_types::Range::new(
+                                lsp_types::Position::new(0, 1),
+                                lsp_types::Position::new(0, 2),
+                            ),
+                            "Hello world".to_string(),
+                        )]
+                        .to_vec(),
+                        version: None,
+                    },
+                },
             )),
             Editor(SetSelectionMode(
                 IfCurrentNotFound::LookForward,
@@ -1383,11 +1942,14 @@ fn diagnostic_info() -> Result<(), anyhow::Error> {
             App(HandleKeyEvent(key!("esc"))),
             Expect(EditorInfoOpen(false)),
             App(Dispatch::HandleLspNotification(
-                LspNotification::PublishDiagnostics(lsp_types::PublishDiagnosticsParams {
-                    uri: Url::from_file_path(s.foo_rs()).unwrap(),
-                    diagnostics: Default::default(),
-                    version: None,
-                }),
+                LspNotification::PublishDiagnostics {
+                    server_name: "rust-analyzer".to_string(),
+                    params: lsp_types::PublishDiagnosticsParams {
+                        uri: Url::from_file_path(s.foo_rs()).unwrap(),
+                        diagnostics: Default::default(),
+                        version: None,
+                    },
+                },
             )),
             Editor(MoveSelection(Next)),
             Expect(EditorInfoOpen(false)),
@@ -1395,6 +1957,52 @@ fn diagnostic_info() -> Result<(), anyhow::Error> {
     })
 }
 
+#[test]
+fn goto_next_and_previous_diagnostic() -> Result<(), anyhow::Error> {
+    execute_test(|s| {
+        let diagnostic = |start: u32, end: u32, message: &str| {
+            lsp_types::Diagnostic::new_simple(
+                lsp_types::Range::new(
+                    lsp_types::Position::new(0, start),
+                    lsp_types::Position::new(0, end),
+                ),
+                message.to_string(),
+            )
+        };
+        Box::new([
+            App(OpenFile(s.foo_rs())),
+            Editor(SetContent("foo bar spam baz".to_string())),
+            App(Dispatch::HandleLspNotification(
+                LspNotification::PublishDiagnostics {
+                    server_name: "rust-analyzer".to_string(),
+                    params: lsp_types::PublishDiagnosticsParams {
+                        uri: Url::from_file_path(s.foo_rs()).unwrap(),
+                        diagnostics: [
+                            diagnostic(8, 12, "Spam is unused"),
+                            diagnostic(4, 7, "Bar is unused"),
+                        ]
+                        .to_vec(),
+                        version: None,
+                    },
+                },
+            )),
+            Editor(GotoNextDiagnostic),
+            Expect(CurrentSelectedTexts(&["bar"])),
+            Expect(EditorInfoContent("Bar is unused")),
+            Editor(GotoNextDiagnostic),
+            Expect(CurrentSelectedTexts(&["spam"])),
+            // Wraps around at the end
+            Editor(GotoNextDiagnostic),
+            Expect(CurrentSelectedTexts(&["bar"])),
+            // Wraps around at the start
+            Editor(GotoPreviousDiagnostic),
+            Expect(CurrentSelectedTexts(&["spam"])),
+            Editor(MoveSelection(Previous)),
+            Expect(CurrentSelectedTexts(&["bar"])),
+        ])
+    })
+}
+
 #[test]
 fn diagnostic_severity_decoration_precedence() -> Result<(), anyhow::Error> {
     use lsp_types::DiagnosticSeverity as S;
@@ -1440,14 +2048,17 @@ fn diagnostic_severity_decoration_precedence() -> Result<(), anyhow::Error> {
                 width: 80,
             })),
             App(Dispatch::HandleLspNotification(
-                LspNotification::PublishDiagnostics(lsp_types::PublishDiagnosticsParams {
-                    uri: Url::from_file_path(s.foo_rs()).unwrap(),
-                    diagnostics: diagnostics
-                        .into_iter()
-                        .map(|(start, end, severity)| diagnostic(start, end, severity))
-                        .collect_vec(),
-                    version: None,
-                }),
+                LspNotification::PublishDiagnostics {
+                    server_name: "rust-analyzer".to_string(),
+                    params: lsp_types::PublishDiagnosticsParams {
+                        uri: Url::from_file_path(s.foo_rs()).unwrap(),
+                        diagnostics: diagnostics
+                            .into_iter()
+                            .map(|(start, end, severity)| diagnostic(start, end, severity))
+                            .collect_vec(),
+                        version: None,
+                    },
+                },
             )),
             ExpectMulti(
                 (0..1)
@@ -1484,12 +2095,15 @@ fn same_range_diagnostics_should_be_merged() -> Result<(), anyhow::Error> {
         Box::new([
             App(OpenFile(s.foo_rs())),
             App(Dispatch::HandleLspNotification(
-                LspNotification::PublishDiagnostics(lsp_types::PublishDiagnosticsParams {
-                    uri: Url::from_file_path(s.foo_rs()).unwrap(),
-                    diagnostics: [diagnostic("foo"), diagnostic("bar"), diagnostic("spam")]
-                        .to_vec(),
-                    version: None,
-                }),
+                LspNotification::PublishDiagnostics {
+                    server_name: "rust-analyzer".to_string(),
+                    params: lsp_types::PublishDiagnosticsParams {
+                        uri: Url::from_file_path(s.foo_rs()).unwrap(),
+                        diagnostics: [diagnostic("foo"), diagnostic("bar"), diagnostic("spam")]
+                            .to_vec(),
+                        version: None,
+                    },
+                },
             )),
             Editor(SetSelectionMode(
                 IfCurrentNotFound::LookForward,
@@ -1525,6 +2139,7 @@ fn code_action() -> anyhow::Result<()> {
                 resource_operations: Vec::new(),
             }),
             command: None,
+            server_name: None,
         };
         Box::new([
             App(OpenFile(s.main_rs())),
@@ -1534,6 +2149,47 @@ fn code_action() -> anyhow::Result<()> {
             )),
             App(HandleKeyEvents(keys!("i n g enter").to_vec())),
             Expect(CurrentComponentContent("a.to_string")),
+            // A late response of another server should not reopen the closed menu
+            App(ReceiveCodeActions([code_action("to_soup")].to_vec())),
+            Expect(ComponentsLength(1)),
+        ])
+    })
+}
+
+#[test]
+fn code_actions_menu() -> anyhow::Result<()> {
+    execute_test(|s| {
+        let code_action = CodeAction {
+            title: "Inline variable".to_string(),
+            kind: None,
+            edit: None,
+            command: None,
+            server_name: None,
+        };
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("let x = foo.bar;".to_string())),
+            Editor(MatchLiteral("foo.bar".to_string())),
+            App(RequestCodeAction {
+                diagnostics: Vec::new(),
+            }),
+            // Code actions are requested for the range of the selection
+            Expect(LspRequestSent(FromEditor::TextDocumentCodeAction {
+                params: RequestParams {
+                    path: s.main_rs(),
+                    position: Position::new(0, 8),
+                    context: Default::default(),
+                },
+                range: Position::new(0, 8)..Position::new(0, 15),
+                diagnostics: Vec::new(),
+            })),
+            // The menu should not be opened if there are no code actions
+            App(ReceiveCodeActions(Vec::new())),
+            Expect(ComponentsLength(1)),
+            App(ReceiveCodeActions([code_action].to_vec())),
+            Expect(ComponentsLength(2)),
+            App(HandleKeyEvents(keys!("esc esc").to_vec())),
+            Expect(ComponentsLength(1)),
         ])
     })
 }
@@ -1576,6 +2232,7 @@ fn cycle_window() -> anyhow::Result<()> {
             detail: None,
             insert_text: None,
             completion_item: Default::default(),
+            server_name: None,
         };
 
         execute_test(|s| {
@@ -1588,6 +2245,7 @@ fn cycle_window() -> anyhow::Result<()> {
                 .into_iter()
                 .map(|item| item.into())
                 .collect(),
+                server_name: None,
             };
             Box::new([
                 App(OpenFile(s.main_rs())),
@@ -1633,6 +2291,7 @@ fn esc_in_normal_mode_in_suggestive_editor_should_close_all_other_windows() -> a
             detail: None,
             insert_text: None,
             completion_item: Default::default(),
+            server_name: None,
         };
         execute_test(|s| {
             Box::new([
@@ -1652,6 +2311,7 @@ fn esc_in_normal_mode_in_suggestive_editor_should_close_all_other_windows() -> a
                     .into_iter()
                     .map(|item| item.into())
                     .collect(),
+                    server_name: None,
                 })),
                 Expect(ComponentCount(3)),
                 App(HandleKeyEvent(key!("esc"))),
@@ -1678,6 +2338,7 @@ fn saving_in_insert_mode_in_suggestive_editor_should_close_all_other_windows() -
             detail: None,
             insert_text: None,
             completion_item: Default::default(),
+            server_name: None,
         };
         execute_test(|s| {
             Box::new([
@@ -1697,6 +2358,7 @@ fn saving_in_insert_mode_in_suggestive_editor_should_close_all_other_windows() -
                     .into_iter()
                     .map(|item| item.into())
                     .collect(),
+                    server_name: None,
                 })),
                 Expect(ComponentCount(3)),
                 Editor(EnterInsertMode(Direction::Start)),
@@ -1770,6 +2432,7 @@ fn editor_info_should_always_come_after_dropdown() -> anyhow::Result<()> {
                 .into_iter()
                 .map(|item| item.into())
                 .collect(),
+                server_name: None,
             })),
             Expect(ComponentCount(3)),
             // But dropdown still comes before editor info
@@ -1794,6 +2457,7 @@ fn dropdown_can_only_be_rendered_on_suggestive_editor_or_prompt() -> anyhow::Res
                 .into_iter()
                 .map(|item| item.into())
                 .collect(),
+                server_name: None,
             }))
         };
         Box::new([
@@ -1827,6 +2491,7 @@ fn only_children_of_root_can_remove_all_other_components() -> anyhow::Result<()>
                 .into_iter()
                 .map(|item| item.into())
                 .collect(),
+                server_name: None,
             }))
         };
         Box::new([
@@ -1923,6 +2588,66 @@ fn workspace_edit() -> anyhow::Result<()> {
     })
 }
 
+#[test]
+fn rename_symbol_applies_workspace_edit_to_every_edited_file() -> anyhow::Result<()> {
+    execute_test(|s| {
+        let foo_rs_content = s.foo_rs().read().unwrap();
+        let rename = |line, start, end| PositionalEdit {
+            range: Position::new(line, start)..Position::new(line, end),
+            new_text: "bar_baz".to_string(),
+        };
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent(
+                "fn foo(x: ()) {}\n\nfn main() {\n    foo(foo(()));\n}\n".to_string(),
+            )),
+            Editor(MatchLiteral("foo".to_string())),
+            // The position of the request should be within the selected symbol,
+            // even when the cursor is placed at the end of the selection
+            Editor(SwapCursorWithAnchor),
+            App(RenameSymbol {
+                new_name: "bar_baz".to_string(),
+            }),
+            Expect(LspRequestSent(FromEditor::TextDocumentRename {
+                params: RequestParams {
+                    path: s.main_rs(),
+                    position: Position::new(0, 3),
+                    context: Default::default(),
+                },
+                new_name: "bar_baz".to_string(),
+            })),
+            App(HandleLspNotification(LspNotification::WorkspaceEdit(
+                WorkspaceEdit {
+                    edits: [
+                        TextDocumentEdit {
+                            path: s.main_rs(),
+                            edits: [rename(0, 3, 6), rename(3, 4, 7), rename(3, 8, 11)].to_vec(),
+                        },
+                        TextDocumentEdit {
+                            path: s.foo_rs(),
+                            edits: [PositionalEdit {
+                                range: Position::new(0, 18)..Position::new(0, 21),
+                                new_text: "Bar".to_string(),
+                            }]
+                            .to_vec(),
+                        },
+                    ]
+                    .to_vec(),
+                    resource_operations: Vec::new(),
+                },
+            ))),
+            Expect(FileContent(
+                s.main_rs(),
+                "fn bar_baz(x: ()) {}\n\nfn main() {\n    bar_baz(bar_baz(()));\n}\n".to_string(),
+            )),
+            Expect(FileContent(
+                s.foo_rs(),
+                foo_rs_content.replacen("struct Foo", "struct Bar", 1),
+            )),
+        ])
+    })
+}
+
 #[test]
 fn request_signature_help() -> anyhow::Result<()> {
     execute_test(|s| {
@@ -1950,7 +2675,6 @@ fn request_signature_help() -> anyhow::Result<()> {
     })
 }
 
-#[serial]
 #[test]
 fn copy_paste_using_system_clipboard() -> Result<(), anyhow::Error> {
     execute_test(|s| {
@@ -1997,7 +2721,6 @@ c1 c2 c3 a1\nb1\nc1
     })
 }
 
-#[serial]
 #[test]
 fn replace_using_system_clipboard() -> Result<(), anyhow::Error> {
     execute_test(|s| {
@@ -2043,3 +2766,1833 @@ c1 c2 a1\nb1\nc1
         }
     })
 }
+
+#[test]
+fn copy_to_and_paste_from_system_clipboard() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("a1 a2 a3".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Word)),
+            Expect(CurrentSelectedTexts(&["a1"])),
+            Editor(CopyToSystemClipboard),
+            Editor(MoveSelection(Next)),
+            Editor(MoveSelection(Next)),
+            Expect(CurrentSelectedTexts(&["a3"])),
+            Editor(PasteFromSystemClipboard),
+            Expect(CurrentComponentContent("a1 a2 a3 a1")),
+        ])
+    })
+}
+
+#[test]
+fn copy_without_system_clipboard_survives_across_files() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("alpha beta".to_string())),
+            Editor(MatchLiteral("alpha".to_string())),
+            Editor(CopyToSystemClipboard),
+            Editor(MatchLiteral("beta".to_string())),
+            Editor(Copy {
+                use_system_clipboard: false,
+            }),
+            App(OpenFile(s.foo_rs())),
+            Editor(SetContent("x y".to_string())),
+            Editor(MatchLiteral("x".to_string())),
+            // The copied text is available in the editor of another file
+            Editor(ReplaceWithCopiedText {
+                cut: false,
+                use_system_clipboard: false,
+            }),
+            Editor(MatchLiteral("y".to_string())),
+            // The system clipboard still holds the text copied explicitly to it
+            Editor(ReplaceWithCopiedText {
+                cut: false,
+                use_system_clipboard: true,
+            }),
+            Expect(CurrentComponentContent("beta alpha")),
+        ])
+    })
+}
+
+#[test]
+fn goto_alternate_file() -> anyhow::Result<()> {
+    execute_test(|s| {
+        let rules = s.new_path(crate::alternate_file::ALTERNATE_FILE_RULES_PATH);
+        std::fs::create_dir_all(rules.parent().unwrap()).unwrap();
+        std::fs::write(&rules, "*.rs -> *_test.rs\n").unwrap();
+        std::fs::write(s.new_path("src/foo_test.rs"), "").unwrap();
+        let foo_test_rs: CanonicalizedPath = s.new_path("src/foo_test.rs").try_into().unwrap();
+        Box::new([
+            App(OpenFile(s.foo_rs())),
+            App(GotoAlternateFile),
+            Expect(CurrentComponentPath(Some(foo_test_rs.clone()))),
+            App(GotoAlternateFile),
+            Expect(CurrentComponentPath(Some(s.foo_rs()))),
+        ])
+    })
+}
+
+#[test]
+fn set_status_line_segments() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo bar".to_string())),
+            Editor(MatchLiteral("bar".to_string())),
+            App(SetStatusLineSegments(
+                [
+                    StatusLineComponent::CursorPosition,
+                    StatusLineComponent::CurrentFilePath,
+                    StatusLineComponent::SelectionCount,
+                    StatusLineComponent::Mode,
+                ]
+                .to_vec(),
+            )),
+            Expect(StatusLineSegments(&["1:5", "src/main.rs", "1 sel", "MOVE"])),
+            App(SetStatusLineSegments(
+                [StatusLineComponent::Diagnostics].to_vec(),
+            )),
+            Expect(StatusLineSegments(&["E:0 W:0"])),
+        ])
+    })
+}
+
+#[test]
+fn buffer_stats_are_shown_in_the_status_line() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("# Title\nfoo bar".to_string())),
+            App(SetStatusLineSegments(
+                [StatusLineComponent::Diagnostics].to_vec(),
+            )),
+            App(BufferStats {
+                exclude_markdown_syntax: true,
+            }),
+            Expect(StatusLineSegments(&[
+                "E:0 W:0",
+                "3 words, 15 chars, 2 lines, 1 min read",
+            ])),
+            // The stats follow the edits
+            Editor(SetContent("foo".to_string())),
+            Expect(StatusLineSegments(&[
+                "E:0 W:0",
+                "1 words, 3 chars, 1 lines, 1 min read",
+            ])),
+            Editor(EnterInsertMode(Direction::Start)),
+            Editor(Insert("baz ".to_string())),
+            Expect(StatusLineSegments(&[
+                "E:0 W:0",
+                "2 words, 7 chars, 1 lines, 1 min read",
+            ])),
+            Editor(EnterNormalMode),
+            App(BufferStats {
+                exclude_markdown_syntax: true,
+            }),
+            Expect(StatusLineSegments(&["E:0 W:0"])),
+        ])
+    })
+}
+
+#[test]
+fn add_workspace_folder() -> anyhow::Result<()> {
+    execute_test(|_| {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let folder: CanonicalizedPath = temp_dir.path().to_path_buf().try_into().unwrap();
+        std::fs::write(folder.to_path_buf().join("zebra.md"), "").unwrap();
+        Box::new([
+            App(TerminalDimensionChanged(Dimension {
+                height: 20,
+                width: 50,
+            })),
+            App(AddWorkspaceFolder(folder.clone())),
+            Expect(LspRequestSent(
+                FromEditor::WorkspaceDidChangeWorkspaceFolders {
+                    added: [folder.clone()].to_vec(),
+                    removed: Vec::new(),
+                },
+            )),
+            App(OpenFilePicker(crate::app::FilePickerKind::NonGitIgnored)),
+            App(HandleKeyEvents(keys!("z e b r a").to_vec())),
+            Expect(AppGridContains("zebra.md")),
+            App(HandleKeyEvents(keys!("esc esc").to_vec())),
+            App(RemoveWorkspaceFolder(folder.clone())),
+            App(OpenFilePicker(crate::app::FilePickerKind::NonGitIgnored)),
+            App(HandleKeyEvents(keys!("z e b r a").to_vec())),
+            Expect(Not(Box::new(AppGridContains("zebra.md")))),
+            // The folder is only deleted when its guard, which is owned by this step, is dropped
+            ExpectCustom(Box::new(move || assert!(temp_dir.path().exists()))),
+        ])
+    })
+}
+
+#[test]
+fn toggle_include_ignored_files() -> anyhow::Result<()> {
+    execute_test(|s| {
+        std::fs::write(s.gitignore().to_path_buf(), "*.txt\n").unwrap();
+        std::fs::write(s.new_path("zebra.txt"), "").unwrap();
+        std::fs::write(s.new_path("zebra.md"), "").unwrap();
+        Box::new([
+            App(TerminalDimensionChanged(Dimension {
+                height: 20,
+                width: 50,
+            })),
+            // Git-ignored files are excluded by default
+            App(OpenFilePicker(crate::app::FilePickerKind::NonGitIgnored)),
+            App(HandleKeyEvents(keys!("z e b r a").to_vec())),
+            Expect(AppGridContains("zebra.md")),
+            Expect(Not(Box::new(AppGridContains("zebra.txt")))),
+            App(HandleKeyEvents(keys!("esc esc").to_vec())),
+            App(SetExtraIgnores(["*.md".to_string()].to_vec())),
+            App(OpenFilePicker(crate::app::FilePickerKind::NonGitIgnored)),
+            App(HandleKeyEvents(keys!("z e b r a").to_vec())),
+            Expect(Not(Box::new(AppGridContains("zebra.md")))),
+            App(HandleKeyEvents(keys!("esc esc").to_vec())),
+            App(ToggleIncludeIgnoredFiles),
+            App(OpenFilePicker(crate::app::FilePickerKind::NonGitIgnored)),
+            App(HandleKeyEvents(keys!("z e b r a").to_vec())),
+            Expect(AppGridContains("zebra.md")),
+            Expect(AppGridContains("zebra.txt")),
+        ])
+    })
+}
+
+#[test]
+fn replace_all_matching_prompts_for_the_pattern_then_the_replacement() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo1 bar foo2".to_string())),
+            App(OpenReplaceAllMatchingPrompt { regex: false }),
+            App(HandleKeyEvents(keys!("f o o enter").to_vec())),
+            Expect(CurrentComponentTitle("Replace all matches of \"foo\" with")),
+            App(HandleKeyEvents(keys!("x y enter").to_vec())),
+            Expect(CurrentComponentPath(Some(s.main_rs()))),
+            Expect(CurrentComponentContent("xy1 bar xy2")),
+        ])
+    })
+}
+
+#[test]
+fn replace_all_matching_with_invalid_regex() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            let main_rs = temp_dir.join("src/main.rs")?;
+            app.handle_dispatch(OpenFile(main_rs.clone()))?;
+            let content = app.get_current_component_content();
+            assert!(app
+                .handle_dispatch_editor(ReplaceAllMatching {
+                    pattern: "S(".to_string(),
+                    replacement: "T(".to_string(),
+                    regex: true,
+                })
+                .is_err());
+            assert_eq!(app.get_current_component_content(), content);
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn reload_file_modified_externally() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            let main_rs = temp_dir.join("src/main.rs")?;
+            app.handle_dispatch(OpenFile(main_rs.clone()))?;
+
+            // Buffers without unsaved changes are reloaded automatically
+            main_rs.write("fn a() {}")?;
+            app.handle_external_modifications()?;
+            assert_eq!(app.get_file_content(&main_rs), "fn a() {}");
+
+            // Buffers with unsaved changes are kept until reloaded explicitly
+            app.handle_dispatch_editor(EnterInsertMode(Direction::Start))?;
+            app.handle_dispatch_editor(Insert("x".to_string()))?;
+            app.handle_dispatch_editor(EnterNormalMode)?;
+            main_rs.write("fn b() {}")?;
+            app.handle_external_modifications()?;
+            assert_eq!(app.get_file_content(&main_rs), "xfn a() {}");
+
+            // Pressing Enter in the prompt keeps the changes
+            app.handle_dispatch(HandleKeyEvents(keys!("enter").to_vec()))?;
+            assert_eq!(app.get_file_content(&main_rs), "xfn a() {}");
+
+            app.handle_dispatch(OpenFile(main_rs.clone()))?;
+            app.handle_dispatch(ReloadFile)?;
+            assert_eq!(app.get_file_content(&main_rs), "fn b() {}");
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn reload_file_modified_externally_maps_selections_of_split_panes() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            let main_rs = temp_dir.join("src/main.rs")?;
+            app.handle_dispatch(OpenFile(main_rs.clone()))?;
+            main_rs.write("fn a() {}\nfn bar() {}")?;
+            app.handle_external_modifications()?;
+            app.handle_dispatch_editor(MatchLiteral("bar".to_string()))?;
+            app.handle_dispatch(SplitVertical)?;
+
+            main_rs.write("fn bar() {}")?;
+            app.handle_external_modifications()?;
+            assert_eq!(app.get_current_selected_texts(), ["bar"]);
+            app.handle_dispatch(OtherWindow)?;
+            assert_eq!(app.get_current_selected_texts(), ["bar"]);
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn autosave_after_idle_interval() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            let now = Rc::new(std::cell::Cell::new(std::time::Instant::now()));
+            app.set_clock({
+                let now = now.clone();
+                Rc::new(move || now.get())
+            });
+            let advance =
+                |millis: u64| now.set(now.get() + std::time::Duration::from_millis(millis));
+            let main_rs = temp_dir.join("src/main.rs")?;
+            let original_content = main_rs.read()?;
+            app.handle_dispatch(OpenFile(main_rs.clone()))?;
+            app.handle_dispatch(EnableAutosave(std::time::Duration::from_secs(1)))?;
+
+            app.handle_dispatch_editor(EnterInsertMode(Direction::Start))?;
+            app.handle_dispatch_editor(Insert("a".to_string()))?;
+            advance(600);
+            app.handle_dispatch_editor(Insert("b".to_string()))?;
+            advance(600);
+            // Rapid edits are coalesced, so the interval restarts from the last edit
+            app.autosave()?;
+            assert_eq!(main_rs.read()?, original_content);
+
+            advance(500);
+            // Nothing is written while insert mode is not exited yet
+            app.autosave()?;
+            assert_eq!(main_rs.read()?, original_content);
+
+            app.handle_dispatch_editor(EnterNormalMode)?;
+            app.autosave()?;
+            assert_eq!(main_rs.read()?, app.get_file_content(&main_rs));
+            assert!(main_rs.read()?.starts_with("ab"));
+
+            // Nor while overtyping
+            app.handle_dispatch_editor(EnterOvertypeMode)?;
+            app.handle_dispatch_editor(Insert("c".to_string()))?;
+            advance(1000);
+            app.autosave()?;
+            assert_ne!(main_rs.read()?, app.get_file_content(&main_rs));
+
+            // A buffer that cannot be written does not keep the others unsaved
+            let unwritable_dir = temp_dir.to_path_buf().join("unwritable");
+            std::fs::create_dir(&unwritable_dir)?;
+            std::fs::write(unwritable_dir.join("bar.rs"), "")?;
+            let bar_rs = temp_dir.join("unwritable/bar.rs")?;
+            app.handle_dispatch(OpenFile(bar_rs.clone()))?;
+            app.handle_dispatch_editor(Insert("x".to_string()))?;
+            std::fs::remove_dir_all(&unwritable_dir)?;
+            advance(1000);
+            app.autosave()?;
+            assert_eq!(main_rs.read()?, app.get_file_content(&main_rs));
+
+            // The failed autosave is retried after another interval
+            std::fs::create_dir(&unwritable_dir)?;
+            app.autosave()?;
+            assert!(!unwritable_dir.join("bar.rs").exists());
+            advance(1000);
+            app.autosave()?;
+            assert_eq!(std::fs::read_to_string(unwritable_dir.join("bar.rs"))?, "x");
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn select_next_node_of_missing_kind() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            app.handle_dispatch(OpenFile(temp_dir.join("src/main.rs")?))?;
+            assert!(app
+                .handle_dispatch_editor(SelectNextNodeOfKind("no_such_kind".to_string()))
+                .is_err());
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn increment_number_by_overflowing_delta() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            app.handle_dispatch(OpenFile(temp_dir.join("src/main.rs")?))?;
+            app.handle_dispatch_editor(SetContent("0 0 0".to_string()))?;
+            app.handle_dispatch_editor(MatchLiteral("0".to_string()))?;
+            assert!(app
+                .handle_dispatch_editor(DecrementNumber(i64::MIN))
+                .is_err());
+            app.handle_dispatch_editor(CursorAddToAllSelections)?;
+            assert!(app
+                .handle_dispatch_editor(IncrementNumberSequence(i64::MAX))
+                .is_err());
+            assert_eq!(
+                app.get_file_content(&temp_dir.join("src/main.rs")?),
+                "0 0 0"
+            );
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn buffer_change_observer() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            let main_rs = temp_dir.join("src/main.rs")?;
+            app.handle_dispatch(OpenFile(main_rs.clone()))?;
+            app.handle_dispatch_editor(EnterInsertMode(Direction::Start))?;
+            // Changes made before any observer is registered are not recorded
+            app.handle_dispatch_editor(Insert(" ".to_string()))?;
+            let changes = Rc::new(std::cell::RefCell::new(Vec::new()));
+            let observed = changes.clone();
+            app.register_buffer_change_observer(Box::new(move |change| {
+                observed.borrow_mut().push(change.clone())
+            }));
+            app.handle_dispatch_editor(Insert("// ".to_string()))?;
+            assert_eq!(
+                changes.borrow().clone(),
+                [crate::buffer::BufferChange {
+                    path: Some(main_rs),
+                    range: Position::new(0, 1)..Position::new(0, 1),
+                    new_text: "// ".to_string(),
+                }]
+                .to_vec()
+            );
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn selections_to_quickfix() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo bar foo baz foo".to_string())),
+            Editor(MatchLiteral("foo".to_owned())),
+            Editor(CursorAddToAllSelections),
+            Expect(CurrentSelectedTexts(&["foo", "foo", "foo"])),
+            App(SelectionsToQuickfix),
+            Editor(CursorKeepPrimaryOnly),
+            Expect(EditorCursorPosition(Position::new(0, 0))),
+            App(GotoQuickfixListItem(Movement::Next)),
+            Expect(EditorCursorPosition(Position::new(0, 8))),
+            App(GotoQuickfixListItem(Movement::Next)),
+            Expect(EditorCursorPosition(Position::new(0, 16))),
+            App(GotoQuickfixListItem(Movement::Previous)),
+            Expect(EditorCursorPosition(Position::new(0, 8))),
+        ])
+    })
+}
+
+#[test]
+fn undo_limit_of_zero_is_rejected() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            assert!(app.handle_dispatch(SetUndoLimit(0)).is_err());
+            // The rejected limit is not applied to the buffers opened afterwards
+            app.handle_dispatch(OpenFile(temp_dir.join("src/main.rs")?))?;
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn quickfix_list_preview() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo\na\nb\nc\nd\ne\nf\ng\nfoo".to_string())),
+            Editor(MatchLiteral("foo".to_owned())),
+            Editor(CursorAddToAllSelections),
+            App(ToggleQuickfixPreview),
+            App(SelectionsToQuickfix),
+            Expect(QuickfixListPreview("foo\na\nb\nc")),
+            Expect(EditorCursorPosition(Position::new(0, 0))),
+            App(GotoQuickfixListItem(Movement::Next)),
+            Expect(QuickfixListPreview("e\nf\ng\nfoo")),
+            // Moving through the list does not jump to the item
+            Expect(EditorCursorPosition(Position::new(0, 0))),
+            App(GotoQuickfixListItem(Movement::Previous)),
+            Expect(QuickfixListPreview("foo\na\nb\nc")),
+            App(GotoQuickfixListItem(Movement::Next)),
+            App(GotoQuickfixListItem(Movement::Current(
+                IfCurrentNotFound::LookForward,
+            ))),
+            Expect(EditorCursorPosition(Position::new(8, 0))),
+            // The preview is hidden once previewing is turned off
+            App(ToggleQuickfixPreview),
+            Expect(QuickfixListPreviewOpen(false)),
+        ])
+    })
+}
+
+#[test]
+fn quickfix_list_preview_of_closed_file() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.foo_rs())),
+            Editor(MatchLiteral("b: ()".to_owned())),
+            Editor(CursorAddToAllSelections),
+            App(ToggleQuickfixPreview),
+            App(SelectionsToQuickfix),
+            App(CloseCurrentWindow),
+            App(GotoQuickfixListItem(Movement::Next)),
+            Expect(QuickfixListPreview(
+                "}\n\npub(crate) fn foo() -> Foo {\n    Foo { a: (), b: () }\n}",
+            )),
+        ])
+    })
+}
+
+#[test]
+fn apply_edits_json() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            app.handle_dispatch(OpenFile(temp_dir.join("src/main.rs")?))?;
+            app.handle_dispatch_editor(SetContent("hello world".to_string()))?;
+            app.handle_dispatch(ApplyEditsJson(
+                r#"[{"start":6,"end":11,"replacement":"there"},{"start":0,"end":5,"replacement":"hi"}]"#
+                    .to_string(),
+            ))?;
+            assert_eq!(app.get_current_component_content(), "hi there");
+
+            // Overlapping edits are rejected without modifying the buffer
+            assert!(app
+                .handle_dispatch(ApplyEditsJson(
+                    r#"[{"start":0,"end":3,"replacement":"x"},{"start":2,"end":4,"replacement":"y"}]"#
+                        .to_string(),
+                ))
+                .is_err());
+            assert_eq!(app.get_current_component_content(), "hi there");
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn diff_buffers() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("a\nb\nc".to_string())),
+            App(OpenFile(s.foo_rs())),
+            Editor(SetContent("a\nx\nc".to_string())),
+            App(DiffBuffers {
+                left: s.main_rs(),
+                right: s.foo_rs(),
+            }),
+            Expect(Quickfixes(Box::new([QuickfixListItem::new(
+                Location {
+                    path: s.foo_rs(),
+                    range: Position::new(1, 0)..Position::new(2, 0),
+                },
+                Some(Info::new(
+                    format!(
+                        "Diff of {} and {}",
+                        s.main_rs().try_display_relative(),
+                        s.foo_rs().try_display_relative()
+                    ),
+                    "b\nx".to_string(),
+                )),
+            )]))),
+        ])
+    })
+}
+
+#[test]
+fn apply_patch() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            // The first line is not expected by the patch, so its hunk is shifted by one line
+            Editor(SetContent(
+                "// comment\nfn main() {\n    println!(\"hello\");\n}\n".to_string(),
+            )),
+            App(ApplyPatch(
+                "--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,3 +1,4 @@
+ fn main() {
+-    println!(\"hello\");
++    println!(\"hello\");
++    println!(\"world\");
+ }
+"
+                .to_string(),
+            )),
+            Expect(CurrentComponentContent(
+                "// comment\nfn main() {\n    println!(\"hello\");\n    println!(\"world\");\n}\n",
+            )),
+        ])
+    })
+}
+
+#[test]
+fn selection_mode_is_remembered_per_file() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Word)),
+            App(OpenFile(s.foo_rs())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Token)),
+            App(OpenFile(s.main_rs())),
+            Expect(CurrentSelectionMode(Word)),
+            // The selection mode is restored after the file is closed and opened again
+            App(CloseCurrentWindow),
+            App(OpenFile(s.main_rs())),
+            Expect(CurrentSelectionMode(Word)),
+            App(OpenFile(s.foo_rs())),
+            Expect(CurrentSelectionMode(Token)),
+        ])
+    })
+}
+
+#[test]
+fn update_language_config_only_restarts_changed_language_servers() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, _| {
+            let rust = shared::language::from_extension("rs")
+                .unwrap()
+                .id()
+                .unwrap();
+            let python = shared::language::from_extension("py")
+                .unwrap()
+                .id()
+                .unwrap();
+            app.mock_lsp_server(
+                shared::language::from_extension("rs").unwrap(),
+                "rust-analyzer",
+            );
+            app.mock_lsp_server(
+                shared::language::from_extension("py").unwrap(),
+                "pyright-langserver",
+            );
+            let configs = [
+                LanguageConfig {
+                    language_id: rust,
+                    command: "mock-rust-analyzer".to_string(),
+                    args: ["--verbose".to_string()].to_vec(),
+                    additional_commands: None,
+                },
+                // Same as the default command
+                LanguageConfig {
+                    language_id: python,
+                    command: "pyright-langserver".to_string(),
+                    args: ["--stdio".to_string()].to_vec(),
+                    additional_commands: None,
+                },
+            ]
+            .to_vec();
+            // The mocked servers are initialized, unlike the servers that replace them
+            app.handle_dispatch(UpdateLanguageConfig(configs.clone()))?;
+            assert!(!app.has_initialized_lsp_server(rust));
+            assert!(app.has_initialized_lsp_server(python));
+
+            // Applying the same configs again does not restart any server
+            app.handle_dispatch(UpdateLanguageConfig(configs))?;
+            assert!(app.has_initialized_lsp_server(python));
+
+            // Adding an additional server restarts the servers of the language
+            app.handle_dispatch(UpdateLanguageConfig(
+                [LanguageConfig {
+                    language_id: python,
+                    command: "pyright-langserver".to_string(),
+                    args: ["--stdio".to_string()].to_vec(),
+                    additional_commands: Some(
+                        [("mock-linter".to_string(), ["--stdio".to_string()].to_vec())].to_vec(),
+                    ),
+                }]
+                .to_vec(),
+            ))?;
+            assert!(!app.has_initialized_lsp_server(python));
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn add_language_server() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, _| {
+            let python = shared::language::from_extension("py")
+                .unwrap()
+                .id()
+                .unwrap();
+            app.mock_lsp_server(
+                shared::language::from_extension("py").unwrap(),
+                "pyright-langserver",
+            );
+            // The arguments can contain any character other than whitespace
+            let dispatches = crate::app::DispatchPrompt::SetLanguageServer { additional: true }
+                .to_dispatches("python mock-linter --rules=a+b")?;
+            assert_eq!(
+                dispatches,
+                crate::app::Dispatches::one(SetLanguageServer {
+                    language_id: python,
+                    command: "mock-linter".to_string(),
+                    args: ["--rules=a+b".to_string()].to_vec(),
+                    additional: true,
+                })
+            );
+            app.handle_dispatches(dispatches)?;
+            // The servers of the language are restarted
+            assert!(!app.has_initialized_lsp_server(python));
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn diagnostics_of_multiple_lsp_servers_are_merged() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            let rust = shared::language::from_extension("rs").unwrap();
+            app.mock_lsp_server(rust.clone(), "rust-analyzer");
+            app.mock_lsp_server(rust, "mock-linter");
+            let main_rs = temp_dir.join("src/main.rs")?;
+            app.handle_dispatch(OpenFile(main_rs.clone()))?;
+            let publish_diagnostics = |server_name: &str, messages: &[&str]| {
+                HandleLspNotification(LspNotification::PublishDiagnostics {
+                    server_name: server_name.to_string(),
+                    params: lsp_types::PublishDiagnosticsParams {
+                        uri: main_rs.to_url().unwrap(),
+                        diagnostics: messages
+                            .iter()
+                            .map(|message| {
+                                lsp_types::Diagnostic::new_simple(
+                                    lsp_types::Range::new(
+                                        lsp_types::Position::new(0, 0),
+                                        lsp_types::Position::new(0, 2),
+                                    ),
+                                    message.to_string(),
+                                )
+                            })
+                            .collect(),
+                        version: None,
+                    },
+                })
+            };
+            let diagnostics = |app: &App<MockFrontend>| {
+                app.current_component()
+                    .borrow()
+                    .editor()
+                    .buffer()
+                    .diagnostics()
+                    .into_iter()
+                    .map(|diagnostic| diagnostic.info())
+                    .collect_vec()
+            };
+            let info = |server_name: &str, message: &str| {
+                Info::new(
+                    format!("Diagnostics ({})", server_name),
+                    message.to_string(),
+                )
+            };
+            app.handle_dispatch(publish_diagnostics("rust-analyzer", &["mismatched types"]))?;
+            app.handle_dispatch(publish_diagnostics("mock-linter", &["unused", "too long"]))?;
+            assert_eq!(
+                diagnostics(&app),
+                [
+                    info("rust-analyzer", "mismatched types"),
+                    info("mock-linter", "unused"),
+                    info("mock-linter", "too long"),
+                ]
+                .to_vec()
+            );
+
+            // Publishing again only replaces the diagnostics of the same server
+            app.handle_dispatch(publish_diagnostics("rust-analyzer", &[]))?;
+            assert_eq!(
+                diagnostics(&app),
+                [
+                    info("mock-linter", "unused"),
+                    info("mock-linter", "too long")
+                ]
+                .to_vec()
+            );
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn pulled_diagnostics_are_merged_with_pushed_diagnostics() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            let rust = shared::language::from_extension("rs").unwrap();
+            app.mock_lsp_server(rust, "rust-analyzer");
+            let main_rs = temp_dir.join("src/main.rs")?;
+            app.handle_dispatch(OpenFile(main_rs.clone()))?;
+            let diagnostic = |message: &str| {
+                lsp_types::Diagnostic::new_simple(
+                    lsp_types::Range::new(
+                        lsp_types::Position::new(0, 0),
+                        lsp_types::Position::new(0, 2),
+                    ),
+                    message.to_string(),
+                )
+            };
+            // The report returned by the server for `textDocument/diagnostic`
+            let pulled_diagnostics = |report: lsp_types::DocumentDiagnosticReport| {
+                HandleLspNotification(LspNotification::PulledDiagnostics {
+                    server_name: "rust-analyzer".to_string(),
+                    pulled: PulledDiagnostics::from_report(
+                        main_rs.clone(),
+                        lsp_types::DocumentDiagnosticReportResult::Report(report),
+                    ),
+                })
+            };
+            let full_report = |messages: &[&str]| {
+                lsp_types::DocumentDiagnosticReport::Full(
+                    lsp_types::RelatedFullDocumentDiagnosticReport {
+                        related_documents: None,
+                        full_document_diagnostic_report: lsp_types::FullDocumentDiagnosticReport {
+                            result_id: Some(messages.join(",")),
+                            items: messages.iter().map(|message| diagnostic(message)).collect(),
+                        },
+                    },
+                )
+            };
+            let diagnostic_messages = |app: &App<MockFrontend>| {
+                app.current_component()
+                    .borrow()
+                    .editor()
+                    .buffer()
+                    .diagnostics()
+                    .into_iter()
+                    .map(|diagnostic| diagnostic.message)
+                    .collect_vec()
+            };
+            app.handle_dispatch(HandleLspNotification(LspNotification::PublishDiagnostics {
+                server_name: "rust-analyzer".to_string(),
+                params: lsp_types::PublishDiagnosticsParams {
+                    uri: main_rs.to_url().unwrap(),
+                    diagnostics: [diagnostic("pushed")].to_vec(),
+                    version: None,
+                },
+            }))?;
+
+            app.handle_dispatch(RefreshDiagnostics)?;
+            assert!(app.lsp_request_sent(&FromEditor::TextDocumentDiagnostic {
+                file_path: main_rs.clone()
+            }));
+            app.handle_dispatch(pulled_diagnostics(full_report(&["pulled"])))?;
+            assert_eq!(diagnostic_messages(&app), ["pushed", "pulled"]);
+
+            // Unchanged diagnostics are kept
+            app.handle_dispatch(pulled_diagnostics(
+                lsp_types::DocumentDiagnosticReport::Unchanged(
+                    lsp_types::RelatedUnchangedDocumentDiagnosticReport {
+                        related_documents: None,
+                        unchanged_document_diagnostic_report:
+                            lsp_types::UnchangedDocumentDiagnosticReport {
+                                result_id: "pulled".to_string(),
+                            },
+                    },
+                ),
+            ))?;
+            assert_eq!(diagnostic_messages(&app), ["pushed", "pulled"]);
+
+            // Pulling again only replaces the pulled diagnostics
+            app.handle_dispatch(pulled_diagnostics(full_report(&["foo", "bar"])))?;
+            assert_eq!(diagnostic_messages(&app), ["pushed", "foo", "bar"]);
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn diagnostic_min_severity_hides_less_severe_diagnostics() -> anyhow::Result<()> {
+    use lsp_types::DiagnosticSeverity as S;
+    execute_test(|s| {
+        let diagnostic = |line: u32, severity: S, message: &str| lsp_types::Diagnostic {
+            severity: Some(severity),
+            ..lsp_types::Diagnostic::new_simple(
+                lsp_types::Range::new(
+                    lsp_types::Position::new(line, 0),
+                    lsp_types::Position::new(line, 1),
+                ),
+                message.to_string(),
+            )
+        };
+        Box::new([
+            App(OpenFile(s.foo_rs())),
+            App(HandleLspNotification(LspNotification::PublishDiagnostics {
+                server_name: "rust-analyzer".to_string(),
+                params: lsp_types::PublishDiagnosticsParams {
+                    uri: Url::from_file_path(s.foo_rs()).unwrap(),
+                    diagnostics: [
+                        diagnostic(0, S::HINT, "hint"),
+                        diagnostic(1, S::INFORMATION, "information"),
+                        diagnostic(2, S::WARNING, "warning"),
+                        diagnostic(3, S::ERROR, "error"),
+                    ]
+                    .to_vec(),
+                    version: None,
+                },
+            })),
+            Expect(CurrentDiagnosticMessages(&[
+                "hint",
+                "information",
+                "warning",
+                "error",
+            ])),
+            App(SetDiagnosticMinSeverity(S::ERROR)),
+            Expect(CurrentDiagnosticMessages(&["error"])),
+            // Navigation only goes through the remaining diagnostics
+            Editor(SetSelectionMode(
+                IfCurrentNotFound::LookForward,
+                SelectionMode::Diagnostic(DiagnosticSeverityRange::All),
+            )),
+            Expect(CurrentSelectedTexts(&["}"])),
+            App(SetDiagnosticMinSeverity(S::HINT)),
+            Expect(CurrentDiagnosticMessages(&[
+                "hint",
+                "information",
+                "warning",
+                "error",
+            ])),
+        ])
+    })
+}
+
+#[test]
+fn open_file_loads_content_in_background() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            let (sender, receiver) = std::sync::mpsc::channel::<()>();
+            let receiver = Mutex::new(receiver);
+            // A slow reader, which only reads the file after being notified
+            app.set_file_reader(Arc::new(move |path: &CanonicalizedPath| {
+                receiver.lock().unwrap().recv()?;
+                path.read()
+            }));
+            let main_rs = temp_dir.join("src/main.rs")?;
+            app.handle_dispatch(OpenFile(main_rs.clone()))?;
+            assert_eq!(app.get_current_component_content(), LOADING_PLACEHOLDER);
+
+            // The editor stays responsive while loading
+            app.handle_dispatch_editor(SetSelectionMode(
+                IfCurrentNotFound::LookForward,
+                SelectionMode::Line,
+            ))?;
+            assert_eq!(app.get_current_selected_texts(), [LOADING_PLACEHOLDER]);
+
+            // Edits are rejected, and saving does not overwrite the file with the placeholder
+            app.handle_dispatch_editor(EnterInsertMode(Direction::Start))?;
+            assert!(app.handle_dispatch_editor(Insert("x".to_string())).is_err());
+            app.handle_dispatch_editor(EnterNormalMode)?;
+            app.handle_dispatch_editor(Save)?;
+            assert_ne!(main_rs.read()?, LOADING_PLACEHOLDER);
+
+            sender.send(())?;
+            app.wait_for_file_loaded()?;
+            assert_eq!(app.get_current_component_content(), main_rs.read()?);
+            // The selection made on the placeholder is reset
+            assert_eq!(app.get_current_selected_texts(), [""]);
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn failing_to_load_file_in_background_closes_placeholder() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            app.set_file_reader(Arc::new(|_: &CanonicalizedPath| {
+                Err(anyhow::anyhow!("Permission denied"))
+            }));
+            let main_rs = temp_dir.join("src/main.rs")?;
+            app.handle_dispatch(OpenFile(main_rs.clone()))?;
+            assert_eq!(app.get_current_component_content(), LOADING_PLACEHOLDER);
+
+            let error = app.wait_for_file_loaded().unwrap_err();
+            assert!(error.to_string().contains("Permission denied"));
+            assert_eq!(app.opened_files_count(), 0);
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn global_search_streams_results_and_can_be_cancelled() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            for index in 0..50 {
+                std::fs::write(
+                    temp_dir.to_path_buf().join(format!("needle_{index}.txt")),
+                    "needle",
+                )?;
+            }
+            // Each needle file is only searched after a permit is sent
+            let (permits_sender, permits) = crossbeam::channel::unbounded::<()>();
+            app.set_global_search_throttle(Arc::new(move |path: &std::path::Path| {
+                if path
+                    .file_name()
+                    .map_or(false, |name| name.to_string_lossy().starts_with("needle_"))
+                {
+                    let _ = permits.recv();
+                }
+            }));
+            let quickfixes_count =
+                |app: &App<MockFrontend>| app.get_quickfix_list().unwrap().items().len();
+            for _ in 0..10 {
+                permits_sender.send(())?;
+            }
+            app.handle_dispatch(UpdateLocalSearchConfig {
+                update: LocalSearchConfigUpdate::Search("needle".to_string()),
+                scope: Scope::Global,
+                show_config_after_enter: false,
+                if_current_not_found: IfCurrentNotFound::LookForward,
+            })?;
+            assert!(app.is_global_search_running());
+
+            // The results of the searched files are available before the search completes
+            let mut found = 0;
+            while found < 10 {
+                found += app
+                    .handle_next_global_search_message()?
+                    .expect("The search should wait for the other needle files");
+            }
+            assert!(app.is_global_search_running());
+            assert_eq!(quickfixes_count(&app), 10);
+
+            app.handle_dispatch(CancelGlobalSearch)?;
+            assert!(!app.is_global_search_running());
+            // The remaining files are let through, but not searched anymore
+            for _ in 0..40 {
+                permits_sender.send(())?;
+            }
+            let mut found_after_cancelling = 0;
+            while let Some(count) = app.handle_next_global_search_message()? {
+                found_after_cancelling += count;
+            }
+            assert_eq!(found_after_cancelling, 0);
+            assert_eq!(quickfixes_count(&app), 10);
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn global_search_does_not_jump_to_the_first_result_after_moving() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.foo_rs())),
+            Editor(SetContent("needle".to_string())),
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("x y".to_string())),
+            App(SaveAll),
+            App(UpdateLocalSearchConfig {
+                update: LocalSearchConfigUpdate::Search("needle".to_string()),
+                scope: Scope::Global,
+                show_config_after_enter: false,
+                if_current_not_found: IfCurrentNotFound::LookForward,
+            }),
+            // Move before the search completes
+            Editor(MatchLiteral("y".to_string())),
+            WaitForGlobalSearch,
+            Expect(CurrentPath(s.main_rs())),
+            Expect(CurrentSelectedTexts(&["y"])),
+        ])
+    })
+}
+
+#[test]
+fn replace_all_in_results() -> anyhow::Result<()> {
+    execute_test(|s| {
+        let main_rs = s.main_rs();
+        let foo_rs = s.foo_rs();
+        Box::new([
+            App(OpenFile(s.foo_rs())),
+            Editor(SetContent("needle x needle".to_string())),
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("y needle".to_string())),
+            App(SaveAll),
+            App(UpdateLocalSearchConfig {
+                update: LocalSearchConfigUpdate::Search("needle".to_string()),
+                scope: Scope::Global,
+                show_config_after_enter: false,
+                if_current_not_found: IfCurrentNotFound::LookForward,
+            }),
+            WaitForGlobalSearch,
+            // Leave foo.rs with unsaved changes
+            App(OpenFile(s.foo_rs())),
+            Editor(MatchLiteral("x".to_string())),
+            Editor(EnterInsertMode(Direction::End)),
+            Editor(Insert("!".to_string())),
+            Editor(EnterNormalMode),
+            App(ReplaceAllInResults("pin".to_string())),
+            Expect(FileContent(s.main_rs(), "y pin".to_string())),
+            Expect(FileContent(s.foo_rs(), "pin x! pin".to_string())),
+            // The unmodified main.rs is saved
+            ExpectLater(Box::new(move || {
+                FileContent(main_rs.clone(), main_rs.read().unwrap())
+            })),
+            // The modified foo.rs is only edited in place
+            ExpectCustom(Box::new(move || {
+                assert_eq!(foo_rs.read().unwrap(), "needle x needle")
+            })),
+        ])
+    })
+}
+
+#[test]
+fn replace_all_in_results_fails_while_any_file_is_loading() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            let opened = temp_dir.to_path_buf().join("opened.txt");
+            let other = temp_dir.to_path_buf().join("other.txt");
+            std::fs::write(&opened, "zzneedle 1")?;
+            std::fs::write(&other, "zzneedle 2")?;
+            app.handle_dispatch(UpdateLocalSearchConfig {
+                update: LocalSearchConfigUpdate::Search("zzneedle".to_string()),
+                scope: Scope::Global,
+                show_config_after_enter: false,
+                if_current_not_found: IfCurrentNotFound::LookForward,
+            })?;
+            app.wait_for_global_search()?;
+
+            // A file that is still loading cannot be edited
+            let (sender, receiver) = std::sync::mpsc::channel::<()>();
+            let receiver = Mutex::new(receiver);
+            app.set_file_reader(Arc::new(move |path: &CanonicalizedPath| {
+                receiver.lock().unwrap().recv()?;
+                path.read()
+            }));
+            app.handle_dispatch(OpenFile(opened.clone().try_into()?))?;
+            let opened_files_count = app.opened_files_count();
+            assert!(app
+                .handle_dispatch(ReplaceAllInResults("pin".to_string()))
+                .is_err());
+            assert_eq!(std::fs::read_to_string(&other)?, "zzneedle 2");
+
+            sender.send(())?;
+            app.wait_for_file_loaded()?;
+            app.handle_dispatch(ReplaceAllInResults("pin".to_string()))?;
+            assert_eq!(app.get_current_component_content(), "pin 1");
+            assert_eq!(std::fs::read_to_string(&opened)?, "pin 1");
+            // The file of the other result is rewritten too, without opening any more files
+            assert_eq!(std::fs::read_to_string(&other)?, "pin 2");
+            assert_eq!(app.opened_files_count(), opened_files_count);
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn replace_all_in_results_skips_files_modified_outside_of_the_editor() -> anyhow::Result<()> {
+    execute_test(|s| {
+        let main_rs = s.main_rs();
+        let foo_rs = s.foo_rs();
+        Box::new([
+            App(OpenFile(s.foo_rs())),
+            Editor(SetContent("zzneedle 1".to_string())),
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("zzneedle 2".to_string())),
+            App(SaveAll),
+            App(UpdateLocalSearchConfig {
+                update: LocalSearchConfigUpdate::Search("zzneedle".to_string()),
+                scope: Scope::Global,
+                show_config_after_enter: false,
+                if_current_not_found: IfCurrentNotFound::LookForward,
+            }),
+            WaitForGlobalSearch,
+            // The found location of foo.rs no longer contains the matched text
+            ExpectCustom(Box::new({
+                let foo_rs = foo_rs.clone();
+                move || foo_rs.write("x zzneedle 1").unwrap()
+            })),
+            App(ReplaceAllInResults("pin".to_string())),
+            ExpectCustom(Box::new(move || {
+                assert_eq!(foo_rs.read().unwrap(), "x zzneedle 1");
+                assert_eq!(main_rs.read().unwrap(), "pin 2");
+            })),
+            Expect(AppGridContains("These locations are skipped")),
+        ])
+    })
+}
+
+#[test]
+fn search_wraps_around_at_the_ends() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("a x a x a".to_string())),
+            App(UpdateLocalSearchConfig {
+                update: LocalSearchConfigUpdate::Search("a".to_string()),
+                scope: Scope::Local,
+                show_config_after_enter: false,
+                if_current_not_found: IfCurrentNotFound::LookForward,
+            }),
+            Expect(EditorCursorPosition(Position::new(0, 0))),
+            Editor(MoveSelection(Next)),
+            Expect(EditorCursorPosition(Position::new(0, 4))),
+            Editor(MoveSelection(Next)),
+            Expect(EditorCursorPosition(Position::new(0, 8))),
+            Editor(MoveSelection(Next)),
+            Expect(EditorCursorPosition(Position::new(0, 0))),
+            Editor(MoveSelection(Previous)),
+            Expect(EditorCursorPosition(Position::new(0, 8))),
+            Expect(CurrentSelectedTexts(&["a"])),
+        ])
+    })
+}
+
+#[test]
+fn search_matches_are_highlighted_while_typing_the_search() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("a x a x a".to_string())),
+            App(SetSearch("a".to_string())),
+            App(OpenSearchPrompt {
+                scope: Scope::Local,
+                if_current_not_found: IfCurrentNotFound::LookForward,
+            }),
+            App(HandleKeyEvents(keys!("x").to_vec())),
+            Expect(HighlightedSearch(Some("x"))),
+            // Close the prompt without entering the search
+            App(HandleKeyEvents(keys!("esc esc").to_vec())),
+            Expect(CurrentComponentPath(Some(s.main_rs()))),
+            // The previous search is kept
+            Expect(HighlightedSearch(Some("a"))),
+            Expect(SearchHighlights(
+                [
+                    (CharIndex(0)..CharIndex(1)).into(),
+                    (CharIndex(4)..CharIndex(5)).into(),
+                    (CharIndex(8)..CharIndex(9)).into(),
+                ]
+                .to_vec(),
+            )),
+            App(GotoNextMatch),
+            Expect(CurrentSelectedTexts(&["a"])),
+            // Entering the search replaces the previous search
+            App(OpenSearchPrompt {
+                scope: Scope::Local,
+                if_current_not_found: IfCurrentNotFound::LookForward,
+            }),
+            App(HandleKeyEvents(keys!("x enter").to_vec())),
+            Expect(HighlightedSearch(Some("x"))),
+            Expect(CurrentSelectedTexts(&["x"])),
+        ])
+    })
+}
+
+#[test]
+fn last_search_is_shared_across_buffers() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo x foo".to_string())),
+            App(SetSearch("foo".to_string())),
+            App(OpenFile(s.foo_rs())),
+            Editor(SetContent("a foo b foo".to_string())),
+            App(GotoNextMatch),
+            Expect(CurrentSelectedTexts(&["foo"])),
+            Expect(EditorCursorPosition(Position::new(0, 2))),
+            App(GotoNextMatch),
+            Expect(EditorCursorPosition(Position::new(0, 8))),
+            App(GotoPreviousMatch),
+            Expect(EditorCursorPosition(Position::new(0, 2))),
+        ])
+    })
+}
+
+#[test]
+fn search_matches_are_highlighted_until_cleared() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo bar foo".to_string())),
+            Expect(SearchHighlights(Vec::new())),
+            App(UpdateLocalSearchConfig {
+                update: LocalSearchConfigUpdate::Search("foo".to_string()),
+                scope: Scope::Local,
+                show_config_after_enter: false,
+                if_current_not_found: IfCurrentNotFound::LookForward,
+            }),
+            Expect(SearchHighlights(
+                [
+                    (CharIndex(0)..CharIndex(3)).into(),
+                    (CharIndex(8)..CharIndex(11)).into(),
+                ]
+                .to_vec(),
+            )),
+            // The highlights are updated by edits
+            Editor(EnterInsertMode(Direction::Start)),
+            Editor(Insert("foo ".to_string())),
+            Expect(CurrentComponentContent("foo foo bar foo")),
+            Expect(SearchHighlights(
+                [
+                    (CharIndex(0)..CharIndex(3)).into(),
+                    (CharIndex(4)..CharIndex(7)).into(),
+                    (CharIndex(12)..CharIndex(15)).into(),
+                ]
+                .to_vec(),
+            )),
+            App(ClearSearchHighlight),
+            Expect(SearchHighlights(Vec::new())),
+        ])
+    })
+}
+
+#[test]
+fn quit_with_modified_buffers() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            // Quit directly if no buffers are modified
+            App(Quit { force: false }),
+            Expect(QuitRequested(true)),
+            Editor(EnterInsertMode(Direction::Start)),
+            Editor(Insert("// ".to_string())),
+            App(Quit { force: false }),
+            Expect(QuitRequested(false)),
+            Expect(CurrentComponentTitle("Quit with 1 modified buffer(s)")),
+            App(Quit { force: true }),
+            Expect(QuitRequested(true)),
+        ])
+    })
+}
+
+#[test]
+fn run_with_selection() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            let commands = Arc::new(Mutex::new(Vec::new()));
+            let executed = commands.clone();
+            app.set_shell_command_runner(Arc::new(move |script, argument| {
+                executed
+                    .lock()
+                    .unwrap()
+                    .push((script.to_string(), argument.to_string()));
+                Ok("test main ... ok".to_string())
+            }));
+            app.handle_dispatch(OpenFile(temp_dir.join("src/main.rs")?))?;
+            app.handle_dispatch_editor(MatchLiteral("main".to_string()))?;
+            app.handle_dispatch(RunWithSelection {
+                command_template: "cargo test {selection} -- --exact".to_string(),
+            })?;
+            // The command runs in the background, without blocking the editor
+            app.wait_for_shell_command_output()?;
+            // The selection is not interpreted by the shell
+            assert_eq!(
+                commands.lock().unwrap().clone(),
+                [(
+                    "cargo test \"$1\" -- --exact".to_string(),
+                    "main".to_string()
+                )]
+                .to_vec()
+            );
+            let component = app.current_component();
+            assert_eq!(component.borrow().content(), "test main ... ok");
+            assert_eq!(
+                component.borrow().title(app.context()),
+                "Output of `cargo test main -- --exact`"
+            );
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn clipboard_is_persisted_across_sessions() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            let cache_path = temp_dir.to_path_buf().join("cache").join("clipboard.json");
+            app.enable_clipboard_persistence(cache_path.clone())?;
+            for text in ["foo", "bar"] {
+                app.handle_dispatch(SetClipboardContent {
+                    copied_texts: CopiedTexts::one(text.to_string()),
+                    use_system_clipboard: false,
+                })?;
+            }
+
+            // Simulate a restart
+            let mut app = App::new(
+                Rc::new(Mutex::new(MockFrontend::new(Box::new(NullWriter)))),
+                temp_dir,
+                Vec::new(),
+            )?;
+            app.enable_clipboard_persistence(cache_path)?;
+            let get = |history_offset| -> anyhow::Result<Option<String>> {
+                Ok(app
+                    .context()
+                    .get_clipboard_content(false, history_offset)?
+                    .map(|copied_texts| copied_texts.get(0)))
+            };
+            assert_eq!(get(0)?, Some("bar".to_string()));
+            assert_eq!(get(-1)?, Some("foo".to_string()));
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn corrupted_persisted_clipboard_is_replaced() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            let cache_path = temp_dir.to_path_buf().join("clipboard.json");
+            std::fs::write(&cache_path, "not json")?;
+            assert!(app
+                .enable_clipboard_persistence(cache_path.clone())
+                .is_err());
+            app.handle_dispatch(SetClipboardContent {
+                copied_texts: CopiedTexts::one("foo".to_string()),
+                use_system_clipboard: false,
+            })?;
+            assert_eq!(std::fs::read_to_string(cache_path)?, r#"[["foo"]]"#);
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn save_and_load_session() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            let main_rs = temp_dir.join("src/main.rs")?;
+            let foo_rs = temp_dir.join("src/foo.rs")?;
+            let session_path = temp_dir.to_path_buf().join("session.json");
+            app.handle_dispatch(OpenFile(main_rs.clone()))?;
+            app.handle_dispatch_editor(MatchLiteral("println".to_string()))?;
+            app.handle_dispatch_editor(SetSelectionMode(
+                IfCurrentNotFound::LookForward,
+                SelectionMode::Word,
+            ))?;
+            app.handle_dispatch(OpenFile(foo_rs.clone()))?;
+            app.handle_dispatch_editor(MatchLiteral("a: ()".to_string()))?;
+            app.handle_dispatch_editor(CursorAddToAllSelections)?;
+            app.handle_dispatch(SaveSession(session_path.clone()))?;
+
+            // Simulate a restart
+            let mut app = App::new(
+                Rc::new(Mutex::new(MockFrontend::new(Box::new(NullWriter)))),
+                temp_dir,
+                Vec::new(),
+            )?;
+            app.disable_lsp();
+            app.handle_dispatch(LoadSession(session_path))?;
+            assert_eq!(app.opened_files_count(), 2);
+            assert_eq!(app.current_component().borrow().path(), Some(foo_rs));
+            assert_eq!(app.get_current_selected_texts(), ["a: ()", "a: ()"]);
+            // The search of the selection mode is not saved
+            assert_eq!(
+                app.current_component().borrow().editor().selection_set.mode,
+                SelectionMode::Custom
+            );
+            app.handle_dispatch(OpenFile(main_rs))?;
+            assert_eq!(app.get_current_selected_texts(), ["println"]);
+            assert_eq!(
+                app.current_component().borrow().editor().selection_set.mode,
+                SelectionMode::Word
+            );
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn load_session_restores_selections_of_files_loaded_in_background() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            let main_rs = temp_dir.join("src/main.rs")?;
+            let session_path = temp_dir.to_path_buf().join("session.json");
+            app.handle_dispatch(OpenFile(main_rs.clone()))?;
+            app.handle_dispatch_editor(MatchLiteral("println".to_string()))?;
+            app.handle_dispatch(SaveSession(session_path.clone()))?;
+
+            // Simulate a restart
+            let mut app = App::new(
+                Rc::new(Mutex::new(MockFrontend::new(Box::new(NullWriter)))),
+                temp_dir,
+                Vec::new(),
+            )?;
+            app.disable_lsp();
+            app.set_file_reader(Arc::new(|path: &CanonicalizedPath| path.read()));
+            app.handle_dispatch(LoadSession(session_path))?;
+            assert_eq!(app.get_current_component_content(), LOADING_PLACEHOLDER);
+            app.wait_for_file_loaded()?;
+            assert_eq!(app.get_current_selected_texts(), ["println"]);
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn paste_cycles_through_the_clipboard_history() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            let main_rs = temp_dir.join("src/main.rs")?;
+            // A depth of 0 would drop every copied text
+            assert!(app.handle_dispatch(SetClipboardHistoryDepth(0)).is_err());
+            app.handle_dispatch(SetClipboardHistoryDepth(3))?;
+            app.handle_dispatch(OpenFile(main_rs.clone()))?;
+            app.handle_dispatch_editor(SetContent("a b c d".to_string()))?;
+            app.handle_dispatch_editor(SetSelectionMode(
+                IfCurrentNotFound::LookForward,
+                SelectionMode::Word,
+            ))?;
+            for _ in 0..4 {
+                app.handle_dispatch_editor(Copy {
+                    use_system_clipboard: false,
+                })?;
+                app.handle_dispatch_editor(MoveSelection(Movement::Next))?;
+            }
+            app.handle_dispatch_editor(Paste {
+                direction: Direction::End,
+                use_system_clipboard: false,
+            })?;
+            assert_eq!(app.get_file_content(&main_rs), "a b c d d");
+            app.handle_dispatch_editor(PasteCycleOlder)?;
+            assert_eq!(app.get_file_content(&main_rs), "a b c d c");
+            app.handle_dispatch_editor(PasteCycleOlder)?;
+            assert_eq!(app.get_file_content(&main_rs), "a b c d b");
+            // "a" is no longer retained, so it cycles back to the latest copied text
+            app.handle_dispatch_editor(PasteCycleOlder)?;
+            assert_eq!(app.get_file_content(&main_rs), "a b c d d");
+            app.handle_dispatch_editor(PasteCycleNewer)?;
+            assert_eq!(app.get_file_content(&main_rs), "a b c d b");
+
+            // Cycling does nothing if the last dispatch is not a paste
+            app.handle_dispatch_editor(MoveSelection(Movement::Previous))?;
+            app.handle_dispatch_editor(PasteCycleOlder)?;
+            assert_eq!(app.get_file_content(&main_rs), "a b c d b");
+
+            // Cycling does nothing after pasting from the system clipboard
+            app.handle_dispatch_editor(Copy {
+                use_system_clipboard: true,
+            })?;
+            app.handle_dispatch_editor(Paste {
+                direction: Direction::End,
+                use_system_clipboard: false,
+            })?;
+            app.handle_dispatch_editor(Paste {
+                direction: Direction::End,
+                use_system_clipboard: true,
+            })?;
+            let content = app.get_file_content(&main_rs);
+            app.handle_dispatch_editor(PasteCycleOlder)?;
+            assert_eq!(app.get_file_content(&main_rs), content);
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn split_panes_share_the_buffer() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo".to_string())),
+            App(SplitVertical),
+            Expect(ComponentsLength(2)),
+            Expect(CurrentComponentContent("foo")),
+            Editor(EnterInsertMode(Direction::Start)),
+            Editor(Insert("bar".to_string())),
+            Editor(EnterNormalMode),
+            Expect(FileContent(s.main_rs(), "barfoo".to_string())),
+            App(OtherWindow),
+            Expect(CurrentComponentContent("barfoo")),
+            App(SplitHorizontal),
+            Expect(ComponentsLength(3)),
+            Expect(CurrentComponentContent("barfoo")),
+        ])
+    })
+}
+
+#[test]
+fn split_panes_keep_their_own_selection_mode() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Word)),
+            App(SplitVertical),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Line)),
+            App(OtherWindow),
+            Expect(CurrentSelectionMode(Word)),
+            App(OtherWindow),
+            Expect(CurrentSelectionMode(Line)),
+        ])
+    })
+}
+
+#[test]
+fn split_panes_are_arranged_by_their_own_orientation() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            use crate::rectangle::Rectangle;
+            let rectangles = |app: &App<MockFrontend>| {
+                app.components()
+                    .into_iter()
+                    .map(|component| component.component().borrow().rectangle().clone())
+                    .collect_vec()
+            };
+            app.handle_dispatch(OpenFile(temp_dir.join("src/main.rs")?))?;
+            let main = rectangles(&app)[0].clone();
+            let left_width = main.width / 2;
+            let right = Rectangle {
+                origin: Position {
+                    column: main.origin.column + left_width as usize + 1,
+                    ..main.origin
+                },
+                width: main.width - left_width - 1,
+                ..main.clone()
+            };
+
+            // The vertical split is placed on the right of the current pane
+            app.handle_dispatch(SplitVertical)?;
+            let left = Rectangle {
+                width: left_width,
+                ..main.clone()
+            };
+            assert_eq!(rectangles(&app), [left.clone(), right.clone()]);
+
+            // The horizontal split of the left pane is placed below it,
+            // while the vertical split is kept
+            app.handle_dispatch(OtherWindow)?;
+            app.handle_dispatch(SplitHorizontal)?;
+            let top_height = left.height / 2;
+            let top = Rectangle {
+                height: top_height,
+                ..left.clone()
+            };
+            let bottom = Rectangle {
+                origin: Position {
+                    line: left.origin.line + top_height as usize + 1,
+                    ..left.origin
+                },
+                height: left.height - top_height - 1,
+                ..left.clone()
+            };
+            assert_eq!(rectangles(&app), [top, right, bottom]);
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn set_mark_and_go_to_mark() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo bar".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Word)),
+            Editor(MatchLiteral("bar".to_string())),
+            App(SetMark('a')),
+            // The mark is shifted by the insertion before it
+            Editor(MoveSelection(First)),
+            Editor(EnterInsertMode(Direction::Start)),
+            Editor(Insert("spam ".to_string())),
+            Editor(EnterNormalMode),
+            App(OpenFile(s.foo_rs())),
+            Expect(CurrentPath(s.foo_rs())),
+            App(GoToMark('a')),
+            Expect(CurrentPath(s.main_rs())),
+            Expect(CurrentComponentContent("spam foo bar")),
+            Expect(CurrentSelectedTexts(&["bar"])),
+            // The mark outlives the buffer of its file
+            App(SaveAll),
+            App(CloseCurrentWindow),
+            Expect(OpenedFilesCount(1)),
+            App(GoToMark('a')),
+            Expect(CurrentPath(s.main_rs())),
+            Expect(CurrentSelectedTexts(&["bar"])),
+        ])
+    })
+}
+
+#[test]
+fn mark_is_dropped_once_its_text_is_deleted() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            let main_rs = temp_dir.join("src/main.rs")?;
+            app.handle_dispatch(OpenFile(main_rs.clone()))?;
+            app.handle_dispatch_editor(SetContent("foo bar".to_string()))?;
+            app.handle_dispatch_editor(SetSelectionMode(
+                IfCurrentNotFound::LookForward,
+                SelectionMode::Word,
+            ))?;
+            app.handle_dispatch_editor(MatchLiteral("bar".to_string()))?;
+            app.handle_dispatch(SetMark('a'))?;
+            app.handle_dispatch_editor(Delete(Direction::End))?;
+            assert_eq!(app.get_current_component_content(), "foo ");
+            let error = app.handle_dispatch(GoToMark('a')).unwrap_err();
+            assert_eq!(error.to_string(), "The text of mark 'a' has been deleted");
+            let error = app.handle_dispatch(GoToMark('a')).unwrap_err();
+            assert_eq!(error.to_string(), "Mark 'a' is not set");
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+#[test]
+fn record_and_replay_macro() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo bar spam".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Word)),
+            App(StartRecordingMacro('q')),
+            // Transform to upper case, then move to the next word
+            App(HandleKeyEvents(keys!("! u l").to_vec())),
+            // Dispatches that are not key events are not recorded
+            Editor(MoveSelection(Previous)),
+            Editor(MoveSelection(Next)),
+            App(StopRecordingMacro),
+            Expect(CurrentComponentContent("FOO bar spam")),
+            Expect(CurrentSelectedTexts(&["bar"])),
+            App(ReplayMacro('q')),
+            Expect(CurrentComponentContent("FOO BAR spam")),
+            Expect(CurrentSelectedTexts(&["spam"])),
+            // The replayed edits are undone at once
+            Editor(Undo),
+            Expect(CurrentComponentContent("FOO bar spam")),
+        ])
+    })
+}
+
+#[test]
+fn replaying_macro_that_replays_itself_fails() -> anyhow::Result<()> {
+    execute_test(|s| {
+        Box::new([
+            App(OpenFile(s.main_rs())),
+            Editor(SetContent("foo bar spam".to_string())),
+            Editor(SetSelectionMode(IfCurrentNotFound::LookForward, Word)),
+            // Record a macro that replays itself
+            App(StartRecordingMacro('q')),
+            App(HandleKeyEvents(
+                keys!("! u l : r e p l a y enter q enter").to_vec(),
+            )),
+            App(StopRecordingMacro),
+            Expect(CurrentComponentContent("FOO bar spam")),
+            // The nested replay fails instead of recursing endlessly
+            App(ReplayMacro('q')),
+            Expect(CurrentComponentContent("FOO BAR spam")),
+            Expect(CurrentSelectedTexts(&["spam"])),
+        ])
+    })
+}
+
+#[test]
+fn toggle_show_absolute_paths() -> anyhow::Result<()> {
+    run_test(
+        || Box::new(NullWriter),
+        Vec::new(),
+        |mut app, temp_dir| {
+            let src = temp_dir.join("src")?.display_absolute();
+            let picker_content = |app: &mut App<MockFrontend>| -> anyhow::Result<String> {
+                app.handle_dispatch(OpenFilePicker(crate::app::FilePickerKind::Opened))?;
+                let content = app
+                    .current_completion_dropdown()
+                    .unwrap()
+                    .borrow()
+                    .content();
+                app.handle_dispatch(CloseCurrentWindow)?;
+                Ok(content)
+            };
+            app.handle_dispatch(OpenFile(temp_dir.join("src/main.rs")?))?;
+            let content = picker_content(&mut app)?;
+            assert!(content.contains("src"));
+            assert!(!content.contains(&src));
+
+            app.handle_dispatch(ToggleShowAbsolutePaths)?;
+            assert!(picker_content(&mut app)?.contains(&src));
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
@@ -71,6 +71,9 @@ pub fn from_zed_theme(url: &str) -> anyhow::Result<Vec<Theme>> {
             };
             let parent_lines_background =
                 primary_selection_background.apply_custom_alpha(background, 0.25);
+            let cursor_line_background = text_color.apply_custom_alpha(background, 0.05);
+            let selection_lines_background =
+                primary_selection_background.apply_custom_alpha(background, 0.1);
             let text_accent = theme
                 .style
                 .text_accent
@@ -125,6 +128,8 @@ pub fn from_zed_theme(url: &str) -> anyhow::Result<Vec<Theme>> {
                             theme.style.tab_inactive_background,
                         )),
                     parent_lines_background,
+                    cursor_line_background,
+                    selection_lines_background,
                     jump_mark_odd: Style::new()
                         .background_color(hex!("#b5485d"))
                         .foreground_color(hex!("#ffffff")),
@@ -87,6 +87,14 @@ impl Theme {
             StyleKey::KeymapKey => self.ui.keymap_key,
             StyleKey::UiFuzzyMatchedChar => self.ui.fuzzy_matched_char,
             StyleKey::ParentLine => Style::new().background_color(self.ui.parent_lines_background),
+            StyleKey::UiLongLineOverflow => self.diagnostic.warning,
+            StyleKey::UiSearchMatch => {
+                Style::new().background_color(self.ui.possible_selection_background)
+            }
+            StyleKey::UiCursorLine => Style::new().background_color(self.ui.cursor_line_background),
+            StyleKey::UiSelectionLine => {
+                Style::new().background_color(self.ui.selection_lines_background)
+            }
         }
     }
 }
@@ -125,6 +133,8 @@ pub(crate) struct UiStyles {
     pub(crate) window_title_focused: Style,
     pub(crate) window_title_unfocused: Style,
     pub(crate) parent_lines_background: Color,
+    pub(crate) cursor_line_background: Color,
+    pub(crate) selection_lines_background: Color,
     pub(crate) jump_mark_odd: Style,
     pub(crate) jump_mark_even: Style,
     pub(crate) text_foreground: Color,
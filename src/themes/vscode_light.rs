@@ -34,6 +34,8 @@ pub fn vscode_light() -> Theme {
                 .foreground_color(hex!("#FFFFFF"))
                 .background_color(hex!("#aaaaaa")),
             parent_lines_background: hex!("#E6EBF0"),
+            cursor_line_background: hex!("#F0F0F0"),
+            selection_lines_background: hex!("#EEF6FC"),
             jump_mark_odd: Style::new()
                 .background_color(hex!("#b5485d"))
                 .foreground_color(hex!("#ffffff")),
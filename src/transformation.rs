@@ -43,6 +43,85 @@ impl std::fmt::Display for Transformation {
     }
 }
 
+/// Built-in transformations of the text of a selection
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TransformKind {
+    Reverse,
+    TrimStart,
+    TrimEnd,
+    Base64Encode,
+    Base64Decode,
+    /// Percent-encodes every character except the unreserved characters of RFC 3986
+    UrlEncode,
+    UrlDecode,
+    /// Escapes the text so that it can be placed within the quotes of a JSON string
+    JsonEscape,
+}
+
+impl std::fmt::Display for TransformKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            TransformKind::Reverse => "Reverse",
+            TransformKind::TrimStart => "Trim Start",
+            TransformKind::TrimEnd => "Trim End",
+            TransformKind::Base64Encode => "Base64 Encode",
+            TransformKind::Base64Decode => "Base64 Decode",
+            TransformKind::UrlEncode => "URL Encode",
+            TransformKind::UrlDecode => "URL Decode",
+            TransformKind::JsonEscape => "JSON Escape",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl TransformKind {
+    /// Returns an error if the text cannot be decoded
+    pub(crate) fn apply(&self, string: &str) -> anyhow::Result<String> {
+        use base64::{engine::general_purpose, Engine as _};
+        match self {
+            TransformKind::Reverse => Ok(string.chars().rev().collect()),
+            TransformKind::TrimStart => Ok(string.trim_start().to_string()),
+            TransformKind::TrimEnd => Ok(string.trim_end().to_string()),
+            TransformKind::Base64Encode => Ok(general_purpose::STANDARD.encode(string)),
+            TransformKind::Base64Decode => Ok(String::from_utf8(
+                general_purpose::STANDARD.decode(string.trim())?,
+            )?),
+            TransformKind::UrlEncode => Ok(string
+                .bytes()
+                .map(|byte| {
+                    if byte.is_ascii_alphanumeric() || b"-_.~".contains(&byte) {
+                        (byte as char).to_string()
+                    } else {
+                        format!("%{byte:02X}")
+                    }
+                })
+                .collect()),
+            TransformKind::UrlDecode => {
+                let mut bytes = Vec::new();
+                let mut iter = string.bytes();
+                while let Some(byte) = iter.next() {
+                    if byte == b'%' {
+                        let hex = [iter.next(), iter.next()]
+                            .into_iter()
+                            .collect::<Option<Vec<_>>>()
+                            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                            .and_then(|hex| String::from_utf8(hex).ok())
+                            .ok_or_else(|| anyhow::anyhow!("Invalid percent-encoding"))?;
+                        bytes.push(u8::from_str_radix(&hex, 16)?)
+                    } else {
+                        bytes.push(byte)
+                    }
+                }
+                Ok(String::from_utf8(bytes)?)
+            }
+            TransformKind::JsonEscape => {
+                let json = serde_json::to_string(string)?;
+                Ok(json[1..json.len() - 1].to_string())
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct MyRegex(pub(crate) regex::Regex);
 
@@ -80,9 +159,121 @@ impl Transformation {
     }
 }
 
+/// Add `delta` to the first integer, hexadecimal or decimal number of `text`,
+/// keeping its leading zeros, its radix prefix and its number of decimal places.
+pub(crate) fn increment_number(text: &str, delta: i64) -> anyhow::Result<String> {
+    let found = lazy_regex::regex!(r"-?(?:0[xX][0-9a-fA-F]+|\d+(?:\.\d+)?)")
+        .find(text)
+        .ok_or_else(|| anyhow::anyhow!("No number is found in {text:?}"))?;
+    // A minus following a word is a subtraction instead of a sign, e.g. `x-1`
+    let start = if found.as_str().starts_with('-')
+        && text[..found.start()].ends_with(|c: char| c.is_alphanumeric() || c == '_')
+    {
+        found.start() + 1
+    } else {
+        found.start()
+    };
+    let number = &text[start..found.end()];
+    let unsigned = number.trim_start_matches('-');
+    let overflow = || anyhow::anyhow!("Adding {delta} to {number} overflows");
+    let sign = |value: i64| if value < 0 { "-" } else { "" };
+    let new_number = if let Some((prefix, digits)) = ["0x", "0X"]
+        .into_iter()
+        .find_map(|prefix| Some((prefix, unsigned.strip_prefix(prefix)?)))
+    {
+        let value = i64::from_str_radix(digits, 16)?;
+        let value = if number.starts_with('-') {
+            -value
+        } else {
+            value
+        };
+        let value = value.checked_add(delta).ok_or_else(overflow)?;
+        let new_digits = format!("{:0width$x}", value.unsigned_abs(), width = digits.len());
+        let new_digits = if digits.chars().any(|c| c.is_ascii_uppercase()) {
+            new_digits.to_uppercase()
+        } else {
+            new_digits
+        };
+        format!("{}{prefix}{new_digits}", sign(value))
+    } else if let Some((_, decimals)) = unsigned.split_once('.') {
+        let value = number.parse::<f64>()? + delta as f64;
+        format!("{:.*}", decimals.len(), value)
+    } else {
+        let value = number
+            .parse::<i64>()?
+            .checked_add(delta)
+            .ok_or_else(overflow)?;
+        format!(
+            "{}{:0width$}",
+            sign(value),
+            value.unsigned_abs(),
+            width = unsigned.len()
+        )
+    };
+    Ok(format!(
+        "{}{new_number}{}",
+        &text[..start],
+        &text[found.end()..]
+    ))
+}
+
 #[cfg(test)]
 mod test_transformation {
-    use super::Transformation;
+    use super::{increment_number, TransformKind, Transformation};
+
+    #[test]
+    fn increment_numbers() {
+        let run = |text: &str, delta: i64| increment_number(text, delta).unwrap();
+        assert_eq!(run("let x = 1", 5), "let x = 6");
+        assert_eq!(run("007", 3), "010");
+        assert_eq!(run("099", 1), "100");
+        assert_eq!(run("-1", 3), "2");
+        assert_eq!(run("x-1", 3), "x-4");
+        assert_eq!(run("0xff", 1), "0x100");
+        assert_eq!(run("0x0F", 1), "0x10");
+        assert_eq!(run("0x0a", -1), "0x09");
+        assert_eq!(run("1.50", -2), "-0.50");
+        assert!(increment_number("none", 1).is_err());
+    }
+
+    #[test]
+    fn reverse() {
+        assert_eq!(
+            TransformKind::Reverse.apply("spongebob 🍍").unwrap(),
+            "🍍 bobegnops"
+        )
+    }
+
+    #[test]
+    fn base64_round_trip() {
+        let encoded = TransformKind::Base64Encode
+            .apply("who lives in a pineapple?")
+            .unwrap();
+        assert_eq!(encoded, "d2hvIGxpdmVzIGluIGEgcGluZWFwcGxlPw==");
+        assert_eq!(
+            TransformKind::Base64Decode.apply(&encoded).unwrap(),
+            "who lives in a pineapple?"
+        );
+        assert!(TransformKind::Base64Decode.apply("not base64!").is_err())
+    }
+
+    #[test]
+    fn url_round_trip() {
+        let encoded = TransformKind::UrlEncode.apply("a b/ç?").unwrap();
+        assert_eq!(encoded, "a%20b%2F%C3%A7%3F");
+        assert_eq!(TransformKind::UrlDecode.apply(&encoded).unwrap(), "a b/ç?");
+        assert!(TransformKind::UrlDecode.apply("100%").is_err())
+    }
+
+    #[test]
+    fn json_escape() {
+        assert_eq!(
+            TransformKind::JsonEscape
+                .apply("say \"hi\"\n\tback\\slash")
+                .unwrap(),
+            "say \\\"hi\\\"\\n\\tback\\\\slash"
+        )
+    }
 
     #[test]
     fn join() {
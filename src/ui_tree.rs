@@ -329,6 +329,7 @@ pub(crate) enum ComponentKind {
     GlobalInfo,
     QuickfixList,
     QuickfixListInfo,
+    QuickfixListPreview,
     Prompt,
     Dropdown,
     DropdownInfo,
@@ -50,6 +50,46 @@ impl<T: Applicable> UndoTree<T> {
         }
     }
 
+    /// Limit the number of undo entries, the oldest entries are dropped when the limit is exceeded.
+    ///
+    /// The newest `limit` entries of the current branch are kept, counting from the undone ones
+    /// closest to the current entry, while the other branches are dropped.
+    /// `target` is the current state, which is used to replay the kept entries.
+    ///
+    /// A `limit` of 0 is rejected, since the current entry has to be kept.
+    pub(crate) fn set_limit(&mut self, target: &T::Target, limit: usize) -> anyhow::Result<()>
+    where
+        T::Target: Clone,
+    {
+        if limit == 0 {
+            return Err(anyhow::anyhow!("The undo limit must be at least 1"));
+        }
+        let head = self.history.head().index;
+        let entries = (0..self.history.len())
+            .filter_map(|index| self.history.get_entry(index))
+            .map(|entry| entry.get().clone())
+            .collect::<Vec<_>>();
+        let start = head.saturating_sub(limit);
+        let end = entries.len().min(start + limit);
+
+        // Go back to the state before the oldest kept entry
+        let mut state = target.clone();
+        for entry in entries[start..head].iter().rev() {
+            entry.new_to_old.apply(&mut state)?;
+        }
+
+        let mut history = History::builder().limit(limit).build();
+        for entry in entries[start..end].iter().cloned() {
+            history.edit(&mut state, entry)?;
+        }
+        // Undo the entries that were undone before the limit is set
+        for _ in head..end {
+            history.undo(&mut state).transpose()?;
+        }
+        self.history = history;
+        Ok(())
+    }
+
     pub(crate) fn display(&self) -> String {
         self.history.display().detailed(false).to_string()
     }